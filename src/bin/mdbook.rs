@@ -1,43 +1,106 @@
-#[macro_use]
 extern crate mdbook;
 #[macro_use]
 extern crate clap;
-
-// Dependencies for the Watch feature
-#[cfg(feature = "watch")]
-extern crate notify;
-#[cfg(feature = "watch")]
-extern crate time;
-#[cfg(feature = "watch")]
-extern crate crossbeam;
-
-// Dependencies for the Serve feature
-#[cfg(feature = "serve")]
-extern crate iron;
-#[cfg(feature = "serve")]
-extern crate staticfile;
-#[cfg(feature = "serve")]
-extern crate ws;
+extern crate log;
+extern crate atty;
 
 use std::env;
 use std::error::Error;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use clap::{App, ArgMatches, SubCommand, AppSettings};
+use log::{Level, LevelFilter, Log, Metadata, Record};
 
-// Uses for the Watch feature
-#[cfg(feature = "watch")]
-use notify::Watcher;
-#[cfg(feature = "watch")]
-use std::sync::mpsc::channel;
+use mdbook::{MDBook, Progress};
+use mdbook::book::NoProgress;
 
+const NAME: &'static str = "mdbook";
 
-use mdbook::MDBook;
+/// Environment variable with the level of the messages that are printed: `off`, `error`, `warn`,
+/// `info` (the default), `debug` or `trace`
+const LOG_VAR: &'static str = "MDBOOK_LOG";
 
-const NAME: &'static str = "mdbook";
+static LOGGER: Logger = Logger;
+
+// Whether the progress line of a build is shown at the end of the standard output
+static PROGRESS_LINE: AtomicBool = AtomicBool::new(false);
+
+// Prints the messages of the library: the progress on the standard output, the warnings and the
+// errors on the standard error
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        clear_progress_line();
+        match record.level() {
+            Level::Error => writeln!(&mut io::stderr(), "[x]: {}", record.args()).ok(),
+            Level::Warn => writeln!(&mut io::stderr(), "[-]: {}", record.args()).ok(),
+            Level::Info => writeln!(&mut io::stdout(), "[*]: {}", record.args()).ok(),
+            Level::Debug | Level::Trace => writeln!(&mut io::stdout(), "{}", record.args()).ok(),
+        };
+    }
+
+    fn flush(&self) {}
+}
+
+fn init_logger() {
+    let level = match env::var(LOG_VAR) {
+        Ok(ref level) if !level.is_empty() => {
+            level.parse().unwrap_or_else(|_| {
+                writeln!(&mut io::stderr(), "[-]: Unknown log level {:?} in {}, using info", level, LOG_VAR).ok();
+                LevelFilter::Info
+            })
+        },
+        _ => LevelFilter::Info,
+    };
+
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+// Shows the progress of a build, a line updated in place in a terminal or every chapter when it is
+// verbose
+struct ProgressReporter {
+    verbose: bool,
+}
+
+impl Progress for ProgressReporter {
+    fn chapter(&self, path: &Path, done: usize, total: usize) {
+        if self.verbose {
+            println!("[*]: Rendered {:?} ({}/{})", path, done, total);
+        } else if atty::is(atty::Stream::Stdout) {
+            clear_progress_line();
+            print!("[*]: Rendered {}/{} chapters", done, total);
+            io::stdout().flush().ok();
+            PROGRESS_LINE.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn finish(&self, _renderer: &str) {
+        clear_progress_line();
+    }
+}
+
+fn clear_progress_line() {
+    if PROGRESS_LINE.swap(false, Ordering::SeqCst) {
+        print!("\r\x1b[K");
+        io::stdout().flush().ok();
+    }
+}
 
 fn main() {
+    init_logger();
+
     // Create a list of valid arguments and sub-commands
     let matches = App::new(NAME)
                     .about("Create a book in form of a static website from markdown files")
@@ -51,26 +114,51 @@ fn main() {
                         // the {n} denotes a newline which will properly aligned in all help messages
                         .arg_from_usage("[dir] 'A directory for your book{n}(Defaults to Current Directory when ommitted)'")
                         .arg_from_usage("--theme 'Copies the default theme into your source folder'")
-                        .arg_from_usage("--force 'skip confirmation prompts'"))
+                        .arg_from_usage("--examples 'Starts the book with example chapters instead of an empty one'")
+                        .arg_from_usage("--force 'skip confirmation prompts and overwrite existing theme files'"))
+                    .subcommand(SubCommand::with_name("new")
+                        .about("Create a new book in a new or empty directory")
+                        .arg_from_usage("<dir> 'The directory of the new book'")
+                        .arg_from_usage("--theme 'Copies the default theme into the source folder'"))
                     .subcommand(SubCommand::with_name("build")
                         .about("Build the book from the markdown files")
-                        .arg_from_usage("[dir] 'A directory for your book{n}(Defaults to Current Directory when ommitted)'"))
+                        .arg_from_usage("[dir] 'A directory for your book{n}(Defaults to Current Directory when ommitted)'")
+                        .arg_from_usage("-c, --config=[file] 'A configuration file to use instead of the book.toml{n}or book.json of the book'")
+                        .arg_from_usage("-d, --dest-dir=[dir] 'Output directory of the book, relative to the current directory{n}(Defaults to the dest of the configuration)'")
+                        .arg_from_usage("-o, --open 'Open the book in the default browser after building it'")
+                        .arg_from_usage("--dry-run 'Show the files the build would create, overwrite or remove{n}without changing anything'")
+                        .arg_from_usage("-q, --quiet 'Only print the warnings and the errors'")
+                        .arg_from_usage("-v, --verbose 'Print every rendered chapter'"))
+                    .subcommand(SubCommand::with_name("clean")
+                        .about("Delete the built book")
+                        .arg_from_usage("[dir] 'A directory for your book{n}(Defaults to Current Directory when ommitted)'")
+                        .arg_from_usage("-c, --config=[file] 'A configuration file to use instead of the book.toml{n}or book.json of the book'")
+                        .arg_from_usage("-d, --dest-dir=[dir] 'Output directory of the book, relative to the current directory{n}(Defaults to the dest of the configuration)'"))
                     .subcommand(SubCommand::with_name("watch")
                         .about("Watch the files for changes")
-                        .arg_from_usage("[dir] 'A directory for your book{n}(Defaults to Current Directory when ommitted)'"))
+                        .arg_from_usage("[dir] 'A directory for your book{n}(Defaults to Current Directory when ommitted)'")
+                        .arg_from_usage("-c, --config=[file] 'A configuration file to use instead of the book.toml{n}or book.json of the book'"))
                     .subcommand(SubCommand::with_name("serve")
                         .about("Serve the book at http://localhost:3000. Rebuild and reload on change.")
                         .arg_from_usage("[dir] 'A directory for your book{n}(Defaults to Current Directory when ommitted)'")
+                        .arg_from_usage("-c, --config=[file] 'A configuration file to use instead of the book.toml{n}or book.json of the book'")
                         .arg_from_usage("-p, --port=[port] 'Use another port{n}(Defaults to 3000)'")
-                        .arg_from_usage("-w, --websocket-port=[ws-port] 'Use another port for the websocket connection (livereload){n}(Defaults to 3001)'"))
+                        .arg_from_usage("-w, --websocket-port=[ws-port] 'Use another port for the websocket connection (livereload){n}(Defaults to 3001)'")
+                        .arg_from_usage("-o, --open 'Open the book in the default browser once it is served'"))
                     .subcommand(SubCommand::with_name("test")
-                        .about("Test that code samples compile"))
+                        .about("Test that code samples compile")
+                        .arg_from_usage("-L, --library-path=[dir]... 'A directory added to the library search path of rustdoc'")
+                        .arg_from_usage("-c, --config=[file] 'A configuration file to use instead of the book.toml{n}or book.json of the book'")
+                        .arg_from_usage("--extern=[name=path]... 'A crate the code samples can use'")
+                        .arg_from_usage("--edition=[edition] 'The edition of the code samples'"))
                     .get_matches();
 
     // Check which subcomamnd the user ran...
     let res = match matches.subcommand() {
         ("init", Some(sub_matches)) => init(sub_matches),
+        ("new", Some(sub_matches)) => new(sub_matches),
         ("build", Some(sub_matches)) => build(sub_matches),
+        ("clean", Some(sub_matches)) => clean(sub_matches),
         #[cfg(feature = "watch")]
         ("watch", Some(sub_matches)) => watch(sub_matches),
         #[cfg(feature = "serve")]
@@ -101,36 +189,26 @@ fn confirm() -> bool {
 fn init(args: &ArgMatches) -> Result<(), Box<Error>> {
 
     let book_dir = get_book_dir(args);
-    let mut book = MDBook::new(&book_dir);
+    // The configuration, when there is one, sets the directories of the book
+    let mut book = try!(MDBook::new(&book_dir).read_config());
 
     // Call the function that does the initialization
-    try!(book.init());
+    if args.is_present("examples") {
+        try!(book.init_with_examples());
+    } else {
+        try!(book.init());
+    }
 
     // If flag `--theme` is present, copy theme to src
     if args.is_present("theme") {
 
-        // Skip this if `--force` is present
-        if !args.is_present("force") {
-            // Print warning
-            print!("\nCopying the default theme to {:?}", book.get_src());
-            println!("could potentially overwrite files already present in that directory.");
-            print!("\nAre you sure you want to continue? (y/n) ");
-
-            // Read answer from user and exit if it's not 'yes'
-            if !confirm() {
-                println!("\nSkipping...\n");
-                println!("All done, no errors...");
-                ::std::process::exit(0);
-            }
-        }
-
-        // Call the function that copies the theme
-        try!(book.copy_theme());
+        // Existing theme files are only overwritten if `--force` is present
+        try!(book.copy_theme(args.is_present("force")));
         println!("\nTheme copied.");
 
     }
 
-    // Because of `src/book/mdbook.rs#L37-L39`, `dest` will always start with `root`
+    // The `.gitignore` of the book can only ignore a destination inside of it
     let is_dest_inside_root = book.get_dest().starts_with(book.get_root());
 
     if !args.is_present("force") && is_dest_inside_root {
@@ -148,12 +226,66 @@ fn init(args: &ArgMatches) -> Result<(), Box<Error>> {
 }
 
 
+// New command implementation
+fn new(args: &ArgMatches) -> Result<(), Box<Error>> {
+    let book_dir = get_book_dir(args);
+    let book = try!(MDBook::create(&book_dir));
+
+    if args.is_present("theme") {
+        try!(book.copy_theme(false));
+        println!("\nTheme copied.");
+    }
+
+    println!("\nCreated a new book in {:?}", book.get_root());
+
+    Ok(())
+}
+
+
 // Build command implementation
 fn build(args: &ArgMatches) -> Result<(), Box<Error>> {
-    let book_dir = get_book_dir(args);
-    let mut book = MDBook::new(&book_dir).read_config();
+    let mut book = try!(load_book(args));
+
+    if args.is_present("dry-run") {
+        let dry_run = try!(book.build_dry_run());
+        for path in &dry_run.removed {
+            println!("[*]: Would remove {:?}", path);
+        }
+        for path in &dry_run.created {
+            println!("[*]: Would create {:?}", path);
+        }
+        for path in &dry_run.overwritten {
+            println!("[*]: Would overwrite {:?}", path);
+        }
+        for name in &dry_run.unlisted {
+            println!("[*]: The files of the {} renderer are not listed, it is a plugin", name);
+        }
+        return Ok(());
+    }
 
-    try!(book.build());
+    let progress: Box<Progress> = if args.is_present("quiet") {
+        log::set_max_level(LevelFilter::Warn);
+        Box::new(NoProgress)
+    } else {
+        Box::new(ProgressReporter { verbose: args.is_present("verbose") })
+    };
+    try!(book.build_with_progress(&*progress));
+
+    if args.is_present("open") {
+        mdbook::utils::open(book.get_dest().join("index.html"));
+    }
+
+    Ok(())
+}
+
+
+// Clean command implementation
+fn clean(args: &ArgMatches) -> Result<(), Box<Error>> {
+    let book = try!(load_book(args));
+
+    for path in try!(book.clean()) {
+        println!("[*]: Removed {:?}", path);
+    }
 
     Ok(())
 }
@@ -162,91 +294,51 @@ fn build(args: &ArgMatches) -> Result<(), Box<Error>> {
 // Watch command implementation
 #[cfg(feature = "watch")]
 fn watch(args: &ArgMatches) -> Result<(), Box<Error>> {
-    let book_dir = get_book_dir(args);
-    let mut book = MDBook::new(&book_dir).read_config();
-
-    trigger_on_change(&mut book, |event, book| {
-        if let Some(path) = event.path {
-            println!("File changed: {:?}\nBuilding book...\n", path);
-            match book.build() {
-                Err(e) => println!("Error while building: {:?}", e),
-                _ => {},
-            }
-            println!("");
+    let mut book = try!(load_book(args));
+
+    try!(mdbook::watch::trigger_on_change(&mut book, |path, book| {
+        println!("File changed: {:?}\nBuilding book...\n", path);
+        match book.build() {
+            Err(e) => println!("Error while building: {:?}", e),
+            _ => {},
         }
-    });
+        println!("");
+    }));
 
     Ok(())
 }
 
 
-// Watch command implementation
+// Serve command implementation
 #[cfg(feature = "serve")]
 fn serve(args: &ArgMatches) -> Result<(), Box<Error>> {
-    const RELOAD_COMMAND: &'static str = "reload";
-
-    let book_dir = get_book_dir(args);
-    let mut book = MDBook::new(&book_dir).read_config();
+    let mut book = try!(load_book(args));
     let port = args.value_of("port").unwrap_or("3000");
     let ws_port = args.value_of("ws-port").unwrap_or("3001");
 
-    let address = format!("localhost:{}", port);
-    let ws_address = format!("localhost:{}", ws_port);
-
-    book.set_livereload(format!(r#"
-        <script type="text/javascript">
-            var socket = new WebSocket("ws://localhost:{}");
-            socket.onmessage = function (event) {{
-                if (event.data === "{}") {{
-                    socket.close();
-                    location.reload(true); // force reload from server (not from cache)
-                }}
-            }};
-
-            window.onbeforeunload = function() {{
-                socket.close();
-            }}
-        </script>
-    "#, ws_port, RELOAD_COMMAND).to_owned());
-
-    try!(book.build());
-
-    let staticfile = staticfile::Static::new(book.get_dest());
-    let iron = iron::Iron::new(staticfile);
-    let _iron = iron.http(&*address).unwrap();
-
-    let ws_server = ws::WebSocket::new(|_| {
-        |_| {
-            Ok(())
-        }
-    }).unwrap();
-
-    let broadcaster = ws_server.broadcaster();
-
-    std::thread::spawn(move || {
-        ws_server.listen(&*ws_address).unwrap();
-    });
-
-    println!("\nServing on {}", address);
-
-    trigger_on_change(&mut book, move |event, book| {
-        if let Some(path) = event.path {
-            println!("File changed: {:?}\nBuilding book...\n", path);
-            match book.build() {
-                Err(e) => println!("Error while building: {:?}", e),
-                _ => broadcaster.send(RELOAD_COMMAND).unwrap(),
-            }
-            println!("");
-        }
-    });
+    try!(mdbook::serve::serve(&mut book, port, ws_port, args.is_present("open")));
 
     Ok(())
 }
 
 
 fn test(args: &ArgMatches) -> Result<(), Box<Error>> {
-    let book_dir = get_book_dir(args);
-    let mut book = MDBook::new(&book_dir).read_config();
+    let mut book = try!(load_book(args));
+
+    // The arguments are added to the ones of the configuration
+    if let Some(paths) = args.values_of("library-path") {
+        let mut library_paths = book.get_test_library_paths().to_vec();
+        library_paths.extend(paths.map(PathBuf::from));
+        book.set_test_library_paths_mut(&library_paths);
+    }
+    if let Some(externs) = args.values_of("extern") {
+        let mut test_externs = book.get_test_externs().to_vec();
+        test_externs.extend(externs.map(|e| e.to_owned()));
+        book.set_test_externs_mut(&test_externs);
+    }
+    if let Some(edition) = args.value_of("edition") {
+        book.set_test_edition_mut(edition);
+    }
 
     try!(book.test());
 
@@ -254,6 +346,23 @@ fn test(args: &ArgMatches) -> Result<(), Box<Error>> {
 }
 
 
+// The book of the directory given in the arguments, configured by the file given with `--config` or by
+// its own configuration file
+fn load_book(args: &ArgMatches) -> Result<MDBook, Box<Error>> {
+    let mut book = MDBook::new(&get_book_dir(args));
+    if let Some(config) = args.value_of("config") {
+        book = book.set_config_path(&env::current_dir().unwrap().join(config));
+    }
+    let book = try!(book.read_config());
+
+    // The destination given on the command line is relative to the current directory, like the
+    // other paths of the command line, and replaces the one of the configuration
+    match args.value_of("dest-dir") {
+        Some(dest) => Ok(book.set_dest_from_cwd(Path::new(dest))),
+        None => Ok(book),
+    }
+}
+
 fn get_book_dir(args: &ArgMatches) -> PathBuf {
     if let Some(dir) = args.value_of("dir") {
         // Check if path is relative from current dir, or absolute...
@@ -267,58 +376,3 @@ fn get_book_dir(args: &ArgMatches) -> PathBuf {
         env::current_dir().unwrap()
     }
 }
-
-
-// Calls the closure when a book source file is changed. This is blocking!
-#[cfg(feature = "watch")]
-fn trigger_on_change<F>(book: &mut MDBook, closure: F) -> ()
-    where F: Fn(notify::Event, &mut MDBook) -> ()
-{
-    // Create a channel to receive the events.
-    let (tx, rx) = channel();
-
-    let w: Result<notify::RecommendedWatcher, notify::Error> = notify::Watcher::new(tx);
-
-    match w {
-        Ok(mut watcher) => {
-            // Add the source directory to the watcher
-            if let Err(e) = watcher.watch(book.get_src()) {
-                println!("Error while watching {:?}:\n    {:?}", book.get_src(), e);
-                ::std::process::exit(0);
-            };
-
-            // Add the book.json file to the watcher if it exists, because it's not
-            // located in the source directory
-            if let Err(_) = watcher.watch(book.get_root().join("book.json")) {
-                // do nothing if book.json is not found
-            }
-
-            let mut previous_time = time::get_time();
-
-            println!("\nListening for changes...\n");
-
-            loop {
-                match rx.recv() {
-                    Ok(event) => {
-                        // Skip the event if an event has already been issued in the last second
-                        let time = time::get_time();
-                        if time - previous_time < time::Duration::seconds(1) {
-                            continue;
-                        } else {
-                            previous_time = time;
-                        }
-
-                        closure(event, book);
-                    },
-                    Err(e) => {
-                        println!("An error occured: {:?}", e);
-                    },
-                }
-            }
-        },
-        Err(e) => {
-            println!("Error while trying to watch the files:\n\n\t{:?}", e);
-            ::std::process::exit(0);
-        },
-    }
-}
@@ -1,4 +1,5 @@
 use book::metadata::BookMetadata;
+use book::bookitem::{self, BookItem};
 
 use std::path;
 
@@ -44,6 +45,25 @@ impl Book {
         self
     }
 
+    /// Replaces the chapters of the book by the items parsed from its `SUMMARY.md`. The unnumbered
+    /// chapters before the first numbered one are the preface, the other ones the appendix.
+    pub fn set_items(&mut self, items: &[BookItem]) -> &mut Self {
+        self.preface.clear();
+        self.chapters.clear();
+        self.appendix.clear();
+
+        for item in items {
+            match *item {
+                BookItem::Chapter(_, ref ch) => self.chapters.push(Chapter::from_item(ch)),
+                BookItem::Affix(ref ch) if self.chapters.is_empty() => self.preface.push(Chapter::from_item(ch)),
+                BookItem::Affix(ref ch) => self.appendix.push(Chapter::from_item(ch)),
+                BookItem::Spacer | BookItem::PartTitle(_) => {},
+            }
+        }
+
+        self
+    }
+
     pub fn get_chapter(&self, section: &[usize]) -> Option<&Chapter> {
         match section.len() {
             0 => None,
@@ -55,6 +75,18 @@ impl Book {
         }
     }
 
+    /// Returns `true` if one of the chapters of the book is written in `file`
+    pub fn contains_file(&self, file: &path::Path) -> bool {
+        self.preface.iter()
+                    .chain(self.chapters.iter())
+                    .chain(self.appendix.iter())
+                    .any(|ch| ch.contains_file(file))
+    }
+
+    pub fn preface(&self) -> &[Chapter] { &self.preface }
+    pub fn chapters(&self) -> &[Chapter] { &self.chapters }
+    pub fn appendix(&self) -> &[Chapter] { &self.appendix }
+
     pub fn mut_metadata(&mut self) -> &mut BookMetadata { &mut self.metadata }
     pub fn metadata(&self) -> &BookMetadata { &self.metadata }
 }
@@ -70,6 +102,26 @@ impl Chapter {
         }
     }
 
+    fn from_item(ch: &bookitem::Chapter) -> Self {
+        let mut chapter = Chapter::new(&ch.name, &ch.path);
+        for item in &ch.sub_items {
+            if let BookItem::Chapter(_, ref sub) = *item {
+                chapter.sub_chapters.push(Chapter::from_item(sub));
+            }
+        }
+        chapter
+    }
+
+    fn contains_file(&self, file: &path::Path) -> bool {
+        // `./chapter.md` and `chapter.md` are the same file
+        let same_file = self.file
+                            .components()
+                            .filter(|c| *c != path::Component::CurDir)
+                            .eq(file.components().filter(|c| *c != path::Component::CurDir));
+
+        same_file || self.sub_chapters.iter().any(|ch| ch.contains_file(file))
+    }
+
     pub fn get_sub_chapter(&self, section: &[usize]) -> Option<&Chapter> {
         match section.len() {
             0 => None,
@@ -85,3 +137,36 @@ impl Chapter {
     pub fn file(&self) -> &path::Path { &self.file }
     pub fn sub_chapters(&self) -> &[Chapter] { &self.sub_chapters }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use book::bookitem::{self, BookItem};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn set_items_and_contains_file() {
+        let mut chapter = bookitem::Chapter::new("Chapter".to_owned(), PathBuf::from("./chapter.md"));
+        chapter.sub_items.push(BookItem::Chapter("1.1.".to_owned(),
+                                                 bookitem::Chapter::new("Sub".to_owned(), PathBuf::from("sub/sub.md"))));
+        let items = vec![BookItem::Affix(bookitem::Chapter::new("Intro".to_owned(), PathBuf::from("intro.md"))),
+                         BookItem::Chapter("1.".to_owned(), chapter),
+                         BookItem::Spacer,
+                         BookItem::Affix(bookitem::Chapter::new("Thanks".to_owned(), PathBuf::from("thanks.md")))];
+
+        let mut book = Book::new("Book");
+        book.set_items(&items);
+
+        assert_eq!(book.preface().len(), 1);
+        assert_eq!(book.chapters().len(), 1);
+        assert_eq!(book.appendix().len(), 1);
+        assert_eq!(book.get_chapter(&[0, 0]).map(|ch| ch.title()), Some("Sub"));
+
+        assert!(book.contains_file(Path::new("chapter.md")));
+        assert!(book.contains_file(Path::new("sub/sub.md")));
+        assert!(book.contains_file(Path::new("thanks.md")));
+        assert!(!book.contains_file(Path::new("missing.md")));
+    }
+}
@@ -1,18 +1,84 @@
-use rustc_serialize::json::Json;
-use std::fs::File;
-use std::io::Read;
+use rustc_serialize::json::{Json, ToJson};
+use toml;
+use std::collections::BTreeMap;
+use std::io;
 use std::path::{Path, PathBuf};
 
+use utils;
+use markdown;
+use errors::MdBookError;
+
+// Values accepted for `default_theme`
+const THEMES: &'static [&'static str] = &["light", "rust", "coal", "navy", "dark", "system"];
+
+// Values accepted for `line_endings`
+const LINE_ENDINGS: &'static [&'static str] = &["lf", "crlf"];
+
+// Keys accepted in `font_family`
+const FONT_STACKS: &'static [&'static str] = &["body", "heading", "code"];
+
 #[derive(Debug, Clone)]
 pub struct BookConfig {
     pub title: String,
     pub author: String,
     pub description: String,
+    pub language: String,
+    pub languages: Vec<String>,
+    pub search: bool,
+    pub number_sections: bool,
+    pub toc_depth: usize,
+    pub fold_level: usize,
+    pub reading_time: bool,
+    pub words_per_minute: usize,
+    pub mathjax_support: bool,
+    pub mermaid: bool,
+    pub highlight_theme: Option<String>,
+    pub line_numbers: bool,
+    pub smart_punctuation: bool,
+    pub emoji: bool,
+    pub language_aliases: BTreeMap<String, String>,
+    pub clean_urls: bool,
+    pub redirects: BTreeMap<String, String>,
+    pub index: Option<PathBuf>,
+    pub summary_files: Vec<PathBuf>,
+    pub additional_css: Vec<PathBuf>,
+    pub additional_js: Vec<PathBuf>,
+    pub fonts: Vec<PathBuf>,
+    pub font_family: BTreeMap<String, String>,
+    pub favicon: Option<PathBuf>,
+    pub logo: Option<PathBuf>,
+    pub head: String,
+    pub default_theme: String,
+    pub site_url: Option<String>,
+    pub git_repository_url: Option<String>,
+    pub edit_url_template: Option<String>,
+    pub create_missing: bool,
+    pub latin1_fallback: bool,
+    pub check_links: bool,
+    pub compress_output: bool,
+    pub standalone: bool,
+    pub line_endings: String,
+    pub minify: bool,
+    pub navigate_hidden: bool,
+    pub git_dates: bool,
+    pub minify_exclude: Vec<String>,
+    pub pre_build: Vec<String>,
+    pub post_build: Vec<String>,
+    pub preprocessors: Vec<String>,
+    pub renderers: Vec<String>,
+    pub watch_debounce_ms: u64,
+    pub watch_paths: Vec<PathBuf>,
+    pub watch_ignore: Vec<String>,
+    pub test_library_paths: Vec<PathBuf>,
+    pub test_externs: Vec<String>,
+    pub test_edition: Option<String>,
     root: PathBuf,
     pub dest: PathBuf,
+    pub preserve: Vec<String>,
     pub src: PathBuf,
+    pub theme_path: Option<PathBuf>,
     pub indent_spaces: i32,
-    multilingual: bool,
+    values: Json,
 }
 
 
@@ -22,70 +88,607 @@ impl BookConfig {
             title: String::new(),
             author: String::new(),
             description: String::new(),
+            language: String::from("en"),
+            languages: vec![],
+            search: true,
+            number_sections: true,
+            toc_depth: 2,
+            fold_level: 0,
+            reading_time: false,
+            words_per_minute: 200,
+            mathjax_support: false,
+            mermaid: false,
+            highlight_theme: None,
+            line_numbers: false,
+            smart_punctuation: false,
+            emoji: false,
+            language_aliases: markdown::default_language_aliases(),
+            clean_urls: false,
+            redirects: BTreeMap::new(),
+            index: None,
+            summary_files: vec![PathBuf::from("SUMMARY.md")],
+            additional_css: vec![],
+            additional_js: vec![],
+            fonts: vec![],
+            font_family: BTreeMap::new(),
+            favicon: None,
+            logo: None,
+            head: String::new(),
+            default_theme: String::from("light"),
+            site_url: None,
+            git_repository_url: None,
+            edit_url_template: None,
+            create_missing: false,
+            latin1_fallback: false,
+            check_links: false,
+            compress_output: false,
+            standalone: false,
+            line_endings: String::from("lf"),
+            minify: false,
+            navigate_hidden: false,
+            git_dates: false,
+            minify_exclude: vec![],
+            pre_build: vec![],
+            post_build: vec![],
+            preprocessors: vec![],
+            renderers: vec![],
+            watch_debounce_ms: 300,
+            watch_paths: vec![],
+            watch_ignore: vec![],
+            test_library_paths: vec![],
+            test_externs: vec![],
+            test_edition: None,
             root: root.to_owned(),
             dest: root.join("book"),
+            preserve: vec![],
             src: root.join("src"),
+            theme_path: None,
             indent_spaces: 4, // indentation used for SUMMARY.md
-            multilingual: false,
+            values: Json::Object(BTreeMap::new()),
         }
     }
 
-    pub fn read_config(&mut self, root: &Path) -> &mut Self {
+    /// Looks for a `book.toml` or a `book.json` file in `root` and fills the config with its values.
+    ///
+    /// `book.toml` is preferred when both files are present. If neither exists the config is left
+    /// untouched.
+
+    pub fn read_config(&mut self, root: &Path) -> Result<&mut Self, MdBookError> {
 
         debug!("[fn]: read_config");
 
-        // If the file does not exist, return early
-        let mut config_file = match File::open(root.join("book.json")) {
-            Ok(f) => f,
-            Err(_) => {
-                debug!("[*]: Failed to open {:?}", root.join("book.json"));
-                return self;
+        let toml = root.join("book.toml");
+        let json = root.join("book.json");
+
+        if toml.exists() {
+            if json.exists() {
+                warn!("Both book.toml and book.json were found in {:?}, book.json will be ignored", root);
+            }
+
+            debug!("[*]: Reading config from {:?}", toml);
+            let data = try!(utils::fs::file_to_string(&toml));
+            try!(self.parse_toml(&data, &format!("{}", toml.display())));
+        } else if json.exists() {
+            debug!("[*]: Reading config from {:?}", json);
+            let data = try!(utils::fs::file_to_string(&json));
+            try!(self.parse_json(&data, &format!("{}", json.display())));
+        } else {
+            debug!("[*]: No book.toml or book.json found in {:?}", root);
+        }
+
+        Ok(self)
+    }
+
+    /// Fills the config with the values of the configuration file at `path`, which can be anywhere:
+    /// a `.toml` file is read like a `book.toml` and any other file like a `book.json`. The paths of
+    /// the configuration are still relative to the root of the book, not to the file.
+    ///
+    /// Unlike [`read_config()`](#method.read_config), an error is returned when the file does not
+    /// exist.
+
+    pub fn read_config_file(&mut self, path: &Path) -> Result<&mut Self, MdBookError> {
+        debug!("[fn]: read_config_file");
+
+        if !path.is_file() {
+            return Err(MdBookError::Io(io::Error::new(io::ErrorKind::NotFound,
+                                                      format!("The configuration file {:?} does not exist", path))));
+        }
+
+        debug!("[*]: Reading config from {:?}", path);
+        let data = try!(utils::fs::file_to_string(path));
+        if path.extension().map_or(false, |ext| ext == "toml") {
+            self.parse_toml(&data, &format!("{}", path.display()))
+        } else {
+            self.parse_json(&data, &format!("{}", path.display()))
+        }
+    }
+
+    /// Fills the config from the content of a `book.toml` file
+
+    pub fn parse_from_toml_string(&mut self, data: &str) -> Result<&mut Self, MdBookError> {
+        self.parse_toml(data, "book.toml")
+    }
+
+    /// Fills the config from the content of a `book.json` file
+
+    pub fn parse_from_json_string(&mut self, data: &str) -> Result<&mut Self, MdBookError> {
+        self.parse_json(data, "book.json")
+    }
+
+    // `file` is only used to point the user at the faulty file in error messages
+    fn parse_toml(&mut self, data: &str, file: &str) -> Result<&mut Self, MdBookError> {
+        let mut parser = toml::Parser::new(data);
+
+        let config = match parser.parse() {
+            Some(c) => c,
+            None => {
+                let mut error_str = format!("{}: could not parse input as TOML\n", file);
+                for error in parser.errors.iter() {
+                    let (line, col) = parser.to_linecol(error.lo);
+                    error_str.push_str(&format!("{}:{} {}\n", line + 1, col + 1, error.desc));
+                }
+
+                return Err(MdBookError::Config(error_str));
             },
         };
 
-        debug!("[*]: Reading config");
-        let mut data = String::new();
+        self.fill_from_values(&toml_table_to_json(&config), file)
+    }
+
+    fn parse_json(&mut self, data: &str, file: &str) -> Result<&mut Self, MdBookError> {
+        let config = match Json::from_str(data) {
+            Ok(c) => c,
+            Err(e) => {
+                return Err(MdBookError::Config(format!("{}: could not parse input as JSON\n{}", file, e)))
+            },
+        };
+
+        self.fill_from_values(&config, file)
+    }
+
+    // Both config formats are converted to JSON values beforehand so that the keys only have to
+    // be extracted in one place
+    fn fill_from_values(&mut self, config: &Json, file: &str) -> Result<&mut Self, MdBookError> {
+        debug!("[*]: Extracting data from config");
+
+        if !config.is_object() {
+            return Err(MdBookError::Config(format!("{}: the configuration should be a table of keys", file)));
+        }
+        self.values = config.clone();
+
+        // Title, author, description
+        if let Some(title) = try!(get_string(config, "title", file)) {
+            self.title = title;
+        }
+        if let Some(author) = try!(get_string(config, "author", file)) {
+            self.author = author;
+        }
+        if let Some(description) = try!(get_string(config, "description", file)) {
+            self.description = description;
+        }
+
+        // Language of the book, used in the `lang` attribute of the html output
+        if let Some(language) = try!(get_string(config, "language", file)) {
+            self.language = language;
+        }
+
+        // Languages of a translated book, every one of them has its own sub directory in the source
+        // directory. The default language is written at the root of the output directory and the
+        // other ones in sub directories.
+        if let Some(languages) = try!(get_string_list(config, "languages", file)) {
+            self.languages = languages;
+        }
+        if !self.languages.is_empty() && !self.languages.contains(&self.language) {
+            return Err(MdBookError::Config(format!("{}: the default language `{}` should be one of the \
+                                                    `languages`",
+                                                   file,
+                                                   self.language)));
+        }
+
+        // Search index and search bar in the html output
+        if let Some(search) = try!(get_bool(config, "search", file)) {
+            self.search = search;
+        }
+
+        // Section numbers of the chapters in the sidebar and in the search results
+        if let Some(number_sections) = try!(get_bool(config, "number_sections", file)) {
+            self.number_sections = number_sections;
+        }
+
+        // Number of heading levels in the table of contents of a chapter
+        if let Some(toc_depth) = try!(get_integer(config, "toc_depth", file)) {
+            if toc_depth < 0 {
+                return Err(MdBookError::Config(format!("{}: the value of `toc_depth` can not be negative", file)));
+            }
+            self.toc_depth = toc_depth as usize;
+        }
+
+        // Number of levels of chapters the sidebar shows expanded, the deeper sections are folded
+        if let Some(fold_level) = try!(get_integer(config, "fold_level", file)) {
+            if fold_level < 0 {
+                return Err(MdBookError::Config(format!("{}: the value of `fold_level` can not be negative", file)));
+            }
+            self.fold_level = fold_level as usize;
+        }
+
+        // Word count and reading time of the chapters in the html output
+        if let Some(reading_time) = try!(get_bool(config, "reading_time", file)) {
+            self.reading_time = reading_time;
+        }
+        if let Some(words_per_minute) = try!(get_integer(config, "words_per_minute", file)) {
+            if words_per_minute <= 0 {
+                return Err(MdBookError::Config(format!("{}: the value of `words_per_minute` should be positive",
+                                                       file)));
+            }
+            self.words_per_minute = words_per_minute as usize;
+        }
+
+        // MathJax in the html output and `$` math delimiters in the markdown
+        if let Some(mathjax_support) = try!(get_bool(config, "mathjax_support", file)) {
+            self.mathjax_support = mathjax_support;
+        }
+
+        // `mermaid` code blocks rendered as diagrams by the Mermaid script
+        if let Some(mermaid) = try!(get_bool(config, "mermaid", file)) {
+            self.mermaid = mermaid;
+        }
 
-        // Just return if an error occured.
-        // I would like to propagate the error, but I have to return `&self`
-        if let Err(_) = config_file.read_to_string(&mut data) {
-            return self;
+        // Theme of the code blocks highlighted at build time, they are highlighted in the browser
+        // when it is not set
+        if let Some(highlight_theme) = try!(get_string(config, "highlight_theme", file)) {
+            self.highlight_theme = Some(highlight_theme);
         }
 
-        // Convert to JSON
-        if let Ok(config) = Json::from_str(&data) {
-            // Extract data
+        // Numbers in front of the lines of the code blocks
+        if let Some(line_numbers) = try!(get_bool(config, "line_numbers", file)) {
+            self.line_numbers = line_numbers;
+        }
+
+        // Curly quotes, dashes and ellipses in the text of the chapters
+        if let Some(smart_punctuation) = try!(get_bool(config, "smart_punctuation", file)) {
+            self.smart_punctuation = smart_punctuation;
+        }
 
-            debug!("[*]: Extracting data from config");
-            // Title, author, description
-            if let Some(a) = config.find_path(&["title"]) {
-                self.title = a.to_string().replace("\"", "")
+        // Emoji in place of the shortcodes of the chapters, like `:rocket:`
+        if let Some(emoji) = try!(get_bool(config, "emoji", file)) {
+            self.emoji = emoji;
+        }
+
+        // Languages of the code blocks renamed before they are highlighted, added to the default ones
+        if let Some(aliases) = try!(get_string_map(config, "language_aliases", file)) {
+            self.language_aliases.extend(aliases);
+        }
+
+        // Every chapter is rendered to `<stem>/index.html` and linked as its directory
+        if let Some(clean_urls) = try!(get_bool(config, "clean_urls", file)) {
+            self.clean_urls = clean_urls;
+        }
+
+        // Pages written at the old paths of the moved chapters, that redirect to their new path
+        if let Some(redirects) = try!(get_string_map(config, "redirects", file)) {
+            self.redirects = redirects;
+        }
+
+        // Chapter rendered as the `index.html` at the root of the output, relative to the source
+        // directory
+        if let Some(index) = try!(get_string(config, "index", file)) {
+            self.index = Some(PathBuf::from(index));
+        }
+
+        // Summary of the book, relative to the source directory, or several summary files read one
+        // after the other
+        if let Some(files) = try!(get_string_or_list(config, "summary_file", file)) {
+            if files.is_empty() {
+                return Err(MdBookError::Config(format!("{}: `summary_file` should name at least one file", file)));
             }
-            if let Some(a) = config.find_path(&["author"]) {
-                self.author = a.to_string().replace("\"", "")
+            self.summary_files = files.iter().map(PathBuf::from).collect();
+        }
+
+        // Stylesheets and scripts added to every page, relative to the root of the book
+        if let Some(paths) = try!(get_string_list(config, "additional_css", file)) {
+            self.additional_css = paths.iter().map(|path| self.get_root().join(path)).collect();
+        }
+        if let Some(paths) = try!(get_string_list(config, "additional_js", file)) {
+            self.additional_js = paths.iter().map(|path| self.get_root().join(path)).collect();
+        }
+
+        // Font files bundled with the book, relative to its root, and the families of the text
+        if let Some(paths) = try!(get_string_list(config, "fonts", file)) {
+            self.fonts = paths.iter().map(|path| self.get_root().join(path)).collect();
+        }
+        if let Some(font_family) = try!(get_string_map(config, "font_family", file)) {
+            if let Some(key) = font_family.keys().find(|key| !FONT_STACKS.contains(&key.as_str())) {
+                return Err(MdBookError::Config(format!("{}: the keys of `font_family` should be one of {}, \
+                                                        found `{}`",
+                                                       file,
+                                                       FONT_STACKS.join(", "),
+                                                       key)));
             }
-            if let Some(a) = config.find_path(&["description"]) {
-                self.description = a.to_string().replace("\"", "")
+            self.font_family = font_family;
+        }
+
+        // Icon of the pages and image at the top of the sidebar, relative to the root of the book
+        if let Some(favicon) = try!(get_string(config, "favicon", file)) {
+            self.favicon = Some(self.get_root().join(favicon));
+        }
+        if let Some(logo) = try!(get_string(config, "logo", file)) {
+            self.logo = Some(self.get_root().join(logo));
+        }
+
+        // Raw html inserted at the end of the head of every page
+        if let Some(head) = try!(get_string(config, "head", file)) {
+            self.head = head;
+        }
+
+        // Theme of the html output until the reader picks another one, `dark` is the `coal` theme
+        // and `system` follows the color scheme preferred by the browser
+        if let Some(default_theme) = try!(get_string(config, "default_theme", file)) {
+            if !THEMES.contains(&default_theme.as_str()) {
+                return Err(MdBookError::Config(format!("{}: the value of `default_theme` should be one of {}, \
+                                                        found `{}`",
+                                                       file,
+                                                       THEMES.join(", "),
+                                                       default_theme)));
+            }
+            self.default_theme = default_theme;
+        }
+
+        // Absolute url or path the book is served from, it always ends with a `/`
+        if let Some(mut site_url) = try!(get_string(config, "site_url", file)) {
+            if !site_url.ends_with('/') {
+                site_url.push('/');
             }
+            self.site_url = Some(site_url);
+        }
+
+        // Repository of the book, used for the link to edit every chapter
+        if let Some(git_repository_url) = try!(get_string(config, "git_repository_url", file)) {
+            self.git_repository_url = Some(git_repository_url);
+        }
+        if let Some(edit_url_template) = try!(get_string(config, "edit_url_template", file)) {
+            self.edit_url_template = Some(edit_url_template);
+        }
 
-            // Destination
-            if let Some(a) = config.find_path(&["dest"]) {
-                let dest = PathBuf::from(&a.to_string().replace("\"", ""));
+        // The chapters of `SUMMARY.md` without a file are created by the build instead of failing it
+        if let Some(create_missing) = try!(get_bool(config, "create_missing", file)) {
+            self.create_missing = create_missing;
+        }
+
+        // The chapters that are not valid UTF-8 are read as latin-1 instead of failing the build
+        if let Some(latin1_fallback) = try!(get_bool(config, "latin1_fallback", file)) {
+            self.latin1_fallback = latin1_fallback;
+        }
 
-                // If path is relative make it absolute from the parent directory of src
-                match dest.is_relative() {
-                    true => {
-                        let dest = self.get_root().join(&dest).to_owned();
-                        self.set_dest(&dest);
+        // Links to chapters, anchors or files that do not exist fail the build
+        if let Some(check_links) = try!(get_bool(config, "check_links", file)) {
+            self.check_links = check_links;
+        }
+
+        // Line endings of the text files of the output, whatever the ones of the sources and the theme
+        if let Some(line_endings) = try!(get_string(config, "line_endings", file)) {
+            if !LINE_ENDINGS.contains(&line_endings.as_str()) {
+                return Err(MdBookError::Config(format!("{}: the value of `line_endings` should be one of {}, \
+                                                        found `{}`",
+                                                       file,
+                                                       LINE_ENDINGS.join(", "),
+                                                       line_endings)));
+            }
+            self.line_endings = line_endings;
+        }
+
+        // The hidden chapters are in the previous and next links of their neighbours
+        if let Some(navigate_hidden) = try!(get_bool(config, "navigate_hidden", file)) {
+            self.navigate_hidden = navigate_hidden;
+        }
+
+        // Date of the last change of every chapter, from git or from its file
+        if let Some(git_dates) = try!(get_bool(config, "git_dates", file)) {
+            self.git_dates = git_dates;
+        }
+
+        // Minified html, css and js in the output, except for the files matching the glob patterns
+        if let Some(minify) = try!(get_bool(config, "minify", file)) {
+            self.minify = minify;
+        }
+        if let Some(patterns) = try!(get_string_list(config, "minify_exclude", file)) {
+            self.minify_exclude = patterns;
+        }
+
+        // Gzip copies of the text files of the output, for the servers that serve them as they are
+        if let Some(compress_output) = try!(get_bool(config, "compress_output", file)) {
+            self.compress_output = compress_output;
+        }
+
+        // A copy of the print page with its assets embedded, a single file to share the book
+        if let Some(standalone) = try!(get_bool(config, "standalone", file)) {
+            self.standalone = standalone;
+        }
+
+        // Shell commands run before and after the build, a single command or a list
+        if let Some(commands) = try!(get_string_or_list(config, "pre_build", file)) {
+            self.pre_build = commands;
+        }
+        if let Some(commands) = try!(get_string_or_list(config, "post_build", file)) {
+            self.post_build = commands;
+        }
+
+        // Plugins, the executables `mdbook-<name>`. The renderers replace the html one, `html`,
+        // `json` and `epub` are the built-in ones.
+        if let Some(preprocessors) = try!(get_string_list(config, "preprocessors", file)) {
+            self.preprocessors = preprocessors;
+        }
+        if let Some(renderers) = try!(get_string_list(config, "renderers", file)) {
+            self.renderers = renderers;
+        }
+
+        // Rebuilds of the watch and serve commands
+        if let Some(watch_debounce_ms) = try!(get_integer(config, "watch_debounce_ms", file)) {
+            if watch_debounce_ms < 0 {
+                return Err(MdBookError::Config(format!("{}: the value of `watch_debounce_ms` can not be negative",
+                                                       file)));
+            }
+            self.watch_debounce_ms = watch_debounce_ms as u64;
+        }
+        if let Some(paths) = try!(get_string_list(config, "watch_paths", file)) {
+            self.watch_paths = paths.iter().map(|path| self.get_root().join(path)).collect();
+        }
+        if let Some(patterns) = try!(get_string_list(config, "watch_ignore", file)) {
+            self.watch_ignore = patterns;
+        }
+
+        // Arguments of `rustdoc` for the code samples that use other crates, the paths are relative
+        // to the root of the book
+        if let Some(paths) = try!(get_string_list(config, "test_library_paths", file)) {
+            self.test_library_paths = paths.iter().map(|path| self.get_root().join(path)).collect();
+        }
+        if let Some(externs) = try!(get_string_list(config, "test_externs", file)) {
+            let mut resolved = vec![];
+            for extern_crate in externs {
+                match extern_crate.find('=') {
+                    Some(i) => {
+                        let path = self.get_root().join(&extern_crate[i + 1..]);
+                        resolved.push(format!("{}={}", &extern_crate[..i], path.display()));
                     },
-                    false => {
-                        self.set_dest(&dest);
+                    None => {
+                        return Err(MdBookError::Config(format!("{}: the values of `test_externs` should be of \
+                                                                the form `name=path`, found `{}`",
+                                                               file,
+                                                               extern_crate)));
                     },
                 }
             }
+            self.test_externs = resolved;
+        }
+        if let Some(edition) = try!(get_string(config, "test_edition", file)) {
+            self.test_edition = Some(edition);
         }
 
-        self
+        // Destination
+        if let Some(dest) = try!(get_string(config, "dest", file)) {
+            let dest = PathBuf::from(&dest);
+
+            // If path is relative make it absolute from the parent directory of src
+            match dest.is_relative() {
+                true => {
+                    let dest = self.get_root().join(&dest).to_owned();
+                    self.set_dest(&dest);
+                },
+                false => {
+                    self.set_dest(&dest);
+                },
+            }
+        }
+        // Glob patterns of the paths of the destination that are kept when it is emptied
+        if let Some(patterns) = try!(get_string_list(config, "preserve", file)) {
+            self.preserve = patterns;
+        }
+
+        // Theme directory, `theme` in the source directory when it is not set
+        if let Some(theme_path) = try!(get_string(config, "theme_path", file)) {
+            let theme_path = PathBuf::from(&theme_path);
+
+            // If path is relative make it absolute from the root of the book
+            match theme_path.is_relative() {
+                true => self.theme_path = Some(self.get_root().join(&theme_path)),
+                false => self.theme_path = Some(theme_path),
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Value of `key` in the configuration file, as it was read. The keys that are not options of
+    /// mdBook are kept too, a preprocessor or a renderer can read its own settings from them.
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        self.values.find(key)
+    }
+
+    /// All the values of the configuration file, as it was read. Values of TOML files are
+    /// converted to JSON.
+
+    pub fn get_values(&self) -> &Json {
+        &self.values
+    }
+
+    /// The values of the configuration file with the options of mdBook as they are used, once the
+    /// setters of the book changed them, under the keys of the configuration file
+
+    pub fn to_json(&self) -> Json {
+        fn path(path: &Path) -> Json {
+            path.to_string_lossy().to_json()
+        }
+        fn paths(paths: &[PathBuf]) -> Json {
+            Json::Array(paths.iter().map(|p| path(p)).collect())
+        }
+
+        let mut values = match self.values {
+            Json::Object(ref values) => values.clone(),
+            _ => BTreeMap::new(),
+        };
+        {
+            let mut set = |key: &str, value: Json| {
+                values.insert(key.to_owned(), value);
+            };
+            set("title", self.title.to_json());
+            set("author", self.author.to_json());
+            set("description", self.description.to_json());
+            set("language", self.language.to_json());
+            set("languages", self.languages.to_json());
+            set("search", self.search.to_json());
+            set("number_sections", self.number_sections.to_json());
+            set("toc_depth", self.toc_depth.to_json());
+            set("fold_level", self.fold_level.to_json());
+            set("reading_time", self.reading_time.to_json());
+            set("words_per_minute", self.words_per_minute.to_json());
+            set("mathjax_support", self.mathjax_support.to_json());
+            set("mermaid", self.mermaid.to_json());
+            set("highlight_theme", self.highlight_theme.to_json());
+            set("line_numbers", self.line_numbers.to_json());
+            set("smart_punctuation", self.smart_punctuation.to_json());
+            set("emoji", self.emoji.to_json());
+            set("language_aliases", self.language_aliases.to_json());
+            set("clean_urls", self.clean_urls.to_json());
+            set("redirects", self.redirects.to_json());
+            set("index", self.index.as_ref().map_or(Json::Null, |index| path(index)));
+            set("summary_file", paths(&self.summary_files));
+            set("additional_css", paths(&self.additional_css));
+            set("additional_js", paths(&self.additional_js));
+            set("fonts", paths(&self.fonts));
+            set("font_family", self.font_family.to_json());
+            set("favicon", self.favicon.as_ref().map_or(Json::Null, |favicon| path(favicon)));
+            set("logo", self.logo.as_ref().map_or(Json::Null, |logo| path(logo)));
+            set("head", self.head.to_json());
+            set("default_theme", self.default_theme.to_json());
+            set("site_url", self.site_url.to_json());
+            set("git_repository_url", self.git_repository_url.to_json());
+            set("edit_url_template", self.edit_url_template.to_json());
+            set("create_missing", self.create_missing.to_json());
+            set("latin1_fallback", self.latin1_fallback.to_json());
+            set("check_links", self.check_links.to_json());
+            set("compress_output", self.compress_output.to_json());
+            set("standalone", self.standalone.to_json());
+            set("line_endings", self.line_endings.to_json());
+            set("minify", self.minify.to_json());
+            set("navigate_hidden", self.navigate_hidden.to_json());
+            set("git_dates", self.git_dates.to_json());
+            set("minify_exclude", self.minify_exclude.to_json());
+            set("pre_build", self.pre_build.to_json());
+            set("post_build", self.post_build.to_json());
+            set("preprocessors", self.preprocessors.to_json());
+            set("renderers", self.renderers.to_json());
+            set("watch_debounce_ms", self.watch_debounce_ms.to_json());
+            set("watch_paths", paths(&self.watch_paths));
+            set("watch_ignore", self.watch_ignore.to_json());
+            set("test_library_paths", paths(&self.test_library_paths));
+            set("test_externs", self.test_externs.to_json());
+            set("test_edition", self.test_edition.to_json());
+            set("dest", path(&self.dest));
+            set("src", path(&self.src));
+            set("preserve", self.preserve.to_json());
+            set("theme_path", self.theme_path.as_ref().map_or(Json::Null, |theme_path| path(theme_path)));
+        }
+        Json::Object(values)
     }
 
     pub fn get_root(&self) -> &Path {
@@ -115,3 +718,376 @@ impl BookConfig {
         self
     }
 }
+
+
+// Returns the string value of `key`, or an error naming the file and the key if it is not a string
+fn get_string(config: &Json, key: &str, file: &str) -> Result<Option<String>, MdBookError> {
+    match config.find(key) {
+        None => Ok(None),
+        Some(&Json::String(ref s)) => Ok(Some(s.to_owned())),
+        Some(_) => {
+            Err(MdBookError::Config(format!("{}: the value of `{}` should be a string", file, key)))
+        },
+    }
+}
+
+// Returns the value of `key` as a list of strings, or an error naming the file and the key if it
+// is not an array of strings
+fn get_string_list(config: &Json, key: &str, file: &str) -> Result<Option<Vec<String>>, MdBookError> {
+    let strings = match config.find(key) {
+        None => return Ok(None),
+        Some(&Json::Array(ref values)) => values.iter().map(|value| value.as_string()).collect::<Option<Vec<_>>>(),
+        Some(_) => None,
+    };
+
+    match strings {
+        Some(strings) => Ok(Some(strings.iter().map(|s| s.to_string()).collect())),
+        None => {
+            Err(MdBookError::Config(format!("{}: the value of `{}` should be an array of strings", file, key)))
+        },
+    }
+}
+
+// Returns the value of `key` as a list of strings, a single string being a list of one, or an error
+// naming the file and the key if it is neither
+fn get_string_or_list(config: &Json, key: &str, file: &str) -> Result<Option<Vec<String>>, MdBookError> {
+    match config.find(key) {
+        Some(&Json::String(ref s)) => Ok(Some(vec![s.to_owned()])),
+        Some(&Json::Array(_)) | None => get_string_list(config, key, file),
+        Some(_) => {
+            Err(MdBookError::Config(format!("{}: the value of `{}` should be a string or an array of strings",
+                                            file,
+                                            key)))
+        },
+    }
+}
+
+// Returns the value of `key` as a map of strings, or an error naming the file and the key if it is
+// not a table of strings
+fn get_string_map(config: &Json, key: &str, file: &str) -> Result<Option<BTreeMap<String, String>>, MdBookError> {
+    let strings = match config.find(key) {
+        None => return Ok(None),
+        Some(&Json::Object(ref values)) => {
+            values.iter()
+                  .map(|(key, value)| value.as_string().map(|value| (key.clone(), value.to_owned())))
+                  .collect::<Option<BTreeMap<_, _>>>()
+        },
+        Some(_) => None,
+    };
+
+    match strings {
+        Some(strings) => Ok(Some(strings)),
+        None => {
+            Err(MdBookError::Config(format!("{}: the value of `{}` should be a table of strings", file, key)))
+        },
+    }
+}
+
+// Returns the boolean value of `key`, or an error naming the file and the key if it is not a boolean
+fn get_bool(config: &Json, key: &str, file: &str) -> Result<Option<bool>, MdBookError> {
+    match config.find(key) {
+        None => Ok(None),
+        Some(&Json::Boolean(b)) => Ok(Some(b)),
+        Some(_) => {
+            Err(MdBookError::Config(format!("{}: the value of `{}` should be a boolean", file, key)))
+        },
+    }
+}
+
+// Returns the integer value of `key`, or an error naming the file and the key if it is not an integer
+fn get_integer(config: &Json, key: &str, file: &str) -> Result<Option<i64>, MdBookError> {
+    match config.find(key) {
+        None => Ok(None),
+        Some(&Json::I64(i)) => Ok(Some(i)),
+        Some(&Json::U64(u)) => Ok(Some(u as i64)),
+        Some(_) => {
+            Err(MdBookError::Config(format!("{}: the value of `{}` should be an integer", file, key)))
+        },
+    }
+}
+
+fn toml_table_to_json(table: &toml::Table) -> Json {
+    let mut object = BTreeMap::new();
+    for (key, value) in table {
+        object.insert(key.to_owned(), toml_to_json(value));
+    }
+    Json::Object(object)
+}
+
+fn toml_to_json(value: &toml::Value) -> Json {
+    match *value {
+        toml::Value::String(ref s) => Json::String(s.to_owned()),
+        toml::Value::Integer(i) => Json::I64(i),
+        toml::Value::Float(f) => Json::F64(f),
+        toml::Value::Boolean(b) => Json::Boolean(b),
+        toml::Value::Datetime(ref d) => Json::String(d.to_owned()),
+        toml::Value::Array(ref a) => Json::Array(a.iter().map(toml_to_json).collect()),
+        toml::Value::Table(ref t) => toml_table_to_json(t),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use super::*;
+
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn parse_from_toml_and_json() {
+        let mut toml_config = BookConfig::new(Path::new("root"));
+        toml_config.parse_from_toml_string(r#"
+title = "mdBook"
+author = "Mathieu David"
+description = "Create books from markdown files"
+language = "fr"
+languages = ["en", "fr"]
+search = false
+number_sections = false
+toc_depth = 3
+fold_level = 1
+reading_time = true
+words_per_minute = 250
+mathjax_support = true
+mermaid = true
+highlight_theme = "InspiredGitHub"
+line_numbers = true
+smart_punctuation = true
+emoji = true
+clean_urls = true
+additional_css = ["custom.css"]
+additional_js = ["js/analytics.js"]
+favicon = "images/icon.png"
+logo = "images/logo.svg"
+head = "<meta name=\"robots\" content=\"noindex\">"
+default_theme = "system"
+site_url = "/mdBook"
+git_repository_url = "https://github.com/azerupi/mdBook"
+edit_url_template = "https://github.com/azerupi/mdBook/edit/master/book-example/src/{path}"
+create_missing = true
+latin1_fallback = true
+check_links = true
+compress_output = true
+standalone = true
+line_endings = "crlf"
+minify = true
+navigate_hidden = true
+git_dates = true
+minify_exclude = ["mermaid.min.js"]
+pre_build = "./generate.sh"
+post_build = ["minify book", "rsync -a book/ server:book"]
+preprocessors = ["toc"]
+renderers = ["html", "pdf"]
+watch_debounce_ms = 500
+watch_paths = ["assets"]
+watch_ignore = ["*.swp", "src/drafts"]
+test_library_paths = ["target/debug/deps"]
+test_externs = ["mdbook=target/debug/libmdbook.rlib"]
+test_edition = "2018"
+index = "README.md"
+summary_file = ["SUMMARY.md", "reference/SUMMARY.md"]
+dest = "output"
+preserve = ["CNAME", "downloads/*.pdf"]
+theme_path = "../theme"
+fonts = ["fonts/Brand-Regular.woff2"]
+
+[font_family]
+body = "Brand"
+
+[language_aliases]
+rs = "rust"
+jsx = "javascript"
+
+[redirects]
+"format/old.html" = "format/config.html#options"
+"#).expect("Error while parsing the TOML config");
+
+        let mut json_config = BookConfig::new(Path::new("root"));
+        json_config.parse_from_json_string(r#"{
+    "title": "mdBook",
+    "author": "Mathieu David",
+    "description": "Create books from markdown files",
+    "language": "fr",
+    "languages": ["en", "fr"],
+    "search": false,
+    "number_sections": false,
+    "toc_depth": 3,
+    "fold_level": 1,
+    "reading_time": true,
+    "words_per_minute": 250,
+    "mathjax_support": true,
+    "mermaid": true,
+    "highlight_theme": "InspiredGitHub",
+    "line_numbers": true,
+    "smart_punctuation": true,
+    "emoji": true,
+    "language_aliases": { "rs": "rust", "jsx": "javascript" },
+    "clean_urls": true,
+    "redirects": { "format/old.html": "format/config.html#options" },
+    "additional_css": ["custom.css"],
+    "additional_js": ["js/analytics.js"],
+    "fonts": ["fonts/Brand-Regular.woff2"],
+    "font_family": { "body": "Brand" },
+    "favicon": "images/icon.png",
+    "logo": "images/logo.svg",
+    "head": "<meta name=\"robots\" content=\"noindex\">",
+    "default_theme": "system",
+    "site_url": "/mdBook",
+    "git_repository_url": "https://github.com/azerupi/mdBook",
+    "edit_url_template": "https://github.com/azerupi/mdBook/edit/master/book-example/src/{path}",
+    "create_missing": true,
+    "latin1_fallback": true,
+    "check_links": true,
+    "compress_output": true,
+    "standalone": true,
+    "line_endings": "crlf",
+    "minify": true,
+    "navigate_hidden": true,
+    "git_dates": true,
+    "minify_exclude": ["mermaid.min.js"],
+    "pre_build": "./generate.sh",
+    "post_build": ["minify book", "rsync -a book/ server:book"],
+    "preprocessors": ["toc"],
+    "renderers": ["html", "pdf"],
+    "watch_debounce_ms": 500,
+    "watch_paths": ["assets"],
+    "watch_ignore": ["*.swp", "src/drafts"],
+    "test_library_paths": ["target/debug/deps"],
+    "test_externs": ["mdbook=target/debug/libmdbook.rlib"],
+    "test_edition": "2018",
+    "index": "README.md",
+    "summary_file": ["SUMMARY.md", "reference/SUMMARY.md"],
+    "dest": "output",
+    "preserve": ["CNAME", "downloads/*.pdf"],
+    "theme_path": "../theme"
+}"#).expect("Error while parsing the JSON config");
+
+        for config in &[toml_config, json_config] {
+            assert_eq!(config.title, "mdBook");
+            assert_eq!(config.author, "Mathieu David");
+            assert_eq!(config.description, "Create books from markdown files");
+            assert_eq!(config.language, "fr");
+            assert_eq!(config.languages, vec!["en".to_owned(), "fr".to_owned()]);
+            assert_eq!(config.search, false);
+            assert_eq!(config.number_sections, false);
+            assert_eq!(config.toc_depth, 3);
+            assert_eq!(config.fold_level, 1);
+            assert_eq!(config.reading_time, true);
+            assert_eq!(config.words_per_minute, 250);
+            assert_eq!(config.mathjax_support, true);
+            assert_eq!(config.mermaid, true);
+            assert_eq!(config.highlight_theme, Some("InspiredGitHub".to_owned()));
+            assert_eq!(config.line_numbers, true);
+            assert_eq!(config.smart_punctuation, true);
+            assert_eq!(config.emoji, true);
+            assert_eq!(config.language_aliases["jsx"], "javascript");
+            assert_eq!(config.language_aliases["sh"], "bash");
+            assert_eq!(config.clean_urls, true);
+            assert_eq!(config.redirects["format/old.html"], "format/config.html#options");
+            assert_eq!(config.index, Some(PathBuf::from("README.md")));
+            assert_eq!(config.summary_files,
+                       vec![PathBuf::from("SUMMARY.md"), PathBuf::from("reference/SUMMARY.md")]);
+            assert_eq!(config.additional_css, vec![PathBuf::from("root/custom.css")]);
+            assert_eq!(config.additional_js, vec![PathBuf::from("root/js/analytics.js")]);
+            assert_eq!(config.fonts, vec![PathBuf::from("root/fonts/Brand-Regular.woff2")]);
+            assert_eq!(config.font_family["body"], "Brand");
+            assert_eq!(config.favicon, Some(PathBuf::from("root/images/icon.png")));
+            assert_eq!(config.logo, Some(PathBuf::from("root/images/logo.svg")));
+            assert_eq!(config.head, "<meta name=\"robots\" content=\"noindex\">");
+            assert_eq!(config.default_theme, "system");
+            assert_eq!(config.site_url, Some("/mdBook/".to_owned()));
+            assert_eq!(config.git_repository_url, Some("https://github.com/azerupi/mdBook".to_owned()));
+            assert_eq!(config.edit_url_template,
+                       Some("https://github.com/azerupi/mdBook/edit/master/book-example/src/{path}".to_owned()));
+            assert_eq!(config.create_missing, true);
+            assert_eq!(config.latin1_fallback, true);
+            assert_eq!(config.check_links, true);
+            assert_eq!(config.compress_output, true);
+            assert_eq!(config.standalone, true);
+            assert_eq!(config.line_endings, "crlf");
+            assert_eq!(config.minify, true);
+            assert_eq!(config.navigate_hidden, true);
+            assert_eq!(config.git_dates, true);
+            assert_eq!(config.minify_exclude, vec!["mermaid.min.js".to_owned()]);
+            assert_eq!(config.pre_build, vec!["./generate.sh".to_owned()]);
+            assert_eq!(config.post_build,
+                       vec!["minify book".to_owned(), "rsync -a book/ server:book".to_owned()]);
+            assert_eq!(config.preprocessors, vec!["toc".to_owned()]);
+            assert_eq!(config.renderers, vec!["html".to_owned(), "pdf".to_owned()]);
+            assert_eq!(config.watch_debounce_ms, 500);
+            assert_eq!(config.watch_paths, vec![PathBuf::from("root/assets")]);
+            assert_eq!(config.watch_ignore, vec!["*.swp".to_owned(), "src/drafts".to_owned()]);
+            assert_eq!(config.test_library_paths, vec![PathBuf::from("root/target/debug/deps")]);
+            assert_eq!(config.test_externs,
+                       vec![format!("mdbook={}", Path::new("root/target/debug/libmdbook.rlib").display())]);
+            assert_eq!(config.test_edition, Some("2018".to_owned()));
+            assert_eq!(config.get_dest(), Path::new("root/output"));
+            assert_eq!(config.preserve, vec!["CNAME".to_owned(), "downloads/*.pdf".to_owned()]);
+            assert_eq!(config.theme_path, Some(PathBuf::from("root/../theme")));
+        }
+    }
+
+    #[test]
+    fn parse_wrong_type() {
+        let mut config = BookConfig::new(Path::new("root"));
+        let err = format!("{}", config.parse_from_toml_string("title = 5").unwrap_err());
+
+        assert!(err.contains("book.toml"));
+        assert!(err.contains("title"));
+    }
+
+    #[test]
+    fn default_language_not_in_languages() {
+        let mut config = BookConfig::new(Path::new("root"));
+        let err = format!("{}", config.parse_from_toml_string("languages = [\"fr\", \"de\"]").unwrap_err());
+
+        assert!(err.contains("`en`"));
+    }
+
+    #[test]
+    fn read_config_from_another_file() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let shared = tmp.path().join("shared.toml");
+        let mut f = File::create(&shared).expect("Could not create shared.toml");
+        write!(f, "title = \"Shared\"\ndest = \"output\"").expect("Could not write to shared.toml");
+
+        let mut config = BookConfig::new(Path::new("books/guide"));
+        config.read_config_file(&shared).unwrap();
+        assert_eq!(config.title, "Shared");
+        assert_eq!(config.dest, PathBuf::from("books/guide/output"));
+
+        let err = format!("{}", config.read_config_file(&tmp.path().join("missing.json")).unwrap_err());
+        assert!(err.contains("missing.json"));
+    }
+
+    #[test]
+    fn custom_keys_are_kept() {
+        let mut config = BookConfig::new(Path::new("root"));
+        assert_eq!(config.get("title"), None);
+
+        config.parse_from_toml_string("title = \"Custom\"\n\n[glossary]\nfile = \"terms.md\"\nstrict = true")
+              .unwrap();
+        assert_eq!(config.get("title"), Some(&Json::String("Custom".to_owned())));
+        assert_eq!(config.get("glossary").and_then(|glossary| glossary.find("file")),
+                   Some(&Json::String("terms.md".to_owned())));
+        assert_eq!(config.get_values().find_path(&["glossary", "strict"]), Some(&Json::Boolean(true)));
+        assert_eq!(config.get("missing"), None);
+    }
+
+    #[test]
+    fn read_config_error_contains_path() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+
+        let mut f = File::create(tmp.path().join("book.json")).expect("Could not create book.json");
+        write!(f, "{{ \"title\": ").expect("Could not write to book.json");
+
+        let mut config = BookConfig::new(tmp.path());
+        let err = format!("{}", config.read_config(tmp.path()).unwrap_err());
+
+        assert!(err.contains(&format!("{}", tmp.path().join("book.json").display())));
+    }
+}
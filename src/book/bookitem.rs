@@ -1,18 +1,29 @@
 use rustc_serialize::json::{Json, ToJson};
 use std::path::PathBuf;
 use std::collections::BTreeMap;
+use std::slice;
 
 #[derive(Debug, Clone)]
 pub enum BookItem {
     Chapter(String, Chapter), // String = section
     Affix(Chapter),
     Spacer,
+    PartTitle(String), // Heading separating groups of chapters
 }
 
 #[derive(Debug, Clone)]
 pub struct Chapter {
     pub name: String,
     pub path: PathBuf,
+    /// The markdown of the chapter, read from its file by `MDBook::build()` before the
+    /// preprocessors run. It is empty for drafts and links to web pages.
+    pub content: String,
+    /// The keys of the [front matter](../../markdown/front_matter/index.html) of the chapter, read
+    /// with its markdown
+    pub metadata: BTreeMap<String, Json>,
+    /// The chapter is marked `{hidden}` in `SUMMARY.md`: it has a page but is not listed in the
+    /// table of contents and has no section number
+    pub hidden: bool,
     pub sub_items: Vec<BookItem>,
 }
 
@@ -23,6 +34,30 @@ pub struct BookItems<'a> {
     pub stack: Vec<(&'a [BookItem], usize)>,
 }
 
+/// Depth-first iterator over mutable views of the book items, returned by
+/// [`MDBook::iter_mut()`](../struct.MDBook.html#method.iter_mut)
+pub struct BookItemsMut<'a> {
+    stack: Vec<slice::IterMut<'a, BookItem>>,
+}
+
+/// Mutable view of a `BookItem`. The sub items of a chapter are not part of it, the iterator returns
+/// them right after the chapter.
+pub enum BookItemMut<'a> {
+    Chapter(&'a mut String, ChapterMut<'a>),
+    Affix(ChapterMut<'a>),
+    Spacer,
+    PartTitle(&'a mut String),
+}
+
+/// Mutable view of the fields of a `Chapter`, except for its sub items
+pub struct ChapterMut<'a> {
+    pub name: &'a mut String,
+    pub path: &'a mut PathBuf,
+    pub content: &'a mut String,
+    pub metadata: &'a mut BTreeMap<String, Json>,
+    pub hidden: &'a mut bool,
+}
+
 
 impl Chapter {
     pub fn new(name: String, path: PathBuf) -> Self {
@@ -30,9 +65,19 @@ impl Chapter {
         Chapter {
             name: name,
             path: path,
+            content: String::new(),
+            metadata: BTreeMap::new(),
+            hidden: false,
             sub_items: vec![],
         }
     }
+
+    /// Returns `true` if the chapter links to a web page (`http://` or `https://`) instead of a
+    /// markdown file, such chapters only appear in the table of contents.
+    pub fn is_url(&self) -> bool {
+        let path = self.path.to_string_lossy();
+        path.starts_with("http://") || path.starts_with("https://")
+    }
 }
 
 
@@ -49,6 +94,120 @@ impl ToJson for Chapter {
     }
 }
 
+impl ToJson for BookItem {
+    fn to_json(&self) -> Json {
+        let mut m: BTreeMap<String, Json> = BTreeMap::new();
+
+        match *self {
+            BookItem::Chapter(ref section, ref ch) => {
+                m.insert("type".to_owned(), "chapter".to_json());
+                m.insert("section".to_owned(), section.to_json());
+                m.extend(chapter_to_json(ch));
+            },
+            BookItem::Affix(ref ch) => {
+                m.insert("type".to_owned(), "affix".to_json());
+                m.extend(chapter_to_json(ch));
+            },
+            BookItem::Spacer => {
+                m.insert("type".to_owned(), "spacer".to_json());
+            },
+            BookItem::PartTitle(ref title) => {
+                m.insert("type".to_owned(), "part".to_json());
+                m.insert("name".to_owned(), title.to_json());
+            },
+        }
+
+        m.to_json()
+    }
+}
+
+// The fields of a chapter including its sub items, flattened in the object of the book item
+fn chapter_to_json(ch: &Chapter) -> BTreeMap<String, Json> {
+    let mut m = match ch.to_json() {
+        Json::Object(m) => m,
+        _ => unreachable!(),
+    };
+    m.insert("sub_items".to_owned(), ch.sub_items.to_json());
+    m
+}
+
+/// Same as the `ToJson` implementation of `BookItem`, with the markdown of every chapter as
+/// `content`, the keys of its front matter, if it has one, as `metadata` and `hidden` for the
+/// hidden chapters. It is the tree of the `book.json` of the json renderer and of the requests to
+/// the [plugins](../../plugin/index.html).
+
+pub fn items_to_json(items: &[BookItem]) -> Json {
+    let mut json = vec![];
+
+    for item in items {
+        let mut object = match item.to_json() {
+            Json::Object(o) => o,
+            _ => unreachable!(),
+        };
+
+        match *item {
+            BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) => {
+                if ch.path != PathBuf::new() && !ch.is_url() {
+                    object.insert("content".to_owned(), ch.content.to_json());
+                }
+                if !ch.metadata.is_empty() {
+                    object.insert("metadata".to_owned(), ch.metadata.to_json());
+                }
+                if ch.hidden {
+                    object.insert("hidden".to_owned(), true.to_json());
+                }
+
+                object.insert("sub_items".to_owned(), items_to_json(&ch.sub_items));
+            },
+            BookItem::Spacer | BookItem::PartTitle(_) => {},
+        }
+
+        json.push(Json::Object(object));
+    }
+
+    Json::Array(json)
+}
+
+/// Reads back the tree written by [`items_to_json()`](fn.items_to_json.html). Only `type` is
+/// required, the other keys are empty when they are missing. The error names the first item that
+/// is not valid.
+
+pub fn items_from_json(json: &Json) -> Result<Vec<BookItem>, String> {
+    let array = match json.as_array() {
+        Some(array) => array,
+        None => return Err("the items should be an array".to_owned()),
+    };
+
+    let mut items = vec![];
+    for (i, item) in array.iter().enumerate() {
+        let string = |key: &str| item.find(key).and_then(Json::as_string).unwrap_or("").to_owned();
+
+        let chapter = || -> Result<Chapter, String> {
+            let mut ch = Chapter::new(string("name"), PathBuf::from(string("path")));
+            ch.content = string("content");
+            if let Some(metadata) = item.find("metadata").and_then(Json::as_object) {
+                ch.metadata = metadata.clone();
+            }
+            ch.hidden = item.find("hidden").and_then(Json::as_boolean).unwrap_or(false);
+            if let Some(sub_items) = item.find("sub_items") {
+                ch.sub_items = try!(items_from_json(sub_items).map_err(|e| format!("{}, in `{}`", e, ch.name)));
+            }
+            Ok(ch)
+        };
+
+        items.push(match item.find("type").and_then(Json::as_string) {
+            Some("chapter") => BookItem::Chapter(string("section"), try!(chapter())),
+            Some("affix") => BookItem::Affix(try!(chapter())),
+            Some("spacer") => BookItem::Spacer,
+            Some("part") => BookItem::PartTitle(string("name")),
+            Some(other) => return Err(format!("item {} has an unknown type `{}`", i + 1, other)),
+            None => return Err(format!("item {} has no type", i + 1)),
+        });
+    }
+
+    Ok(items)
+}
+
 
 
 // Shamelessly copied from Rustbook
@@ -75,7 +234,7 @@ impl<'a> Iterator for BookItems<'a> {
                         self.items = &ch.sub_items[..];
                         self.current_index = 0;
                     },
-                    BookItem::Spacer => {
+                    BookItem::Spacer | BookItem::PartTitle(_) => {
                         self.current_index += 1;
                     },
                 }
@@ -85,3 +244,118 @@ impl<'a> Iterator for BookItems<'a> {
         }
     }
 }
+
+
+impl<'a> BookItemsMut<'a> {
+    pub fn new(items: &'a mut [BookItem]) -> Self {
+        BookItemsMut { stack: vec![items.iter_mut()] }
+    }
+}
+
+impl<'a> Iterator for BookItemsMut<'a> {
+    type Item = BookItemMut<'a>;
+
+    fn next(&mut self) -> Option<BookItemMut<'a>> {
+        loop {
+            let item = match self.stack.last_mut() {
+                None => return None,
+                Some(items) => items.next(),
+            };
+
+            let item = match item {
+                None => {
+                    self.stack.pop();
+                    continue;
+                },
+                Some(item) => item,
+            };
+
+            // The borrow of the chapter is split, its sub items are visited next
+            return Some(match *item {
+                BookItem::Chapter(ref mut section, ref mut ch) => {
+                    let (ch, sub_items) = split_chapter(ch);
+                    self.stack.push(sub_items.iter_mut());
+                    BookItemMut::Chapter(section, ch)
+                },
+                BookItem::Affix(ref mut ch) => {
+                    let (ch, sub_items) = split_chapter(ch);
+                    self.stack.push(sub_items.iter_mut());
+                    BookItemMut::Affix(ch)
+                },
+                BookItem::Spacer => BookItemMut::Spacer,
+                BookItem::PartTitle(ref mut title) => BookItemMut::PartTitle(title),
+            });
+        }
+    }
+}
+
+fn split_chapter(ch: &mut Chapter) -> (ChapterMut, &mut Vec<BookItem>) {
+    let Chapter { ref mut name, ref mut path, ref mut content, ref mut metadata, ref mut hidden, ref mut sub_items } =
+        *ch;
+    (ChapterMut {
+        name: name,
+        path: path,
+        content: content,
+        metadata: metadata,
+        hidden: hidden,
+    },
+     sub_items)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    #[test]
+    fn iter_mut_is_depth_first() {
+        let mut parent = Chapter::new("a".to_owned(), PathBuf::from("a.md"));
+        parent.sub_items.push(BookItem::Chapter("1.1.".to_owned(), Chapter::new("b".to_owned(), PathBuf::from("b.md"))));
+        let mut items = vec![BookItem::Chapter("1.".to_owned(), parent),
+                             BookItem::Spacer,
+                             BookItem::Affix(Chapter::new("c".to_owned(), PathBuf::from("c.md")))];
+
+        for item in BookItemsMut::new(&mut items) {
+            if let BookItemMut::Chapter(_, ch) = item {
+                ch.name.push_str(" (chapter)");
+            }
+        }
+
+        let names = BookItems { items: &items, current_index: 0, stack: vec![] }
+                        .filter_map(|item| match *item {
+                            BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) => Some(ch.name.clone()),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a (chapter)", "b (chapter)", "c"]);
+    }
+
+    #[test]
+    fn items_contain_tree_and_markdown() {
+        let mut sub = Chapter::new("Sub".to_owned(), PathBuf::from("sub.md"));
+        sub.content = "# Sub".to_owned();
+        sub.metadata.insert("draft".to_owned(), true.to_json());
+
+        let mut chapter = Chapter::new("Chapter".to_owned(), PathBuf::new());
+        chapter.sub_items.push(BookItem::Chapter("1.1.".to_owned(), sub));
+        let items = vec![BookItem::Chapter("1.".to_owned(), chapter), BookItem::Spacer];
+
+        let json = items_to_json(&items);
+
+        assert_eq!(json.to_string(),
+                   r##"[{"name":"Chapter","path":"","section":"1.","sub_items":[{"content":"# Sub","metadata":{"draft":true},"name":"Sub","path":"sub.md","section":"1.1.","sub_items":[],"type":"chapter"}],"type":"chapter"},{"type":"spacer"}]"##);
+        assert_eq!(items_to_json(&items_from_json(&json).unwrap()), json);
+    }
+
+    #[test]
+    fn invalid_items() {
+        let json = Json::from_str(r#"[{"type": "part", "name": "Part"}, {"type": "chapter", "name": "A",
+                                      "sub_items": [{"type": "appendix"}]}]"#)
+                       .unwrap();
+
+        assert_eq!(items_from_json(&json).unwrap_err(), "item 1 has an unknown type `appendix`, in `A`");
+        assert_eq!(items_from_json(&Json::Null).unwrap_err(), "the items should be an array");
+    }
+}
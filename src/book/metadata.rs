@@ -40,6 +40,11 @@ impl BookMetadata {
         self
     }
 
+    pub fn set_language(&mut self, language: Language) -> &mut Self {
+        self.language = language;
+        self
+    }
+
     pub fn add_author(&mut self, author: Author) -> &mut Self {
         self.authors.push(author);
         self
@@ -61,6 +66,19 @@ impl Author {
 }
 
 
+impl Language {
+    pub fn new(name: &str, code: &str) -> Self {
+        Language {
+            name: name.to_owned(),
+            code: code.to_owned(),
+        }
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+    pub fn code(&self) -> &str { &self.code }
+}
+
+
 impl Default for Language {
     fn default() -> Self {
         Language {
@@ -2,39 +2,110 @@ pub mod bookitem;
 pub mod bookconfig;
 pub mod metadata;
 pub mod book;
+pub mod progress;
 
-pub use self::bookitem::{BookItem, BookItems};
+pub use self::bookitem::{BookItem, BookItems, BookItemsMut, BookItemMut, ChapterMut};
 pub use self::bookconfig::BookConfig;
+pub use self::progress::{Progress, NoProgress};
 
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
 use std::fs::{self, File};
 use std::error::Error;
-use std::io;
-use std::io::Write;
-use std::io::ErrorKind;
-use std::process::Command;
+use std::io::{self, Write};
+use std::mem;
+use std::cmp;
+use std::process::{Command, Output, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rustc_serialize::json::{Json, ToJson};
 
 use {theme, markdown, utils};
-use renderer::{Renderer, HtmlHandlebars};
+use errors::MdBookError;
+use renderer::{self, Renderer, HtmlHandlebars, JsonRenderer, EpubRenderer, ExternalRenderer, CACHE_FILE};
+use preprocess::{Preprocessor, IncludePreprocessor, ExternalPreprocessor};
+
+
+/// Files of the starter book written by [`MDBook::init_with_examples()`](struct.MDBook.html#method.init_with_examples)
+const EXAMPLES: &'static [(&'static str, &'static str)] = &[
+    ("SUMMARY.md", include_str!("template/SUMMARY.md")),
+    ("introduction.md", include_str!("template/introduction.md")),
+    ("chapter_1.md", include_str!("template/chapter_1.md")),
+    ("chapter_2.md", include_str!("template/chapter_2.md")),
+];
+
+/// Extensions of the files of the output that get a gzip copy with `compress_output`
+const COMPRESSED_EXTENSIONS: &'static [&'static str] = &["html", "css", "js", "json"];
+
+/// Size in bytes under which the files of the output are not compressed
+const COMPRESS_MIN_SIZE: u64 = 1024;
+
+/// Environment variable that overrides the source directory of the configuration file
+pub const SRC_VAR: &'static str = "MDBOOK_SRC";
+
+/// Environment variable that overrides the destination directory of the configuration file
+pub const DEST_VAR: &'static str = "MDBOOK_DEST";
+
+/// What a build would do to the destination directory, returned by
+/// [`MDBook::build_dry_run()`](struct.MDBook.html#method.build_dry_run). The paths are absolute.
+#[derive(Debug, Default)]
+pub struct DryRun {
+    /// Files and directories removed by the clean step that precedes a full build
+    pub removed: Vec<PathBuf>,
+    /// Files written where there is no file yet
+    pub created: Vec<PathBuf>,
+    /// Existing files that are written again
+    pub overwritten: Vec<PathBuf>,
+    /// Names of the renderers whose files can not be listed without running them, the plugins
+    pub unlisted: Vec<String>,
+}
 
+/// A chapter of the book with a page in the output, returned by
+/// [`MDBook::chapters()`](struct.MDBook.html#method.chapters)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChapterInfo {
+    /// Section number of the chapter, `1.2.`, `None` for the unnumbered chapters
+    pub section: Option<String>,
+    /// Title of the chapter, the `title` of its front matter when it has one
+    pub title: String,
+    /// Markdown file of the chapter, relative to the source directory
+    pub path: PathBuf,
+    /// Page of the chapter, relative to the destination directory
+    pub output_path: PathBuf,
+}
 
 pub struct MDBook {
     root: PathBuf,
+    config_path: Option<PathBuf>,
+    // The options of the book, read from the configuration file and changed by the setters
+    config: BookConfig,
+    // Output and source directories of the language being built
     dest: PathBuf,
     src: PathBuf,
-
-    title: String,
-    author: String,
-    description: String,
-
-    default_language: &'static str,
-    books: HashMap<&'static str, book::Book>,
+    // Language being built, the default one outside of a build
+    language: String,
+    books: HashMap<String, book::Book>,
 
     pub content: Vec<BookItem>,
-    renderer: Box<Renderer>,
-
+    // The renderers of the `renderers` of the configuration, the html one when it has none,
+    // replaced when it is read again
+    configured_renderers: Vec<Box<Renderer>>,
+    // The renderers added with `add_renderer()`, they run after the configured ones
+    renderers: Vec<Box<Renderer>>,
+    // Time spent in every renderer during the last build, for all the languages
+    render_times: Vec<Duration>,
+    preprocessors: Vec<Box<Preprocessor>>,
+    // The plugins of the `preprocessors` of the configuration, replaced when it is read again
+    configured_preprocessors: Vec<Box<Preprocessor>>,
+    plugin_path: Vec<PathBuf>,
+
+    theme_path: Option<PathBuf>,
     livereload: Option<String>,
+    template_data: BTreeMap<String, Json>,
 }
 
 impl MDBook {
@@ -48,31 +119,41 @@ impl MDBook {
     pub fn new(root: &Path) -> MDBook {
 
         if !root.exists() || !root.is_dir() {
-            output!("{:?} No directory with that name", root);
+            warn!("{:?} No directory with that name", root);
         }
 
+        let config = BookConfig::new(root);
+
         MDBook {
             root: root.to_owned(),
-            dest: PathBuf::from("book"),
-            src: PathBuf::from("src"),
-
-            title: String::new(),
-            author: String::new(),
-            description: String::new(),
-
-            default_language: "en",
+            config_path: None,
+            dest: config.dest.clone(),
+            src: config.src.clone(),
+            language: String::from("en"),
             books: HashMap::new(),
 
             content: vec![],
-            renderer: Box::new(HtmlHandlebars::new()),
-
+            configured_renderers: vec![Box::new(HtmlHandlebars::new())],
+            renderers: vec![],
+            render_times: vec![],
+            preprocessors: vec![Box::new(IncludePreprocessor)],
+            configured_preprocessors: vec![],
+            plugin_path: vec![],
+
+            theme_path: None,
             livereload: None,
+            template_data: BTreeMap::new(),
+            config: config,
         }
     }
 
     /// Returns a flat depth-first iterator over the elements of the book, it returns an [BookItem enum](bookitem.html):
     /// `(section: String, bookitem: &BookItem)`
     ///
+    /// The items come in the order of `SUMMARY.md`, the unnumbered `Affix` chapters included: prefix
+    /// chapters before the first numbered one and suffix chapters after the last one. Every chapter
+    /// is followed by its sub-chapters.
+    ///
     /// ```no_run
     /// # extern crate mdbook;
     /// # use mdbook::MDBook;
@@ -85,6 +166,7 @@ impl MDBook {
     ///         &BookItem::Chapter(ref section, ref chapter) => {},
     ///         &BookItem::Affix(ref chapter) => {},
     ///         &BookItem::Spacer => {},
+    ///         &BookItem::PartTitle(ref title) => {},
     ///     }
     /// }
     ///
@@ -106,6 +188,101 @@ impl MDBook {
         }
     }
 
+    /// Same depth-first traversal as [`iter()`](#method.iter), but the items can be modified. The
+    /// chapters are returned as [mutable views](bookitem/struct.ChapterMut.html) of their fields,
+    /// their sub items follow them.
+    ///
+    /// ```no_run
+    /// # extern crate mdbook;
+    /// # use mdbook::MDBook;
+    /// # use mdbook::book::BookItemMut;
+    /// # use std::path::Path;
+    /// # fn main() {
+    /// # let mut book = MDBook::new(Path::new("mybook"));
+    /// for item in book.iter_mut() {
+    ///     if let BookItemMut::Chapter(_, chapter) = item {
+    ///         chapter.name.make_ascii_uppercase();
+    ///     }
+    /// }
+    /// # }
+    /// ```
+
+    pub fn iter_mut(&mut self) -> BookItemsMut {
+        BookItemsMut::new(&mut self.content[..])
+    }
+
+    /// Returns the chapters that have a page in the output, in the order of
+    /// [`iter()`](#method.iter), with their section number, their title and the paths of their
+    /// markdown file and of their page. The drafts and the links to web pages are left out.
+    ///
+    /// Nothing needs to be rendered: when the book was not built yet, `SUMMARY.md` is read from the
+    /// source directory. The list is empty when it cannot be read. The titles come from the front
+    /// matter only once the chapters were read by a build.
+    ///
+    /// ```no_run
+    /// # extern crate mdbook;
+    /// # use mdbook::MDBook;
+    /// # use std::path::Path;
+    /// # fn main() {
+    /// let book = MDBook::new(Path::new("mybook")).read_config().unwrap();
+    /// for chapter in book.chapters() {
+    ///     println!("{} {} -> {}", chapter.section.unwrap_or_default(), chapter.title,
+    ///              chapter.output_path.display());
+    /// }
+    /// # }
+    /// ```
+
+    pub fn chapters(&self) -> Vec<ChapterInfo> {
+        let parsed;
+        let items = if self.content.is_empty() {
+            parsed = markdown::summary::construct_bookitems(&self.root.join(&self.src), &self.config.summary_files)
+                         .unwrap_or_default();
+            &parsed[..]
+        } else {
+            &self.content[..]
+        };
+        let items = BookItems {
+            items: items,
+            current_index: 0,
+            stack: Vec::new(),
+        };
+
+        items.filter_map(|item| {
+                 let (section, ch) = match *item {
+                     BookItem::Chapter(_, ref ch) if ch.hidden => (None, ch),
+                     BookItem::Chapter(ref section, ref ch) => (Some(section.clone()), ch),
+                     BookItem::Affix(ref ch) => (None, ch),
+                     BookItem::Spacer | BookItem::PartTitle(_) => return None,
+                 };
+                 if ch.path == PathBuf::new() || ch.is_url() {
+                     return None;
+                 }
+
+                 let title = match ch.metadata.get("title") {
+                     Some(&Json::String(ref title)) => title.clone(),
+                     _ => ch.name.clone(),
+                 };
+                 Some(ChapterInfo {
+                     section: section,
+                     title: title,
+                     path: ch.path.clone(),
+                     output_path: self.page_path(&ch.path),
+                 })
+             })
+             .collect()
+    }
+
+    /// Path of the page of the chapter at `path`, relative to the destination directory:
+    /// `intro.html`, or `intro/index.html` with [clean urls](#method.set_clean_urls)
+
+    pub fn page_path(&self, path: &Path) -> PathBuf {
+        if self.config.clean_urls && path.file_stem().map_or(false, |stem| stem != "index") {
+            path.with_extension("").join("index.html")
+        } else {
+            path.with_extension("html")
+        }
+    }
+
     /// `init()` creates some boilerplate files and directories to get you started with your book.
     ///
     /// ```text
@@ -117,39 +294,130 @@ impl MDBook {
     /// ```
     ///
     /// It uses the paths given as source and output directories and adds a `SUMMARY.md` and a
-    /// `chapter_1.md` to the source directory.
+    /// `chapter_1.md` to the source directory. For a translated book, they are added to the sub
+    /// directory of every language in the source directory.
 
-    pub fn init(&mut self) -> Result<(), Box<Error>> {
+    pub fn init(&mut self) -> Result<(), MdBookError> {
 
         debug!("[fn]: init");
 
         if !self.root.exists() {
             fs::create_dir_all(&self.root).unwrap();
-            output!("{:?} created", &self.root);
+            info!("{:?} created", &self.root);
+        }
+
+        self.for_each_language(|book| book.init_language())
+    }
+
+    /// Same as [`init()`](#method.init), but the book starts with example chapters instead of an
+    /// empty `chapter_1.md`: an introduction and two chapters that show the markdown the book
+    /// supports, lists, tables, footnotes, code blocks and callouts, to learn by editing them.
+    ///
+    /// The examples are only written in the source directories that have no `SUMMARY.md` yet, and
+    /// the existing files are never overwritten.
+
+    pub fn init_with_examples(&mut self) -> Result<(), MdBookError> {
+        debug!("[fn]: init_with_examples");
+
+        try!(self.for_each_language(|book| {
+            if book.summary_path().exists() {
+                return Ok(());
+            }
+            try!(fs::create_dir_all(&book.src));
+
+            for &(name, content) in EXAMPLES {
+                let path = if name == "SUMMARY.md" {
+                    book.summary_path()
+                } else {
+                    book.src.join(name)
+                };
+                if !path.exists() {
+                    debug!("[*]: Writing the example {:?}", path);
+                    let mut f = try!(File::create(path));
+                    try!(f.write_all(content.as_bytes()));
+                }
+            }
+            Ok(())
+        }));
+
+        self.init()
+    }
+
+    /// Creates a brand new book in `root` and returns it with its configuration read.
+    ///
+    /// Unlike [`init()`](#method.init), which fills in what is missing in an existing book, it
+    /// fails when `root` exists and is not an empty directory. It creates `root` with a
+    /// `book.json` whose title is the name of the directory, the source and output directories,
+    /// a `SUMMARY.md` and a `chapter_1.md`, and a `.gitignore` for the output.
+    ///
+    /// ```no_run
+    /// # extern crate mdbook;
+    /// # use mdbook::MDBook;
+    /// # use std::path::Path;
+    /// # fn main() {
+    /// let mut book = MDBook::create(Path::new("my-book")).unwrap();
+    /// book.build().unwrap();
+    /// # }
+    /// ```
+
+    pub fn create(root: &Path) -> Result<MDBook, MdBookError> {
+        debug!("[fn]: create");
+
+        if root.exists() {
+            let empty = root.is_dir() && try!(fs::read_dir(root)).next().is_none();
+            if !empty {
+                return Err(MdBookError::Io(io::Error::new(io::ErrorKind::AlreadyExists,
+                                                          format!("{:?} already exists and is not an empty \
+                                                                   directory",
+                                                                  root))));
+            }
         }
 
+        try!(fs::create_dir_all(root));
+
+        let title = root.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut config = BTreeMap::new();
+        config.insert("title".to_owned(), title.to_json());
+        config.insert("author".to_owned(), "".to_json());
+        config.insert("description".to_owned(), "".to_json());
+        let mut f = try!(File::create(root.join("book.json")));
+        try!(writeln!(f, "{}", config.to_json().pretty()));
+
+        let mut book = try!(MDBook::new(root).read_config());
+        try!(book.init());
+        book.create_gitignore();
+
+        Ok(book)
+    }
+
+    // Creates the source and output directories, `SUMMARY.md` and the missing chapter files of the
+    // language being built
+    fn init_language(&mut self) -> Result<(), MdBookError> {
         {
 
             if !self.dest.exists() {
                 debug!("[*]: {:?} does not exist, trying to create directory", self.dest);
-                try!(fs::create_dir(&self.dest));
+                try!(fs::create_dir_all(&self.dest));
             }
 
             if !self.src.exists() {
                 debug!("[*]: {:?} does not exist, trying to create directory", self.src);
-                try!(fs::create_dir(&self.src));
+                try!(fs::create_dir_all(&self.src));
             }
 
-            let summary = self.src.join("SUMMARY.md");
+            let summary = self.summary_path();
 
             if !summary.exists() {
 
                 // Summary does not exist, create it
 
-                debug!("[*]: {:?} does not exist, trying to create SUMMARY.md", src.join("SUMMARY.md"));
-                let mut f = try!(File::create(&self.src.join("SUMMARY.md")));
+                debug!("[*]: {:?} does not exist, trying to create it", summary);
+                if let Some(parent) = summary.parent() {
+                    try!(fs::create_dir_all(parent));
+                }
+                let mut f = try!(File::create(&summary));
 
-                debug!("[*]: Writing to SUMMARY.md");
+                debug!("[*]: Writing to {:?}", summary);
 
                 try!(writeln!(f, "# Summary"));
                 try!(writeln!(f, ""));
@@ -164,8 +432,15 @@ impl MDBook {
         for item in self.iter() {
             debug!("[*]: item: {:?}", item);
             match *item {
-                BookItem::Spacer => continue,
+                BookItem::Spacer | BookItem::PartTitle(_) => continue,
                 BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) => {
+                    // Only local chapters get a file, the others are links to outside resources
+                    if ch.is_url() || ch.path.is_absolute() {
+                        warn!("{:?} is not a path inside of the source directory, no file is created for it",
+                                ch.path);
+                        continue;
+                    }
+
                     if ch.path != PathBuf::new() {
                         let path = self.src.join(&ch.path);
 
@@ -192,9 +467,10 @@ impl MDBook {
         if !gitignore.exists() {
             // Gitignore does not exist, create it
 
-            // Because of `src/book/mdbook.rs#L37-L39`, `dest` will always start with `root`. If it
-            // is not, `strip_prefix` will return an Error.
+            // A destination outside of the book, absolute or set from the current directory, is not
+            // ignored by the repository of the book
             if !self.get_dest().starts_with(&self.root) {
+                debug!("[*]: {:?} is outside of the book, no .gitignore is created", self.get_dest());
                 return;
             }
 
@@ -215,257 +491,2710 @@ impl MDBook {
     }
 
     /// The `build()` method is the one where everything happens. First it parses `SUMMARY.md` to
-    /// construct the book's structure in the form of a `Vec<BookItem>` and then calls `render()`
-    /// method of the current renderer.
+    /// construct the book's structure in the form of a `Vec<BookItem>` and reads the markdown of the
+    /// chapters. Then it runs the preprocessors, which can modify the book, and calls the `render()`
+    /// method of every renderer.
     ///
-    /// It is the renderer who generates all the output files.
-    pub fn build(&mut self) -> Result<(), Box<Error>> {
-        debug!("[fn]: build");
-
-        try!(self.init());
+    /// It is the renderers who generate all the output files. When there is more than one
+    /// renderer, each of them writes its output to its own sub directory of the destination, named
+    /// after the renderer (`html`, `epub`, ...), so that they don't overwrite each other's files.
+    /// Two renderers with the same name are a [`Config`](../errors/enum.MdBookError.html) error.
+    ///
+    /// The html renderer keeps a cache of the rendered chapters in its output directory. When it is
+    /// there, the output of the previous build is updated in place: only the chapters that changed
+    /// are converted and only the pages that changed are rewritten. Otherwise, for example after
+    /// [`clean()`](#method.clean), the output directory is cleaned and the book fully rebuilt.
+    ///
+    /// When [`languages`](#method.set_languages) are set, every language is built from its sub
+    /// directory of the source directory (`src/en/SUMMARY.md`, `src/fr/SUMMARY.md`, ...). The
+    /// default language is written at the root of the destination and the other ones in a sub
+    /// directory named after the language (`book/fr/`, ...).
+    ///
+    /// The [`pre_build`](#method.set_pre_build) commands run first and the
+    /// [`post_build`](#method.set_post_build) commands once the book is rendered. A command that
+    /// fails stops the build with a [`Command`](../errors/enum.MdBookError.html) error.
+    pub fn build(&mut self) -> Result<(), MdBookError> {
+        self.build_with_progress(&NoProgress)
+    }
 
-        // Clean output directory
-        try!(utils::fs::remove_dir_content(&self.dest));
+    /// Builds the book like [`build()`](#method.build) and reports its progress to `progress`: when
+    /// every renderer starts and finishes, and every chapter rendered by the html renderer.
 
-        try!(self.renderer.render(&self));
+    pub fn build_with_progress(&mut self, progress: &Progress) -> Result<(), MdBookError> {
+        debug!("[fn]: build");
 
-        Ok(())
-    }
+        try!(self.check_renderer_names());
 
+        for command in &self.config.pre_build {
+            try!(self.run_build_command(command));
+        }
 
-    pub fn get_gitignore(&self) -> PathBuf {
-        self.root.join(".gitignore")
-    }
+        // A chapter whose file was renamed or removed fails the build instead of getting an empty
+        // page, unless the missing files are to be created
+        let missing = try!(self.missing_chapter_files());
+        if !missing.is_empty() {
+            if !self.config.create_missing {
+                return Err(MdBookError::MissingChapters(missing));
+            }
+            for path in &missing {
+                warn!("{:?} does not exist, it is created", path);
+            }
+        }
 
-    pub fn copy_theme(&self) -> Result<(), Box<Error>> {
-        debug!("[fn]: copy_theme");
+        for path in try!(self.unreferenced_files()) {
+            warn!("{:?} is not a chapter of SUMMARY.md", path);
+        }
 
-        let theme_dir = self.src.join("theme");
+        try!(self.init());
 
-        if !theme_dir.exists() {
-            debug!("[*]: {:?} does not exist, trying to create directory", theme_dir);
-            try!(fs::create_dir(&theme_dir));
+        // Clean output directory, unless it can be updated in place
+        if !self.is_cached() {
+            try!(self.clean());
         }
 
-        // index.hbs
-        let mut index = try!(File::create(&theme_dir.join("index.hbs")));
-        try!(index.write_all(theme::INDEX));
+        try!(self.parse_books());
 
-        // book.css
-        let mut css = try!(File::create(&theme_dir.join("book.css")));
-        try!(css.write_all(theme::CSS));
+        self.render_times = vec![Duration::from_secs(0); self.all_renderers().len()];
+        try!(self.for_each_language(|book| book.build_language(progress)));
 
-        // favicon.png
-        let mut favicon = try!(File::create(&theme_dir.join("favicon.png")));
-        try!(favicon.write_all(theme::FAVICON));
+        let times = self.all_renderers()
+                        .into_iter()
+                        .zip(&self.render_times)
+                        .map(|(renderer, time)| format!("{}: {}", renderer.name(), format_duration(*time)))
+                        .collect::<Vec<_>>();
+        info!("Rendered the book ({})", times.join(", "));
 
-        // book.js
-        let mut js = try!(File::create(&theme_dir.join("book.js")));
-        try!(js.write_all(theme::JS));
+        if self.config.compress_output {
+            let compressed = try!(utils::fs::gzip_files(&self.dest, COMPRESSED_EXTENSIONS, COMPRESS_MIN_SIZE));
+            info!("Compressed {} files", compressed.len());
+        }
 
-        // highlight.css
-        let mut highlight_css = try!(File::create(&theme_dir.join("highlight.css")));
-        try!(highlight_css.write_all(theme::HIGHLIGHT_CSS));
+        for command in &self.config.post_build {
+            try!(self.run_build_command(command));
+        }
 
-        // highlight.js
-        let mut highlight_js = try!(File::create(&theme_dir.join("highlight.js")));
-        try!(highlight_js.write_all(theme::HIGHLIGHT_JS));
+        Ok(())
+    }
 
+    // Runs a `pre_build` or `post_build` command with the shell, from the root of the book and with
+    // the destination in `MDBOOK_DEST`. Its standard output is shown, its standard error is kept
+    // for the error when it fails.
+    fn run_build_command(&self, command: &str) -> Result<(), MdBookError> {
+        info!("Running `{}`", command);
+
+        let output = try!(utils::shell(command)
+                              .current_dir(&self.root)
+                              .env(DEST_VAR, &self.dest)
+                              .stdout(Stdio::inherit())
+                              .output());
+        if !output.status.success() {
+            return Err(MdBookError::Command {
+                command: command.to_owned(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        }
         Ok(())
     }
 
-    /// Parses the `book.json` file (if it exists) to extract the configuration parameters.
-    /// The `book.json` file should be in the root directory of the book.
-    /// The root directory is the one specified when creating a new `MDBook`
-    ///
-    /// ```no_run
-    /// # extern crate mdbook;
-    /// # use mdbook::MDBook;
-    /// # use std::path::Path;
-    /// # fn main() {
-    /// let mut book = MDBook::new(Path::new("root_dir"));
-    /// # }
-    /// ```
-    ///
-    /// In this example, `root_dir` will be the root directory of our book and is specified in function
-    /// of the current working directory by using a relative path instead of an absolute path.
+    /// Returns the files of the chapters of `SUMMARY.md` that do not exist, in every language. The
+    /// chapters without a file (drafts) and the links to web pages are not checked. A missing
+    /// `SUMMARY.md` is not an error, [`init()`](#method.init) creates it.
 
-    pub fn read_config(mut self) -> Self {
+    pub fn missing_chapter_files(&mut self) -> Result<Vec<PathBuf>, MdBookError> {
+        let mut missing = vec![];
+
+        try!(self.for_each_language(|book| {
+            if !book.summary_path().exists() {
+                return Ok(());
+            }
+            try!(book.parse_summary());
+
+            for item in book.iter() {
+                match *item {
+                    BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) => {
+                        let path = book.src.join(&ch.path);
+                        if ch.path != PathBuf::new() && !ch.is_url() && !path.is_file() {
+                            missing.push(path);
+                        }
+                    },
+                    BookItem::Spacer | BookItem::PartTitle(_) => {},
+                }
+            }
+            Ok(())
+        }));
 
-        let config = BookConfig::new(&self.root)
-                                .read_config(&self.root)
-                                .to_owned();
+        Ok(missing)
+    }
 
-        // Temporary
-        let mut english = book::Book::new(&config.title);
+    /// Returns the markdown files of the source directory that are not a chapter of `SUMMARY.md`,
+    /// in every language, so that forgotten chapters can be cleaned up. `SUMMARY.md`, `404.md`,
+    /// the theme directory and an output directory inside of the source directory are left out.
 
-        english.mut_metadata()
-               .set_description(&config.description)
-               .add_author(metadata::Author::new(&config.author));
+    pub fn unreferenced_files(&mut self) -> Result<Vec<PathBuf>, MdBookError> {
+        let mut unreferenced = vec![];
 
-        self.books.insert("en", english);
-        self.default_language = "en";
+        try!(self.for_each_language(|book| {
+            if !book.summary_path().exists() {
+                return Ok(());
+            }
+            try!(book.parse_summary());
+
+            let chapters = book.iter()
+                               .filter_map(|item| match *item {
+                                   BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) => {
+                                       // `./intro.md` and `intro.md` are the same file
+                                       let path = ch.path
+                                                    .components()
+                                                    .filter(|c| *c != Component::CurDir)
+                                                    .collect::<PathBuf>();
+                                       Some(book.src.join(path))
+                                   },
+                                   BookItem::Spacer | BookItem::PartTitle(_) => None,
+                               })
+                               .collect::<HashSet<_>>();
+
+            let mut skipped = book.config.summary_files.iter().map(|file| book.src.join(file)).collect::<Vec<_>>();
+            skipped.extend(vec![book.src.join("404.md"), book.get_theme_path(), book.dest.clone()]);
+            for path in try!(markdown_files(&book.src, &skipped)) {
+                if !chapters.contains(&path) {
+                    unreferenced.push(path);
+                }
+            }
+            Ok(())
+        }));
 
-        self.title = config.title;
-        self.description = config.description;
-        self.author = config.author;
+        Ok(unreferenced)
+    }
 
-        self.dest = config.dest;
-        self.src = config.src;
+    /// Renders the book with the html renderer and returns the generated files, keyed by their path
+    /// relative to the destination directory, without writing anything to disk. This is meant for
+    /// embedding mdBook in a server that keeps the pages in memory.
+    ///
+    /// The book is read and preprocessed like in [`build()`](#method.build), but the configured
+    /// renderers are not used and the source directory is not initialized. Every page is rendered,
+    /// the cache of a previous build is ignored. The pages of a translated book are prefixed with
+    /// their language like on disk (`fr/index.html`, ...).
+    pub fn render_to_map(&mut self) -> Result<HashMap<PathBuf, Vec<u8>>, MdBookError> {
+        debug!("[fn]: render_to_map");
+
+        try!(self.parse_books());
+
+        let dest = self.dest.clone();
+        let mut files = HashMap::new();
+        try!(self.for_each_language(|book| {
+            try!(book.prepare_language(true));
+
+            let prefix = book.dest.strip_prefix(&dest).map(|p| p.to_owned()).unwrap_or_default();
+            let rendered = try!(HtmlHandlebars::new().render_to_map(book).map_err(MdBookError::Render));
+            for (path, content) in rendered {
+                files.insert(prefix.join(path), content);
+            }
+            Ok(())
+        }));
 
-        self
+        Ok(files)
     }
 
-    /// You can change the default renderer to another one by using this method. The only requirement
-    /// is for your renderer to implement the [Renderer trait](../../renderer/renderer/trait.Renderer.html)
+    /// Renders the html page of a single chapter, the item of [`iter()`](#method.iter) being edited
+    /// for example, without rendering the rest of the book. It is much faster than a build, which
+    /// makes it handy for a live preview in an editor.
+    ///
+    /// The markdown is the `content` of the chapter as it is: set it to the text being edited, it is
+    /// neither read from the file nor preprocessed. The page has the sidebar and the theme of a
+    /// [`build()`](#method.build), but no links to the previous and next chapters. Items that are
+    /// not a chapter with a file, like drafts or links to web pages, are an error.
     ///
     /// ```no_run
-    /// extern crate mdbook;
-    /// use mdbook::MDBook;
-    /// use mdbook::renderer::HtmlHandlebars;
+    /// # extern crate mdbook;
+    /// # use mdbook::{MDBook, BookItem};
     /// # use std::path::Path;
+    /// # fn main() {
+    /// let mut book = MDBook::new(Path::new("mybook")).read_config().unwrap();
+    /// book.build().unwrap();
     ///
-    /// fn main() {
-    ///     let mut book = MDBook::new(Path::new("mybook"))
-    ///                         .set_renderer(Box::new(HtmlHandlebars::new()));
-    ///
-    ///     // In this example we replace the default renderer by the default renderer...
-    ///     // Don't forget to put your renderer in a Box
+    /// let mut item = book.iter().next().unwrap().clone();
+    /// if let BookItem::Chapter(_, ref mut chapter) = item {
+    ///     chapter.content = "# Chapter 1\n\nEdited text".to_owned();
     /// }
+    /// let html = book.render_chapter(&item).unwrap();
+    /// # }
     /// ```
-    ///
-    /// **note:** Don't forget to put your renderer in a `Box` before passing it to `set_renderer()`
 
-    pub fn set_renderer(mut self, renderer: Box<Renderer>) -> Self {
-        self.renderer = renderer;
-        self
+    pub fn render_chapter(&self, item: &BookItem) -> Result<String, MdBookError> {
+        debug!("[fn]: render_chapter");
+
+        match *item {
+            BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) if ch.path != PathBuf::new() && !ch.is_url() => {
+                HtmlHandlebars::new().render_chapter(self, ch).map_err(MdBookError::Render)
+            },
+            _ => {
+                Err(MdBookError::Io(io::Error::new(io::ErrorKind::InvalidInput,
+                                                   "Only the chapters with a file have a page")))
+            },
+        }
     }
 
-    pub fn test(&mut self) -> Result<(), Box<Error>> {
-        // read in the chapters
-        try!(self.parse_summary());
-        for item in self.iter() {
+    /// Reports what [`build()`](#method.build) would do to the destination directory, without
+    /// writing or removing anything: the content removed by the clean step, and the files of every
+    /// renderer that would be created or overwritten, in the output directory of the renderer. The
+    /// chapters are parsed and rendered in memory like with [`render_to_map()`](#method.render_to_map),
+    /// with the cache of the previous build: the pages that the build would keep as they are are
+    /// not listed. With [`compress_output`](#method.set_compress_output) the gzip copies are listed
+    /// too, those of the written files whatever their size.
+    ///
+    /// Nothing is run: the preprocessors of the configuration, which are external executables, are
+    /// skipped, and the files of the renderers that are plugins are not listed, their names are in
+    /// [`unlisted`](struct.DryRun.html#structfield.unlisted). The listed files are those of the
+    /// book as the built-in preprocessors leave it.
+    ///
+    /// It fails in the same cases as a build, for example when the destination is the root of the
+    /// book. The files of the missing chapters are never created, so it also fails when there are
+    /// some, even when [`create_missing`](#method.set_create_missing) is set.
 
-            match *item {
-                BookItem::Chapter(_, ref ch) => {
-                    if ch.path != PathBuf::new() {
+    pub fn build_dry_run(&mut self) -> Result<DryRun, MdBookError> {
+        debug!("[fn]: build_dry_run");
 
-                        let path = self.get_src().join(&ch.path);
+        let missing = try!(self.missing_chapter_files());
+        if !missing.is_empty() {
+            return Err(MdBookError::MissingChapters(missing));
+        }
 
-                        println!("[*]: Testing file: {:?}", path);
+        try!(self.check_renderer_names());
 
-                        let output_result = Command::new("rustdoc")
-                                                .arg(&path)
-                                                .arg("--test")
-                                                .output();
-                        let output = try!(output_result);
+        let mut dry_run = DryRun::default();
+        if !self.is_cached() {
+            dry_run.removed = try!(utils::fs::removable_dir_content_except(&self.dest, &[&self.root, &self.src], &self.config.preserve));
+        }
 
-                        if !output.status.success() {
-                            return Err(Box::new(io::Error::new(ErrorKind::Other, format!(
-                                            "{}\n{}",
-                                            String::from_utf8_lossy(&output.stdout),
-                                            String::from_utf8_lossy(&output.stderr)))) as Box<Error>);
-                        }
-                    }
-                },
-                _ => {},
+        try!(self.parse_books());
+
+        let dest = self.dest.clone();
+        let mut files = vec![];
+        let mut unlisted = vec![];
+        try!(self.for_each_language(|book| {
+            try!(book.prepare_language(false));
+
+            book.for_each_renderer(|_, renderer, book| {
+                let prefix = book.dest.strip_prefix(&dest).map(|p| p.to_owned()).unwrap_or_default();
+                match try!(renderer.files_to_write(book).map_err(MdBookError::Render)) {
+                    Some(written) => files.extend(written.into_iter().map(|path| prefix.join(path))),
+                    None if !unlisted.iter().any(|name| name == renderer.name()) => {
+                        unlisted.push(renderer.name().to_owned());
+                    },
+                    None => {},
+                }
+                Ok(())
+            })
+        }));
+        if self.config.compress_output {
+            let compressed = try!(self.compressed_copies(&files, &dry_run.removed));
+            files.extend(compressed);
+        }
+        files.sort();
+        files.dedup();
+        for path in files {
+            let path = self.dest.join(path);
+            if path.exists() {
+                dry_run.overwritten.push(path);
+            } else {
+                dry_run.created.push(path);
             }
         }
-        Ok(())
-    }
+        dry_run.unlisted = unlisted;
 
-    pub fn get_root(&self) -> &Path {
-        &self.root
+        Ok(dry_run)
     }
 
-    pub fn set_dest(mut self, dest: &Path) -> Self {
+    /// Output files created, modified or removed by the last [`build()`](#method.build), relative to
+    /// the destination directory, as recorded in the cache of the html renderer. A file that was
+    /// rewritten with the same content is not a change. Returns `None` when it is not known, when
+    /// the book has no html output or when its last build failed.
 
-        // Handle absolute and relative paths
-        match dest.is_absolute() {
-            true => {
-                self.dest = dest.to_owned();
-            },
-            false => {
-                let dest = self.root.join(dest).to_owned();
-                self.dest = dest;
-            },
+    pub fn changed_files(&self) -> Option<Vec<PathBuf>> {
+        let mut changed: Option<Vec<PathBuf>> = None;
+
+        for (_, _, language_dest) in self.language_dirs() {
+            for dest in self.renderer_dests(&language_dest) {
+                if let Some(files) = renderer::changed_files(&dest) {
+                    let prefix = dest.strip_prefix(&self.dest).unwrap_or(Path::new("")).to_owned();
+                    changed.get_or_insert(vec![]).extend(files.iter().map(|file| prefix.join(file)));
+                }
+            }
         }
 
-        self
+        changed
     }
 
-    pub fn get_dest(&self) -> &Path {
-        &self.dest
+    // Whether the output of a previous build can be updated in place instead of being cleaned
+    fn is_cached(&self) -> bool {
+        self.language_dirs().iter().any(|&(_, _, ref dest)| {
+            self.renderer_dests(dest).iter().any(|dest| dest.join(CACHE_FILE).exists())
+        })
     }
 
-    pub fn set_src(mut self, src: &Path) -> Self {
+    // The chapters of every language are known before any of them is rendered, so that the
+    // renderers can link to the translations of a chapter
+    fn parse_books(&mut self) -> Result<(), MdBookError> {
+        self.for_each_language(|book| {
+            try!(book.parse_summary());
+
+            let title = book.config.title.clone();
+            book.books
+                .entry(book.language.clone())
+                .or_insert_with(|| book::Book::new(&title))
+                .set_items(&book.content);
+            Ok(())
+        })
+    }
 
-        // Handle absolute and relative paths
-        match src.is_absolute() {
-            true => {
-                self.src = src.to_owned();
-            },
-            false => {
-                let src = self.root.join(src).to_owned();
-                self.src = src;
-            },
-        }
+    // Reads, preprocesses and renders the chapters of the language being built
+    fn build_language(&mut self, progress: &Progress) -> Result<(), MdBookError> {
+        try!(self.prepare_language(true));
+        let total = self.chapters().len();
+
+        self.for_each_renderer(|i, renderer, book| {
+            let start = Instant::now();
+            progress.start(renderer.name(), total);
+            let rendered = renderer.render_with_progress(book, progress);
+            progress.finish(renderer.name());
+            let time = start.elapsed();
+            debug!("[*]: {} renderer took {}", renderer.name(), format_duration(time));
+            if let Some(total) = book.render_times.get_mut(i) {
+                *total += time;
+            }
 
-        self
+            rendered.map_err(MdBookError::Render)
+        })
     }
 
-    pub fn get_src(&self) -> &Path {
-        &self.src
-    }
+    // Calls `f` with the index of every renderer, the renderer and the book, whose destination is
+    // the output directory of the renderer. Stops at the first error.
+    fn for_each_renderer<F>(&mut self, mut f: F) -> Result<(), MdBookError>
+        where F: FnMut(usize, &Renderer, &mut MDBook) -> Result<(), MdBookError>
+    {
+        // The renderers are moved out of the book while they borrow it
+        let dests = self.renderer_dests(&self.dest);
+        let configured = mem::replace(&mut self.configured_renderers, vec![]);
+        let renderers = mem::replace(&mut self.renderers, vec![]);
+        let dest = self.dest.clone();
+
+        let mut result = Ok(());
+        for (i, (renderer, renderer_dest)) in configured.iter().chain(&renderers).zip(dests).enumerate() {
+            self.dest = renderer_dest;
+
+            result = f(i, &**renderer, self);
+            if result.is_err() {
+                break;
+            }
+        }
 
-    pub fn set_title(mut self, title: &str) -> Self {
-        self.title = title.to_owned();
-        self
-    }
+        self.dest = dest;
+        self.configured_renderers = configured;
+        self.renderers = renderers;
 
-    pub fn get_title(&self) -> &str {
-        &self.title
+        result
     }
 
-    pub fn set_author(mut self, author: &str) -> Self {
-        self.author = author.to_owned();
-        self
-    }
+    // Paths, relative to the destination, of the gzip copies that `compress_output` writes once
+    // `written` are written and `removed` are removed. The size of the written files is not known,
+    // they are all compressed.
+    fn compressed_copies(&self, written: &[PathBuf], removed: &[PathBuf]) -> Result<Vec<PathBuf>, MdBookError> {
+        let mut kept = vec![];
+        if self.dest.is_dir() {
+            for path in try!(utils::fs::list_files_except_ext(&self.dest, &[&self.dest.join(".git")], &[])) {
+                let absolute = self.dest.join(&path);
+                if !removed.iter().any(|removed| absolute.starts_with(removed)) &&
+                   try!(fs::metadata(&absolute)).len() >= COMPRESS_MIN_SIZE {
+                    kept.push(path);
+                }
+            }
+        }
 
-    pub fn get_author(&self) -> &str {
-        &self.author
+        Ok(written.iter()
+                  .chain(&kept)
+                  .filter(|path| path.extension().map_or(false, |ext| COMPRESSED_EXTENSIONS.contains(&&*ext.to_string_lossy())))
+                  .map(|path| {
+                      let mut gz = path.clone().into_os_string();
+                      gz.push(".gz");
+                      PathBuf::from(gz)
+                  })
+                  .collect())
     }
 
-    pub fn set_description(mut self, description: &str) -> Self {
-        self.description = description.to_owned();
-        self
+    // Reads and preprocesses the chapters of the language being built. The plugins of the
+    // configuration, external executables, only run with `plugins`.
+    fn prepare_language(&mut self, plugins: bool) -> Result<(), MdBookError> {
+        try!(self.parse_summary());
+        try!(self.read_chapters());
+
+        // The preprocessors are moved out of the book while they modify it, the plugins of the
+        // configuration run last
+        let preprocessors = mem::replace(&mut self.preprocessors, vec![]);
+        let configured = mem::replace(&mut self.configured_preprocessors, vec![]);
+        let mut preprocessed = Ok(());
+        let plugins = if plugins { &configured[..] } else { &[] };
+        for preprocessor in preprocessors.iter().chain(plugins) {
+            if let Err(e) = preprocessor.run(self) {
+                preprocessed = Err(MdBookError::Preprocess(e));
+                break;
+            }
+        }
+        self.preprocessors = preprocessors;
+        self.configured_preprocessors = configured;
+        preprocessed
     }
 
-    pub fn get_description(&self) -> &str {
-        &self.description
-    }
 
-    pub fn set_livereload(&mut self, livereload: String) -> &mut Self {
-        self.livereload = Some(livereload);
-        self
+    /// Removes the generated output from the destination directory and returns the paths that were
+    /// removed. The `.git` directory of the destination, or the `.git` file of a `gh-pages`
+    /// worktree, is always kept, as well as the paths matching the
+    /// [`preserve`](#method.set_preserve) patterns. The cache of the html renderer is removed too,
+    /// so the next [`build()`](#method.build) renders the whole book again.
+    ///
+    /// It fails without removing anything when the destination is the current directory, the root
+    /// or the source directory of the book, or one of their parents. When some entries can not be
+    /// removed the others are still removed and the error lists them.
+
+    pub fn clean(&self) -> Result<Vec<PathBuf>, MdBookError> {
+        debug!("[fn]: clean");
+
+        Ok(try!(utils::fs::remove_dir_content_except(&self.dest, &[&self.root, &self.src], &self.config.preserve)))
     }
 
-    pub fn unset_livereload(&mut self) -> &Self {
-        self.livereload = None;
-        self
+    pub fn get_gitignore(&self) -> PathBuf {
+        self.root.join(".gitignore")
     }
 
-    pub fn get_livereload(&self) -> Option<&String> {
-        match self.livereload {
-            Some(ref livereload) => Some(&livereload),
-            None => None,
+    /// Copies the default theme to the theme directory (`src/theme` unless another path was set with
+    /// [`set_theme_path()`](#method.set_theme_path)) so that it can be customized.
+    ///
+    /// Files that already exist are left untouched so that customizations are not lost, unless
+    /// `force` is `true` in which case they are overwritten with the default theme.
+
+    pub fn copy_theme(&self, force: bool) -> Result<(), Box<Error>> {
+        debug!("[fn]: copy_theme");
+
+        let theme_dir = self.get_theme_path();
+
+        if !theme_dir.exists() {
+            debug!("[*]: {:?} does not exist, trying to create directory", theme_dir);
+            try!(fs::create_dir_all(&theme_dir));
+        }
+
+        let files: [(&str, &[u8]); 6] = [("index.hbs", theme::INDEX),
+                                         ("book.css", theme::CSS),
+                                         ("favicon.png", theme::FAVICON),
+                                         ("book.js", theme::JS),
+                                         ("highlight.css", theme::HIGHLIGHT_CSS),
+                                         ("highlight.js", theme::HIGHLIGHT_JS)];
+
+        for &(name, content) in files.iter() {
+            let path = theme_dir.join(name);
+
+            if path.exists() && !force {
+                warn!("{:?} already exists, leaving it untouched", path);
+                continue;
+            }
+
+            let mut file = try!(File::create(&path));
+            try!(file.write_all(content));
         }
-    }
 
-    // Construct book
-    fn parse_summary(&mut self) -> Result<(), Box<Error>> {
-        // When append becomes stable, use self.content.append() ...
-        self.content = try!(markdown::summary::construct_bookitems(&self.src.join("SUMMARY.md")));
         Ok(())
     }
+
+    /// Parses the `book.toml` or `book.json` file (if it exists), or the file given to
+    /// [`set_config_path()`](#method.set_config_path), to extract the configuration parameters.
+    /// The configuration file should be in the root directory of the book. When both files are present,
+    /// `book.toml` is used.
+    /// The root directory is the one specified when creating a new `MDBook`
+    ///
+    /// ```no_run
+    /// # extern crate mdbook;
+    /// # use mdbook::MDBook;
+    /// # use std::path::Path;
+    /// # fn main() {
+    /// let mut book = MDBook::new(Path::new("root_dir")).read_config().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// In this example, `root_dir` will be the root directory of our book and is specified in function
+    /// of the current working directory by using a relative path instead of an absolute path.
+    ///
+    /// An error is returned when the configuration file can not be read or parsed, the error message
+    /// contains the path of the offending file. A file that can not be parsed or an invalid value is
+    /// a [`Config`](../errors/enum.MdBookError.html) error.
+    ///
+    /// Every option of the book is reset: it takes the value of the configuration file, or its
+    /// default value when the file does not set it. The values given with the setters before
+    /// reading the configuration are lost, call the setters after `read_config()` to override the
+    /// configuration file.
+
+    pub fn read_config(mut self) -> Result<Self, MdBookError> {
+        try!(self.read_config_mut());
+        Ok(self)
+    }
+
+    /// `&mut` version of [`read_config()`](#method.read_config), for a book that is not owned, for
+    /// example because it is a field of a larger struct
+    ///
+    /// ```no_run
+    /// # extern crate mdbook;
+    /// # use mdbook::MDBook;
+    /// # use std::path::Path;
+    /// struct Tool {
+    ///     book: MDBook,
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut tool = Tool { book: MDBook::new(Path::new("mybook")) };
+    ///
+    /// tool.book.read_config_mut().unwrap()
+    ///          .set_dest_mut(Path::new("output"))
+    ///          .set_title_mut("My book");
+    /// # }
+    /// ```
+
+    pub fn read_config_mut(&mut self) -> Result<&mut Self, MdBookError> {
+
+        let mut config = BookConfig::new(&self.root);
+        match self.config_path {
+            Some(ref config_path) => try!(config.read_config_file(config_path)),
+            None => try!(config.read_config(&self.root)),
+        };
+        // One book per language, their chapters are filled in by `build()`
+        self.books.clear();
+        let languages = if config.languages.is_empty() {
+            vec![config.language.clone()]
+        } else {
+            config.languages.clone()
+        };
+        for language in languages {
+            let mut book = book::Book::new(&config.title);
+
+            book.mut_metadata()
+                .set_description(&config.description)
+                .set_language(metadata::Language::new(&language, &language))
+                .add_author(metadata::Author::new(&config.author));
+
+            self.books.insert(language, book);
+        }
+
+        self.configured_preprocessors = config.preprocessors
+                                              .iter()
+                                              .map(|name| Box::new(ExternalPreprocessor::new(name)) as Box<Preprocessor>)
+                                              .collect();
+        self.configured_renderers = if config.renderers.is_empty() {
+            vec![Box::new(HtmlHandlebars::new())]
+        } else {
+            config.renderers.iter().map(|name| renderer_named(name)).collect()
+        };
+
+        self.language = config.language.clone();
+        self.dest = config.dest.clone();
+        self.src = config.src.clone();
+        self.theme_path = config.theme_path.clone();
+        self.config = config;
+
+        // The environment takes precedence over the configuration file
+        self.override_paths(env::var_os(SRC_VAR), env::var_os(DEST_VAR));
+
+        Ok(self)
+    }
+
+    // Replaces the source and destination directories by the given paths, if they are set and not
+    // empty. Relative paths are resolved against the root like with `set_src()` and `set_dest()`.
+    fn override_paths(&mut self, src: Option<OsString>, dest: Option<OsString>) {
+        match src {
+            Some(ref src) if !src.is_empty() => {
+                self.set_src_mut(Path::new(src));
+            },
+            _ => {},
+        }
+        match dest {
+            Some(ref dest) if !dest.is_empty() => {
+                self.set_dest_mut(Path::new(dest));
+            },
+            _ => {},
+        }
+    }
+
+    /// You can change the default renderer to another one by using this method, it replaces all the
+    /// renderers of the book, those of the configuration included, until the configuration is read
+    /// again. The only requirement is for your renderer to implement the
+    /// [Renderer trait](../../renderer/renderer/trait.Renderer.html)
+    ///
+    /// ```no_run
+    /// extern crate mdbook;
+    /// use mdbook::MDBook;
+    /// use mdbook::renderer::HtmlHandlebars;
+    /// # use std::path::Path;
+    ///
+    /// fn main() {
+    ///     let mut book = MDBook::new(Path::new("mybook"))
+    ///                         .set_renderer(Box::new(HtmlHandlebars::new()));
+    ///
+    ///     // In this example we replace the default renderer by the default renderer...
+    ///     // Don't forget to put your renderer in a Box
+    /// }
+    /// ```
+    ///
+    /// **note:** Don't forget to put your renderer in a `Box` before passing it to `set_renderer()`
+
+    pub fn set_renderer(mut self, renderer: Box<Renderer>) -> Self {
+        self.set_renderer_mut(renderer);
+        self
+    }
+
+    /// `&mut` version of [`set_renderer()`](#method.set_renderer)
+
+    pub fn set_renderer_mut(&mut self, renderer: Box<Renderer>) -> &mut Self {
+        self.configured_renderers.clear();
+        self.renderers = vec![renderer];
+        self
+    }
+
+    /// Adds a renderer to the ones already used by the book. `build()` parses the book once and
+    /// then runs all the renderers one after the other. The renderers of the configuration come
+    /// first, the added ones are kept when the configuration is read again.
+    ///
+    /// ```no_run
+    /// extern crate mdbook;
+    /// use mdbook::MDBook;
+    /// use mdbook::renderer::EpubRenderer;
+    /// # use std::path::Path;
+    ///
+    /// fn main() {
+    ///     // Renders to html and to epub
+    ///     let mut book = MDBook::new(Path::new("mybook"))
+    ///                         .add_renderer(Box::new(EpubRenderer::new()));
+    /// }
+    /// ```
+
+    pub fn add_renderer(mut self, renderer: Box<Renderer>) -> Self {
+        self.add_renderer_mut(renderer);
+        self
+    }
+
+    /// `&mut` version of [`add_renderer()`](#method.add_renderer)
+
+    pub fn add_renderer_mut(&mut self, renderer: Box<Renderer>) -> &mut Self {
+        self.renderers.push(renderer);
+        self
+    }
+
+    /// Adds a preprocessor, it runs after the built-in ones and the ones added before it. The
+    /// preprocessors can modify the book after its chapters are read from the source directory,
+    /// they have to implement the [Preprocessor trait](../preprocess/trait.Preprocessor.html).
+    ///
+    /// ```no_run
+    /// extern crate mdbook;
+    /// use mdbook::{MDBook, Preprocessor};
+    /// use mdbook::book::BookItemMut;
+    /// # use std::error::Error;
+    /// # use std::path::Path;
+    ///
+    /// // Appends a line to every chapter
+    /// struct Footer;
+    ///
+    /// impl Preprocessor for Footer {
+    ///     fn run(&self, book: &mut MDBook) -> Result<(), Box<Error>> {
+    ///         for item in book.iter_mut() {
+    ///             if let BookItemMut::Chapter(_, chapter) = item {
+    ///                 chapter.content.push_str("\n\n---\nMade with mdBook\n");
+    ///             }
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     let mut book = MDBook::new(Path::new("mybook"))
+    ///                         .add_preprocessor(Box::new(Footer));
+    /// }
+    /// ```
+
+    pub fn add_preprocessor(mut self, preprocessor: Box<Preprocessor>) -> Self {
+        self.add_preprocessor_mut(preprocessor);
+        self
+    }
+
+    /// `&mut` version of [`add_preprocessor()`](#method.add_preprocessor)
+
+    pub fn add_preprocessor_mut(&mut self, preprocessor: Box<Preprocessor>) -> &mut Self {
+        self.preprocessors.push(preprocessor);
+        self
+    }
+
+    /// Sets the directories where the executables of the [plugins](../plugin/index.html) are
+    /// looked for before the `PATH`, for example the plugins shipped with an application. A
+    /// relative path is relative to the root of the book.
+
+    pub fn set_plugin_path(mut self, dirs: &[PathBuf]) -> Self {
+        self.set_plugin_path_mut(dirs);
+        self
+    }
+
+    /// `&mut` version of [`set_plugin_path()`](#method.set_plugin_path)
+
+    pub fn set_plugin_path_mut(&mut self, dirs: &[PathBuf]) -> &mut Self {
+        self.plugin_path = dirs.iter().map(|dir| self.root.join(dir)).collect();
+        self
+    }
+
+    pub fn get_plugin_path(&self) -> &[PathBuf] {
+        &self.plugin_path
+    }
+
+    /// Runs `rustdoc --test` on every chapter to test its code samples. The chapters are tested in
+    /// parallel and all of them are tested even when some fail, the error then contains the path
+    /// and the output of `rustdoc` of every failed chapter.
+    ///
+    /// The [library paths](#method.set_test_library_paths), [extern crates](#method.set_test_externs)
+    /// and [edition](#method.set_test_edition) of the book are passed to `rustdoc`.
+
+    pub fn test(&mut self) -> Result<(), MdBookError> {
+        // read in the chapters
+        let mut paths = vec![];
+        try!(self.for_each_language(|book| {
+            try!(book.parse_summary());
+
+            for item in book.iter() {
+                if let BookItem::Chapter(_, ref ch) = *item {
+                    let path = book.get_src().join(&ch.path);
+                    if ch.path != PathBuf::new() && !ch.is_url() && !paths.contains(&path) {
+                        paths.push(path);
+                    }
+                }
+            }
+            Ok(())
+        }));
+
+        let mut args = vec![];
+        for path in &self.config.test_library_paths {
+            args.push("-L".to_owned());
+            args.push(path.to_string_lossy().into_owned());
+        }
+        for extern_crate in &self.config.test_externs {
+            args.push("--extern".to_owned());
+            args.push(extern_crate.clone());
+        }
+        if let Some(ref edition) = self.config.test_edition {
+            args.push("--edition".to_owned());
+            args.push(edition.clone());
+        }
+
+        let mut failures = vec![];
+        for (path, output) in paths.iter().zip(run_doctests(&paths, &args)) {
+            let output = try!(output);
+
+            if output.status.success() {
+                info!("[*]: {:?} ok", path);
+            } else {
+                warn!("[*]: {:?} FAILED", path);
+                failures.push(format!("---- {} ----\n{}\n{}",
+                                      path.display(),
+                                      String::from_utf8_lossy(&output.stdout),
+                                      String::from_utf8_lossy(&output.stderr)));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(MdBookError::Test(format!("{}\n{} of {} chapters failed",
+                                          failures.join("\n"),
+                                          failures.len(),
+                                          paths.len())))
+        }
+    }
+
+    pub fn get_root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Sets the configuration file that [`read_config()`](#method.read_config) reads instead of
+    /// the `book.toml` or `book.json` of the root, for example a file shared by several books. It
+    /// can be a `.toml` or a `.json` file. The paths of the configuration, like `src` and `dest`,
+    /// are still relative to the root of the book. Relative paths are resolved against the root of
+    /// the book.
+    ///
+    /// ```no_run
+    /// # extern crate mdbook;
+    /// # use mdbook::MDBook;
+    /// # use std::path::Path;
+    /// # fn main() {
+    /// let mut book = MDBook::new(Path::new("books/guide"))
+    ///                    .set_config_path(Path::new("../shared.toml"))
+    ///                    .read_config()
+    ///                    .unwrap();
+    /// # }
+    /// ```
+
+    pub fn set_config_path(mut self, config_path: &Path) -> Self {
+        self.set_config_path_mut(config_path);
+        self
+    }
+
+    /// `&mut` version of [`set_config_path()`](#method.set_config_path)
+
+    pub fn set_config_path_mut(&mut self, config_path: &Path) -> &mut Self {
+        self.config_path = Some(self.root.join(config_path));
+        self
+    }
+
+    pub fn get_config_path(&self) -> Option<&Path> {
+        self.config_path.as_ref().map(|p| p.as_path())
+    }
+
+    /// The configuration of the book, read by [`read_config()`](#method.read_config) with the keys
+    /// of the configuration file that are not options of mdBook. The book keeps its options there:
+    /// the setters of the book change it and the getters read from it.
+    ///
+    /// ```no_run
+    /// # extern crate mdbook;
+    /// # use mdbook::MDBook;
+    /// # use std::path::Path;
+    /// # fn main() {
+    /// let book = MDBook::new(Path::new("my-book")).read_config().unwrap();
+    /// let glossary = book.config().get("glossary");
+    /// # }
+    /// ```
+
+    pub fn config(&self) -> &BookConfig {
+        &self.config
+    }
+
+    /// Sets the destination directory, where the book is rendered. A relative path is relative to
+    /// the root of the book, like the `dest` of the configuration, see
+    /// [`set_dest_from_cwd()`](#method.set_dest_from_cwd) for a path relative to the current
+    /// directory.
+
+    pub fn set_dest(mut self, dest: &Path) -> Self {
+        self.set_dest_mut(dest);
+        self
+    }
+
+    /// `&mut` version of [`set_dest()`](#method.set_dest)
+
+    pub fn set_dest_mut(&mut self, dest: &Path) -> &mut Self {
+
+        // Handle absolute and relative paths
+        match dest.is_absolute() {
+            true => {
+                self.dest = dest.to_owned();
+            },
+            false => {
+                let dest = self.root.join(dest).to_owned();
+                self.dest = dest;
+            },
+        }
+
+        self.config.dest = self.dest.clone();
+        self
+    }
+
+    /// Sets the destination directory like [`set_dest()`](#method.set_dest), but a relative path is
+    /// relative to the current directory instead of the root of the book, like the paths given on
+    /// the command line. The destination can then be outside of the book, for example in a
+    /// temporary directory, and no `.gitignore` is created for it.
+
+    pub fn set_dest_from_cwd(mut self, dest: &Path) -> Self {
+        self.set_dest_from_cwd_mut(dest);
+        self
+    }
+
+    /// `&mut` version of [`set_dest_from_cwd()`](#method.set_dest_from_cwd)
+
+    pub fn set_dest_from_cwd_mut(&mut self, dest: &Path) -> &mut Self {
+        // Without a current directory the relative path is kept, the system resolves it against the
+        // current directory anyway
+        self.dest = match env::current_dir() {
+            Ok(ref cwd) if dest.is_relative() => cwd.join(dest),
+            _ => dest.to_owned(),
+        };
+        self.config.dest = self.dest.clone();
+        self
+    }
+
+    pub fn get_dest(&self) -> &Path {
+        &self.dest
+    }
+
+    /// Sets the glob patterns of the paths of the destination that are kept by
+    /// [`clean()`](#method.clean), relative to the destination. For example `CNAME` or `.nojekyll`
+    /// for GitHub Pages.
+
+    pub fn set_preserve(mut self, patterns: &[String]) -> Self {
+        self.set_preserve_mut(patterns);
+        self
+    }
+
+    /// `&mut` version of [`set_preserve()`](#method.set_preserve)
+
+    pub fn set_preserve_mut(&mut self, patterns: &[String]) -> &mut Self {
+        self.config.preserve = patterns.to_vec();
+        self
+    }
+
+    pub fn get_preserve(&self) -> &[String] {
+        &self.config.preserve
+    }
+
+    pub fn set_src(mut self, src: &Path) -> Self {
+        self.set_src_mut(src);
+        self
+    }
+
+    /// `&mut` version of [`set_src()`](#method.set_src)
+
+    pub fn set_src_mut(&mut self, src: &Path) -> &mut Self {
+
+        // Handle absolute and relative paths
+        match src.is_absolute() {
+            true => {
+                self.src = src.to_owned();
+            },
+            false => {
+                let src = self.root.join(src).to_owned();
+                self.src = src;
+            },
+        }
+
+        self.config.src = self.src.clone();
+        self
+    }
+
+    pub fn get_src(&self) -> &Path {
+        &self.src
+    }
+
+    /// Sets the directory the theme is loaded from, a relative path is resolved against the root of
+    /// the book. Files missing from this directory fall back to the default theme.
+
+    pub fn set_theme_path(mut self, theme_path: &Path) -> Self {
+        self.set_theme_path_mut(theme_path);
+        self
+    }
+
+    /// `&mut` version of [`set_theme_path()`](#method.set_theme_path)
+
+    pub fn set_theme_path_mut(&mut self, theme_path: &Path) -> &mut Self {
+
+        // Handle absolute and relative paths
+        match theme_path.is_absolute() {
+            true => {
+                self.theme_path = Some(theme_path.to_owned());
+            },
+            false => {
+                let theme_path = self.root.join(theme_path).to_owned();
+                self.theme_path = Some(theme_path);
+            },
+        }
+
+        self.config.theme_path = self.theme_path.clone();
+        self
+    }
+
+    /// Returns the theme directory, `theme` in the source directory by default
+
+    pub fn get_theme_path(&self) -> PathBuf {
+        match self.theme_path {
+            Some(ref path) => path.clone(),
+            None => self.src.join("theme"),
+        }
+    }
+
+    pub fn set_title(mut self, title: &str) -> Self {
+        self.set_title_mut(title);
+        self
+    }
+
+    /// `&mut` version of [`set_title()`](#method.set_title)
+
+    pub fn set_title_mut(&mut self, title: &str) -> &mut Self {
+        self.config.title = title.to_owned();
+        self
+    }
+
+    pub fn get_title(&self) -> &str {
+        &self.config.title
+    }
+
+    pub fn set_author(mut self, author: &str) -> Self {
+        self.set_author_mut(author);
+        self
+    }
+
+    /// `&mut` version of [`set_author()`](#method.set_author)
+
+    pub fn set_author_mut(&mut self, author: &str) -> &mut Self {
+        self.config.author = author.to_owned();
+        self
+    }
+
+    pub fn get_author(&self) -> &str {
+        &self.config.author
+    }
+
+    pub fn set_description(mut self, description: &str) -> Self {
+        self.set_description_mut(description);
+        self
+    }
+
+    /// `&mut` version of [`set_description()`](#method.set_description)
+
+    pub fn set_description_mut(&mut self, description: &str) -> &mut Self {
+        self.config.description = description.to_owned();
+        self
+    }
+
+    pub fn get_description(&self) -> &str {
+        &self.config.description
+    }
+
+    pub fn set_default_language(mut self, language: &str) -> Self {
+        self.set_default_language_mut(language);
+        self
+    }
+
+    /// `&mut` version of [`set_default_language()`](#method.set_default_language)
+
+    pub fn set_default_language_mut(&mut self, language: &str) -> &mut Self {
+        self.config.language = language.to_owned();
+        self.language = language.to_owned();
+        self
+    }
+
+    pub fn get_default_language(&self) -> &str {
+        &self.config.language
+    }
+
+    /// Sets the languages of a translated book, see [`build()`](#method.build). The default
+    /// language should be one of them.
+
+    pub fn set_languages(mut self, languages: &[String]) -> Self {
+        self.set_languages_mut(languages);
+        self
+    }
+
+    /// `&mut` version of [`set_languages()`](#method.set_languages)
+
+    pub fn set_languages_mut(&mut self, languages: &[String]) -> &mut Self {
+        self.config.languages = languages.to_vec();
+        self
+    }
+
+    pub fn get_languages(&self) -> &[String] {
+        &self.config.languages
+    }
+
+    /// Returns the language being built, the renderers use it for their output. It is the default
+    /// language outside of [`build()`](#method.build).
+
+    pub fn get_language(&self) -> &str {
+        &self.language
+    }
+
+    /// Returns the book of a language, its chapters are known once it is built
+
+    pub fn get_book(&self, language: &str) -> Option<&book::Book> {
+        self.books.get(language)
+    }
+
+    pub fn set_livereload(&mut self, livereload: String) -> &mut Self {
+        self.livereload = Some(livereload);
+        self
+    }
+
+    pub fn unset_livereload(&mut self) -> &Self {
+        self.livereload = None;
+        self
+    }
+
+    pub fn get_livereload(&self) -> Option<&String> {
+        match self.livereload {
+            Some(ref livereload) => Some(&livereload),
+            None => None,
+        }
+    }
+
+    /// Enables or disables the search index and the search bar in the html output
+    pub fn set_search(mut self, search: bool) -> Self {
+        self.set_search_mut(search);
+        self
+    }
+
+    /// `&mut` version of [`set_search()`](#method.set_search)
+
+    pub fn set_search_mut(&mut self, search: bool) -> &mut Self {
+        self.config.search = search;
+        self
+    }
+
+    pub fn get_search(&self) -> bool {
+        self.config.search
+    }
+
+    /// Shows the section numbers of the chapters, `1.2.`, in the sidebar and in the search results of
+    /// the html output. Enabled by default. The numbers are still computed and available to the
+    /// renderers in [`BookItem::Chapter`](bookitem/enum.BookItem.html).
+
+    pub fn set_number_sections(mut self, number_sections: bool) -> Self {
+        self.set_number_sections_mut(number_sections);
+        self
+    }
+
+    /// `&mut` version of [`set_number_sections()`](#method.set_number_sections)
+
+    pub fn set_number_sections_mut(&mut self, number_sections: bool) -> &mut Self {
+        self.config.number_sections = number_sections;
+        self
+    }
+
+    pub fn get_number_sections(&self) -> bool {
+        self.config.number_sections
+    }
+
+    /// Sets the number of heading levels shown in the table of contents of every chapter, starting
+    /// at `h2`. `0` disables the table of contents.
+
+    pub fn set_toc_depth(mut self, toc_depth: usize) -> Self {
+        self.set_toc_depth_mut(toc_depth);
+        self
+    }
+
+    /// `&mut` version of [`set_toc_depth()`](#method.set_toc_depth)
+
+    pub fn set_toc_depth_mut(&mut self, toc_depth: usize) -> &mut Self {
+        self.config.toc_depth = toc_depth;
+        self
+    }
+
+    pub fn get_toc_depth(&self) -> usize {
+        self.config.toc_depth
+    }
+
+    /// Sets the number of levels of chapters that the sidebar of the html output shows expanded on
+    /// the first visit, the deeper sections are folded until the reader opens them. The sections
+    /// of the current chapter are always open and the sections the reader opened or closed keep
+    /// their state. `0`, the default, expands every section.
+
+    pub fn set_fold_level(mut self, fold_level: usize) -> Self {
+        self.set_fold_level_mut(fold_level);
+        self
+    }
+
+    /// `&mut` version of [`set_fold_level()`](#method.set_fold_level)
+
+    pub fn set_fold_level_mut(&mut self, fold_level: usize) -> &mut Self {
+        self.config.fold_level = fold_level;
+        self
+    }
+
+    pub fn get_fold_level(&self) -> usize {
+        self.config.fold_level
+    }
+
+    /// Shows the estimated reading time of every chapter in the html output, from its number of
+    /// words and the [`words_per_minute`](#method.set_words_per_minute). Disabled by default.
+
+    pub fn set_reading_time(mut self, reading_time: bool) -> Self {
+        self.set_reading_time_mut(reading_time);
+        self
+    }
+
+    /// `&mut` version of [`set_reading_time()`](#method.set_reading_time)
+
+    pub fn set_reading_time_mut(&mut self, reading_time: bool) -> &mut Self {
+        self.config.reading_time = reading_time;
+        self
+    }
+
+    pub fn get_reading_time(&self) -> bool {
+        self.config.reading_time
+    }
+
+    /// Sets the reading speed used to estimate the reading time of the chapters, 200 words per
+    /// minute by default
+
+    pub fn set_words_per_minute(mut self, words_per_minute: usize) -> Self {
+        self.set_words_per_minute_mut(words_per_minute);
+        self
+    }
+
+    /// `&mut` version of [`set_words_per_minute()`](#method.set_words_per_minute)
+
+    pub fn set_words_per_minute_mut(&mut self, words_per_minute: usize) -> &mut Self {
+        self.config.words_per_minute = words_per_minute;
+        self
+    }
+
+    pub fn get_words_per_minute(&self) -> usize {
+        self.config.words_per_minute
+    }
+
+    /// Enables MathJax in the html output, `$...$` and `$$...$$` in the chapters are then rendered
+    /// as math
+
+    pub fn set_mathjax_support(mut self, mathjax_support: bool) -> Self {
+        self.set_mathjax_support_mut(mathjax_support);
+        self
+    }
+
+    /// `&mut` version of [`set_mathjax_support()`](#method.set_mathjax_support)
+
+    pub fn set_mathjax_support_mut(&mut self, mathjax_support: bool) -> &mut Self {
+        self.config.mathjax_support = mathjax_support;
+        self
+    }
+
+    pub fn get_mathjax_support(&self) -> bool {
+        self.config.mathjax_support
+    }
+
+    /// Renders the code blocks in the `mermaid` language as diagrams in the html output. The
+    /// Mermaid script is only loaded by the pages that contain a diagram.
+
+    pub fn set_mermaid(mut self, mermaid: bool) -> Self {
+        self.set_mermaid_mut(mermaid);
+        self
+    }
+
+    /// `&mut` version of [`set_mermaid()`](#method.set_mermaid)
+
+    pub fn set_mermaid_mut(&mut self, mermaid: bool) -> &mut Self {
+        self.config.mermaid = mermaid;
+        self
+    }
+
+    pub fn get_mermaid(&self) -> bool {
+        self.config.mermaid
+    }
+
+    /// Highlights the code blocks at build time with one of the themes bundled with
+    /// [syntect](https://github.com/trishume/syntect), for example `InspiredGitHub` or
+    /// `base16-ocean.dark`. Without a highlight theme the code is highlighted in the browser.
+
+    pub fn set_highlight_theme(mut self, highlight_theme: &str) -> Self {
+        self.set_highlight_theme_mut(highlight_theme);
+        self
+    }
+
+    /// `&mut` version of [`set_highlight_theme()`](#method.set_highlight_theme)
+
+    pub fn set_highlight_theme_mut(&mut self, highlight_theme: &str) -> &mut Self {
+        self.config.highlight_theme = Some(highlight_theme.to_owned());
+        self
+    }
+
+    pub fn get_highlight_theme(&self) -> Option<&str> {
+        self.config.highlight_theme.as_ref().map(|theme| theme.as_str())
+    }
+
+    /// Shows the line numbers of the code blocks in the html output
+
+    pub fn set_line_numbers(mut self, line_numbers: bool) -> Self {
+        self.set_line_numbers_mut(line_numbers);
+        self
+    }
+
+    /// `&mut` version of [`set_line_numbers()`](#method.set_line_numbers)
+
+    pub fn set_line_numbers_mut(&mut self, line_numbers: bool) -> &mut Self {
+        self.config.line_numbers = line_numbers;
+        self
+    }
+
+    pub fn get_line_numbers(&self) -> bool {
+        self.config.line_numbers
+    }
+
+    /// Turns the straight quotes of the chapters into curly quotes, `--` and `---` into en and em
+    /// dashes and `...` into an ellipsis. The code spans, the code blocks and the inline html are
+    /// left as they are. Disabled by default.
+
+    pub fn set_smart_punctuation(mut self, smart_punctuation: bool) -> Self {
+        self.set_smart_punctuation_mut(smart_punctuation);
+        self
+    }
+
+    /// `&mut` version of [`set_smart_punctuation()`](#method.set_smart_punctuation)
+
+    pub fn set_smart_punctuation_mut(&mut self, smart_punctuation: bool) -> &mut Self {
+        self.config.smart_punctuation = smart_punctuation;
+        self
+    }
+
+    pub fn get_smart_punctuation(&self) -> bool {
+        self.config.smart_punctuation
+    }
+
+    /// Replaces the emoji shortcodes of the chapters, like `:rocket:` or `:warning:`, with their
+    /// emoji. The code spans, the code blocks and the unknown shortcodes are left as they are.
+    /// Disabled by default.
+
+    pub fn set_emoji(mut self, emoji: bool) -> Self {
+        self.set_emoji_mut(emoji);
+        self
+    }
+
+    /// `&mut` version of [`set_emoji()`](#method.set_emoji)
+
+    pub fn set_emoji_mut(&mut self, emoji: bool) -> &mut Self {
+        self.config.emoji = emoji;
+        self
+    }
+
+    pub fn get_emoji(&self) -> bool {
+        self.config.emoji
+    }
+
+    /// Sets the languages of the code blocks that are renamed before they are highlighted in the
+    /// html output, for example `rs` to `rust`. They replace the default aliases.
+
+    pub fn set_language_aliases(mut self, aliases: BTreeMap<String, String>) -> Self {
+        self.set_language_aliases_mut(aliases);
+        self
+    }
+
+    /// `&mut` version of [`set_language_aliases()`](#method.set_language_aliases)
+
+    pub fn set_language_aliases_mut(&mut self, aliases: BTreeMap<String, String>) -> &mut Self {
+        self.config.language_aliases = aliases;
+        self
+    }
+
+    pub fn get_language_aliases(&self) -> &BTreeMap<String, String> {
+        &self.config.language_aliases
+    }
+
+    /// Renders every chapter of the html output to `<stem>/index.html` instead of `<stem>.html`
+    /// and links to it as `<stem>/`, for hosts that serve the `index.html` of a directory. A
+    /// chapter named `index.md` keeps its `index.html`.
+
+    pub fn set_clean_urls(mut self, clean_urls: bool) -> Self {
+        self.set_clean_urls_mut(clean_urls);
+        self
+    }
+
+    /// `&mut` version of [`set_clean_urls()`](#method.set_clean_urls)
+
+    pub fn set_clean_urls_mut(&mut self, clean_urls: bool) -> &mut Self {
+        self.config.clean_urls = clean_urls;
+        self
+    }
+
+    pub fn get_clean_urls(&self) -> bool {
+        self.config.clean_urls
+    }
+
+    /// Sets the pages that redirect the old addresses of moved chapters to their new one, keyed by
+    /// their path in the output, like `old/intro.html`, and relative to its root as well. The html
+    /// renderer writes a small page at every old path that sends the browser to the new one, so
+    /// that the bookmarks keep working. A path ending with `/` gets an `index.html`.
+
+    pub fn set_redirects(mut self, redirects: BTreeMap<String, String>) -> Self {
+        self.set_redirects_mut(redirects);
+        self
+    }
+
+    /// `&mut` version of [`set_redirects()`](#method.set_redirects)
+
+    pub fn set_redirects_mut(&mut self, redirects: BTreeMap<String, String>) -> &mut Self {
+        self.config.redirects = redirects;
+        self
+    }
+
+    pub fn get_redirects(&self) -> &BTreeMap<String, String> {
+        &self.config.redirects
+    }
+
+    /// Adds a value to the data of the templates of every page, for example the version of the
+    /// documented software or the date of the build, available as `{{ key }}` in the theme. The
+    /// keys that the renderer uses itself, like `title`, `content`, `previous` or `next`, can not
+    /// be replaced: they are ignored with a warning.
+    ///
+    /// ```no_run
+    /// extern crate mdbook;
+    /// extern crate rustc_serialize;
+    /// use mdbook::MDBook;
+    /// use rustc_serialize::json::ToJson;
+    /// # use std::path::Path;
+    ///
+    /// fn main() {
+    ///     let book = MDBook::new(Path::new("mybook"))
+    ///                    .add_template_data("version", "1.2.0".to_json());
+    /// }
+    /// ```
+
+    pub fn add_template_data(mut self, key: &str, value: Json) -> Self {
+        self.add_template_data_mut(key, value);
+        self
+    }
+
+    /// `&mut` version of [`add_template_data()`](#method.add_template_data)
+
+    pub fn add_template_data_mut(&mut self, key: &str, value: Json) -> &mut Self {
+        self.template_data.insert(key.to_owned(), value);
+        self
+    }
+
+    pub fn get_template_data(&self) -> &BTreeMap<String, Json> {
+        &self.template_data
+    }
+
+    /// Sets the chapter rendered as the `index.html` at the root of the html output, by its path
+    /// relative to the source directory, like `README.md`. By default it is the first chapter of
+    /// the book that has a page. The build fails when the chapter is not in the book.
+
+    pub fn set_index(mut self, index: &Path) -> Self {
+        self.set_index_mut(index);
+        self
+    }
+
+    /// `&mut` version of [`set_index()`](#method.set_index)
+
+    pub fn set_index_mut(&mut self, index: &Path) -> &mut Self {
+        self.config.index = Some(index.to_owned());
+        self
+    }
+
+    pub fn get_index(&self) -> Option<&Path> {
+        self.config.index.as_ref().map(|p| p.as_path())
+    }
+
+    /// Sets the summary files of the book, relative to the source directory, `SUMMARY.md` by
+    /// default. Several files are read one after the other as a single table of contents, every
+    /// file after the first one starts a new part. Only the first file is created by
+    /// [`init()`](#method.init) when it does not exist.
+
+    pub fn set_summary_files(mut self, files: &[PathBuf]) -> Self {
+        self.set_summary_files_mut(files);
+        self
+    }
+
+    /// `&mut` version of [`set_summary_files()`](#method.set_summary_files)
+
+    pub fn set_summary_files_mut(&mut self, files: &[PathBuf]) -> &mut Self {
+        self.config.summary_files = files.to_vec();
+        self
+    }
+
+    pub fn get_summary_files(&self) -> &[PathBuf] {
+        &self.config.summary_files
+    }
+
+    /// Sets the stylesheets that are copied to the destination and linked from every page of the
+    /// html output, after the ones of the theme. Relative paths are resolved against the root of
+    /// the book.
+
+    pub fn set_additional_css(mut self, paths: &[PathBuf]) -> Self {
+        self.set_additional_css_mut(paths);
+        self
+    }
+
+    /// `&mut` version of [`set_additional_css()`](#method.set_additional_css)
+
+    pub fn set_additional_css_mut(&mut self, paths: &[PathBuf]) -> &mut Self {
+        self.config.additional_css = paths.iter().map(|path| self.root.join(path)).collect();
+        self
+    }
+
+    pub fn get_additional_css(&self) -> &[PathBuf] {
+        &self.config.additional_css
+    }
+
+    /// Sets the scripts that are copied to the destination and loaded by every page of the html
+    /// output, after the ones of the theme. Relative paths are resolved against the root of the
+    /// book.
+
+    pub fn set_additional_js(mut self, paths: &[PathBuf]) -> Self {
+        self.set_additional_js_mut(paths);
+        self
+    }
+
+    /// `&mut` version of [`set_additional_js()`](#method.set_additional_js)
+
+    pub fn set_additional_js_mut(&mut self, paths: &[PathBuf]) -> &mut Self {
+        self.config.additional_js = paths.iter().map(|path| self.root.join(path)).collect();
+        self
+    }
+
+    pub fn get_additional_js(&self) -> &[PathBuf] {
+        &self.config.additional_js
+    }
+
+    /// Sets the font files (`woff2`, `woff`, `ttf` or `otf`) bundled with the html output. They are
+    /// copied to the destination and declared in a `fonts.css` loaded before the stylesheet of the
+    /// theme, under the family of their name: `Brand-BoldItalic.woff2` is the bold italic face of
+    /// `Brand`. Relative paths are resolved against the root of the book.
+
+    pub fn set_fonts(mut self, paths: &[PathBuf]) -> Self {
+        self.set_fonts_mut(paths);
+        self
+    }
+
+    /// `&mut` version of [`set_fonts()`](#method.set_fonts)
+
+    pub fn set_fonts_mut(&mut self, paths: &[PathBuf]) -> &mut Self {
+        self.config.fonts = paths.iter().map(|path| self.root.join(path)).collect();
+        self
+    }
+
+    pub fn get_fonts(&self) -> &[PathBuf] {
+        &self.config.fonts
+    }
+
+    /// Sets the font families of the text of the html output, keyed by `body`, `heading` or `code`,
+    /// for example `Brand` to use the bundled [`fonts`](#method.set_fonts). The font of the theme
+    /// is used for the other keys.
+
+    pub fn set_font_family(mut self, font_family: BTreeMap<String, String>) -> Self {
+        self.set_font_family_mut(font_family);
+        self
+    }
+
+    /// `&mut` version of [`set_font_family()`](#method.set_font_family)
+
+    pub fn set_font_family_mut(&mut self, font_family: BTreeMap<String, String>) -> &mut Self {
+        self.config.font_family = font_family;
+        self
+    }
+
+    pub fn get_font_family(&self) -> &BTreeMap<String, String> {
+        &self.config.font_family
+    }
+
+    /// Sets the icon of the pages of the html output, instead of the favicon of the theme. A
+    /// relative path is resolved against the root of the book.
+
+    pub fn set_favicon(mut self, favicon: &Path) -> Self {
+        self.set_favicon_mut(favicon);
+        self
+    }
+
+    /// `&mut` version of [`set_favicon()`](#method.set_favicon)
+
+    pub fn set_favicon_mut(&mut self, favicon: &Path) -> &mut Self {
+        self.config.favicon = Some(self.root.join(favicon));
+        self
+    }
+
+    pub fn get_favicon(&self) -> Option<&Path> {
+        self.config.favicon.as_ref().map(|p| p.as_path())
+    }
+
+    /// Sets the image shown at the top of the sidebar of the html output, linking to the first page
+    /// of the book. A relative path is resolved against the root of the book.
+
+    pub fn set_logo(mut self, logo: &Path) -> Self {
+        self.set_logo_mut(logo);
+        self
+    }
+
+    /// `&mut` version of [`set_logo()`](#method.set_logo)
+
+    pub fn set_logo_mut(&mut self, logo: &Path) -> &mut Self {
+        self.config.logo = Some(self.root.join(logo));
+        self
+    }
+
+    pub fn get_logo(&self) -> Option<&Path> {
+        self.config.logo.as_ref().map(|p| p.as_path())
+    }
+
+    /// Sets raw html that is inserted as it is at the end of the `<head>` of every page of the html
+    /// output, for example `<meta>` tags or an analytics snippet
+
+    pub fn set_head(mut self, head: &str) -> Self {
+        self.set_head_mut(head);
+        self
+    }
+
+    /// `&mut` version of [`set_head()`](#method.set_head)
+
+    pub fn set_head_mut(&mut self, head: &str) -> &mut Self {
+        self.config.head = head.to_owned();
+        self
+    }
+
+    pub fn get_head(&self) -> &str {
+        &self.config.head
+    }
+
+    /// Sets the theme of the html output until the reader picks another one: `light`, `rust`,
+    /// `coal`, `navy`, `dark` for `coal` or `system` to follow the color scheme preferred by the
+    /// browser
+
+    pub fn set_default_theme(mut self, theme: &str) -> Self {
+        self.set_default_theme_mut(theme);
+        self
+    }
+
+    /// `&mut` version of [`set_default_theme()`](#method.set_default_theme)
+
+    pub fn set_default_theme_mut(&mut self, theme: &str) -> &mut Self {
+        self.config.default_theme = theme.to_owned();
+        self
+    }
+
+    pub fn get_default_theme(&self) -> &str {
+        &self.config.default_theme
+    }
+
+    /// Sets the absolute url or path the book is served from, for example `/docs/` or
+    /// `https://example.com/docs/`. The pages of the html output then load their assets and link
+    /// to each other from there instead of with relative paths. Without it only the `404.html`
+    /// page, which is served at any depth, uses absolute links, starting at `/`. A missing
+    /// trailing `/` is added.
+
+    pub fn set_site_url(mut self, url: &str) -> Self {
+        self.set_site_url_mut(url);
+        self
+    }
+
+    /// `&mut` version of [`set_site_url()`](#method.set_site_url)
+
+    pub fn set_site_url_mut(&mut self, url: &str) -> &mut Self {
+        let mut url = url.to_owned();
+        if !url.ends_with('/') {
+            url.push('/');
+        }
+        self.config.site_url = Some(url);
+        self
+    }
+
+    pub fn get_site_url(&self) -> Option<&str> {
+        self.config.site_url.as_ref().map(|url| url.as_str())
+    }
+
+    /// Creates the files of the chapters of `SUMMARY.md` that do not exist when the book is built,
+    /// with a warning, instead of failing the build. Disabled by default.
+
+    pub fn set_create_missing(mut self, create_missing: bool) -> Self {
+        self.set_create_missing_mut(create_missing);
+        self
+    }
+
+    /// `&mut` version of [`set_create_missing()`](#method.set_create_missing)
+
+    pub fn set_create_missing_mut(&mut self, create_missing: bool) -> &mut Self {
+        self.config.create_missing = create_missing;
+        self
+    }
+
+    pub fn get_create_missing(&self) -> bool {
+        self.config.create_missing
+    }
+
+    /// Reads the chapters that are not valid UTF-8 as latin-1, with a warning, instead of failing the
+    /// build with the position of the first invalid byte. Disabled by default.
+
+    pub fn set_latin1_fallback(mut self, latin1_fallback: bool) -> Self {
+        self.set_latin1_fallback_mut(latin1_fallback);
+        self
+    }
+
+    /// `&mut` version of [`set_latin1_fallback()`](#method.set_latin1_fallback)
+
+    pub fn set_latin1_fallback_mut(&mut self, latin1_fallback: bool) -> &mut Self {
+        self.config.latin1_fallback = latin1_fallback;
+        self
+    }
+
+    pub fn get_latin1_fallback(&self) -> bool {
+        self.config.latin1_fallback
+    }
+
+    /// Fails the html rendering when a relative link of a chapter points to a chapter, an anchor or
+    /// a file that does not exist, with the list of all the broken links. The links to web pages
+    /// are not checked. Disabled by default.
+
+    pub fn set_check_links(mut self, check_links: bool) -> Self {
+        self.set_check_links_mut(check_links);
+        self
+    }
+
+    /// `&mut` version of [`set_check_links()`](#method.set_check_links)
+
+    pub fn set_check_links_mut(&mut self, check_links: bool) -> &mut Self {
+        self.config.check_links = check_links;
+        self
+    }
+
+    pub fn get_check_links(&self) -> bool {
+        self.config.check_links
+    }
+
+    /// Writes a gzip copy, `page.html.gz`, next to the html, css, js and json files of the output
+    /// after the rendering, for the servers and CDNs that serve the precompressed files as they
+    /// are. The files smaller than 1 KiB are not worth it and are left alone. Disabled by default.
+
+    pub fn set_compress_output(mut self, compress_output: bool) -> Self {
+        self.set_compress_output_mut(compress_output);
+        self
+    }
+
+    /// `&mut` version of [`set_compress_output()`](#method.set_compress_output)
+
+    pub fn set_compress_output_mut(&mut self, compress_output: bool) -> &mut Self {
+        self.config.compress_output = compress_output;
+        self
+    }
+
+    pub fn get_compress_output(&self) -> bool {
+        self.config.compress_output
+    }
+
+    /// Writes `standalone.html` next to `print.html`: the whole book in a single file that opens
+    /// from anywhere, without a network. The stylesheets and scripts are copied into the page and
+    /// the images and fonts are embedded as `data:` urls, except the ones larger than 512 KiB that
+    /// stay next to it. Disabled by default.
+
+    pub fn set_standalone(mut self, standalone: bool) -> Self {
+        self.set_standalone_mut(standalone);
+        self
+    }
+
+    /// `&mut` version of [`set_standalone()`](#method.set_standalone)
+
+    pub fn set_standalone_mut(&mut self, standalone: bool) -> &mut Self {
+        self.config.standalone = standalone;
+        self
+    }
+
+    pub fn get_standalone(&self) -> bool {
+        self.config.standalone
+    }
+
+    /// Sets the line endings of the html, css, js and json files of the output, `lf` or `crlf`,
+    /// whatever the ones of the sources and of the theme. `lf` by default.
+
+    pub fn set_line_endings(mut self, line_endings: &str) -> Self {
+        self.set_line_endings_mut(line_endings);
+        self
+    }
+
+    /// `&mut` version of [`set_line_endings()`](#method.set_line_endings)
+
+    pub fn set_line_endings_mut(&mut self, line_endings: &str) -> &mut Self {
+        self.config.line_endings = line_endings.to_owned();
+        self
+    }
+
+    pub fn get_line_endings(&self) -> &str {
+        &self.config.line_endings
+    }
+
+    /// Minifies the html, css and js files generated by the html renderer. The whitespace of the
+    /// code blocks is kept as it is. Disabled by default.
+
+    pub fn set_minify(mut self, minify: bool) -> Self {
+        self.set_minify_mut(minify);
+        self
+    }
+
+    /// `&mut` version of [`set_minify()`](#method.set_minify)
+
+    pub fn set_minify_mut(&mut self, minify: bool) -> &mut Self {
+        self.config.minify = minify;
+        self
+    }
+
+    pub fn get_minify(&self) -> bool {
+        self.config.minify
+    }
+
+    /// Links the pages of the chapters marked `{hidden}` in `SUMMARY.md` from the previous and next
+    /// links of their neighbours. By default the hidden chapters are only reachable from the links
+    /// of the chapters and are skipped by the previous and next links.
+
+    pub fn set_navigate_hidden(mut self, navigate_hidden: bool) -> Self {
+        self.set_navigate_hidden_mut(navigate_hidden);
+        self
+    }
+
+    /// `&mut` version of [`set_navigate_hidden()`](#method.set_navigate_hidden)
+
+    pub fn set_navigate_hidden_mut(&mut self, navigate_hidden: bool) -> &mut Self {
+        self.config.navigate_hidden = navigate_hidden;
+        self
+    }
+
+    pub fn get_navigate_hidden(&self) -> bool {
+        self.config.navigate_hidden
+    }
+
+    /// Shows the date of the last change of every chapter in the html output: the date of the last
+    /// commit of its file when the book is in a git repository, or else the modification time of
+    /// the file. Disabled by default.
+
+    pub fn set_git_dates(mut self, git_dates: bool) -> Self {
+        self.set_git_dates_mut(git_dates);
+        self
+    }
+
+    /// `&mut` version of [`set_git_dates()`](#method.set_git_dates)
+
+    pub fn set_git_dates_mut(&mut self, git_dates: bool) -> &mut Self {
+        self.config.git_dates = git_dates;
+        self
+    }
+
+    pub fn get_git_dates(&self) -> bool {
+        self.config.git_dates
+    }
+
+    /// Sets the glob patterns of the files of the output that are not minified, relative to the
+    /// destination, for the files that a minification could break. For example `theme/*.js`.
+
+    pub fn set_minify_exclude(mut self, patterns: &[String]) -> Self {
+        self.set_minify_exclude_mut(patterns);
+        self
+    }
+
+    /// `&mut` version of [`set_minify_exclude()`](#method.set_minify_exclude)
+
+    pub fn set_minify_exclude_mut(&mut self, patterns: &[String]) -> &mut Self {
+        self.config.minify_exclude = patterns.to_vec();
+        self
+    }
+
+    pub fn get_minify_exclude(&self) -> &[String] {
+        &self.config.minify_exclude
+    }
+
+    /// Sets the shell commands that [`build()`](#method.build) runs, in order, before reading the
+    /// book, for example to generate chapters
+
+    pub fn set_pre_build(mut self, commands: &[String]) -> Self {
+        self.set_pre_build_mut(commands);
+        self
+    }
+
+    /// `&mut` version of [`set_pre_build()`](#method.set_pre_build)
+
+    pub fn set_pre_build_mut(&mut self, commands: &[String]) -> &mut Self {
+        self.config.pre_build = commands.to_vec();
+        self
+    }
+
+    pub fn get_pre_build(&self) -> &[String] {
+        &self.config.pre_build
+    }
+
+    /// Sets the shell commands that [`build()`](#method.build) runs, in order, once the book is
+    /// rendered, for example to deploy it
+
+    pub fn set_post_build(mut self, commands: &[String]) -> Self {
+        self.set_post_build_mut(commands);
+        self
+    }
+
+    /// `&mut` version of [`set_post_build()`](#method.set_post_build)
+
+    pub fn set_post_build_mut(&mut self, commands: &[String]) -> &mut Self {
+        self.config.post_build = commands.to_vec();
+        self
+    }
+
+    pub fn get_post_build(&self) -> &[String] {
+        &self.config.post_build
+    }
+
+    /// Sets the url of the git repository of the book, every page of the html output then gets a
+    /// link to its source at `<url>/edit/master/<src>/<path of the chapter>`
+
+    pub fn set_git_repository_url(mut self, url: &str) -> Self {
+        self.set_git_repository_url_mut(url);
+        self
+    }
+
+    /// `&mut` version of [`set_git_repository_url()`](#method.set_git_repository_url)
+
+    pub fn set_git_repository_url_mut(&mut self, url: &str) -> &mut Self {
+        self.config.git_repository_url = Some(url.to_owned());
+        self
+    }
+
+    pub fn get_git_repository_url(&self) -> Option<&str> {
+        self.config.git_repository_url.as_ref().map(|url| url.as_str())
+    }
+
+    /// Sets the url of the link to edit a chapter, `{path}` is replaced by the path of the chapter
+    /// relative to the source directory. It takes precedence over the git repository url.
+
+    pub fn set_edit_url_template(mut self, template: &str) -> Self {
+        self.set_edit_url_template_mut(template);
+        self
+    }
+
+    /// `&mut` version of [`set_edit_url_template()`](#method.set_edit_url_template)
+
+    pub fn set_edit_url_template_mut(&mut self, template: &str) -> &mut Self {
+        self.config.edit_url_template = Some(template.to_owned());
+        self
+    }
+
+    pub fn get_edit_url_template(&self) -> Option<&str> {
+        self.config.edit_url_template.as_ref().map(|template| template.as_str())
+    }
+
+    /// Sets the number of milliseconds the watch and serve commands wait for the file system to stay
+    /// quiet after a change before rebuilding the book, 300 by default
+
+    pub fn set_watch_debounce_ms(mut self, watch_debounce_ms: u64) -> Self {
+        self.set_watch_debounce_ms_mut(watch_debounce_ms);
+        self
+    }
+
+    /// `&mut` version of [`set_watch_debounce_ms()`](#method.set_watch_debounce_ms)
+
+    pub fn set_watch_debounce_ms_mut(&mut self, watch_debounce_ms: u64) -> &mut Self {
+        self.config.watch_debounce_ms = watch_debounce_ms;
+        self
+    }
+
+    pub fn get_watch_debounce_ms(&self) -> u64 {
+        self.config.watch_debounce_ms
+    }
+
+    /// Sets the paths watched in addition to the source directory, the theme and the config file.
+    /// Relative paths are relative to the root of the book.
+
+    pub fn set_watch_paths(mut self, paths: &[PathBuf]) -> Self {
+        self.set_watch_paths_mut(paths);
+        self
+    }
+
+    /// `&mut` version of [`set_watch_paths()`](#method.set_watch_paths)
+
+    pub fn set_watch_paths_mut(&mut self, paths: &[PathBuf]) -> &mut Self {
+        self.config.watch_paths = paths.iter().map(|path| self.root.join(path)).collect();
+        self
+    }
+
+    pub fn get_watch_paths(&self) -> &[PathBuf] {
+        &self.config.watch_paths
+    }
+
+    /// Sets the changes that do not trigger a rebuild: paths relative to the root of the book, or
+    /// `*.ext` for all the files with the extension `ext`
+
+    pub fn set_watch_ignore(mut self, patterns: &[String]) -> Self {
+        self.set_watch_ignore_mut(patterns);
+        self
+    }
+
+    /// `&mut` version of [`set_watch_ignore()`](#method.set_watch_ignore)
+
+    pub fn set_watch_ignore_mut(&mut self, patterns: &[String]) -> &mut Self {
+        self.config.watch_ignore = patterns.to_vec();
+        self
+    }
+
+    pub fn get_watch_ignore(&self) -> &[String] {
+        &self.config.watch_ignore
+    }
+
+    /// Sets the directories added to the library search path of `rustdoc` (`-L`) by
+    /// [`test()`](#method.test), for code samples that use other crates
+
+    pub fn set_test_library_paths(mut self, paths: &[PathBuf]) -> Self {
+        self.set_test_library_paths_mut(paths);
+        self
+    }
+
+    /// `&mut` version of [`set_test_library_paths()`](#method.set_test_library_paths)
+
+    pub fn set_test_library_paths_mut(&mut self, paths: &[PathBuf]) -> &mut Self {
+        self.config.test_library_paths = paths.to_vec();
+        self
+    }
+
+    pub fn get_test_library_paths(&self) -> &[PathBuf] {
+        &self.config.test_library_paths
+    }
+
+    /// Sets the crates passed to `rustdoc` with `--extern` by [`test()`](#method.test), in the form
+    /// `name=path`
+
+    pub fn set_test_externs(mut self, externs: &[String]) -> Self {
+        self.set_test_externs_mut(externs);
+        self
+    }
+
+    /// `&mut` version of [`set_test_externs()`](#method.set_test_externs)
+
+    pub fn set_test_externs_mut(&mut self, externs: &[String]) -> &mut Self {
+        self.config.test_externs = externs.to_vec();
+        self
+    }
+
+    pub fn get_test_externs(&self) -> &[String] {
+        &self.config.test_externs
+    }
+
+    /// Sets the edition of the code samples tested by [`test()`](#method.test), `rustdoc` uses its
+    /// default edition when it is not set
+
+    pub fn set_test_edition(mut self, edition: &str) -> Self {
+        self.set_test_edition_mut(edition);
+        self
+    }
+
+    /// `&mut` version of [`set_test_edition()`](#method.set_test_edition)
+
+    pub fn set_test_edition_mut(&mut self, edition: &str) -> &mut Self {
+        self.config.test_edition = Some(edition.to_owned());
+        self
+    }
+
+    pub fn get_test_edition(&self) -> Option<&str> {
+        self.config.test_edition.as_ref().map(|edition| edition.as_str())
+    }
+
+    // The renderers of the configuration, then the ones added with `add_renderer()`
+    fn all_renderers(&self) -> Vec<&Renderer> {
+        self.configured_renderers.iter().chain(&self.renderers).map(|renderer| &**renderer).collect()
+    }
+
+    // Output directory of every renderer in `dest`, each one gets a sub directory named after it
+    // when there are several
+    fn renderer_dests(&self, dest: &Path) -> Vec<PathBuf> {
+        let renderers = self.all_renderers();
+        if renderers.len() > 1 {
+            renderers.iter().map(|renderer| dest.join(renderer.name())).collect()
+        } else {
+            vec![dest.to_owned()]
+        }
+    }
+
+    // The renderers would write to the same directory when two of them have the same name
+    fn check_renderer_names(&self) -> Result<(), MdBookError> {
+        let mut names = HashSet::new();
+        for renderer in self.all_renderers() {
+            if !names.insert(renderer.name()) {
+                return Err(MdBookError::Config(format!("There are two renderers named `{}`, their output would \
+                                                        be written to the same directory",
+                                                       renderer.name())));
+            }
+        }
+        Ok(())
+    }
+
+    // Language, source and output directory of every language to build. The default language comes
+    // last, so that `content` holds its chapters once the book is built.
+    fn language_dirs(&self) -> Vec<(String, PathBuf, PathBuf)> {
+        if self.config.languages.is_empty() {
+            return vec![(self.config.language.clone(), self.src.clone(), self.dest.clone())];
+        }
+
+        let mut dirs = self.config.languages
+                           .iter()
+                           .filter(|language| **language != self.config.language)
+                           .map(|language| (language.clone(), self.src.join(language), self.dest.join(language)))
+                           .collect::<Vec<_>>();
+        dirs.push((self.config.language.clone(), self.src.join(&self.config.language), self.dest.clone()));
+        dirs
+    }
+
+    // Runs `f` for every language, with the source and output directories set to the ones of that
+    // language. The theme is shared by all the languages.
+    fn for_each_language<F>(&mut self, mut f: F) -> Result<(), MdBookError>
+        where F: FnMut(&mut MDBook) -> Result<(), MdBookError>
+    {
+        let src = self.src.clone();
+        let dest = self.dest.clone();
+        let theme_path = mem::replace(&mut self.theme_path, None);
+        self.theme_path = Some(match theme_path {
+            Some(ref path) => path.clone(),
+            None => src.join("theme"),
+        });
+
+        let mut result = Ok(());
+        for (language, language_src, language_dest) in self.language_dirs() {
+            self.language = language;
+            self.src = language_src;
+            self.dest = language_dest;
+
+            result = f(self);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.language = self.config.language.clone();
+        self.src = src;
+        self.dest = dest;
+        self.theme_path = theme_path;
+
+        result
+    }
+
+    // Reads the markdown of the chapters that have a file in the source directory
+    fn read_chapters(&mut self) -> Result<(), MdBookError> {
+        let src = self.src.clone();
+        let latin1_fallback = self.config.latin1_fallback;
+
+        for item in self.iter_mut() {
+            let ch = match item {
+                BookItemMut::Chapter(_, ch) | BookItemMut::Affix(ch) => ch,
+                BookItemMut::Spacer | BookItemMut::PartTitle(_) => continue,
+            };
+
+            let path = ch.path.to_string_lossy().into_owned();
+            if path.is_empty() || path.starts_with("http://") || path.starts_with("https://") {
+                continue;
+            }
+
+            debug!("[*]: Reading file: {:?}", src.join(&*ch.path));
+            let text = try!(utils::fs::read_text(&src.join(&*ch.path), latin1_fallback));
+
+            // The front matter is not part of the markdown
+            let (metadata, content) = try!(markdown::front_matter::split(&text).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", src.join(&*ch.path).display(), e))
+            }));
+            *ch.metadata = metadata;
+            *ch.content = content.to_owned();
+        }
+
+        Ok(())
+    }
+
+    // Construct book
+    fn parse_summary(&mut self) -> Result<(), MdBookError> {
+        // When append becomes stable, use self.content.append() ...
+        self.content = try!(markdown::summary::construct_bookitems(&self.src, &self.config.summary_files));
+        Ok(())
+    }
+
+    // The first summary file of the language being built, the one created when it is missing
+    fn summary_path(&self) -> PathBuf {
+        match self.config.summary_files.first() {
+            Some(file) => self.src.join(file),
+            None => self.src.join("SUMMARY.md"),
+        }
+    }
+}
+
+
+// The markdown files in `dir` and its sub directories, except the `skipped` files and directories
+fn markdown_files(dir: &Path, skipped: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+
+    for entry in try!(fs::read_dir(dir)) {
+        let path = try!(entry).path();
+        if skipped.contains(&path) {
+            continue;
+        }
+
+        if path.is_dir() {
+            files.extend(try!(markdown_files(&path, skipped)));
+        } else if path.extension().map_or(false, |ext| ext == "md") {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+// The built-in renderer `name` or the plugin `mdbook-<name>`
+fn renderer_named(name: &str) -> Box<Renderer> {
+    match name {
+        "html" => Box::new(HtmlHandlebars::new()),
+        "json" => Box::new(JsonRenderer::new()),
+        "epub" => Box::new(EpubRenderer::new()),
+        _ => Box::new(ExternalRenderer::new(name)),
+    }
+}
+
+// Duration in seconds with two decimals, like `1.25s`
+fn format_duration(duration: Duration) -> String {
+    let hundredths = duration.subsec_nanos() / 10_000_000;
+    format!("{}.{:02}s", duration.as_secs(), hundredths)
+}
+
+// Runs `rustdoc --test` with `args` on every file on a few threads and returns the outputs in the
+// order of the files
+fn run_doctests(paths: &[PathBuf], args: &[String]) -> Vec<io::Result<Output>> {
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let next = Arc::new(Mutex::new(0));
+    let (sender, receiver) = mpsc::channel();
+
+    let workers = (0..cmp::min(threads, paths.len()))
+                      .map(|_| {
+                          let paths = paths.to_vec();
+                          let args = args.to_vec();
+                          let next = next.clone();
+                          let sender = sender.clone();
+
+                          thread::spawn(move || {
+                              loop {
+                                  let i = {
+                                      let mut next = next.lock().unwrap();
+                                      *next += 1;
+                                      *next - 1
+                                  };
+                                  if i >= paths.len() {
+                                      break;
+                                  }
+
+                                  debug!("[*]: Testing file: {:?}", paths[i]);
+                                  let output = Command::new("rustdoc").arg(&paths[i]).arg("--test").args(&args).output();
+                                  sender.send((i, output)).unwrap();
+                              }
+                          })
+                      })
+                      .collect::<Vec<_>>();
+    drop(sender);
+
+    let mut outputs = receiver.iter().collect::<Vec<_>>();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    outputs.sort_by_key(|&(i, _)| i);
+    outputs.into_iter().map(|(_, output)| output).collect()
+}
+
+
+// ---------------------------------------------------------------------------------
+//      Tests
+//
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use super::*;
+
+    #[test]
+    fn create_new_book() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let root = tmp.path().join("my-book");
+
+        let book = MDBook::create(&root).unwrap();
+
+        assert_eq!(book.get_title(), "my-book");
+        assert_eq!(book.get_src(), root.join("src").as_path());
+        for file in &["book.json", ".gitignore", "src/SUMMARY.md", "src/chapter_1.md"] {
+            assert!(root.join(file).is_file(), "{} was not created", file);
+        }
+        assert!(root.join("book").is_dir());
+
+        // The directory is not empty anymore
+        assert!(MDBook::create(&root).is_err());
+    }
+
+    #[test]
+    fn init_with_example_chapters() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut book = MDBook::new(tmp.path()).read_config().unwrap();
+        book.init_with_examples().unwrap();
+
+        let titles = book.chapters().into_iter().map(|chapter| chapter.title).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Introduction", "Writing chapters", "Code and callouts"]);
+        for &(name, content) in EXAMPLES {
+            assert_eq!(utils::fs::file_to_string(&book.get_src().join(name)).unwrap(), content);
+        }
+        book.build().unwrap();
+
+        // An existing book is left as it is
+        let mut summary = File::create(book.get_src().join("SUMMARY.md")).unwrap();
+        write!(summary, "# Summary\n\n- [Mine](./mine.md)\n").unwrap();
+        fs::remove_file(book.get_src().join("chapter_2.md")).unwrap();
+        book.init_with_examples().unwrap();
+        assert!(book.get_src().join("mine.md").exists());
+        assert!(!book.get_src().join("chapter_2.md").exists());
+    }
+
+    #[test]
+    fn paths_overridden_by_environment() {
+        let mut book = MDBook::new(Path::new("root"));
+
+        book.override_paths(Some(OsString::from("sources")), Some(OsString::from("/tmp/output")));
+        assert_eq!(book.get_src(), Path::new("root/sources"));
+        assert_eq!(book.get_dest(), Path::new("/tmp/output"));
+
+        // Unset and empty variables keep the configured paths
+        book.override_paths(None, Some(OsString::new()));
+        assert_eq!(book.get_src(), Path::new("root/sources"));
+        assert_eq!(book.get_dest(), Path::new("/tmp/output"));
+    }
+
+    #[test]
+    fn dest_relative_to_the_root_or_the_current_directory() {
+        let book = MDBook::new(Path::new("root")).set_dest(Path::new("output"));
+        assert_eq!(book.get_dest(), Path::new("root/output"));
+
+        let book = book.set_dest_from_cwd(Path::new("output"));
+        assert_eq!(book.get_dest(), env::current_dir().unwrap().join("output").as_path());
+
+        let book = book.set_dest_from_cwd(Path::new("/tmp/output"));
+        assert_eq!(book.get_dest(), Path::new("/tmp/output"));
+    }
+
+    #[test]
+    fn gitignore_only_for_a_dest_inside_of_the_book() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let root = tmp.path().join("book");
+        fs::create_dir_all(&root).unwrap();
+
+        let book = MDBook::new(&root).set_dest_from_cwd(&tmp.path().join("output"));
+        book.create_gitignore();
+        assert!(!root.join(".gitignore").exists());
+
+        let book = book.set_dest(Path::new("output"));
+        book.create_gitignore();
+        assert_eq!(fs::read_to_string(root.join(".gitignore")).unwrap(), "output\n");
+    }
+
+    #[test]
+    fn config_of_the_book() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut f = File::create(tmp.path().join("book.toml")).unwrap();
+        write!(f, "title = \"Configured\"\nglossary = \"terms.md\"").unwrap();
+
+        let book = MDBook::new(tmp.path()).read_config().unwrap().set_title("Renamed").set_dest(Path::new("out"));
+        assert_eq!(book.config().title, "Renamed");
+        assert_eq!(book.config().dest, tmp.path().join("out"));
+        assert_eq!(book.config().get("glossary"), Some(&Json::String("terms.md".to_owned())));
+        assert_eq!(book.get_title(), "Renamed");
+
+        let values = book.config().to_json();
+        assert_eq!(values.find("title"), Some(&Json::String("Renamed".to_owned())));
+        assert_eq!(values.find("glossary"), Some(&Json::String("terms.md".to_owned())));
+        assert_eq!(values.find("search"), Some(&Json::Boolean(true)));
+    }
+
+    #[test]
+    fn read_config_resets_every_option() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        File::create(tmp.path().join("book.toml")).unwrap().write_all(b"title = \"Configured\"").unwrap();
+
+        let book = MDBook::new(tmp.path())
+            .set_title("Programmatic")
+            .set_search(false)
+            .set_highlight_theme("dark")
+            .set_site_url("https://example.com/")
+            .set_index(Path::new("start.md"))
+            .read_config()
+            .unwrap();
+        assert_eq!(book.get_title(), "Configured");
+        assert!(book.get_search());
+        assert_eq!(book.get_highlight_theme(), None);
+        assert_eq!(book.get_site_url(), None);
+        assert_eq!(book.get_index(), None);
+    }
+
+    #[test]
+    fn invalid_config_is_a_config_error() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        File::create(tmp.path().join("book.toml")).unwrap().write_all(b"toc_depth = -1").unwrap();
+        match MDBook::new(tmp.path()).read_config() {
+            Err(MdBookError::Config(ref message)) => assert!(message.contains("toc_depth"), "{}", message),
+            Err(e) => panic!("expected a configuration error, got {:?}", e),
+            Ok(_) => panic!("expected a configuration error"),
+        }
+
+        File::create(tmp.path().join("book.toml")).unwrap().write_all(b"title = ").unwrap();
+        match MDBook::new(tmp.path()).read_config() {
+            Err(MdBookError::Config(ref message)) => assert!(message.contains("book.toml"), "{}", message),
+            Err(e) => panic!("expected a configuration error, got {:?}", e),
+            Ok(_) => panic!("expected a configuration error"),
+        }
+
+        let book = MDBook::new(tmp.path()).set_config_path(Path::new("missing.toml"));
+        match book.read_config() {
+            Err(MdBookError::Io(ref e)) => assert_eq!(e.kind(), io::ErrorKind::NotFound),
+            Err(e) => panic!("expected an io error, got {:?}", e),
+            Ok(_) => panic!("expected an io error"),
+        }
+    }
+
+    #[test]
+    fn build_commands() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut book = MDBook::create(&tmp.path().join("book")).unwrap();
+
+        book.set_pre_build_mut(&["echo generated> pre.txt".to_owned()]);
+        book.set_post_build_mut(&["echo rendered> post.txt".to_owned()]);
+        book.build().unwrap();
+        assert!(book.get_root().join("pre.txt").exists());
+        assert!(book.get_root().join("post.txt").exists());
+
+        book.set_post_build_mut(&["echo broken link 1>&2 && exit 3".to_owned()]);
+        match book.build() {
+            Err(MdBookError::Command { ref command, ref stderr }) => {
+                assert_eq!(command, "echo broken link 1>&2 && exit 3");
+                assert_eq!(stderr.trim(), "broken link");
+            },
+            other => panic!("expected a failed command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn output_of_every_renderer_in_its_directory() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut book = MDBook::create(&tmp.path().join("book")).unwrap().add_renderer(Box::new(JsonRenderer::new()));
+        book.build().unwrap();
+        assert!(book.get_dest().join("html/chapter_1.html").exists());
+        assert!(book.get_dest().join("json/book.json").exists());
+
+        let mut book = book.add_renderer(Box::new(HtmlHandlebars::new()));
+        match book.build() {
+            Err(MdBookError::Config(ref message)) => assert!(message.contains("two renderers named `html`"), "{}", message),
+            other => panic!("expected a configuration error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn added_renderers_after_the_configured_ones() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let root = tmp.path().join("book");
+        MDBook::create(&root).unwrap();
+        File::create(root.join("book.toml")).unwrap().write_all(b"renderers = [\"json\"]").unwrap();
+
+        let mut book = MDBook::new(&root).add_renderer(Box::new(EpubRenderer::new())).read_config().unwrap();
+        let names = book.all_renderers().iter().map(|renderer| renderer.name().to_owned()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["json".to_owned(), "epub".to_owned()]);
+
+        book.read_config_mut().unwrap();
+        assert_eq!(book.all_renderers().len(), 2);
+
+        File::create(root.join("book.toml")).unwrap().write_all(b"title = \"No renderers\"").unwrap();
+        book.read_config_mut().unwrap();
+        let names = book.all_renderers().iter().map(|renderer| renderer.name().to_owned()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["html".to_owned(), "epub".to_owned()]);
+
+        book.set_renderer_mut(Box::new(JsonRenderer::new()));
+        assert_eq!(book.all_renderers().len(), 1);
+    }
+
+    #[test]
+    fn durations_in_seconds() {
+        assert_eq!(format_duration(Duration::from_millis(1250)), "1.25s");
+        assert_eq!(format_duration(Duration::from_millis(3)), "0.00s");
+        assert_eq!(format_duration(Duration::from_secs(61)), "61.00s");
+    }
+
+    #[test]
+    fn dry_run_writes_nothing() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut book = MDBook::create(&tmp.path().join("book")).unwrap();
+        let dest = book.get_dest().to_owned();
+        File::create(dest.join("old.html")).unwrap();
+
+        let dry_run = book.build_dry_run().unwrap();
+        assert_eq!(dry_run.removed, vec![dest.join("old.html")]);
+        assert!(dry_run.created.contains(&dest.join("chapter_1.html")));
+        assert!(dry_run.overwritten.is_empty());
+        assert!(dest.join("old.html").exists());
+        assert!(!dest.join("chapter_1.html").exists());
+
+        // The output of the previous build is updated in place, the pages of the unchanged chapters
+        // are kept as they are
+        book.build().unwrap();
+        let dry_run = book.build_dry_run().unwrap();
+        assert!(dry_run.removed.is_empty());
+        assert!(dry_run.overwritten.contains(&dest.join("book.css")));
+        assert!(!dry_run.overwritten.contains(&dest.join("chapter_1.html")));
+
+        File::create(book.get_src().join("chapter_1.md")).unwrap().write_all(b"# Edited").unwrap();
+        let dry_run = book.build_dry_run().unwrap();
+        assert!(dry_run.overwritten.contains(&dest.join("chapter_1.html")));
+    }
+
+    #[test]
+    fn dry_run_of_every_renderer() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let root = tmp.path().join("book");
+        MDBook::create(&root).unwrap();
+        File::create(root.join("book.toml"))
+            .unwrap()
+            .write_all(b"renderers = [\"html\", \"json\"]\ncompress_output = true")
+            .unwrap();
+
+        let mut book = MDBook::new(&root).read_config().unwrap();
+        let dest = book.get_dest().to_owned();
+        let dry_run = book.build_dry_run().unwrap();
+        assert!(dry_run.created.contains(&dest.join("html/chapter_1.html")));
+        assert!(dry_run.created.contains(&dest.join("html/chapter_1.html.gz")));
+        assert!(dry_run.created.contains(&dest.join("json/book.json")));
+        assert!(!dry_run.created.contains(&dest.join("chapter_1.html")));
+        assert!(dry_run.unlisted.is_empty());
+
+        book.build().unwrap();
+        for path in utils::fs::list_files_except_ext(&dest, &[], &[]).unwrap() {
+            if path.file_name().map_or(false, |name| name != CACHE_FILE) {
+                assert!(dry_run.created.contains(&dest.join(&path)), "{:?} is not listed", path);
+            }
+        }
+
+        // The plugins are not run
+        File::create(root.join("book.toml"))
+            .unwrap()
+            .write_all(b"renderers = [\"html\", \"missing-renderer\"]\npreprocessors = [\"missing-preprocessor\"]")
+            .unwrap();
+        let mut book = MDBook::new(&root).read_config().unwrap();
+        let dry_run = book.build_dry_run().unwrap();
+        assert!(dry_run.overwritten.contains(&dest.join("html/book.css")));
+        assert_eq!(dry_run.unlisted, vec!["missing-renderer".to_owned()]);
+    }
+
+    #[test]
+    fn files_changed_by_the_last_build() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut book = MDBook::create(&tmp.path().join("book")).unwrap();
+        assert_eq!(book.changed_files(), None);
+
+        book.build().unwrap();
+        let changed = book.changed_files().unwrap();
+        assert!(changed.contains(&PathBuf::from("chapter_1.html")));
+        assert!(changed.contains(&PathBuf::from("book.css")));
+
+        // Nothing changes when nothing was edited
+        book.build().unwrap();
+        assert_eq!(book.changed_files(), Some(vec![]));
+
+        let mut f = File::create(book.get_src().join("chapter_1.md")).unwrap();
+        write!(f, "# Chapter 1\n\nEdited").unwrap();
+        book.build().unwrap();
+        let changed = book.changed_files().unwrap();
+        assert!(changed.contains(&PathBuf::from("chapter_1.html")));
+        assert!(changed.contains(&PathBuf::from("print.html")));
+        assert!(!changed.contains(&PathBuf::from("book.css")));
+    }
+
+    #[test]
+    fn files_changed_in_a_translated_book() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let root = tmp.path().join("book");
+        for language in &["en", "fr"] {
+            fs::create_dir_all(root.join("src").join(language)).unwrap();
+            File::create(root.join("src").join(language).join("SUMMARY.md"))
+                .unwrap()
+                .write_all(b"# Summary\n\n- [Intro](intro.md)\n")
+                .unwrap();
+            File::create(root.join("src").join(language).join("intro.md")).unwrap().write_all(b"# Intro").unwrap();
+        }
+        let mut book = MDBook::new(&root).set_languages(&["en".to_owned(), "fr".to_owned()]);
+        book.build().unwrap();
+
+        // The paths are relative to the served directory, the pages of a translation are in the
+        // directory of their language
+        File::create(root.join("src/fr/intro.md")).unwrap().write_all(b"# Introduction").unwrap();
+        book.build().unwrap();
+        let changed = book.changed_files().unwrap();
+        assert!(changed.contains(&PathBuf::from("fr/intro.html")));
+        assert!(!changed.contains(&PathBuf::from("intro.html")));
+        assert!(changed.iter().all(|path| book.get_dest().join(path).exists()));
+    }
+
+    #[test]
+    fn conversion_options_are_not_taken_from_the_cache() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut book = MDBook::create(&tmp.path().join("book")).unwrap();
+        let mut f = File::create(book.get_src().join("chapter_1.md")).unwrap();
+        write!(f, "# Chapter 1\n\n```rust\nlet x = 1;\n```\n").unwrap();
+        let page = book.get_dest().join("chapter_1.html");
+
+        book.build().unwrap();
+        assert!(!fs::read_to_string(&page).unwrap().contains("line-numbers"));
+
+        book.set_line_numbers_mut(true);
+        book.build().unwrap();
+        assert!(fs::read_to_string(&page).unwrap().contains("<pre class=\"line-numbers\">"));
+        assert!(book.changed_files().unwrap().contains(&PathBuf::from("chapter_1.html")));
+    }
+
+    #[test]
+    fn unreferenced_markdown_files() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut book = MDBook::create(&tmp.path().join("book")).unwrap();
+        let src = book.get_src().to_owned();
+        fs::create_dir_all(src.join("old")).unwrap();
+        fs::create_dir_all(src.join("theme")).unwrap();
+        for file in &["old/draft.md", "theme/notes.md", "404.md", "image.png"] {
+            File::create(src.join(file)).unwrap();
+        }
+        let mut summary = File::create(src.join("SUMMARY.md")).unwrap();
+        write!(summary, "# Summary\n\n- [One](./chapter_1.md)\n").unwrap();
+
+        assert_eq!(book.unreferenced_files().unwrap(), vec![src.join("old/draft.md")]);
+    }
+
+    #[test]
+    fn chapters_without_rendering() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let root = tmp.path().join("book");
+        MDBook::create(&root).unwrap();
+        let mut summary = File::create(root.join("src/SUMMARY.md")).unwrap();
+        write!(summary, "# Summary\n\n[Intro](intro.md)\n\n- [One](chapter_1.md)\n    - [Nested](one/index.md)\n\
+                         - Draft\n- [Web](https://example.com)\n").unwrap();
+
+        // A book that was not built, the summary is read from its file
+        let book = MDBook::new(&root).set_clean_urls(true);
+        let chapter = |section: Option<&str>, title: &str, path: &str, output_path: &str| {
+            ChapterInfo {
+                section: section.map(|s| s.to_owned()),
+                title: title.to_owned(),
+                path: PathBuf::from(path),
+                output_path: PathBuf::from(output_path),
+            }
+        };
+        assert_eq!(book.chapters(),
+                   vec![chapter(None, "Intro", "intro.md", "intro/index.html"),
+                        chapter(Some("1."), "One", "chapter_1.md", "chapter_1/index.html"),
+                        chapter(Some("1.1."), "Nested", "one/index.md", "one/index.html")]);
+        assert!(!root.join("book/intro").exists());
+    }
+
+    #[test]
+    fn render_a_single_chapter() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut book = MDBook::create(&tmp.path().join("book")).unwrap();
+        let mut summary = File::create(book.get_src().join("SUMMARY.md")).unwrap();
+        write!(summary, "# Summary\n\n- [One](chapter_1.md)\n- [Two](two.md)\n- Draft\n").unwrap();
+        File::create(book.get_src().join("two.md")).unwrap();
+        book.build().unwrap();
+        fs::remove_file(book.get_dest().join("two.html")).unwrap();
+
+        let mut items = book.iter().cloned().collect::<Vec<_>>();
+        if let BookItem::Chapter(_, ref mut ch) = items[1] {
+            ch.content = "# Two\n\nBeing *edited*".to_owned();
+        }
+        let html = book.render_chapter(&items[1]).unwrap();
+        assert!(html.contains("<p>Being <em>edited</em></p>"));
+        assert!(html.contains("<title>Two"));
+        assert!(html.contains("href=\"chapter_1.html\""));
+        assert!(!html.contains("class=\"nav-chapters previous\""));
+        assert!(!book.get_dest().join("two.html").exists());
+
+        assert!(book.render_chapter(&items[2]).is_err());
+    }
+
+    #[test]
+    fn build_fails_on_missing_chapters() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut book = MDBook::create(&tmp.path().join("book")).unwrap();
+        let mut summary = File::create(book.get_src().join("SUMMARY.md")).unwrap();
+        write!(summary, "# Summary\n\n- [One](chapter_1.md)\n- [Two](two.md)\n- Draft\n\
+                         - [Web](https://example.com)\n").unwrap();
+
+        let missing = vec![book.get_src().join("two.md")];
+        assert_eq!(book.missing_chapter_files().unwrap(), missing);
+        match book.build() {
+            Err(MdBookError::MissingChapters(paths)) => assert_eq!(paths, missing),
+            _ => panic!("The build should fail on the missing chapter"),
+        }
+        assert!(!missing[0].exists());
+
+        book.set_create_missing_mut(true);
+        book.build().unwrap();
+        assert!(missing[0].exists());
+    }
 }
@@ -0,0 +1,73 @@
+//! Progress of a build
+//!
+//! [`MDBook::build_with_progress()`](../struct.MDBook.html#method.build_with_progress) tells a
+//! [`Progress`](trait.Progress.html) when every renderer starts and finishes and, for the renderers
+//! that report it, when every chapter is rendered. The command line tool shows it as a progress line,
+//! an application can drive its own progress bar with it.
+
+use std::path::Path;
+
+
+/// Receives the progress of a build. All the methods do nothing by default, an implementation only
+/// overrides the ones it needs. The methods take `&self`, state that changes during the build goes
+/// in a `Cell` or a channel.
+
+pub trait Progress {
+    /// The renderer named `renderer` starts rendering the `total` chapters of the book that have a
+    /// page. It is called for every language of a translated book.
+    fn start(&self, _renderer: &str, _total: usize) {}
+
+    /// The chapter at `path`, relative to the source directory, was rendered. `done` of the `total`
+    /// chapters are rendered.
+    fn chapter(&self, _path: &Path, _done: usize, _total: usize) {}
+
+    /// The renderer named `renderer` finished rendering the book, or failed to
+    fn finish(&self, _renderer: &str) {}
+}
+
+/// Reports nothing, the progress of [`MDBook::build()`](../struct.MDBook.html#method.build)
+
+pub struct NoProgress;
+
+impl Progress for NoProgress {}
+
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use super::*;
+    use book::MDBook;
+
+    use std::cell::RefCell;
+    use std::path::Path;
+
+    struct Recorder {
+        events: RefCell<Vec<String>>,
+    }
+
+    impl Progress for Recorder {
+        fn start(&self, renderer: &str, total: usize) {
+            self.events.borrow_mut().push(format!("start {} {}", renderer, total));
+        }
+
+        fn chapter(&self, path: &Path, done: usize, total: usize) {
+            self.events.borrow_mut().push(format!("{} {}/{}", path.display(), done, total));
+        }
+
+        fn finish(&self, renderer: &str) {
+            self.events.borrow_mut().push(format!("finish {}", renderer));
+        }
+    }
+
+    #[test]
+    fn progress_of_the_build() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let mut book = MDBook::create(&tmp.path().join("book")).unwrap();
+        let recorder = Recorder { events: RefCell::new(vec![]) };
+
+        book.build_with_progress(&recorder).unwrap();
+        assert_eq!(*recorder.events.borrow(),
+                   vec!["start html 1".to_owned(), "./chapter_1.md 1/1".to_owned(), "finish html".to_owned()]);
+    }
+}
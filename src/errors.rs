@@ -0,0 +1,131 @@
+//! The error type returned by the main operations of mdBook
+//!
+//! [`MdBookError`](enum.MdBookError.html) lets library consumers match on the kind of failure.
+//! It implements `std::error::Error`, so code that works with `Box<Error>` can keep using `try!`
+//! on the methods that return it.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+
+#[derive(Debug)]
+pub enum MdBookError {
+    /// Reading or writing a file failed
+    Io(io::Error),
+    /// A summary file is malformed, `file` is its path relative to the source directory and `line`
+    /// the 1-based line of the offending entry
+    SummaryParse { file: PathBuf, line: usize, message: String },
+    /// The configuration is invalid
+    Config(String),
+    /// The files of these chapters of `SUMMARY.md` do not exist in the source directory
+    MissingChapters(Vec<PathBuf>),
+    /// A preprocessor failed to modify the book
+    Preprocess(Box<Error>),
+    /// A renderer failed to generate its output
+    Render(Box<Error>),
+    /// The code samples of some chapters failed to compile or run, contains the output of `rustdoc`
+    /// for every failed chapter
+    Test(String),
+    /// A `pre_build` or `post_build` command failed, contains what it wrote to its standard error
+    Command { command: String, stderr: String },
+    /// Any other error, mostly coming from functions that still return a `Box<Error>`
+    Other(Box<Error>),
+}
+
+impl fmt::Display for MdBookError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MdBookError::Io(ref e) => write!(f, "{}", e),
+            MdBookError::SummaryParse { ref file, line, ref message } => {
+                write!(f, "{}:{}: {}", file.display(), line, message)
+            },
+            MdBookError::Config(ref message) => write!(f, "{}", message),
+            MdBookError::MissingChapters(ref paths) => {
+                try!(write!(f, "The files of these chapters of SUMMARY.md do not exist:"));
+                for path in paths {
+                    try!(write!(f, "\n- {}", path.display()));
+                }
+                Ok(())
+            },
+            MdBookError::Preprocess(ref e) => write!(f, "{}", e),
+            MdBookError::Render(ref e) => write!(f, "{}", e),
+            MdBookError::Test(ref output) => write!(f, "{}", output),
+            MdBookError::Command { ref command, ref stderr } => {
+                try!(write!(f, "The command `{}` failed", command));
+                if !stderr.trim().is_empty() {
+                    try!(write!(f, ":\n{}", stderr.trim_right()));
+                }
+                Ok(())
+            },
+            MdBookError::Other(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for MdBookError {
+    fn description(&self) -> &str {
+        match *self {
+            MdBookError::Io(ref e) => e.description(),
+            MdBookError::SummaryParse { .. } => "the summary could not be parsed",
+            MdBookError::Config(_) => "invalid configuration",
+            MdBookError::MissingChapters(_) => "the files of some chapters do not exist",
+            MdBookError::Preprocess(ref e) => e.description(),
+            MdBookError::Render(ref e) => e.description(),
+            MdBookError::Test(_) => "the tests of some chapters failed",
+            MdBookError::Command { .. } => "a build command failed",
+            MdBookError::Other(ref e) => e.description(),
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            MdBookError::Io(ref e) => Some(e),
+            MdBookError::Preprocess(ref e) => Some(&**e),
+            MdBookError::Render(ref e) => Some(&**e),
+            MdBookError::Other(ref e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MdBookError {
+    fn from(e: io::Error) -> Self {
+        MdBookError::Io(e)
+    }
+}
+
+impl From<Box<Error>> for MdBookError {
+    fn from(e: Box<Error>) -> Self {
+        MdBookError::Other(e)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::error::Error;
+    use std::io;
+    use std::path::PathBuf;
+
+    #[test]
+    fn converts_to_and_from_box_error() {
+        let error: MdBookError = io::Error::new(io::ErrorKind::NotFound, "missing").into();
+        let boxed: Box<Error> = Box::new(error);
+        assert_eq!(format!("{}", boxed), "missing");
+
+        let summary = MdBookError::SummaryParse {
+            file: PathBuf::from("SUMMARY.md"),
+            line: 3,
+            message: "unexpected item".to_owned(),
+        };
+        assert_eq!(format!("{}", summary), "SUMMARY.md:3: unexpected item");
+
+        let missing = MdBookError::MissingChapters(vec![PathBuf::from("src/a.md"), PathBuf::from("src/b.md")]);
+        assert_eq!(format!("{}", missing),
+                   "The files of these chapters of SUMMARY.md do not exist:\n- src/a.md\n- src/b.md");
+    }
+}
@@ -27,16 +27,26 @@
 //!     let mut book =  MDBook::new(Path::new("my-book"))   // Path to root
 //!                         .set_src(Path::new("src"))      // Path from root to source directory
 //!                         .set_dest(Path::new("book"))    // Path from root to output directory
-//!                         .read_config();                 // Parse book.json file for configuration
+//!                         .read_config()                  // Parse book.json file for configuration
+//!                         .unwrap();
 //!
 //!     book.build().unwrap();                              // Render the book
 //! }
 //! ```
 //!
+//! ## Logging
+//!
+//! The messages of mdBook go through the [`log`](https://docs.rs/log) facade: the progress of the
+//! build, like the files that are created, is logged at the `info` level, the problems that do not
+//! stop it at the `warn` level and the internal steps at the `debug` level. Nothing is printed
+//! until the application installs a logger, the command line tool installs one that is configured
+//! with the `MDBOOK_LOG` environment variable.
+//!
 //! ## Implementing a new Renderer
 //!
 //! If you want to create a new renderer for mdBook, the only thing you have to do is to implement
-//! the [Renderer trait](renderer/renderer/trait.Renderer.html)
+//! the [Renderer trait](renderer/renderer/trait.Renderer.html): `render()` creates the output and `name()`
+//! returns a short name, like `html`, used in the messages of the build.
 //!
 //! And then you can swap in your renderer like this:
 //!
@@ -55,6 +65,8 @@
 //! ```
 //! If you make a renderer, you get the book constructed in form of `Vec<BookItems>` and you get
 //! the book config in a `BookConfig` struct.
+//! `MDBook::config()` also keeps the keys of the configuration file that mdBook does not know, a
+//! renderer or a preprocessor can read its own settings from them with `book.config().get("key")`.
 //!
 //! It's your responsability to create the necessary files in the correct directories.
 //!
@@ -73,16 +85,42 @@ extern crate rustc_serialize;
 extern crate handlebars;
 extern crate pulldown_cmark;
 extern crate toml;
+extern crate zip;
+extern crate syntect;
+extern crate yaml_rust;
+extern crate flate2;
+#[macro_use]
+extern crate log;
+
+// Dependencies for the Watch feature
+#[cfg(feature = "watch")]
+extern crate notify;
+
+// Dependencies for the Serve feature
+#[cfg(feature = "serve")]
+extern crate iron;
+#[cfg(feature = "serve")]
+extern crate staticfile;
+#[cfg(feature = "serve")]
+extern crate ws;
 
-#[macro_use] pub mod macros;
 pub mod book;
 pub mod config;
+pub mod errors;
 pub mod markdown;
+pub mod plugin;
+pub mod preprocess;
 pub mod renderer;
 pub mod theme;
 pub mod utils;
+#[cfg(feature = "watch")]
+pub mod watch;
+#[cfg(feature = "serve")]
+pub mod serve;
 
 pub use book::MDBook;
 pub use book::BookItem;
 pub use book::BookConfig;
+pub use book::Progress;
 pub use renderer::Renderer;
+pub use preprocess::Preprocessor;
@@ -0,0 +1,373 @@
+//! Emoji shortcodes, like `:rocket:` for 🚀
+//!
+//! The shortcodes are the usual ones of GitHub and Slack: the faces, the people, the animals, the
+//! food, the places, the objects, the symbols and the flags of the most common countries, with
+//! their aliases, like `:+1:` and `:thumbsup:`. A shortcode is made of lowercase letters, digits,
+//! `_`, `+` and `-` between two colons. The text between two colons that is not a known shortcode
+//! is kept as it is, like the `:30:` of `10:30:00`.
+
+
+// Shortcodes without their colons and their emoji, sorted by shortcode
+const EMOJI: &'static [(&'static str, &'static str)] = &[
+    ("+1", "👍"), ("-1", "👎"), ("100", "💯"), ("1234", "🔢"), ("1st_place_medal", "🥇"),
+    ("2nd_place_medal", "🥈"), ("3rd_place_medal", "🥉"), ("8ball", "🎱"), ("a", "🅰️"), ("ab", "🆎"),
+    ("abacus", "🧮"), ("abc", "🔤"), ("abcd", "🔡"), ("adhesive_bandage", "🩹"), ("adult", "🧑"),
+    ("airplane", "✈️"), ("alarm_clock", "⏰"), ("alembic", "⚗️"), ("alien", "👽"),
+    ("ambulance", "🚑"), ("amphora", "🏺"), ("anchor", "⚓"), ("angel", "👼"), ("anger", "💢"),
+    ("angry", "😠"), ("anguished", "😧"), ("ant", "🐜"), ("apple", "🍎"), ("aquarius", "♒"),
+    ("ar", "🇦🇷"), ("aries", "♈"), ("arrow_backward", "◀️"), ("arrow_double_down", "⏬"),
+    ("arrow_double_up", "⏫"), ("arrow_down", "⬇️"), ("arrow_down_small", "🔽"),
+    ("arrow_forward", "▶️"), ("arrow_heading_down", "⤵️"), ("arrow_heading_up", "⤴️"),
+    ("arrow_left", "⬅️"), ("arrow_lower_left", "↙️"), ("arrow_lower_right", "↘️"),
+    ("arrow_right", "➡️"), ("arrow_right_hook", "↪️"), ("arrow_up", "⬆️"), ("arrow_up_down", "↕️"),
+    ("arrow_up_small", "🔼"), ("arrow_upper_left", "↖️"), ("arrow_upper_right", "↗️"),
+    ("arrows_clockwise", "🔃"), ("arrows_counterclockwise", "🔄"), ("art", "🎨"),
+    ("articulated_lorry", "🚛"), ("artificial_satellite", "🛰️"), ("asterisk", "*️⃣"),
+    ("astonished", "😲"), ("at", "🇦🇹"), ("athletic_shoe", "👟"), ("atm", "🏧"), ("atom_symbol", "⚛️"),
+    ("au", "🇦🇺"), ("avocado", "🥑"), ("axe", "🪓"), ("b", "🅱️"), ("baby", "👶"), ("baby_bottle", "🍼"),
+    ("baby_chick", "🐤"), ("baby_symbol", "🚼"), ("back", "🔙"), ("bacon", "🥓"), ("badger", "🦡"),
+    ("badminton", "🏸"), ("bagel", "🥯"), ("baggage_claim", "🛄"), ("baguette_bread", "🥖"),
+    ("balance_scale", "⚖️"), ("balloon", "🎈"), ("ballot_box", "🗳️"),
+    ("ballot_box_with_check", "☑️"), ("bamboo", "🎍"), ("banana", "🍌"), ("bangbang", "‼️"),
+    ("banjo", "🪕"), ("bank", "🏦"), ("bar_chart", "📊"), ("barber", "💈"), ("baseball", "⚾"),
+    ("basket", "🧺"), ("basketball", "🏀"), ("bat", "🦇"), ("bathtub", "🛁"), ("battery", "🔋"),
+    ("be", "🇧🇪"), ("beach_umbrella", "🏖️"), ("bear", "🐻"), ("bed", "🛏️"), ("bee", "🐝"),
+    ("beer", "🍺"), ("beers", "🍻"), ("beetle", "🐞"), ("beginner", "🔰"), ("bell", "🔔"),
+    ("bellhop_bell", "🛎️"), ("bento", "🍱"), ("bike", "🚲"), ("bikini", "👙"), ("billed_cap", "🧢"),
+    ("biohazard", "☣️"), ("bird", "🐦"), ("birthday", "🎂"), ("black_circle", "⚫"),
+    ("black_flag", "🏴"), ("black_heart", "🖤"), ("black_joker", "🃏"), ("black_large_square", "⬛"),
+    ("black_medium_square", "◼️"), ("black_nib", "✒️"), ("black_small_square", "▪️"),
+    ("black_square_button", "🔲"), ("blossom", "🌼"), ("blowfish", "🐡"), ("blue_book", "📘"),
+    ("blue_car", "🚙"), ("blue_heart", "💙"), ("blue_square", "🟦"), ("blush", "😊"), ("boar", "🐗"),
+    ("boat", "⛵"), ("bomb", "💣"), ("book", "📖"), ("bookmark", "🔖"), ("bookmark_tabs", "📑"),
+    ("books", "📚"), ("boom", "💥"), ("boot", "👢"), ("bouquet", "💐"), ("bow", "🙇"),
+    ("bow_and_arrow", "🏹"), ("bowl_with_spoon", "🥣"), ("bowling", "🎳"), ("boxing_glove", "🥊"),
+    ("boy", "👦"), ("br", "🇧🇷"), ("brain", "🧠"), ("bread", "🍞"), ("bricks", "🧱"),
+    ("bridge_at_night", "🌉"), ("briefcase", "💼"), ("broccoli", "🥦"), ("broken_heart", "💔"),
+    ("broom", "🧹"), ("brown_circle", "🟤"), ("brown_heart", "🤎"), ("brown_square", "🟫"),
+    ("bug", "🐛"), ("building_construction", "🏗️"), ("bulb", "💡"), ("bullettrain_front", "🚅"),
+    ("bullettrain_side", "🚄"), ("burrito", "🌯"), ("bus", "🚌"), ("busstop", "🚏"),
+    ("bust_in_silhouette", "👤"), ("busts_in_silhouette", "👥"), ("butter", "🧈"), ("butterfly", "🦋"),
+    ("ca", "🇨🇦"), ("cactus", "🌵"), ("cake", "🍰"), ("calendar", "📆"), ("call_me_hand", "🤙"),
+    ("calling", "📲"), ("camel", "🐫"), ("camera", "📷"), ("camera_flash", "📸"), ("camping", "🏕️"),
+    ("cancer", "♋"), ("candle", "🕯️"), ("candy", "🍬"), ("canned_food", "🥫"), ("canoe", "🛶"),
+    ("capital_abcd", "🔠"), ("capricorn", "♑"), ("car", "🚗"), ("card_file_box", "🗃️"),
+    ("card_index", "📇"), ("card_index_dividers", "🗂️"), ("carousel_horse", "🎠"), ("carrot", "🥕"),
+    ("cat", "🐱"), ("cat2", "🐈"), ("cd", "💿"), ("ch", "🇨🇭"), ("chains", "⛓️"), ("chair", "🪑"),
+    ("champagne", "🍾"), ("chart", "💹"), ("chart_with_downwards_trend", "📉"),
+    ("chart_with_upwards_trend", "📈"), ("checkered_flag", "🏁"), ("cheese", "🧀"), ("cherries", "🍒"),
+    ("cherry_blossom", "🌸"), ("chess_pawn", "♟️"), ("chestnut", "🌰"), ("chicken", "🐔"),
+    ("child", "🧒"), ("children_crossing", "🚸"), ("chipmunk", "🐿️"), ("chocolate_bar", "🍫"),
+    ("chopsticks", "🥢"), ("christmas_tree", "🎄"), ("church", "⛪"), ("cinema", "🎦"),
+    ("circus_tent", "🎪"), ("city_sunrise", "🌇"), ("city_sunset", "🌆"), ("cityscape", "🏙️"),
+    ("cl", "🆑"), ("clamp", "🗜️"), ("clap", "👏"), ("clapper", "🎬"), ("classical_building", "🏛️"),
+    ("clinking_glasses", "🥂"), ("clipboard", "📋"), ("clock12", "🕛"), ("clock3", "🕒"),
+    ("clock6", "🕕"), ("clock9", "🕘"), ("closed_book", "📕"), ("closed_lock_with_key", "🔐"),
+    ("closed_umbrella", "🌂"), ("cloud", "☁️"), ("cloud_with_lightning", "🌩️"),
+    ("cloud_with_lightning_and_rain", "⛈️"), ("cloud_with_rain", "🌧️"), ("cloud_with_snow", "🌨️"),
+    ("clown_face", "🤡"), ("clubs", "♣️"), ("cn", "🇨🇳"), ("coat", "🧥"), ("cocktail", "🍸"),
+    ("coconut", "🥥"), ("coffee", "☕"), ("coffin", "⚰️"), ("cold_face", "🥶"), ("cold_sweat", "😰"),
+    ("collision", "💥"), ("comet", "☄️"), ("compass", "🧭"), ("computer", "💻"),
+    ("computer_mouse", "🖱️"), ("confetti_ball", "🎊"), ("confounded", "😖"), ("confused", "😕"),
+    ("congratulations", "㊗️"), ("construction", "🚧"), ("construction_worker", "👷"),
+    ("control_knobs", "🎛️"), ("convenience_store", "🏪"), ("cookie", "🍪"), ("cool", "🆒"),
+    ("cop", "👮"), ("copyright", "©️"), ("corn", "🌽"), ("couch_and_lamp", "🛋️"), ("cow", "🐮"),
+    ("cow2", "🐄"), ("cowboy_hat_face", "🤠"), ("crab", "🦀"), ("crayon", "🖍️"), ("credit_card", "💳"),
+    ("crescent_moon", "🌙"), ("cricket", "🦗"), ("cricket_game", "🏏"), ("crocodile", "🐊"),
+    ("croissant", "🥐"), ("crossed_fingers", "🤞"), ("crossed_flags", "🎌"), ("crossed_swords", "⚔️"),
+    ("crown", "👑"), ("cry", "😢"), ("crying_cat_face", "😿"), ("crystal_ball", "🔮"),
+    ("cucumber", "🥒"), ("cup_with_straw", "🥤"), ("cupcake", "🧁"), ("cupid", "💘"),
+    ("curling_stone", "🥌"), ("curly_loop", "➰"), ("currency_exchange", "💱"), ("curry", "🍛"),
+    ("cursing_face", "🤬"), ("custard", "🍮"), ("customs", "🛃"), ("cut_of_meat", "🥩"),
+    ("cyclone", "🌀"), ("cz", "🇨🇿"), ("dagger", "🗡️"), ("dancer", "💃"), ("dancers", "👯"),
+    ("dango", "🍡"), ("dark_sunglasses", "🕶️"), ("dart", "🎯"), ("dash", "💨"), ("date", "📅"),
+    ("de", "🇩🇪"), ("deciduous_tree", "🌳"), ("deer", "🦌"), ("department_store", "🏬"),
+    ("derelict_house", "🏚️"), ("desert", "🏜️"), ("desert_island", "🏝️"),
+    ("desktop_computer", "🖥️"), ("detective", "🕵️"), ("diamond_shape_with_a_dot_inside", "💠"),
+    ("diamonds", "♦️"), ("disappointed", "😞"), ("disappointed_relieved", "😥"),
+    ("diving_mask", "🤿"), ("diya_lamp", "🪔"), ("dizzy", "💫"), ("dizzy_face", "😵"), ("dk", "🇩🇰"),
+    ("dna", "🧬"), ("do_not_litter", "🚯"), ("dog", "🐶"), ("dog2", "🐕"), ("dollar", "💵"),
+    ("dolls", "🎎"), ("dolphin", "🐬"), ("door", "🚪"), ("doughnut", "🍩"), ("dove", "🕊️"),
+    ("dragon", "🐉"), ("dragon_face", "🐲"), ("dress", "👗"), ("dromedary_camel", "🐪"),
+    ("drooling_face", "🤤"), ("drop_of_blood", "🩸"), ("droplet", "💧"), ("drum", "🥁"), ("duck", "🦆"),
+    ("dumpling", "🥟"), ("dvd", "📀"), ("e-mail", "📧"), ("eagle", "🦅"), ("ear", "👂"),
+    ("ear_of_rice", "🌾"), ("earth_africa", "🌍"), ("earth_americas", "🌎"), ("earth_asia", "🌏"),
+    ("egg", "🥚"), ("eggplant", "🍆"), ("eight", "8️⃣"), ("eight_pointed_black_star", "✴️"),
+    ("eight_spoked_asterisk", "✳️"), ("eject_button", "⏏️"), ("electric_plug", "🔌"),
+    ("elephant", "🐘"), ("email", "✉️"), ("end", "🔚"), ("envelope", "✉️"),
+    ("envelope_with_arrow", "📩"), ("es", "🇪🇸"), ("eu", "🇪🇺"), ("euro", "💶"),
+    ("european_castle", "🏰"), ("european_post_office", "🏤"), ("evergreen_tree", "🌲"),
+    ("exclamation", "❗"), ("exploding_head", "🤯"), ("expressionless", "😑"), ("eye", "👁️"),
+    ("eyeglasses", "👓"), ("eyes", "👀"), ("face_with_head_bandage", "🤕"),
+    ("face_with_thermometer", "🤒"), ("facepalm", "🤦"), ("facepunch", "👊"), ("factory", "🏭"),
+    ("fairy", "🧚"), ("fallen_leaf", "🍂"), ("family", "👪"), ("fast_forward", "⏩"), ("fax", "📠"),
+    ("fearful", "😨"), ("feet", "🐾"), ("female_sign", "♀️"), ("ferris_wheel", "🎡"), ("ferry", "⛴️"),
+    ("fi", "🇫🇮"), ("field_hockey", "🏑"), ("file_cabinet", "🗄️"), ("file_folder", "📁"),
+    ("film_projector", "📽️"), ("film_strip", "🎞️"), ("fire", "🔥"), ("fire_engine", "🚒"),
+    ("fire_extinguisher", "🧯"), ("firecracker", "🧨"), ("fireworks", "🎆"),
+    ("first_quarter_moon", "🌓"), ("fish", "🐟"), ("fish_cake", "🍥"), ("fishing_pole_and_fish", "🎣"),
+    ("fist", "✊"), ("fist_left", "🤛"), ("fist_oncoming", "👊"), ("fist_raised", "✊"),
+    ("fist_right", "🤜"), ("five", "5️⃣"), ("flags", "🎏"), ("flamingo", "🦩"), ("flashlight", "🔦"),
+    ("fleur_de_lis", "⚜️"), ("flight_arrival", "🛬"), ("flight_departure", "🛫"), ("flipper", "🐬"),
+    ("floppy_disk", "💾"), ("flower_playing_cards", "🎴"), ("flushed", "😳"), ("flying_saucer", "🛸"),
+    ("fog", "🌫️"), ("foggy", "🌁"), ("foot", "🦶"), ("football", "🏈"), ("footprints", "👣"),
+    ("fork_and_knife", "🍴"), ("fortune_cookie", "🥠"), ("fountain", "⛲"), ("fountain_pen", "🖋️"),
+    ("four", "4️⃣"), ("four_leaf_clover", "🍀"), ("fox_face", "🦊"), ("fr", "🇫🇷"),
+    ("framed_picture", "🖼️"), ("free", "🆓"), ("fried_egg", "🍳"), ("fried_shrimp", "🍤"),
+    ("fries", "🍟"), ("frog", "🐸"), ("frowning", "😦"), ("frowning_face", "☹️"), ("fu", "🖕"),
+    ("fuelpump", "⛽"), ("full_moon", "🌕"), ("full_moon_with_face", "🌝"), ("funeral_urn", "⚱️"),
+    ("game_die", "🎲"), ("garlic", "🧄"), ("gb", "🇬🇧"), ("gear", "⚙️"), ("gem", "💎"),
+    ("gemini", "♊"), ("ghost", "👻"), ("gift", "🎁"), ("gift_heart", "💝"), ("giraffe", "🦒"),
+    ("girl", "👧"), ("globe_with_meridians", "🌐"), ("gloves", "🧤"), ("goal_net", "🥅"),
+    ("goat", "🐐"), ("goggles", "🥽"), ("golf", "⛳"), ("gorilla", "🦍"), ("gr", "🇬🇷"),
+    ("grapes", "🍇"), ("green_apple", "🍏"), ("green_book", "📗"), ("green_circle", "🟢"),
+    ("green_heart", "💚"), ("green_salad", "🥗"), ("green_square", "🟩"), ("grey_exclamation", "❕"),
+    ("grey_question", "❔"), ("grimacing", "😬"), ("grin", "😁"), ("grinning", "😀"), ("guard", "💂"),
+    ("guide_dog", "🦮"), ("guitar", "🎸"), ("gun", "🔫"), ("hamburger", "🍔"), ("hammer", "🔨"),
+    ("hammer_and_pick", "⚒️"), ("hammer_and_wrench", "🛠️"), ("hamster", "🐹"), ("hand", "✋"),
+    ("hand_over_mouth", "🤭"), ("handbag", "👜"), ("handshake", "🤝"), ("hankey", "💩"),
+    ("hash", "#️⃣"), ("hatched_chick", "🐥"), ("hatching_chick", "🐣"), ("headphones", "🎧"),
+    ("hear_no_evil", "🙉"), ("heart", "❤️"), ("heart_decoration", "💟"), ("heart_eyes", "😍"),
+    ("heart_eyes_cat", "😻"), ("heartbeat", "💓"), ("heartpulse", "💗"), ("hearts", "♥️"),
+    ("heavy_check_mark", "✔️"), ("heavy_division_sign", "➗"), ("heavy_dollar_sign", "💲"),
+    ("heavy_exclamation_mark", "❗"), ("heavy_heart_exclamation", "❣️"), ("heavy_minus_sign", "➖"),
+    ("heavy_multiplication_x", "✖️"), ("heavy_plus_sign", "➕"), ("hedgehog", "🦔"),
+    ("helicopter", "🚁"), ("herb", "🌿"), ("hibiscus", "🌺"), ("high_brightness", "🔆"),
+    ("high_heel", "👠"), ("hippopotamus", "🦛"), ("hocho", "🔪"), ("hole", "🕳️"), ("honey_pot", "🍯"),
+    ("honeybee", "🐝"), ("horse", "🐴"), ("hospital", "🏥"), ("hot_face", "🥵"), ("hot_pepper", "🌶️"),
+    ("hotdog", "🌭"), ("hotel", "🏨"), ("hotsprings", "♨️"), ("hourglass", "⌛"),
+    ("hourglass_flowing_sand", "⏳"), ("house", "🏠"), ("house_with_garden", "🏡"), ("houses", "🏘️"),
+    ("hugs", "🤗"), ("hushed", "😯"), ("ice_cream", "🍨"), ("ice_cube", "🧊"), ("ice_hockey", "🏒"),
+    ("ice_skate", "⛸️"), ("icecream", "🍦"), ("id", "🆔"), ("ie", "🇮🇪"), ("il", "🇮🇱"), ("imp", "👿"),
+    ("in", "🇮🇳"), ("inbox_tray", "📥"), ("incoming_envelope", "📨"), ("infinity", "♾️"),
+    ("information_source", "ℹ️"), ("innocent", "😇"), ("interrobang", "⁉️"), ("iphone", "📱"),
+    ("it", "🇮🇹"), ("izakaya_lantern", "🏮"), ("jack_o_lantern", "🎃"), ("japan", "🗾"),
+    ("japanese_castle", "🏯"), ("japanese_goblin", "👺"), ("japanese_ogre", "👹"), ("jeans", "👖"),
+    ("jigsaw", "🧩"), ("joy", "😂"), ("joy_cat", "😹"), ("joystick", "🕹️"), ("jp", "🇯🇵"),
+    ("kaaba", "🕋"), ("kangaroo", "🦘"), ("key", "🔑"), ("keyboard", "⌨️"), ("keycap_ten", "🔟"),
+    ("kick_scooter", "🛴"), ("kimono", "👘"), ("kiss", "💋"), ("kissing", "😗"), ("kissing_cat", "😽"),
+    ("kissing_closed_eyes", "😚"), ("kissing_heart", "😘"), ("kissing_smiling_eyes", "😙"),
+    ("kite", "🪁"), ("kiwi_fruit", "🥝"), ("knife", "🔪"), ("koala", "🐨"), ("koko", "🈁"),
+    ("kr", "🇰🇷"), ("lab_coat", "🥼"), ("label", "🏷️"), ("lady_beetle", "🐞"), ("lantern", "🏮"),
+    ("large_blue_circle", "🔵"), ("large_blue_diamond", "🔷"), ("large_orange_diamond", "🔶"),
+    ("last_quarter_moon", "🌗"), ("latin_cross", "✝️"), ("laughing", "😆"), ("leafy_green", "🥬"),
+    ("leaves", "🍃"), ("ledger", "📒"), ("left_luggage", "🛅"), ("left_right_arrow", "↔️"),
+    ("left_speech_bubble", "🗨️"), ("leftwards_arrow_with_hook", "↩️"), ("leg", "🦵"),
+    ("lemon", "🍋"), ("leo", "♌"), ("leopard", "🐆"), ("level_slider", "🎚️"), ("libra", "♎"),
+    ("light_rail", "🚈"), ("link", "🔗"), ("lion", "🦁"), ("lips", "👄"), ("lipstick", "💄"),
+    ("lizard", "🦎"), ("llama", "🦙"), ("lobster", "🦞"), ("lock", "🔒"), ("lock_with_ink_pen", "🔏"),
+    ("lollipop", "🍭"), ("loop", "➿"), ("lotion_bottle", "🧴"), ("loud_sound", "🔊"),
+    ("loudspeaker", "📢"), ("love_hotel", "🏩"), ("love_letter", "💌"), ("love_you_gesture", "🤟"),
+    ("low_brightness", "🔅"), ("luggage", "🧳"), ("lying_face", "🤥"), ("m", "Ⓜ️"), ("mag", "🔍"),
+    ("mag_right", "🔎"), ("mage", "🧙"), ("magnet", "🧲"), ("mahjong", "🀄"), ("mailbox", "📫"),
+    ("mailbox_closed", "📪"), ("mailbox_with_mail", "📬"), ("mailbox_with_no_mail", "📭"),
+    ("male_sign", "♂️"), ("man", "👨"), ("man_dancing", "🕺"), ("man_technologist", "👨‍💻"),
+    ("mango", "🥭"), ("mans_shoe", "👞"), ("mantelpiece_clock", "🕰️"), ("maple_leaf", "🍁"),
+    ("martial_arts_uniform", "🥋"), ("mask", "😷"), ("meat_on_bone", "🍖"), ("mechanical_arm", "🦾"),
+    ("medal_military", "🎖️"), ("medal_sports", "🏅"), ("medical_symbol", "⚕️"), ("mega", "📣"),
+    ("melon", "🍈"), ("memo", "📝"), ("menorah", "🕎"), ("mens", "🚹"), ("metal", "🤘"), ("metro", "🚇"),
+    ("microbe", "🦠"), ("microphone", "🎤"), ("microscope", "🔬"), ("middle_finger", "🖕"),
+    ("milk_glass", "🥛"), ("milky_way", "🌌"), ("minibus", "🚐"), ("minidisc", "💽"),
+    ("mobile_phone_off", "📴"), ("money_mouth_face", "🤑"), ("money_with_wings", "💸"),
+    ("moneybag", "💰"), ("monkey", "🐒"), ("monkey_face", "🐵"), ("monocle_face", "🧐"),
+    ("monorail", "🚝"), ("mortar_board", "🎓"), ("mosque", "🕌"), ("mosquito", "🦟"),
+    ("motor_boat", "🛥️"), ("motor_scooter", "🛵"), ("motorcycle", "🏍️"), ("motorway", "🛣️"),
+    ("mount_fuji", "🗻"), ("mountain", "⛰️"), ("mountain_railway", "🚞"), ("mountain_snow", "🏔️"),
+    ("mouse", "🐭"), ("mouse2", "🐁"), ("movie_camera", "🎥"), ("moyai", "🗿"), ("muscle", "💪"),
+    ("mushroom", "🍄"), ("musical_keyboard", "🎹"), ("musical_note", "🎵"), ("musical_score", "🎼"),
+    ("mute", "🔇"), ("mx", "🇲🇽"), ("nail_care", "💅"), ("name_badge", "📛"), ("national_park", "🏞️"),
+    ("nauseated_face", "🤢"), ("nazar_amulet", "🧿"), ("necktie", "👔"),
+    ("negative_squared_cross_mark", "❎"), ("nerd_face", "🤓"), ("neutral_face", "😐"), ("new", "🆕"),
+    ("new_moon", "🌑"), ("new_moon_with_face", "🌚"), ("newspaper", "📰"), ("newspaper_roll", "🗞️"),
+    ("next_track_button", "⏭️"), ("ng", "🆖"), ("night_with_stars", "🌃"), ("nine", "9️⃣"),
+    ("nl", "🇳🇱"), ("no", "🇳🇴"), ("no_bell", "🔕"), ("no_bicycles", "🚳"), ("no_entry", "⛔"),
+    ("no_entry_sign", "🚫"), ("no_good", "🙅"), ("no_mobile_phones", "📵"), ("no_mouth", "😶"),
+    ("no_pedestrians", "🚷"), ("no_smoking", "🚭"), ("non-potable_water", "🚱"), ("nose", "👃"),
+    ("notebook", "📓"), ("notebook_with_decorative_cover", "📔"), ("notes", "🎶"),
+    ("nut_and_bolt", "🔩"), ("nz", "🇳🇿"), ("o", "⭕"), ("o2", "🅾️"), ("ocean", "🌊"),
+    ("octopus", "🐙"), ("oden", "🍢"), ("office", "🏢"), ("ok", "🆗"), ("ok_hand", "👌"),
+    ("ok_person", "🙆"), ("old_key", "🗝️"), ("older_adult", "🧓"), ("older_man", "👴"),
+    ("older_woman", "👵"), ("om", "🕉️"), ("on", "🔛"), ("oncoming_automobile", "🚘"),
+    ("oncoming_bus", "🚍"), ("oncoming_police_car", "🚔"), ("oncoming_taxi", "🚖"), ("one", "1️⃣"),
+    ("onion", "🧅"), ("open_book", "📖"), ("open_file_folder", "📂"), ("open_hands", "👐"),
+    ("open_mouth", "😮"), ("open_umbrella", "☂️"), ("ophiuchus", "⛎"), ("orange", "🍊"),
+    ("orange_book", "📙"), ("orange_circle", "🟠"), ("orange_heart", "🧡"), ("orange_square", "🟧"),
+    ("orthodox_cross", "☦️"), ("otter", "🦦"), ("outbox_tray", "📤"), ("owl", "🦉"), ("ox", "🐂"),
+    ("package", "📦"), ("page_facing_up", "📄"), ("page_with_curl", "📃"), ("pager", "📟"),
+    ("paintbrush", "🖌️"), ("palm_tree", "🌴"), ("palms_up_together", "🤲"), ("pancakes", "🥞"),
+    ("panda_face", "🐼"), ("paperclip", "📎"), ("paperclips", "🖇️"), ("parachute", "🪂"),
+    ("parasol_on_ground", "⛱️"), ("parking", "🅿️"), ("parrot", "🦜"),
+    ("part_alternation_mark", "〽️"), ("partly_sunny", "⛅"), ("partying_face", "🥳"),
+    ("passenger_ship", "🛳️"), ("passport_control", "🛂"), ("pause_button", "⏸️"),
+    ("paw_prints", "🐾"), ("peace_symbol", "☮️"), ("peach", "🍑"), ("peacock", "🦚"),
+    ("peanuts", "🥜"), ("pear", "🍐"), ("pen", "🖊️"), ("pencil", "📝"), ("pencil2", "✏️"),
+    ("penguin", "🐧"), ("pensive", "😔"), ("performing_arts", "🎭"), ("persevere", "😣"),
+    ("person_frowning", "🙍"), ("person_pouting", "🙎"), ("petri_dish", "🧫"), ("phone", "☎️"),
+    ("pick", "⛏️"), ("pie", "🥧"), ("pig", "🐷"), ("pig2", "🐖"), ("pig_nose", "🐽"), ("pill", "💊"),
+    ("pinching_hand", "🤏"), ("pineapple", "🍍"), ("ping_pong", "🏓"), ("pirate_flag", "🏴‍☠️"),
+    ("pisces", "♓"), ("pizza", "🍕"), ("pl", "🇵🇱"), ("place_of_worship", "🛐"),
+    ("plate_with_cutlery", "🍽️"), ("play_or_pause_button", "⏯️"), ("pleading_face", "🥺"),
+    ("point_down", "👇"), ("point_left", "👈"), ("point_right", "👉"), ("point_up", "☝️"),
+    ("point_up_2", "👆"), ("police_car", "🚓"), ("poodle", "🐩"), ("poop", "💩"), ("popcorn", "🍿"),
+    ("post_office", "🏣"), ("postal_horn", "📯"), ("postbox", "📮"), ("potable_water", "🚰"),
+    ("potato", "🥔"), ("pouch", "👝"), ("poultry_leg", "🍗"), ("pound", "💷"), ("pout", "😡"),
+    ("pouting_cat", "😾"), ("pray", "🙏"), ("prayer_beads", "📿"), ("pretzel", "🥨"),
+    ("previous_track_button", "⏮️"), ("prince", "🤴"), ("princess", "👸"), ("printer", "🖨️"),
+    ("probing_cane", "🦯"), ("pt", "🇵🇹"), ("punch", "👊"), ("purple_circle", "🟣"),
+    ("purple_heart", "💜"), ("purple_square", "🟪"), ("purse", "👛"), ("pushpin", "📌"),
+    ("put_litter_in_its_place", "🚮"), ("question", "❓"), ("rabbit", "🐰"), ("rabbit2", "🐇"),
+    ("raccoon", "🦝"), ("racehorse", "🐎"), ("racing_car", "🏎️"), ("radio", "📻"),
+    ("radio_button", "🔘"), ("radioactive", "☢️"), ("rage", "😡"), ("railway_car", "🚃"),
+    ("railway_track", "🛤️"), ("rainbow", "🌈"), ("rainbow_flag", "🏳️‍🌈"),
+    ("raised_back_of_hand", "🤚"), ("raised_eyebrow", "🤨"), ("raised_hand", "✋"),
+    ("raised_hand_with_fingers_splayed", "🖐️"), ("raised_hands", "🙌"), ("raising_hand", "🙋"),
+    ("ram", "🐏"), ("ramen", "🍜"), ("rat", "🐀"), ("razor", "🪒"), ("receipt", "🧾"),
+    ("record_button", "⏺️"), ("recycle", "♻️"), ("red_car", "🚗"), ("red_circle", "🔴"),
+    ("red_envelope", "🧧"), ("red_square", "🟥"), ("registered", "®️"), ("relaxed", "☺️"),
+    ("relieved", "😌"), ("reminder_ribbon", "🎗️"), ("repeat", "🔁"), ("repeat_one", "🔂"),
+    ("rescue_worker_helmet", "⛑️"), ("restroom", "🚻"), ("revolving_hearts", "💞"), ("rewind", "⏪"),
+    ("rhinoceros", "🦏"), ("ribbon", "🎀"), ("rice", "🍚"), ("rice_ball", "🍙"), ("rice_cracker", "🍘"),
+    ("rice_scene", "🎑"), ("right_anger_bubble", "🗯️"), ("ring", "💍"), ("ringed_planet", "🪐"),
+    ("robot", "🤖"), ("rocket", "🚀"), ("rofl", "🤣"), ("roll_eyes", "🙄"), ("roll_of_paper", "🧻"),
+    ("roller_coaster", "🎢"), ("rooster", "🐓"), ("rose", "🌹"), ("rosette", "🏵️"),
+    ("rotating_light", "🚨"), ("round_pushpin", "📍"), ("ru", "🇷🇺"), ("rugby_football", "🏉"),
+    ("runner", "🏃"), ("running", "🏃"), ("running_shirt_with_sash", "🎽"), ("safety_pin", "🧷"),
+    ("sagittarius", "♐"), ("sailboat", "⛵"), ("sake", "🍶"), ("salt", "🧂"), ("sandal", "👡"),
+    ("sandwich", "🥪"), ("santa", "🎅"), ("satellite", "📡"), ("satisfied", "😆"), ("sauropod", "🦕"),
+    ("saxophone", "🎷"), ("scarf", "🧣"), ("school", "🏫"), ("school_satchel", "🎒"),
+    ("scientist", "🧑‍🔬"), ("scissors", "✂️"), ("scorpion", "🦂"), ("scorpius", "♏"),
+    ("scream", "😱"), ("scream_cat", "🙀"), ("scroll", "📜"), ("se", "🇸🇪"), ("seat", "💺"),
+    ("secret", "㊙️"), ("see_no_evil", "🙈"), ("seedling", "🌱"), ("selfie", "🤳"), ("seven", "7️⃣"),
+    ("shamrock", "☘️"), ("shark", "🦈"), ("shaved_ice", "🍧"), ("sheep", "🐑"), ("shell", "🐚"),
+    ("shield", "🛡️"), ("ship", "🚢"), ("shirt", "👕"), ("shoe", "👞"), ("shopping", "🛍️"),
+    ("shopping_cart", "🛒"), ("shower", "🚿"), ("shrimp", "🦐"), ("shrug", "🤷"),
+    ("shushing_face", "🤫"), ("signal_strength", "📶"), ("six", "6️⃣"), ("six_pointed_star", "🔯"),
+    ("skateboard", "🛹"), ("ski", "🎿"), ("skull", "💀"), ("skunk", "🦨"), ("sled", "🛷"),
+    ("sleeping", "😴"), ("sleepy", "😪"), ("slightly_frowning_face", "🙁"),
+    ("slightly_smiling_face", "🙂"), ("slot_machine", "🎰"), ("sloth", "🦥"),
+    ("small_airplane", "🛩️"), ("small_blue_diamond", "🔹"), ("small_orange_diamond", "🔸"),
+    ("small_red_triangle", "🔺"), ("small_red_triangle_down", "🔻"), ("smile", "😄"),
+    ("smile_cat", "😸"), ("smiley", "😃"), ("smiley_cat", "😺"),
+    ("smiling_face_with_three_hearts", "🥰"), ("smiling_imp", "😈"), ("smirk", "😏"),
+    ("smirk_cat", "😼"), ("smoking", "🚬"), ("snail", "🐌"), ("snake", "🐍"), ("sneezing_face", "🤧"),
+    ("snowflake", "❄️"), ("snowman", "⛄"), ("snowman_with_snow", "☃️"), ("soap", "🧼"),
+    ("sob", "😭"), ("soccer", "⚽"), ("socks", "🧦"), ("softball", "🥎"), ("soon", "🔜"), ("sos", "🆘"),
+    ("sound", "🔉"), ("space_invader", "👾"), ("spades", "♠️"), ("spaghetti", "🍝"), ("sparkle", "❇️"),
+    ("sparkler", "🎇"), ("sparkles", "✨"), ("sparkling_heart", "💖"), ("speak_no_evil", "🙊"),
+    ("speaker", "🔈"), ("speech_balloon", "💬"), ("speedboat", "🚤"), ("spider", "🕷️"),
+    ("spider_web", "🕸️"), ("spiral_calendar", "🗓️"), ("spiral_notepad", "🗒️"), ("sponge", "🧽"),
+    ("spoon", "🥄"), ("squid", "🦑"), ("stadium", "🏟️"), ("star", "⭐"), ("star2", "🌟"),
+    ("star_and_crescent", "☪️"), ("star_of_david", "✡️"), ("star_struck", "🤩"), ("stars", "🌠"),
+    ("station", "🚉"), ("statue_of_liberty", "🗽"), ("steam_locomotive", "🚂"), ("stethoscope", "🩺"),
+    ("stew", "🍲"), ("stop_button", "⏹️"), ("stop_sign", "🛑"), ("stopwatch", "⏱️"),
+    ("straight_ruler", "📏"), ("strawberry", "🍓"), ("stuck_out_tongue", "😛"),
+    ("stuck_out_tongue_closed_eyes", "😝"), ("stuck_out_tongue_winking_eye", "😜"),
+    ("student", "🧑‍🎓"), ("studio_microphone", "🎙️"), ("sun_behind_large_cloud", "🌥️"),
+    ("sun_behind_rain_cloud", "🌦️"), ("sun_behind_small_cloud", "🌤️"), ("sun_with_face", "🌞"),
+    ("sunflower", "🌻"), ("sunglasses", "😎"), ("sunny", "☀️"), ("sunrise", "🌅"),
+    ("sunrise_over_mountains", "🌄"), ("superhero", "🦸"), ("supervillain", "🦹"), ("sushi", "🍣"),
+    ("swan", "🦢"), ("sweat", "😓"), ("sweat_drops", "💦"), ("sweat_smile", "😅"),
+    ("sweet_potato", "🍠"), ("symbols", "🔣"), ("synagogue", "🕍"), ("syringe", "💉"), ("t-rex", "🦖"),
+    ("taco", "🌮"), ("tada", "🎉"), ("takeout_box", "🥡"), ("tanabata_tree", "🎋"), ("tangerine", "🍊"),
+    ("taurus", "♉"), ("taxi", "🚕"), ("tea", "🍵"), ("teacher", "🧑‍🏫"), ("technologist", "🧑‍💻"),
+    ("teddy_bear", "🧸"), ("telephone", "☎️"), ("telephone_receiver", "📞"), ("telescope", "🔭"),
+    ("tennis", "🎾"), ("tent", "⛺"), ("test_tube", "🧪"), ("thermometer", "🌡️"), ("thinking", "🤔"),
+    ("thought_balloon", "💭"), ("thread", "🧵"), ("three", "3️⃣"), ("thumbsdown", "👎"),
+    ("thumbsup", "👍"), ("ticket", "🎫"), ("tickets", "🎟️"), ("tiger", "🐯"), ("tiger2", "🐅"),
+    ("timer_clock", "⏲️"), ("tipping_hand_person", "💁"), ("tired_face", "😫"), ("tm", "™️"),
+    ("toilet", "🚽"), ("tokyo_tower", "🗼"), ("tomato", "🍅"), ("tongue", "👅"), ("toolbox", "🧰"),
+    ("top", "🔝"), ("tophat", "🎩"), ("tornado", "🌪️"), ("tr", "🇹🇷"), ("trackball", "🖲️"),
+    ("tractor", "🚜"), ("traffic_light", "🚥"), ("train", "🚋"), ("train2", "🚆"), ("tram", "🚊"),
+    ("triangular_flag_on_post", "🚩"), ("triangular_ruler", "📐"), ("trident", "🔱"),
+    ("triumph", "😤"), ("trolleybus", "🚎"), ("trophy", "🏆"), ("tropical_drink", "🍹"),
+    ("tropical_fish", "🐠"), ("truck", "🚚"), ("trumpet", "🎺"), ("tshirt", "👕"), ("tulip", "🌷"),
+    ("tumbler_glass", "🥃"), ("turkey", "🦃"), ("turtle", "🐢"), ("tv", "📺"),
+    ("twisted_rightwards_arrows", "🔀"), ("two", "2️⃣"), ("two_hearts", "💕"), ("ua", "🇺🇦"),
+    ("uk", "🇬🇧"), ("umbrella", "☔"), ("unamused", "😒"), ("underage", "🔞"), ("unicorn", "🦄"),
+    ("unlock", "🔓"), ("up", "🆙"), ("upside_down_face", "🙃"), ("us", "🇺🇸"), ("v", "✌️"),
+    ("vampire", "🧛"), ("vertical_traffic_light", "🚦"), ("vhs", "📼"), ("vibration_mode", "📳"),
+    ("video_camera", "📹"), ("video_game", "🎮"), ("violin", "🎻"), ("virgo", "♍"), ("volcano", "🌋"),
+    ("volleyball", "🏐"), ("vomiting_face", "🤮"), ("vs", "🆚"), ("vulcan_salute", "🖖"),
+    ("walking", "🚶"), ("warning", "⚠️"), ("wastebasket", "🗑️"), ("watch", "⌚"),
+    ("watermelon", "🍉"), ("wave", "👋"), ("wavy_dash", "〰️"), ("wc", "🚾"), ("weary", "😩"),
+    ("wedding", "💒"), ("whale", "🐳"), ("whale2", "🐋"), ("wheel_of_dharma", "☸️"),
+    ("wheelchair", "♿"), ("white_check_mark", "✅"), ("white_circle", "⚪"), ("white_flag", "🏳️"),
+    ("white_flower", "💮"), ("white_heart", "🤍"), ("white_large_square", "⬜"),
+    ("white_medium_square", "◻️"), ("white_small_square", "▫️"), ("white_square_button", "🔳"),
+    ("wilted_flower", "🥀"), ("wind_chime", "🎐"), ("wind_face", "🌬️"), ("wine_glass", "🍷"),
+    ("wink", "😉"), ("wolf", "🐺"), ("woman", "👩"), ("woman_technologist", "👩‍💻"),
+    ("womans_clothes", "👚"), ("womans_hat", "👒"), ("womens", "🚺"), ("woozy_face", "🥴"),
+    ("world_map", "🗺️"), ("worried", "😟"), ("wrench", "🔧"), ("writing_hand", "✍️"), ("x", "❌"),
+    ("yarn", "🧶"), ("yawning_face", "🥱"), ("yellow_circle", "🟡"), ("yellow_heart", "💛"),
+    ("yellow_square", "🟨"), ("yen", "💴"), ("yin_yang", "☯️"), ("yo_yo", "🪀"), ("yum", "😋"),
+    ("za", "🇿🇦"), ("zany_face", "🤪"), ("zap", "⚡"), ("zebra", "🦓"), ("zero", "0️⃣"),
+    ("zipper_mouth_face", "🤐"), ("zombie", "🧟"), ("zzz", "💤"),
+];
+
+
+/// Returns the emoji of a shortcode given without its colons, `None` when it is unknown
+
+pub fn emoji(shortcode: &str) -> Option<&'static str> {
+    EMOJI.binary_search_by(|&(code, _)| code.cmp(shortcode))
+         .ok()
+         .map(|i| EMOJI[i].1)
+}
+
+/// Replaces the known shortcodes of `text` with their emoji
+
+pub fn replace_shortcodes(text: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(':') {
+        replaced.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        // The closing colon of a shortcode is the first character that can not be part of it
+        let end = after.find(|c| !is_shortcode_char(c)).unwrap_or(after.len());
+        if end > 0 && after[end..].starts_with(':') {
+            if let Some(emoji) = emoji(&after[..end]) {
+                replaced.push_str(emoji);
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+
+        // An unknown shortcode keeps its colon, its closing one can open the next shortcode
+        replaced.push(':');
+        rest = after;
+    }
+    replaced.push_str(rest);
+
+    replaced
+}
+
+
+fn is_shortcode_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '+' || c == '-'
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcodes_are_sorted_and_unique() {
+        for pair in EMOJI.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "{} is not before {}", pair[0].0, pair[1].0);
+        }
+        assert!(EMOJI.iter().all(|&(code, _)| code.chars().all(is_shortcode_char)));
+    }
+
+    #[test]
+    fn known_shortcodes_are_replaced() {
+        assert_eq!(emoji("rocket"), Some("🚀"));
+        assert_eq!(emoji("+1"), emoji("thumbsup"));
+        assert_eq!(emoji("Rocket"), None);
+
+        assert_eq!(replace_shortcodes(":warning: Breaking :rocket::tada:"), "⚠️ Breaking 🚀🎉");
+        assert_eq!(replace_shortcodes("at 10:30:00, :unknown: and :fr:"), "at 10:30:00, :unknown: and 🇫🇷");
+        assert_eq!(replace_shortcodes("a::heart: :: :"), "a:❤️ :: :");
+    }
+}
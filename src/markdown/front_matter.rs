@@ -0,0 +1,143 @@
+//! Front matter of the chapters
+//!
+//! A chapter can start with a YAML block between two `---` lines, its metadata:
+//!
+//! ```markdown
+//! ---
+//! title: Getting started with mdBook
+//! description: Install mdBook and create your first book
+//! ---
+//!
+//! # Getting started
+//! ```
+//!
+//! The block is removed from the markdown when the chapter is read and its keys are put in the
+//! `metadata` of the chapter. The html renderer uses the `title` and the `description`, the other
+//! keys are kept for the preprocessors.
+
+use std::cmp;
+use std::collections::BTreeMap;
+
+use rustc_serialize::json::Json;
+use yaml_rust::{Yaml, YamlLoader};
+
+
+/// Splits the front matter from the markdown of a chapter. Returns the metadata, empty when there is
+/// no front matter, and the rest of the markdown.
+///
+/// The front matter starts with a `---` line at the very beginning of the text and ends with the
+/// next `---` or `...` line. Without a closing line the text has no front matter. An error is
+/// returned when the block is not valid YAML or not a mapping of keys to values.
+
+pub fn split(text: &str) -> Result<(BTreeMap<String, Json>, &str), String> {
+    let body = match strip_fence(text, "---") {
+        Some(body) => body,
+        None => return Ok((BTreeMap::new(), text)),
+    };
+
+    // Start of the closing line and start of the markdown after it
+    let mut end = None;
+    let mut start = 0;
+    for line in body.split('\n') {
+        let next = start + line.len() + 1;
+        let fence = line.trim_right_matches('\r');
+        if fence == "---" || fence == "..." {
+            end = Some((start, next));
+            break;
+        }
+        start = next;
+    }
+    let (yaml, markdown) = match end {
+        Some((end, next)) => (&body[..end], &body[cmp::min(next, body.len())..]),
+        None => return Ok((BTreeMap::new(), text)),
+    };
+
+    let documents = try!(YamlLoader::load_from_str(yaml).map_err(|e| format!("invalid front matter: {}", e)));
+    let metadata = match documents.into_iter().next() {
+        None | Some(Yaml::Null) => BTreeMap::new(),
+        Some(Yaml::Hash(hash)) => {
+            match yaml_to_json(Yaml::Hash(hash)) {
+                Json::Object(metadata) => metadata,
+                _ => unreachable!(),
+            }
+        },
+        Some(_) => return Err("invalid front matter: expected `key: value` lines".to_owned()),
+    };
+
+    Ok((metadata, markdown))
+}
+
+// The text after the first line if it is `fence`
+fn strip_fence<'a>(text: &'a str, fence: &str) -> Option<&'a str> {
+    let first_line = text.find('\n').map_or(text, |i| &text[..i]);
+    if first_line.trim_right_matches('\r') == fence {
+        Some(&text[cmp::min(first_line.len() + 1, text.len())..])
+    } else {
+        None
+    }
+}
+
+// Aliases are not resolved by the YAML parser, they become `null` like the explicit null values
+fn yaml_to_json(yaml: Yaml) -> Json {
+    match yaml {
+        Yaml::Real(real) => real.parse().map(Json::F64).unwrap_or(Json::String(real)),
+        Yaml::Integer(integer) => Json::I64(integer),
+        Yaml::String(string) => Json::String(string),
+        Yaml::Boolean(boolean) => Json::Boolean(boolean),
+        Yaml::Array(array) => Json::Array(array.into_iter().map(yaml_to_json).collect()),
+        Yaml::Hash(hash) => {
+            Json::Object(hash.into_iter()
+                             .filter_map(|(key, value)| {
+                                 let key = match key {
+                                     Yaml::String(key) | Yaml::Real(key) => key,
+                                     Yaml::Integer(key) => key.to_string(),
+                                     Yaml::Boolean(key) => key.to_string(),
+                                     _ => return None,
+                                 };
+                                 Some((key, yaml_to_json(value)))
+                             })
+                             .collect())
+        },
+        Yaml::Alias(_) | Yaml::Null | Yaml::BadValue => Json::Null,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rustc_serialize::json::Json;
+
+    #[test]
+    fn split_front_matter() {
+        let text = "---\ntitle: Getting started\ndraft: true\nweight: 2\ntags: [intro, install]\n---\n# Title\n";
+        let (metadata, markdown) = split(text).unwrap();
+
+        assert_eq!(markdown, "# Title\n");
+        assert_eq!(metadata["title"], Json::String("Getting started".to_owned()));
+        assert_eq!(metadata["draft"], Json::Boolean(true));
+        assert_eq!(metadata["weight"], Json::I64(2));
+        assert_eq!(metadata["tags"],
+                   Json::Array(vec![Json::String("intro".to_owned()), Json::String("install".to_owned())]));
+    }
+
+    #[test]
+    fn split_without_front_matter() {
+        for text in &["# Title\n\n---\n\nText\n", "---\n\nA thematic break, no closing line\n", ""] {
+            let (metadata, markdown) = split(text).unwrap();
+            assert!(metadata.is_empty());
+            assert_eq!(markdown, *text);
+        }
+
+        let (metadata, markdown) = split("---\r\n---\r\nText").unwrap();
+        assert!(metadata.is_empty());
+        assert_eq!(markdown, "Text");
+    }
+
+    #[test]
+    fn split_invalid_front_matter() {
+        assert!(split("---\ntitle: [unclosed\n---\n").unwrap_err().contains("invalid front matter"));
+        assert!(split("---\n- a list\n---\n").unwrap_err().contains("key: value"));
+    }
+}
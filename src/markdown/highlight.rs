@@ -0,0 +1,134 @@
+//! Syntax highlighting of the code blocks at build time
+//!
+//! The code is highlighted with [syntect](https://github.com/trishume/syntect) and the tokens are
+//! wrapped in `<span>` elements whose classes are prefixed with `syn-`. Their colors come from the
+//! stylesheet generated for one of the bundled themes by [`theme_css()`](fn.theme_css.html).
+//!
+//! The spans never cross the end of a line, this way the lines of the code can still be handled
+//! one by one in the browser, for example to hide the lines of rust code starting with `#`.
+
+use std::error::Error;
+use std::io;
+
+use syntect::highlighting::ThemeSet;
+use syntect::html::{ClassStyle, css_for_theme_with_class_style, line_tokens_to_classed_spans};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "syn-" };
+
+/// Highlights code using the syntaxes bundled with syntect
+pub struct Highlighter {
+    syntaxes: SyntaxSet,
+}
+
+impl Highlighter {
+    pub fn new() -> Self {
+        Highlighter { syntaxes: SyntaxSet::load_defaults_newlines() }
+    }
+
+    /// Returns the highlighted html of `code`, `info` is the info string of the fenced code block
+    /// (`rust,ignore`). Returns `None` when the language is unknown.
+    ///
+    /// The html is meant to be put in a `<code>` element with the `syn-code` class, which gives it
+    /// the colors of the theme.
+
+    pub fn highlight(&self, code: &str, info: &str) -> Option<String> {
+        let language = info.split(|c: char| c == ',' || c.is_whitespace()).next().unwrap_or("");
+        if language.is_empty() {
+            return None;
+        }
+
+        let syntax = match self.syntaxes.find_syntax_by_token(language) {
+            Some(syntax) => syntax,
+            None => return None,
+        };
+
+        let mut state = ParseState::new(syntax);
+        let mut stack = ScopeStack::new();
+        let mut html = String::with_capacity(code.len() * 2);
+
+        for line in LinesWithEndings::from(code) {
+            // Reopen the spans of the scopes that go on from the previous line
+            for scope in stack.as_slice() {
+                html.push_str("<span class=\"");
+                html.push_str(&classes(&scope.build_string()));
+                html.push_str("\">");
+            }
+
+            let ops = match state.parse_line(line, &self.syntaxes) {
+                Ok(ops) => ops,
+                Err(_) => return None,
+            };
+            let (spans, _) = match line_tokens_to_classed_spans(line, &ops, CLASS_STYLE, &mut stack) {
+                Ok(spans) => spans,
+                Err(_) => return None,
+            };
+
+            let newline = spans.ends_with('\n');
+            html.push_str(spans.trim_right_matches('\n'));
+            for _ in stack.as_slice() {
+                html.push_str("</span>");
+            }
+            if newline {
+                html.push('\n');
+            }
+        }
+
+        Some(html)
+    }
+}
+
+/// Returns the names of the bundled themes
+pub fn theme_names() -> Vec<String> {
+    ThemeSet::load_defaults().themes.keys().cloned().collect()
+}
+
+/// Returns the stylesheet giving the colors of the theme `name` to the highlighted code
+
+pub fn theme_css(name: &str) -> Result<String, Box<Error>> {
+    let themes = ThemeSet::load_defaults();
+
+    let theme = match themes.themes.get(name) {
+        Some(theme) => theme,
+        None => {
+            return Err(Box::new(io::Error::new(io::ErrorKind::InvalidInput,
+                                               format!("Unknown highlight theme `{}`, the available themes are: {}",
+                                                       name,
+                                                       theme_names().join(", ")))))
+        },
+    };
+
+    match css_for_theme_with_class_style(theme, CLASS_STYLE) {
+        Ok(css) => Ok(css),
+        Err(e) => Err(Box::new(io::Error::new(io::ErrorKind::Other, format!("{}", e)))),
+    }
+}
+
+
+// Same classes as the ones syntect gives to a scope, `source.rust` becomes `syn-source syn-rust`
+fn classes(scope: &str) -> String {
+    scope.split('.').map(|atom| format!("syn-{}", atom)).collect::<Vec<_>>().join(" ")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_are_closed_on_every_line() {
+        let highlighter = Highlighter::new();
+        let html = highlighter.highlight("/* a\nb */\nfn main() {}\n", "rust,ignore").unwrap();
+
+        assert_eq!(html.lines().count(), 3);
+        for line in html.lines() {
+            assert_eq!(line.matches("<span").count(), line.matches("</span>").count());
+        }
+        assert!(html.contains("<span class=\"syn-storage syn-type syn-function syn-rust\">fn</span>"));
+
+        assert_eq!(highlighter.highlight("code", "not-a-language"), None);
+        assert!(theme_css("not a theme").is_err());
+    }
+}
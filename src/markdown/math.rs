@@ -0,0 +1,184 @@
+//! Keeps `$...$` and `$$...$$` math intact through the markdown conversion
+//!
+//! The markdown parser would otherwise turn the `_` and `*` of the equations into emphasis. Before
+//! the conversion [`protect()`](fn.protect.html) replaces every math span with a placeholder made
+//! of letters and digits only, [`restore()`](fn.restore.html) then puts the equations back in the
+//! html using the `\(...\)` and `\[...\]` delimiters that MathJax recognizes. Math in code blocks
+//! and inline code is left alone, a dollar sign can be escaped with `\$`.
+
+use utils::html::escape_html;
+
+
+/// Replaces the math spans of `text` with placeholders, returns the new text and the equations
+/// with their delimiters in the order of the placeholders.
+
+pub fn protect(text: &str) -> (String, Vec<String>) {
+    let mut out = String::with_capacity(text.len());
+    let mut math = vec![];
+
+    let mut in_fence = false;
+    // Content of a `$$` block that spans several lines
+    let mut display: Option<String> = None;
+
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            match display {
+                Some(ref mut d) => d.push('\n'),
+                None => out.push('\n'),
+            }
+        }
+
+        if display.is_none() {
+            let trimmed = line.trim_left();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                out.push_str(line);
+                continue;
+            }
+            if in_fence {
+                out.push_str(line);
+                continue;
+            }
+        }
+
+        protect_line(line, &mut display, &mut out, &mut math);
+    }
+
+    // A `$$` that is never closed is not math
+    if let Some(d) = display {
+        out.push_str("$$");
+        out.push_str(&d);
+    }
+
+    (out, math)
+}
+
+/// Replaces the placeholders created by [`protect()`](fn.protect.html) in the rendered html with
+/// the escaped equations.
+
+pub fn restore(html: &str, math: &[String]) -> String {
+    let mut html = html.to_owned();
+    for (i, equation) in math.iter().enumerate() {
+        html = html.replace(&placeholder(i), &escape_html(equation));
+    }
+    html
+}
+
+
+fn protect_line(line: &str, display: &mut Option<String>, out: &mut String, math: &mut Vec<String>) {
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        // Inside of a `$$` block, look for its end
+        if display.is_some() {
+            let equation = display.take().unwrap();
+            match rest.find("$$") {
+                Some(end) => {
+                    push_math(format!("\\[{}{}\\]", equation, &rest[..end]), out, math);
+                    rest = &rest[end + 2..];
+                    continue;
+                },
+                None => {
+                    *display = Some(equation + rest);
+                    return;
+                },
+            }
+        }
+
+        let c = rest.chars().next().unwrap();
+        let after = &rest[c.len_utf8()..];
+
+        match c {
+            // Escaped dollar sign
+            '\\' if after.starts_with('$') => {
+                out.push_str("\\$");
+                rest = &after[1..];
+            },
+            // Inline code, copied up to the closing run of backticks
+            '`' => {
+                let ticks = rest.len() - rest.trim_left_matches('`').len();
+                let fence = &rest[..ticks];
+                match rest[ticks..].find(fence) {
+                    Some(end) => {
+                        out.push_str(&rest[..ticks + end + ticks]);
+                        rest = &rest[ticks + end + ticks..];
+                    },
+                    None => {
+                        out.push_str(fence);
+                        rest = &rest[ticks..];
+                    },
+                }
+            },
+            '$' if after.starts_with('$') => {
+                *display = Some(String::new());
+                rest = &after[1..];
+            },
+            '$' => {
+                match inline_end(after) {
+                    Some(end) => {
+                        push_math(format!("\\({}\\)", &after[..end]), out, math);
+                        rest = &after[end + 1..];
+                    },
+                    None => {
+                        out.push('$');
+                        rest = after;
+                    },
+                }
+            },
+            _ => {
+                out.push(c);
+                rest = after;
+            },
+        }
+    }
+}
+
+// Returns the position of the `$` closing an inline equation. Like in Pandoc, the equation can not
+// start or end with a space and the closing `$` can not be followed by a digit, so that amounts of
+// money are not taken for math. A `$` next to another one belongs to a `$$` block.
+fn inline_end(text: &str) -> Option<usize> {
+    if text.is_empty() || text.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let mut previous = ' ';
+    for (i, c) in text.char_indices() {
+        if c == '$' && previous != '\\' && previous != '$' && !previous.is_whitespace() && i > 0 {
+            let next = text[i + 1..].chars().next();
+            if next.map_or(true, |n| !n.is_digit(10) && n != '$') {
+                return Some(i);
+            }
+        }
+        previous = c;
+    }
+
+    None
+}
+
+fn push_math(equation: String, out: &mut String, math: &mut Vec<String>) {
+    out.push_str(&placeholder(math.len()));
+    math.push(equation);
+}
+
+// Only letters and digits, the markdown parser leaves it as it is. The trailing `x` makes sure
+// that the placeholder of the 1st equation is not a prefix of the one of the 10th.
+fn placeholder(index: usize) -> String {
+    format!("mdbookmath{}x", index)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use markdown::render;
+
+    #[test]
+    fn math_survives_markdown() {
+        let text = "Let $a_1 * b_2$ cost $5 and $$\n\\sum_{i=0}^n x_i\n$$ but not `$x_y$`.";
+        let (protected, math) = protect(text);
+        let html = restore(&render(&protected), &math);
+
+        assert_eq!(html,
+                   "<p>Let \\(a_1 * b_2\\) cost $5 and \\[\n\\sum_{i=0}^n x_i\n\\] but not <code>$x_y$</code>.</p>\n");
+    }
+}
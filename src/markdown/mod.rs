@@ -1,13 +1,86 @@
 pub mod summary;
+pub mod math;
+pub mod highlight;
+pub mod front_matter;
+pub mod emoji;
 
-use pulldown_cmark::{Parser, html, Options, OPTION_ENABLE_TABLES, OPTION_ENABLE_FOOTNOTES};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use pulldown_cmark::{Parser, Event, Tag, html, Options, Alignment, OPTION_ENABLE_TABLES, OPTION_ENABLE_FOOTNOTES};
+
+use utils::normalize_line_endings;
+use utils::html::{escape_html, slugify, unique_id};
+use self::highlight::Highlighter;
+
+
+/// A heading of a rendered chapter
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    /// 1 for `h1`, 2 for `h2`, ...
+    pub level: i32,
+    /// The text of the heading, without formatting
+    pub text: String,
+    /// The id of the heading in the rendered html
+    pub id: String,
+}
+
+/// How the code blocks are rendered by [`render_with_headings()`](fn.render_with_headings.html)
+#[derive(Clone, Copy, Default)]
+pub struct CodeOptions<'a> {
+    /// Highlights the code blocks in a known language at build time
+    pub highlighter: Option<&'a Highlighter>,
+    /// Wraps every line of the code blocks in a `<span class="line">`, so that the theme can
+    /// number them
+    pub line_numbers: bool,
+    /// Renders the code blocks in the `mermaid` language as a `<div class="mermaid">` with the text
+    /// of the diagram, for the Mermaid script
+    pub mermaid: bool,
+    /// Languages of the code blocks that are renamed before they are highlighted, for example `rs`
+    /// to `rust`
+    pub language_aliases: Option<&'a BTreeMap<String, String>>,
+    /// Turns the straight quotes of the text into curly ones, `--` and `---` into dashes and `...`
+    /// into an ellipsis. The code and the inline html are left as they are.
+    pub smart_punctuation: bool,
+    /// Replaces the emoji shortcodes of the text, like `:rocket:`, with their emoji. The code and
+    /// the unknown shortcodes are left as they are.
+    pub emoji: bool,
+}
+
+// Attributes of the Rust code blocks for `rustdoc`, a code block with only these attributes is Rust
+const RUSTDOC_ATTRIBUTES: &'static [&'static str] = &["ignore", "no_run", "should_panic", "compile_fail",
+                                                      "test_harness", "allow_fail", "edition2015",
+                                                      "edition2018", "edition2021"];
+
+/// The default [`language_aliases`](struct.CodeOptions.html#structfield.language_aliases), for the
+/// languages that highlight.js does not know under these names
+pub fn default_language_aliases() -> BTreeMap<String, String> {
+    [("rs", "rust"), ("sh", "bash"), ("shell", "bash"), ("console", "bash")]
+        .iter()
+        .map(|&(alias, language)| (alias.to_owned(), language.to_owned()))
+        .collect()
+}
 
 ///
 ///
 /// Wrapper around the pulldown-cmark parser and renderer to render markdown
 
 pub fn render(text: &str) -> String {
+    render_with_headings(text, CodeOptions::default()).0
+}
+
+/// Renders markdown like [`render()`](fn.render.html) and also returns the headings of the text, in
+/// the order they appear. The code blocks are rendered according to `code`.
+
+pub fn render_with_headings(text: &str, code: CodeOptions) -> (String, Vec<Heading>) {
+    // The `\r` of Windows line endings would be kept in the code blocks
+    let normalized;
+    let text = if text.contains('\r') {
+        normalized = normalize_line_endings(text, "\n");
+        &normalized
+    } else {
+        text
+    };
+
     let mut s = String::with_capacity(text.len() * 3 / 2);
 
     let mut opts = Options::empty();
@@ -15,6 +88,1001 @@ pub fn render(text: &str) -> String {
     opts.insert(OPTION_ENABLE_FOOTNOTES);
 
     let p = Parser::new_ext(&text, opts);
-    html::push_html(&mut s, p);
-    s
+    let mut headings = vec![];
+    let mut events = p.collect();
+    if code.emoji {
+        events = render_emoji(events);
+    }
+    if code.smart_punctuation {
+        events = render_smart_punctuation(events);
+    }
+    let events = render_code_blocks(render_headings(events, &mut headings), code);
+    let events = render_task_lists(render_strikethrough(render_footnotes(render_tables(events))));
+    let events = render_admonitions(events);
+
+    html::push_html(&mut s, events.into_iter());
+    (s, headings)
+}
+
+// Replaces the events of the code blocks that are highlighted, get numbered lines or have hidden
+// lines with their html, the others are rendered as usual
+fn render_code_blocks<'a>(events: Vec<Event<'a>>, options: CodeOptions) -> Vec<Event<'a>> {
+    let mut rendered = Vec::with_capacity(events.len());
+
+    // The events of the code block being read and its code
+    let mut block: Option<(Vec<Event<'a>>, String)> = None;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                block = Some((vec![event], String::new()));
+            },
+            Event::End(Tag::CodeBlock(info)) => {
+                let (mut events, code) = block.take().unwrap_or((vec![], String::new()));
+
+                // Same class as the one given by pulldown-cmark, unless the language has an alias or
+                // attributes for `rustdoc`
+                let tag = info.split(' ').next().unwrap_or("");
+                let (language, attributes) = code_language(tag, options.language_aliases);
+                let mut class = if language.is_empty() {
+                    String::new()
+                } else {
+                    format!("language-{}", escape_html(&language))
+                };
+
+                if options.mermaid && language == "mermaid" {
+                    rendered.push(Event::Html(format!("<div class=\"mermaid\">{}</div>\n", escape_html(&code)).into()));
+                    continue;
+                }
+
+                // The hidden lines are kept in the markdown for `rustdoc` but not shown in the book
+                let hidden = if language == "rust" {
+                    hide_lines(&code)
+                } else {
+                    None
+                };
+                let (code, hidden) = match hidden {
+                    Some((code, hidden)) => (code, hidden),
+                    None => (code, vec![]),
+                };
+
+                let highlighted = options.highlighter.and_then(|highlighter| highlighter.highlight(&code, &language));
+                let mut html = match highlighted {
+                    Some(html) => {
+                        class.push_str(" syn-code");
+                        html
+                    },
+                    None if options.line_numbers || !hidden.is_empty() || language != tag => escape_html(&code),
+                    None => {
+                        events.push(Event::End(Tag::CodeBlock(info)));
+                        rendered.extend(events);
+                        continue;
+                    },
+                };
+
+                let mut pre = String::from("<pre");
+                if options.line_numbers {
+                    html = number_lines(&html);
+                    pre.push_str(" class=\"line-numbers\"");
+                }
+                for attribute in &attributes {
+                    pre.push_str(&format!(" data-{}=\"true\"", escape_html(&attribute.replace('_', "-"))));
+                }
+                pre.push('>');
+                if !hidden.is_empty() {
+                    html = wrap_hidden_lines(&html, &hidden);
+                }
+
+                let class = class.trim();
+                let code = if class.is_empty() {
+                    "<code>".to_owned()
+                } else {
+                    format!("<code class=\"{}\">", class)
+                };
+                rendered.push(Event::Html(format!("{}{}{}</code></pre>\n", pre, code, html).into()));
+            },
+            _ => {
+                match block {
+                    Some((ref mut events, ref mut code)) => {
+                        if let Event::Text(ref text) = event {
+                            code.push_str(text);
+                        }
+                        events.push(event);
+                    },
+                    None => rendered.push(event),
+                }
+            },
+        }
+    }
+
+    rendered
+}
+
+// The language of a code block from the first word of its info string, and the other attributes
+// of the comma separated list: `rust,no_run` is `rust` with `no_run`. A list of attributes for
+// `rustdoc` is Rust and a language with an alias is replaced by it.
+fn code_language(tag: &str, aliases: Option<&BTreeMap<String, String>>) -> (String, Vec<String>) {
+    let mut language = None;
+    let mut attributes = vec![];
+
+    for word in tag.split(',').map(str::trim).filter(|word| !word.is_empty()) {
+        if language.is_none() && !RUSTDOC_ATTRIBUTES.contains(&word) {
+            language = Some(word);
+        } else {
+            attributes.push(word.to_owned());
+        }
+    }
+
+    let language = match language {
+        Some(language) => aliases.and_then(|aliases| aliases.get(language)).map_or(language, |alias| alias),
+        None if !attributes.is_empty() => "rust",
+        None => "",
+    };
+    (language.to_owned(), attributes)
+}
+
+// Finds the hidden lines of a Rust code block like `rustdoc` does: the lines starting with `# ` and the
+// lines that are only `#`, a `##` at the start of a line is an escaped `#`. Returns the code without
+// these markers and whether every line is hidden, or `None` when no line is hidden.
+fn hide_lines(code: &str) -> Option<(String, Vec<bool>)> {
+    let mut stripped = String::with_capacity(code.len());
+    let mut hidden = vec![];
+
+    for line in code.lines() {
+        let trimmed = line.trim_left();
+        let indent = &line[..line.len() - trimmed.len()];
+
+        if trimmed == "#" {
+            hidden.push(true);
+        } else if trimmed.starts_with("# ") {
+            hidden.push(true);
+            stripped.push_str(indent);
+            stripped.push_str(&trimmed[2..]);
+        } else if trimmed.starts_with("##") {
+            hidden.push(false);
+            stripped.push_str(indent);
+            stripped.push_str(&trimmed[1..]);
+        } else {
+            hidden.push(false);
+            stripped.push_str(line);
+        }
+        stripped.push('\n');
+    }
+
+    if hidden.contains(&true) {
+        Some((stripped, hidden))
+    } else {
+        None
+    }
+}
+
+// Wraps the hidden lines of the html of a code block, with their line break, in
+// `<span class="hidden">` so that the theme can reveal them. The html of every line has to be
+// complete.
+fn wrap_hidden_lines(html: &str, hidden: &[bool]) -> String {
+    let mut wrapped = String::with_capacity(html.len() + hidden.len() * 30);
+    for (i, line) in html.trim_right_matches('\n').split('\n').enumerate() {
+        if hidden.get(i) == Some(&true) {
+            wrapped.push_str("<span class=\"hidden\">");
+            wrapped.push_str(line);
+            wrapped.push_str("\n</span>");
+        } else {
+            wrapped.push_str(line);
+            wrapped.push('\n');
+        }
+    }
+    wrapped
+}
+
+// Wraps the lines of the html of a code block in `<span class="line">`, the html of every line has
+// to be complete
+fn number_lines(html: &str) -> String {
+    let mut numbered = String::with_capacity(html.len() * 3 / 2);
+    for line in html.trim_right_matches('\n').split('\n') {
+        numbered.push_str("<span class=\"line\">");
+        numbered.push_str(line);
+        numbered.push_str("</span>\n");
+    }
+    numbered
+}
+
+// Every heading gets an id made from its text, headings with the same text get a numbered suffix.
+// The heading is wrapped in a link to itself so that readers can easily link to a section.
+fn render_headings<'a>(events: Vec<Event<'a>>, headings: &mut Vec<Heading>) -> Vec<Event<'a>> {
+    let mut rendered = Vec::with_capacity(events.len());
+    let mut used = HashSet::new();
+
+    // The events of the heading being read, they are only rendered once its text is known
+    let mut heading: Option<Vec<Event<'a>>> = None;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::Header(_)) => heading = Some(vec![]),
+            Event::End(Tag::Header(level)) => {
+                let content = heading.take().unwrap_or(vec![]);
+
+                let mut text = String::new();
+                for event in &content {
+                    if let Event::Text(ref t) = *event {
+                        text.push_str(t);
+                    }
+                }
+                let id = unique_id(&slugify(&text), &mut used);
+                headings.push(Heading {
+                    level: level,
+                    text: text,
+                    id: id.clone(),
+                });
+
+                rendered.push(Event::Html(format!("<a class=\"header\" href=\"#{0}\"><h{1} id=\"{0}\">", id, level)
+                                              .into()));
+                rendered.extend(content);
+                rendered.push(Event::Html(format!("</h{}></a>\n", level).into()));
+            },
+            event => {
+                match heading {
+                    Some(ref mut content) => content.push(event),
+                    None => rendered.push(event),
+                }
+            },
+        }
+    }
+
+    rendered
+}
+
+// pulldown-cmark puts the alignment of a column in the obsolete `align` attribute of the cells, the
+// cells are rendered here with a `text-align` style instead. Escaped pipes are also unescaped in
+// inline code, the parser only does it for regular text.
+fn render_tables<'a>(events: Vec<Event<'a>>) -> Vec<Event<'a>> {
+    let mut rendered = Vec::with_capacity(events.len());
+
+    let mut alignments = vec![];
+    let mut in_table = false;
+    let mut in_head = false;
+    let mut in_code = false;
+    let mut cell = 0;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::Table(a)) => {
+                in_table = true;
+                alignments = a.clone();
+                rendered.push(Event::Start(Tag::Table(a)));
+            },
+            Event::End(Tag::Table(a)) => {
+                in_table = false;
+                rendered.push(Event::End(Tag::Table(a)));
+            },
+            Event::Start(Tag::TableHead) => {
+                in_head = true;
+                cell = 0;
+                rendered.push(Event::Start(Tag::TableHead));
+            },
+            Event::End(Tag::TableHead) => {
+                in_head = false;
+                rendered.push(Event::End(Tag::TableHead));
+            },
+            Event::Start(Tag::TableRow) => {
+                cell = 0;
+                rendered.push(Event::Start(Tag::TableRow));
+            },
+            Event::Start(Tag::TableCell) => {
+                let style = match alignments.get(cell) {
+                    Some(&Alignment::Left) => " style=\"text-align: left\"",
+                    Some(&Alignment::Center) => " style=\"text-align: center\"",
+                    Some(&Alignment::Right) => " style=\"text-align: right\"",
+                    _ => "",
+                };
+                let tag = if in_head { "th" } else { "td" };
+                rendered.push(Event::Html(format!("<{}{}>", tag, style).into()));
+            },
+            Event::End(Tag::TableCell) => {
+                let tag = if in_head { "th" } else { "td" };
+                rendered.push(Event::Html(format!("</{}>", tag).into()));
+                cell += 1;
+            },
+            Event::Start(Tag::Code) => {
+                in_code = true;
+                rendered.push(Event::Start(Tag::Code));
+            },
+            Event::End(Tag::Code) => {
+                in_code = false;
+                rendered.push(Event::End(Tag::Code));
+            },
+            Event::Text(ref text) if in_table && in_code && text.contains("\\|") => {
+                rendered.push(Event::Text(text.replace("\\|", "|").into()));
+            },
+            event => rendered.push(event),
+        }
+    }
+
+    rendered
+}
+
+// Footnotes are numbered in the order of their first reference and their definitions are moved to
+// a list at the end of the chapter, whatever the order they are defined in. References without a
+// definition are rendered as text with a warning.
+fn render_footnotes<'a>(events: Vec<Event<'a>>) -> Vec<Event<'a>> {
+    let mut definitions: HashMap<String, Vec<Event<'a>>> = HashMap::new();
+    let mut defined = vec![];
+    let mut content = vec![];
+
+    // Take the definitions out of the content
+    let mut current: Option<(String, Vec<Event<'a>>)> = None;
+    for event in events {
+        match event {
+            Event::Start(Tag::FootnoteDefinition(name)) => {
+                current = Some((name.into_owned(), vec![]));
+            },
+            Event::End(Tag::FootnoteDefinition(_)) => {
+                if let Some((name, definition)) = current.take() {
+                    if !definitions.contains_key(&name) {
+                        defined.push(name.clone());
+                    }
+                    definitions.insert(name, definition);
+                }
+            },
+            event => {
+                match current {
+                    Some((_, ref mut definition)) => definition.push(event),
+                    None => content.push(event),
+                }
+            },
+        }
+    }
+
+    let mut numbers = HashMap::new();
+    let mut order = vec![];
+    let mut rendered = number_footnotes(content, &definitions, &mut numbers, &mut order);
+
+    // Definitions that are never referenced are still shown, after the others
+    let mut unreferenced = vec![];
+    for name in defined {
+        if !numbers.contains_key(&name) {
+            numbers.insert(name.clone(), order.len() + 1);
+            order.push(name.clone());
+            unreferenced.push(name);
+        }
+    }
+
+    if order.is_empty() {
+        return rendered;
+    }
+
+    rendered.push(Event::Html("<hr class=\"footnotes-separator\" />\n<ol class=\"footnotes\">\n".into()));
+
+    // References inside of a definition can number new footnotes, so `order` can grow in the loop
+    let mut i = 0;
+    while i < order.len() {
+        let number = i + 1;
+        let definition = definitions.get(&order[i]).cloned().unwrap_or(vec![]);
+
+        rendered.push(Event::Html(format!("<li id=\"footnote-{}\">\n", number).into()));
+        rendered.extend(number_footnotes(definition, &definitions, &mut numbers, &mut order));
+        if !unreferenced.contains(&order[i]) {
+            rendered.push(Event::Html(format!("<a href=\"#footnote-reference-{}\" class=\"footnote-back\">↩</a>\n",
+                                              number)
+                                          .into()));
+        }
+        rendered.push(Event::Html("</li>\n".into()));
+        i += 1;
+    }
+
+    rendered.push(Event::Html("</ol>\n".into()));
+    rendered
+}
+
+// Replaces the footnote references by links to the definitions
+fn number_footnotes<'a>(events: Vec<Event<'a>>,
+                        definitions: &HashMap<String, Vec<Event<'a>>>,
+                        numbers: &mut HashMap<String, usize>,
+                        order: &mut Vec<String>)
+                        -> Vec<Event<'a>> {
+    let mut rendered = Vec::with_capacity(events.len());
+
+    for event in events {
+        match event {
+            Event::FootnoteReference(name) => {
+                if !definitions.contains_key(&*name) {
+                    warn!("The footnote [^{}] is referenced but not defined", name);
+                    rendered.push(Event::Text(format!("[^{}]", name).into()));
+                    continue;
+                }
+
+                // Only the first reference gets an id, it is the one the definition links back to
+                let (number, id) = match numbers.get(&*name) {
+                    Some(&number) => (number, String::new()),
+                    None => {
+                        let number = order.len() + 1;
+                        (number, format!(" id=\"footnote-reference-{}\"", number))
+                    },
+                };
+                if id != "" {
+                    numbers.insert(name.clone().into_owned(), number);
+                    order.push(name.into_owned());
+                }
+
+                rendered.push(Event::Html(format!("<sup class=\"footnote-reference\"><a href=\"#footnote-{0}\"{1}>{0}</a></sup>",
+                                                  number,
+                                                  id)
+                                              .into()));
+            },
+            event => rendered.push(event),
+        }
+    }
+
+    rendered
+}
+
+// A list item starting with `[ ]` or `[x]` followed by a space is a task, the marker is rendered as
+// a disabled checkbox. Brackets anywhere else are left alone.
+fn render_task_lists<'a>(events: Vec<Event<'a>>) -> Vec<Event<'a>> {
+    // Indices of the items that are tasks, with the state of their checkbox
+    let mut tasks = HashMap::new();
+
+    for (i, event) in events.iter().enumerate() {
+        if let Event::Start(Tag::Item) = *event {
+            // The text of a loose item is in a paragraph
+            let text = match (events.get(i + 1), events.get(i + 2)) {
+                (Some(&Event::Text(ref text)), _) => text,
+                (Some(&Event::Start(Tag::Paragraph)), Some(&Event::Text(ref text))) => text,
+                _ => continue,
+            };
+
+            if text.starts_with("[ ] ") {
+                tasks.insert(i, false);
+            } else if text.starts_with("[x] ") || text.starts_with("[X] ") {
+                tasks.insert(i, true);
+            }
+        }
+    }
+
+    let mut rendered = Vec::with_capacity(events.len());
+    let mut checked = None;
+
+    for (i, event) in events.into_iter().enumerate() {
+        match event {
+            Event::Start(Tag::Item) if tasks.contains_key(&i) => {
+                rendered.push(Event::Html("<li class=\"task-list-item\">".into()));
+                checked = tasks.get(&i).cloned();
+            },
+            Event::Text(text) => {
+                match checked.take() {
+                    Some(checked) => {
+                        let checkbox = if checked {
+                            "<input type=\"checkbox\" disabled=\"\" checked=\"\" />"
+                        } else {
+                            "<input type=\"checkbox\" disabled=\"\" />"
+                        };
+                        rendered.push(Event::Html(checkbox.into()));
+                        rendered.push(Event::Text(text[3..].to_owned().into()));
+                    },
+                    None => rendered.push(Event::Text(text)),
+                }
+            },
+            event => rendered.push(event),
+        }
+    }
+
+    rendered
+}
+
+
+// Kinds of admonitions with their icon, the other kinds are rendered as a generic callout
+const ADMONITIONS: &'static [(&'static str, &'static str)] = &[("note", "fa-info-circle"),
+                                                                ("tip", "fa-lightbulb-o"),
+                                                                ("warning", "fa-exclamation-triangle"),
+                                                                ("danger", "fa-times-circle")];
+
+// Renders the block quotes whose first line is a bold word, `> **Note**`, as admonitions: a
+// `<div class="admonition note">` with a title instead of the quote. The rest of the quote is
+// rendered as usual. A bold word followed by more text on its line is a regular quote.
+fn render_admonitions<'a>(events: Vec<Event<'a>>) -> Vec<Event<'a>> {
+    let events = merge_text(events);
+    let mut rendered = Vec::with_capacity(events.len());
+    // Whether every open block quote is an admonition
+    let mut quotes = vec![];
+
+    let mut i = 0;
+    while i < events.len() {
+        match events[i] {
+            Event::Start(Tag::BlockQuote) => {
+                let title = match (events.get(i + 1), events.get(i + 2), events.get(i + 3), events.get(i + 4)) {
+                    (Some(&Event::Start(Tag::Paragraph)),
+                     Some(&Event::Start(Tag::Strong)),
+                     Some(&Event::Text(ref title)),
+                     Some(&Event::End(Tag::Strong))) => Some(title.trim_right_matches(':')),
+                    _ => None,
+                };
+                let title = title.filter(|title| !title.is_empty() && title.chars().all(char::is_alphabetic));
+                let alone = match events.get(i + 5) {
+                    Some(&Event::SoftBreak) | Some(&Event::HardBreak) | Some(&Event::End(Tag::Paragraph)) => true,
+                    _ => false,
+                };
+
+                match title {
+                    Some(title) if alone => {
+                        let kind = title.to_lowercase();
+                        let (class, icon) = match ADMONITIONS.iter().find(|&&(known, _)| known == kind) {
+                            Some(&(known, icon)) => (format!("admonition {}", known), icon),
+                            None => {
+                                warn!("Unknown admonition `{}`, it is rendered as a generic callout", title);
+                                ("admonition".to_owned(), "fa-comment")
+                            },
+                        };
+                        rendered.push(Event::Html(format!("<div class=\"{}\"><p class=\"admonition-title\">\
+                                                           <i class=\"fa {}\"></i> {}</p>\n",
+                                                          class,
+                                                          icon,
+                                                          title)
+                                                      .into()));
+
+                        // The content goes on in a new paragraph after the title
+                        match events[i + 5] {
+                            Event::End(Tag::Paragraph) => {},
+                            _ => rendered.push(Event::Start(Tag::Paragraph)),
+                        }
+                        quotes.push(true);
+                        i += 6;
+                        continue;
+                    },
+                    _ => quotes.push(false),
+                }
+                rendered.push(events[i].clone());
+            },
+            Event::End(Tag::BlockQuote) => {
+                if quotes.pop() == Some(true) {
+                    rendered.push(Event::Html("</div>\n".into()));
+                } else {
+                    rendered.push(events[i].clone());
+                }
+            },
+            ref event => rendered.push(event.clone()),
+        }
+        i += 1;
+    }
+
+    rendered
+}
+
+// Renders the text between two `~~` as deleted text. Only runs of exactly two tildes count, the
+// opening one must be followed by a non-whitespace character and the closing one preceded by one,
+// in the same paragraph and emphasis. Code spans and code blocks are left untouched.
+fn render_strikethrough<'a>(events: Vec<Event<'a>>) -> Vec<Event<'a>> {
+    let mut rendered: Vec<Event<'a>> = Vec::with_capacity(events.len());
+    // Index of the pending opening `~~` in `rendered`, with the inline depth it is at
+    let mut open: Option<(usize, usize)> = None;
+    let mut depth = 0;
+    let mut in_code = false;
+
+    for event in merge_text(events) {
+        match event {
+            Event::Start(Tag::Code) | Event::Start(Tag::CodeBlock(_)) => {
+                in_code = true;
+                rendered.push(event);
+            },
+            Event::End(Tag::Code) | Event::End(Tag::CodeBlock(_)) => {
+                in_code = false;
+                rendered.push(event);
+            },
+            Event::Start(tag) => {
+                if is_inline(&tag) {
+                    depth += 1;
+                } else {
+                    open = None;
+                }
+                rendered.push(Event::Start(tag));
+            },
+            Event::End(tag) => {
+                if is_inline(&tag) {
+                    depth -= 1;
+                    if open.map_or(false, |(_, d)| d > depth) {
+                        open = None;
+                    }
+                } else {
+                    open = None;
+                }
+                rendered.push(Event::End(tag));
+            },
+            Event::Text(ref text) if !in_code && text.contains("~~") => {
+                let chars: Vec<(usize, char)> = text.char_indices().collect();
+                let mut start = 0;
+                let mut i = 0;
+
+                while i < chars.len() {
+                    if chars[i].1 != '~' {
+                        i += 1;
+                        continue;
+                    }
+
+                    let mut end = i;
+                    while end < chars.len() && chars[end].1 == '~' {
+                        end += 1;
+                    }
+
+                    if end - i == 2 {
+                        let can_open = chars.get(end).map_or(true, |&(_, c)| !c.is_whitespace());
+                        let can_close = i == 0 || !chars[i - 1].1.is_whitespace();
+                        let at = chars[i].0;
+
+                        match open {
+                            Some((index, d)) if can_close && d == depth => {
+                                rendered.push(Event::Text(text[start..at].to_owned().into()));
+                                rendered[index] = Event::Html("<del>".into());
+                                rendered.push(Event::Html("</del>".into()));
+                                open = None;
+                                start = at + 2;
+                            },
+                            _ if can_open => {
+                                rendered.push(Event::Text(text[start..at].to_owned().into()));
+                                // Stays as text if the strikethrough is never closed
+                                open = Some((rendered.len(), depth));
+                                rendered.push(Event::Text("~~".into()));
+                                start = at + 2;
+                            },
+                            _ => {},
+                        }
+                    }
+
+                    i = end;
+                }
+
+                rendered.push(Event::Text(text[start..].to_owned().into()));
+            },
+            event => rendered.push(event),
+        }
+    }
+
+    rendered
+}
+
+// Replaces the punctuation of the text outside of the code with its typographic version. The
+// inline html is a separate event, the quotes of its attributes are kept.
+fn render_smart_punctuation<'a>(events: Vec<Event<'a>>) -> Vec<Event<'a>> {
+    let mut rendered: Vec<Event<'a>> = Vec::with_capacity(events.len());
+    // The character before the current text in its block, to tell opening quotes from closing ones
+    let mut previous = None;
+    let mut in_code = false;
+
+    for event in merge_text(events) {
+        match event {
+            Event::Start(Tag::Code) | Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(Tag::Code) => {
+                in_code = false;
+                previous = Some('`');
+            },
+            Event::End(Tag::CodeBlock(_)) => in_code = false,
+            Event::Start(ref tag) | Event::End(ref tag) if !is_inline(tag) => previous = None,
+            Event::SoftBreak | Event::HardBreak => previous = Some(' '),
+            Event::Text(ref text) if in_code => {
+                if let Some(last) = text.chars().last() {
+                    previous = Some(last);
+                }
+            },
+            Event::Text(text) => {
+                let text = smart_punctuation(&text, previous);
+                if let Some(last) = text.chars().last() {
+                    previous = Some(last);
+                }
+                rendered.push(Event::Text(text.into()));
+                continue;
+            },
+            _ => {},
+        }
+        rendered.push(event);
+    }
+
+    rendered
+}
+
+// Replaces the emoji shortcodes of the text outside of the code. The inline html is a separate
+// event, its attributes are kept.
+fn render_emoji<'a>(events: Vec<Event<'a>>) -> Vec<Event<'a>> {
+    let mut rendered: Vec<Event<'a>> = Vec::with_capacity(events.len());
+    let mut in_code = false;
+
+    for event in merge_text(events) {
+        match event {
+            Event::Start(Tag::Code) | Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(Tag::Code) | Event::End(Tag::CodeBlock(_)) => in_code = false,
+            Event::Text(ref text) if !in_code && text.contains(':') => {
+                rendered.push(Event::Text(emoji::replace_shortcodes(text).into()));
+                continue;
+            },
+            _ => {},
+        }
+        rendered.push(event);
+    }
+
+    rendered
+}
+
+// Curly quotes, dashes and ellipses of `text`, whose preceding character is `previous`. A quote
+// opens after a space, an opening bracket or a dash, and at the start of a block.
+fn smart_punctuation(text: &str, mut previous: Option<char>) -> String {
+    let mut smart = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let opens = previous.map_or(true, |p| p.is_whitespace() || "([{<\u{2013}\u{2014}-".contains(p));
+        let replacement = match c {
+            '"' if opens => '\u{201c}',
+            '"' => '\u{201d}',
+            '\'' if opens => '\u{2018}',
+            '\'' => '\u{2019}',
+            '.' if text_continues(&chars, "..") => {
+                chars.next();
+                chars.next();
+                '\u{2026}'
+            },
+            '-' if text_continues(&chars, "--") => {
+                chars.next();
+                chars.next();
+                '\u{2014}'
+            },
+            '-' if text_continues(&chars, "-") => {
+                chars.next();
+                '\u{2013}'
+            },
+            c => c,
+        };
+        smart.push(replacement);
+        previous = Some(replacement);
+    }
+
+    smart
+}
+
+// Whether the next characters of `chars` are `expected`
+fn text_continues<I: Iterator<Item = char> + Clone>(chars: &I, expected: &str) -> bool {
+    let mut next = chars.clone();
+    expected.chars().all(|c| next.next() == Some(c))
+}
+
+// The parser splits the text around special characters, adjacent text events are joined so that
+// the tildes of a run end up in the same event
+fn merge_text<'a>(events: Vec<Event<'a>>) -> Vec<Event<'a>> {
+    let mut merged: Vec<Event<'a>> = Vec::with_capacity(events.len());
+
+    for event in events {
+        if let Event::Text(text) = event {
+            if let Some(&mut Event::Text(ref mut previous)) = merged.last_mut() {
+                previous.to_mut().push_str(&text);
+                continue;
+            }
+            merged.push(Event::Text(text));
+        } else {
+            merged.push(event);
+        }
+    }
+
+    merged
+}
+
+fn is_inline(tag: &Tag) -> bool {
+    match *tag {
+        Tag::Emphasis | Tag::Strong | Tag::Code | Tag::Link(..) | Tag::Image(..) => true,
+        _ => false,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_table_alignment() {
+        let table = "| a | b | c | d |\n|:--|:-:|--:|---|\n| 1 | 2 | 3 | 4 |\n";
+
+        assert_eq!(render(table),
+                   "<table><thead><tr><th style=\"text-align: left\"> a </th>\
+                    <th style=\"text-align: center\"> b </th>\
+                    <th style=\"text-align: right\"> c </th><th> d </th></tr></thead><tbody>\n\
+                    <tr><td style=\"text-align: left\"> 1 </td>\
+                    <td style=\"text-align: center\"> 2 </td>\
+                    <td style=\"text-align: right\"> 3 </td><td> 4 </td></tr>\n\
+                    </tbody></table>\n");
+    }
+
+    #[test]
+    fn render_table_escaped_pipes() {
+        let table = "| a \\| b | `c \\| d` |\n|---|---|\n";
+
+        assert_eq!(render(table),
+                   "<table><thead><tr><th> a | b </th><th> <code>c | d</code> </th></tr></thead><tbody>\n\
+                    </tbody></table>\n");
+    }
+
+    #[test]
+    fn render_heading_ids() {
+        let text = "# Hello, *World*\n\n## Hello World\n\n## Hello World\n";
+
+        assert_eq!(render(text),
+                   "<a class=\"header\" href=\"#hello-world\"><h1 id=\"hello-world\">Hello, <em>World</em></h1></a>\n\
+                    <a class=\"header\" href=\"#hello-world-1\"><h2 id=\"hello-world-1\">Hello World</h2></a>\n\
+                    <a class=\"header\" href=\"#hello-world-2\"><h2 id=\"hello-world-2\">Hello World</h2></a>\n");
+
+        let (_, headings) = render_with_headings(text, CodeOptions::default());
+        assert_eq!(headings[0],
+                   Heading {
+                       level: 1,
+                       text: "Hello, World".to_owned(),
+                       id: "hello-world".to_owned(),
+                   });
+        assert_eq!(headings[2].id, "hello-world-2");
+    }
+
+    #[test]
+    fn render_line_numbers() {
+        let code = CodeOptions {
+            highlighter: None,
+            line_numbers: true,
+            mermaid: false,
+            language_aliases: None,
+            smart_punctuation: false,
+            emoji: false,
+        };
+        let (html, _) = render_with_headings("```rust\nlet a = 1 < 2;\nlet b;\n```\n\nSome `code`", code);
+
+        assert_eq!(html,
+                   "<pre class=\"line-numbers\"><code class=\"language-rust\"><span class=\"line\">let a = 1 &lt; \
+                    2;</span>\n<span class=\"line\">let b;</span>\n</code></pre>\n<p>Some <code>code</code></p>\n");
+    }
+
+    #[test]
+    fn render_mermaid_diagrams() {
+        let text = "```mermaid\ngraph TD;\n    A-->B;\n```\n\n\
+                    ```mermaid\nsequenceDiagram\n    A->>B: Hi & bye\n```\n";
+        let code = CodeOptions {
+            highlighter: None,
+            line_numbers: true,
+            mermaid: true,
+            language_aliases: None,
+            smart_punctuation: false,
+            emoji: false,
+        };
+
+        assert_eq!(render_with_headings(text, code).0,
+                   "<div class=\"mermaid\">graph TD;\n    A--&gt;B;\n</div>\n\
+                    <div class=\"mermaid\">sequenceDiagram\n    A-&gt;&gt;B: Hi &amp; bye\n</div>\n");
+
+        // Without the option they are code blocks
+        assert!(render(text).starts_with("<pre><code class=\"language-mermaid\">graph TD;"));
+    }
+
+    #[test]
+    fn render_language_aliases() {
+        let aliases = default_language_aliases();
+        let code = CodeOptions { language_aliases: Some(&aliases), ..CodeOptions::default() };
+        let text = "```rs\nfn main() {}\n```\n\n```rust,no_run,should_panic\npanic!();\n```\n\n\
+                    ```ignore\nlet a;\n```\n\n```python\nprint('a')\n```\n";
+
+        assert_eq!(render_with_headings(text, code).0,
+                   "<pre><code class=\"language-rust\">fn main() {}\n</code></pre>\n\
+                    <pre data-no-run=\"true\" data-should-panic=\"true\"><code class=\"language-rust\">panic!();\n\
+                    </code></pre>\n\
+                    <pre data-ignore=\"true\"><code class=\"language-rust\">let a;\n</code></pre>\n\
+                    <pre><code class=\"language-python\">print('a')\n</code></pre>\n");
+    }
+
+    #[test]
+    fn render_hidden_lines() {
+        let text = "```rust\n# fn main() {\n    let a = 1;\n#\n    ##[allow(unused)]\n    #[derive(Debug)]\n# }\n```\n\n\
+                    ```python\n# comment\n```\n";
+
+        assert_eq!(render(text),
+                   "<pre><code class=\"language-rust\"><span class=\"hidden\">fn main() {\n</span>    let a = 1;\n\
+                    <span class=\"hidden\">\n</span>    #[allow(unused)]\n    #[derive(Debug)]\n\
+                    <span class=\"hidden\">}\n</span></code></pre>\n\
+                    <pre><code class=\"language-python\"># comment\n</code></pre>\n");
+    }
+
+    #[test]
+    fn render_admonition_blocks() {
+        assert_eq!(render("> **Note**\n> Some *text*.\n\n> Quote\n"),
+                   "<div class=\"admonition note\"><p class=\"admonition-title\">\
+                    <i class=\"fa fa-info-circle\"></i> Note</p>\n<p>Some <em>text</em>.</p>\n</div>\n\
+                    <blockquote>\n<p>Quote</p>\n</blockquote>\n");
+        assert_eq!(render("> **Warning:**\n>\n> Careful\n> - a\n"),
+                   "<div class=\"admonition warning\"><p class=\"admonition-title\">\
+                    <i class=\"fa fa-exclamation-triangle\"></i> Warning</p>\n<p>Careful</p>\n\
+                    <ul>\n<li>a</li>\n</ul>\n</div>\n");
+    }
+
+    #[test]
+    fn render_unknown_admonitions_and_quotes() {
+        assert_eq!(render("> **Info**\n> x\n"),
+                   "<div class=\"admonition\"><p class=\"admonition-title\">\
+                    <i class=\"fa fa-comment\"></i> Info</p>\n<p>x</p>\n</div>\n");
+        assert_eq!(render("> **Note** inline\n"),
+                   "<blockquote>\n<p><strong>Note</strong> inline</p>\n</blockquote>\n");
+        assert_eq!(render("> **Two words**\n> x\n"),
+                   "<blockquote>\n<p><strong>Two words</strong>\nx</p>\n</blockquote>\n");
+    }
+
+    #[test]
+    fn render_task_list_markers() {
+        let text = "- [ ] todo\n- [x] done\n- [X]\n- [y] no\n- text [ ] and [x] text\n";
+
+        assert_eq!(render(text),
+                   "<ul>\n<li class=\"task-list-item\"><input type=\"checkbox\" disabled=\"\" /> todo</li>\n\
+                    <li class=\"task-list-item\"><input type=\"checkbox\" disabled=\"\" checked=\"\" /> done</li>\n\
+                    <li>[X]</li>\n<li>[y] no</li>\n<li>text [ ] and [x] text</li>\n</ul>\n");
+    }
+
+    #[test]
+    fn render_footnotes_in_reference_order() {
+        let text = "[^b]: Second\n\n[^a]: First\n\nText[^a] and[^b], again[^a], missing[^c]\n";
+
+        assert_eq!(render(text),
+                   "<p>Text<sup class=\"footnote-reference\"><a href=\"#footnote-1\" id=\"footnote-reference-1\">1</a></sup> \
+                    and<sup class=\"footnote-reference\"><a href=\"#footnote-2\" id=\"footnote-reference-2\">2</a></sup>, \
+                    again<sup class=\"footnote-reference\"><a href=\"#footnote-1\">1</a></sup>, missing[^c]</p>\n\
+                    <hr class=\"footnotes-separator\" />\n<ol class=\"footnotes\">\n\
+                    <li id=\"footnote-1\">\n<p>First</p>\n<a href=\"#footnote-reference-1\" class=\"footnote-back\">↩</a>\n</li>\n\
+                    <li id=\"footnote-2\">\n<p>Second</p>\n<a href=\"#footnote-reference-2\" class=\"footnote-back\">↩</a>\n</li>\n\
+                    </ol>\n");
+    }
+
+    #[test]
+    fn render_strikethrough_spans() {
+        assert_eq!(render("~~gone~~ and ~~*also* gone~~\n"),
+                   "<p><del>gone</del> and <del><em>also</em> gone</del></p>\n");
+        assert_eq!(render("~one~ ~~~three~~~ ~~ spaced ~~ ~~open\n"),
+                   "<p>~one~ ~~~three~~~ ~~ spaced ~~ ~~open</p>\n");
+        assert_eq!(render("`~~code~~` ~~a *b~~ c*\n"),
+                   "<p><code>~~code~~</code> ~~a <em>b~~ c</em></p>\n");
+    }
+
+    #[test]
+    fn render_smart_punctuation() {
+        let options = CodeOptions { smart_punctuation: true, ..CodeOptions::default() };
+        let smart = |text| render_with_headings(text, options).0;
+
+        assert_eq!(smart("\"Don't,\" she said -- twice --- and *'left'*...\n"),
+                   "<p>\u{201c}Don\u{2019}t,\u{201d} she said \u{2013} twice \u{2014} and \
+                    <em>\u{2018}left\u{2019}</em>\u{2026}</p>\n");
+        assert_eq!(smart("(\"a\") the '90s and `x`'s\nend\"\n"),
+                   "<p>(\u{201c}a\u{201d}) the \u{2018}90s and <code>x</code>\u{2019}s\nend\u{201d}</p>\n");
+        // The straight quotes are the default
+        assert_eq!(render("\"a\" -- b...\n"), "<p>&quot;a&quot; -- b...</p>\n");
+    }
+
+    #[test]
+    fn smart_punctuation_leaves_code_and_html_alone() {
+        let options = CodeOptions { smart_punctuation: true, ..CodeOptions::default() };
+        let smart = |text| render_with_headings(text, options).0;
+
+        assert_eq!(smart("`\"a\" -- b...` <a href=\"x.html\" title='t'>\"link\"</a>\n"),
+                   "<p><code>&quot;a&quot; -- b...</code> <a href=\"x.html\" title='t'>\u{201c}link\u{201d}</a></p>\n");
+        assert_eq!(smart("```\nlet s = \"--\";\n```\n"),
+                   "<pre><code>let s = &quot;--&quot;;\n</code></pre>\n");
+        assert_eq!(smart("<div class=\"note\">\n\"raw\" -- html\n</div>\n"),
+                   "<div class=\"note\">\n\"raw\" -- html\n</div>\n");
+    }
+
+    #[test]
+    fn render_emoji_outside_of_code() {
+        let options = CodeOptions { emoji: true, ..CodeOptions::default() };
+        let emoji = |text| render_with_headings(text, options).0;
+
+        assert_eq!(emoji(":rocket: *Breaking*: `:x:` and :not_an_emoji:\n"),
+                   "<p>🚀 <em>Breaking</em>: <code>:x:</code> and :not_an_emoji:</p>\n");
+        assert_eq!(emoji("```\nlet s = \":tada:\";\n```\n"),
+                   "<pre><code>let s = &quot;:tada:&quot;;\n</code></pre>\n");
+        assert_eq!(render(":tada:"), "<p>:tada:</p>\n");
+    }
+
+    #[test]
+    fn windows_line_endings_do_not_reach_the_code_blocks() {
+        let markdown = "# Title\r\n\r\nSome\r\ntext\r\n\r\n```rust\r\nfn main() {\r\n}\r\n```\r\n\r\n    indented\r\n";
+        let html = render(markdown);
+        assert!(!html.contains('\r'));
+        assert_eq!(html, render(&markdown.replace("\r\n", "\n")));
+
+        let options = CodeOptions { line_numbers: true, ..CodeOptions::default() };
+        assert!(!render_with_headings(markdown, options).0.contains('\r'));
+    }
 }
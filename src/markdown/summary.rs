@@ -1,211 +1,397 @@
-use std::path::PathBuf;
-use std::fs::File;
-use std::io::{Read, Result, Error, ErrorKind};
+use std::path::{Component, Path, PathBuf};
+use std::iter::Peekable;
 use book::bookitem::{BookItem, Chapter};
-
-pub fn construct_bookitems(path: &PathBuf) -> Result<Vec<BookItem>> {
+use errors::MdBookError;
+use utils;
+
+// Marker at the end of the entry of a chapter that is not listed in the table of contents
+const HIDDEN_MARKER: &'static str = "{hidden}";
+
+/// Parses the summary `files` of the book, relative to the source directory `src`, into its items.
+///
+/// The files are read one after the other as a single table of contents. Every file after the
+/// first one starts a new part: its leading header, `# Reference`, is the title of the part and a
+/// file without one is separated from the previous entries by a spacer. The chapters of a file are
+/// relative to its directory, the section numbers go on from one file to the next.
+pub fn construct_bookitems(src: &Path, files: &[PathBuf]) -> Result<Vec<BookItem>, MdBookError> {
     debug!("[fn]: construct_bookitems");
-    let mut summary = String::new();
-    try!(try!(File::open(path)).read_to_string(&mut summary));
+    let mut summaries = vec![];
+    for file in files {
+        let summary = try!(utils::fs::file_to_string(&src.join(file)));
+        summaries.push((file.clone(), summary));
+    }
 
-    debug!("[*]: Parse SUMMARY.md");
-    let top_items = try!(parse_level(&mut summary.split('\n').collect(), 0, vec![0]));
-    debug!("[*]: Done parsing SUMMARY.md");
+    debug!("[*]: Parse the summary");
+    let top_items = try!(parse_summaries(&summaries));
+    debug!("[*]: Done parsing the summary");
     Ok(top_items)
 }
 
-fn parse_level(summary: &mut Vec<&str>, current_level: i32, mut section: Vec<i32>) -> Result<Vec<BookItem>> {
+// `summaries` contains the path of every summary file, relative to the source directory, and its
+// content
+fn parse_summaries(summaries: &[(PathBuf, String)]) -> Result<Vec<BookItem>, MdBookError> {
+    debug!("[fn]: parse_summaries");
+
+    let mut entries = vec![];
+
+    for &(ref file, ref summary) in summaries {
+        let directory = file.parent().unwrap_or(Path::new(""));
+
+        // Indentation widths of the current item and its ancestors. A line that is indented more than
+        // the last one opens a new level, a line indented less closes the levels until it fits. This
+        // way any consistent indentation (2 or 4 spaces, tabs or a mix of them) gives the same result.
+        let mut indents: Vec<usize> = vec![];
+        // Indentation added by the first nested item, going deeper by more than this skips a level
+        let mut step: Option<usize> = None;
+        // The list entries after a `# ---` divider are unnumbered, until the next part title
+        let mut unnumbered = false;
+        // The entries of the previous files are separated from the ones of this file
+        let mut separated = entries.is_empty();
+
+        for (line_number, line) in summary.lines().enumerate() {
+            let error = |message| summary_error(file, line_number + 1, message);
+
+            if is_divider(line) {
+                unnumbered = true;
+                indents.clear();
+                continue;
+            }
+
+            let item = match parse_line(line) {
+                Ok(Some(item)) => item,
+                Ok(None) => continue,
+                Err(message) => return Err(error(message)),
+            };
+
+            // Every chapter file lives in the source directory, a path can not go outside of it
+            let item = match item {
+                BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch)
+                    if !ch.is_url() && !is_inside_source(&directory.join(&ch.path)) => {
+                    return Err(error(format!("'{}' is outside of the source directory", ch.path.display())));
+                },
+                BookItem::Chapter(s, ch) => BookItem::Chapter(s, relative_to(directory, ch)),
+                BookItem::Affix(ch) => BookItem::Affix(relative_to(directory, ch)),
+                _ => item,
+            };
+
+            let item = match item {
+                BookItem::PartTitle(_) => {
+                    unnumbered = false;
+
+                    // A header before any entry is the title of the summary, not a part
+                    if entries.is_empty() {
+                        continue;
+                    }
+
+                    // Parts separate lists, the next entry starts at the root level again
+                    indents.clear();
+                    item
+                },
+                BookItem::Chapter(_, ch) if unnumbered => BookItem::Affix(ch),
+                _ => item,
+            };
+
+            match item {
+                BookItem::PartTitle(_) => {},
+                _ if !separated => entries.push((0, file.as_path(), line_number + 1, BookItem::Spacer)),
+                _ => {},
+            }
+            separated = true;
+
+            let width = indentation(line, 4);
+
+            while indents.last().map_or(false, |&last| width < last) {
+                indents.pop();
+            }
+            if indents.last().map_or(true, |&last| width > last) {
+                if let Some(&last) = indents.last() {
+                    match step {
+                        Some(step) if width - last > step => {
+                            return Err(error(format!("'{}' is indented more than one level deeper than the \
+                                                      previous item",
+                                                     line.trim())));
+                        },
+                        None => step = Some(width - last),
+                        _ => {},
+                    }
+                }
+                indents.push(width);
+            }
+
+            entries.push((indents.len() - 1, file.as_path(), line_number + 1, item));
+        }
+    }
+
+    parse_level(&mut entries.into_iter().peekable(), 0, vec![0])
+}
+
+// `entries` contains the indentation level, the summary file, the line number and the item of every
+// entry
+fn parse_level<'a, I>(entries: &mut Peekable<I>, current_level: usize, mut section: Vec<i32>)
+                      -> Result<Vec<BookItem>, MdBookError>
+    where I: Iterator<Item = (usize, &'a Path, usize, BookItem)>
+{
     debug!("[fn]: parse_level");
     let mut items: Vec<BookItem> = vec![];
 
     // Construct the book recursively
-    while !summary.is_empty() {
-        let item: BookItem;
-        // Indentation level of the line to parse
-        let level = try!(level(summary[0], 4));
-
-        // if level < current_level we remove the last digit of section, exit the current function,
-        // and return the parsed level to the calling function.
+    loop {
+        // Indentation level of the next item
+        let (level, file, line) = match entries.peek() {
+            Some(&(level, file, line, _)) => (level, file, line),
+            None => break,
+        };
+
+        // if level < current_level we exit the current function and return the parsed level to the
+        // calling function.
         if level < current_level {
             break;
         }
 
-        // if level > current_level we call ourselves to go one level deeper
+        // if level > current_level we call ourselves to go one level deeper. The indentation stack
+        // guarantees that it is exactly one level deeper.
         if level > current_level {
-            // Level can not be root level !!
             // Add a sub-number to section
             section.push(0);
-            let last = items.pop().expect("There should be at least one item since this can't be the root level");
 
-            item = if let BookItem::Chapter(ref s, ref ch) = last {
-                let mut ch = ch.clone();
-                ch.sub_items = try!(parse_level(summary, level, section.clone()));
-                items.push(BookItem::Chapter(s.clone(), ch));
+            match items.pop() {
+                Some(BookItem::Chapter(_, ref ch)) if ch.hidden => {
+                    return Err(summary_error(file, line, "hidden chapters can not have sub-chapters".to_owned()));
+                },
+                Some(BookItem::Chapter(s, mut ch)) => {
+                    ch.sub_items = try!(parse_level(entries, level, section.clone()));
+                    items.push(BookItem::Chapter(s, ch));
+
+                    // Remove the last number from the section, because we got back to our level..
+                    section.pop();
+                    continue;
+                },
+                _ => return Err(summary_error(file, line, "only numbered chapters can have sub-chapters".to_owned())),
+            }
+        }
 
-                // Remove the last number from the section, because we got back to our level..
-                section.pop();
-                continue;
-            } else {
-                return Err(Error::new(ErrorKind::Other,
-                                      format!("Your summary.md is messed up\n\n
-                        Prefix, \
-                                               Suffix and Spacer elements can only exist on the root level.\n
-                        \
-                                               Prefix elements can only exist before any chapter and there can be \
-                                               no chapters after suffix elements.")));
-            };
+        // level and current_level are the same, parse the item
+        let (_, _, _, parsed_item) = entries.next().expect("peek() returned an item");
+
+        // Eliminate possible errors and set section to -1 after suffix
+        match parsed_item {
+            // error if level != 0 and BookItem is != Chapter
+            BookItem::Affix(_) | BookItem::Spacer | BookItem::PartTitle(_) if level > 0 => {
+                return Err(summary_error(file,
+                                         line,
+                                         "prefix and suffix chapters, spacers and part titles can only exist on \
+                                          the root level"
+                                             .to_owned()));
+            },
 
-        } else {
-            // level and current_level are the same, parse the line
-            item = if let Some(parsed_item) = parse_line(summary[0]) {
-
-                // Eliminate possible errors and set section to -1 after suffix
-                match parsed_item {
-                    // error if level != 0 and BookItem is != Chapter
-                    BookItem::Affix(_) | BookItem::Spacer if level > 0 => {
-                        return Err(Error::new(ErrorKind::Other,
-                                              format!("Your summary.md is messed up\n\n
-                                \
-                                                       Prefix, Suffix and Spacer elements can only exist on the \
-                                                       root level.\n
-                                Prefix \
-                                                       elements can only exist before any chapter and there can be \
-                                                       no chapters after suffix elements.")))
-                    },
-
-                    // error if BookItem == Chapter and section == -1
-                    BookItem::Chapter(_, _) if section[0] == -1 => {
-                        return Err(Error::new(ErrorKind::Other,
-                                              format!("Your summary.md is messed up\n\n
-                                \
-                                                       Prefix, Suffix and Spacer elements can only exist on the \
-                                                       root level.\n
-                                Prefix \
-                                                       elements can only exist before any chapter and there can be \
-                                                       no chapters after suffix elements.")))
-                    },
-
-                    // Set section = -1 after suffix
-                    BookItem::Affix(_) if section[0] > 0 => {
-                        section[0] = -1;
-                    },
-
-                    _ => {},
-                }
+            // error if BookItem == Chapter and section == -1
+            BookItem::Chapter(_, _) if section[0] == -1 => {
+                return Err(summary_error(file,
+                                         line,
+                                         "numbered chapters can not come after suffix chapters".to_owned()));
+            },
 
-                match parsed_item {
-                    BookItem::Chapter(_, ch) => {
-                        // Increment section
-                        let len = section.len() - 1;
-                        section[len] += 1;
-                        let s = section.iter().fold("".to_owned(), |s, i| s + &i.to_string() + ".");
-                        BookItem::Chapter(s, ch)
-                    },
-                    _ => parsed_item,
-                }
+            // Set section = -1 after suffix
+            BookItem::Affix(_) if section[0] > 0 => {
+                section[0] = -1;
+            },
 
-            } else {
-                // If parse_line does not return Some(_) continue...
-                summary.remove(0);
-                continue;
-            };
+            _ => {},
         }
 
-        summary.remove(0);
+        let item = match parsed_item {
+            // A hidden chapter does not take a number, the next chapter gets it
+            BookItem::Chapter(_, ch) if ch.hidden => BookItem::Chapter(String::new(), ch),
+            BookItem::Chapter(_, ch) => {
+                // Increment section
+                let len = section.len() - 1;
+                section[len] += 1;
+                let s = section.iter().fold("".to_owned(), |s, i| s + &i.to_string() + ".");
+                BookItem::Chapter(s, ch)
+            },
+            _ => parsed_item,
+        };
+
         items.push(item)
     }
     debug!("[*]: Level: {:?}", items);
     Ok(items)
 }
 
+fn summary_error(file: &Path, line: usize, message: String) -> MdBookError {
+    MdBookError::SummaryParse {
+        file: file.to_owned(),
+        line: line,
+        message: message,
+    }
+}
+
+
+// Whether a relative path stays inside of the directory it is relative to, once its `..` are
+// resolved
+fn is_inside_source(path: &Path) -> bool {
+    let mut depth = 0;
+    for component in path.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {},
+            Component::ParentDir if depth > 0 => depth -= 1,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+
+// The chapter of a summary file in `directory` of the source directory, with its path relative to
+// the source directory. Its `..` are resolved, it is known to stay inside of the source directory.
+fn relative_to(directory: &Path, mut ch: Chapter) -> Chapter {
+    if directory == Path::new("") || ch.is_url() || ch.path == PathBuf::new() {
+        return ch;
+    }
+
+    let mut path = PathBuf::new();
+    for component in directory.join(&ch.path).components() {
+        match component {
+            Component::ParentDir => {
+                path.pop();
+            },
+            Component::CurDir => {},
+            component => path.push(component.as_os_str()),
+        }
+    }
+    ch.path = path;
+    ch
+}
+
+
+// A header made of dashes only, `# ---`, starts a group of unnumbered entries
+fn is_divider(line: &str) -> bool {
+    let line = line.trim();
+    let title = line.trim_left_matches('#').trim();
+    line.starts_with('#') && !title.is_empty() && title.chars().all(|c| c == '-')
+}
+
 
-fn level(line: &str, spaces_in_tab: i32) -> Result<i32> {
-    debug!("[fn]: level");
-    let mut spaces = 0;
-    let mut level = 0;
+// Returns the width of the leading whitespace of a line, tabs advance to the next tab stop
+fn indentation(line: &str, spaces_in_tab: usize) -> usize {
+    let mut width = 0;
 
     for ch in line.chars() {
         match ch {
-            ' ' => spaces += 1,
-            '\t' => level += 1,
+            ' ' => width += 1,
+            '\t' => width += spaces_in_tab - width % spaces_in_tab,
             _ => break,
         }
-        if spaces >= spaces_in_tab {
-            level += 1;
-            spaces = 0;
-        }
-    }
-
-    // If there are spaces left, there is an indentation error
-    if spaces > 0 {
-        debug!("[SUMMARY.md]:");
-        debug!("\t[line]: {}", line);
-        debug!("[*]: There is an indentation error on this line. Indentation should be {} spaces", spaces_in_tab);
-        return Err(Error::new(ErrorKind::Other, format!("Indentation error on line:\n\n{}", line)));
     }
 
-    Ok(level)
+    width
 }
 
 
-fn parse_line(l: &str) -> Option<BookItem> {
+// Returns `None` for the lines that are ignored and a description of the mistake for malformed
+// entries
+fn parse_line(l: &str) -> Result<Option<BookItem>, String> {
     debug!("[fn]: parse_line");
 
     // Remove leading and trailing spaces or tabs
     let line = l.trim_matches(|c: char| c == ' ' || c == '\t');
 
+    // Part titles are headers, "# Part I"
+    if line.starts_with('#') {
+        debug!("[*]: Line is part title");
+        let title = line.trim_left_matches('#').trim();
+        return Ok(Some(BookItem::PartTitle(title.to_owned())));
+    }
+
     // Spacers are "------"
     if line.starts_with("--") {
         debug!("[*]: Line is spacer");
-        return Some(BookItem::Spacer);
+        return Ok(Some(BookItem::Spacer));
     }
 
+    // A chapter marked `{hidden}` has a page but is not listed
+    let (line, hidden) = if line.ends_with(HIDDEN_MARKER) {
+        (line[..line.len() - HIDDEN_MARKER.len()].trim_right(), true)
+    } else {
+        (line, false)
+    };
+
     if let Some(c) = line.chars().nth(0) {
         match c {
             // List item
             '-' | '*' => {
                 debug!("[*]: Line is list element");
 
-                if let Some((name, path)) = read_link(line) {
-                    return Some(BookItem::Chapter("0".to_owned(), Chapter::new(name, path)));
-                } else {
-                    return None;
+                // A list item without a link is a draft, its chapter has no file yet
+                let text = line[1..].trim();
+                if !text.contains('[') {
+                    debug!("[*]: List element is a draft");
+                    if hidden {
+                        return Err(format!("the draft '{}' can not be hidden, it has no page", text));
+                    }
+                    return Ok(Some(BookItem::Chapter("0".to_owned(), Chapter::new(text.to_owned(), PathBuf::new()))));
                 }
+
+                let (name, path) = try!(read_entry(line, "- [Title](path)"));
+                let mut ch = Chapter::new(name, path);
+                ch.hidden = hidden;
+                return Ok(Some(BookItem::Chapter("0".to_owned(), ch)));
             },
             // Non-list element
             '[' => {
                 debug!("[*]: Line is a link element");
 
-                if let Some((name, path)) = read_link(line) {
-                    return Some(BookItem::Affix(Chapter::new(name, path)));
-                } else {
-                    return None;
-                }
+                let (name, path) = try!(read_entry(line, "[Title](path)"));
+                let mut ch = Chapter::new(name, path);
+                ch.hidden = hidden;
+                return Ok(Some(BookItem::Affix(ch)));
             },
             _ => {},
         }
     }
 
-    None
+    Ok(None)
+}
+
+// Reads the link of an entry and checks that it points to a markdown file, `expected` is the
+// syntax of the entry shown in the error message
+fn read_entry(line: &str, expected: &str) -> Result<(String, PathBuf), String> {
+    let (name, path) = match read_link(line) {
+        Some(link) => link,
+        None => return Err(format!("expected '{}', found '{}'", expected, line)),
+    };
+
+    if path == PathBuf::new() {
+        return Err(format!("the link of '{}' has an empty path", name));
+    }
+
+    let is_url = {
+        let path = path.to_string_lossy();
+        path.starts_with("http://") || path.starts_with("https://")
+    };
+    if !is_url && path.extension().map_or(true, |ext| ext != "md") {
+        return Err(format!("expected a link to a '.md' file, found '{}'", path.display()));
+    }
+
+    Ok((name, path))
 }
 
 fn read_link(line: &str) -> Option<(String, PathBuf)> {
     let mut start_delimitor;
     let mut end_delimitor;
 
-    // In the future, support for list item that is not a link
-    // Not sure if I should error on line I can't parse or just ignore them...
     if let Some(i) = line.find('[') {
         start_delimitor = i;
     } else {
-        debug!("[*]: '[' not found, this line is not a link. Ignoring...");
+        debug!("[*]: '[' not found, this line is not a link.");
         return None;
     }
 
     if let Some(i) = line[start_delimitor..].find("](") {
         end_delimitor = start_delimitor + i;
     } else {
-        debug!("[*]: '](' not found, this line is not a link. Ignoring...");
+        debug!("[*]: '](' not found, this line is not a link.");
         return None;
     }
 
@@ -215,7 +401,7 @@ fn read_link(line: &str) -> Option<(String, PathBuf)> {
     if let Some(i) = line[start_delimitor..].find(')') {
         end_delimitor = start_delimitor + i;
     } else {
-        debug!("[*]: ')' not found, this line is not a link. Ignoring...");
+        debug!("[*]: ')' not found, this line is not a link.");
         return None;
     }
 
@@ -223,3 +409,268 @@ fn read_link(line: &str) -> Option<(String, PathBuf)> {
 
     Some((name, path))
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use book::bookitem::BookItem;
+
+    fn parse_summary(summary: &str) -> Result<Vec<BookItem>, MdBookError> {
+        parse_summaries(&[(PathBuf::from("SUMMARY.md"), summary.to_owned())])
+    }
+
+    // Flattens the parsed items into (section, name) pairs
+    fn sections(items: &[BookItem]) -> Vec<(String, String)> {
+        let mut result = vec![];
+        for item in items {
+            if let BookItem::Chapter(ref s, ref ch) = *item {
+                result.push((s.clone(), ch.name.clone()));
+                result.extend(sections(&ch.sub_items));
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn parse_deep_nesting() {
+        let summary = "# Summary
+
+- [A](a.md)
+    - [B](b.md)
+        - [C](c.md)
+            - [D](d.md)
+    - [E](e.md)
+- [F](f.md)
+";
+        let items = parse_summary(summary).unwrap();
+
+        assert_eq!(sections(&items),
+                   vec![("1.".to_owned(), "A".to_owned()),
+                        ("1.1.".to_owned(), "B".to_owned()),
+                        ("1.1.1.".to_owned(), "C".to_owned()),
+                        ("1.1.1.1.".to_owned(), "D".to_owned()),
+                        ("1.2.".to_owned(), "E".to_owned()),
+                        ("2.".to_owned(), "F".to_owned())]);
+    }
+
+    #[test]
+    fn parse_mixed_indentation() {
+        let summary = "- [A](a.md)\n\t- [B](b.md)\n  \t- [C](c.md)\n  - [D](d.md)\n";
+        let items = parse_summary(summary).unwrap();
+
+        assert_eq!(sections(&items),
+                   vec![("1.".to_owned(), "A".to_owned()),
+                        ("1.1.".to_owned(), "B".to_owned()),
+                        ("1.2.".to_owned(), "C".to_owned()),
+                        ("1.3.".to_owned(), "D".to_owned())]);
+    }
+
+    #[test]
+    fn parse_part_titles() {
+        let summary = "# Summary
+
+[Intro](intro.md)
+
+# Part I
+
+- [A](a.md)
+    - [B](b.md)
+
+## Part II
+
+- [C](c.md)
+";
+        let items = parse_summary(summary).unwrap();
+
+        let parts: Vec<_> = items.iter()
+                                 .filter_map(|item| match *item {
+                                     BookItem::PartTitle(ref title) => Some(title.clone()),
+                                     _ => None,
+                                 })
+                                 .collect();
+        assert_eq!(parts, vec!["Part I".to_owned(), "Part II".to_owned()]);
+        assert_eq!(sections(&items),
+                   vec![("1.".to_owned(), "A".to_owned()),
+                        ("1.1.".to_owned(), "B".to_owned()),
+                        ("2.".to_owned(), "C".to_owned())]);
+    }
+
+    #[test]
+    fn parse_unnumbered_entries() {
+        let summary = "# Summary
+
+# ---
+
+- [Preface](preface.md)
+- [Foreword](foreword.md)
+
+# Part I
+
+- [A](a.md)
+    - [B](b.md)
+- [C](c.md)
+
+# ---
+
+- [Appendix](appendix.md)
+[Glossary](glossary.md)
+";
+        let items = parse_summary(summary).unwrap();
+
+        let affixes: Vec<_> = items.iter()
+                                   .filter_map(|item| match *item {
+                                       BookItem::Affix(ref ch) => Some(ch.name.clone()),
+                                       _ => None,
+                                   })
+                                   .collect();
+        assert_eq!(affixes,
+                   vec!["Preface".to_owned(), "Foreword".to_owned(), "Appendix".to_owned(), "Glossary".to_owned()]);
+        assert_eq!(sections(&items),
+                   vec![("1.".to_owned(), "A".to_owned()),
+                        ("1.1.".to_owned(), "B".to_owned()),
+                        ("2.".to_owned(), "C".to_owned())]);
+
+        // Unnumbered entries have no sub-chapters
+        assert_eq!(error("# ---\n- [A](a.md)\n    - [B](b.md)\n"),
+                   (3, "only numbered chapters can have sub-chapters".to_owned()));
+    }
+
+    #[test]
+    fn parse_draft_chapters() {
+        let summary = "- [A](a.md)\n    - Future chapter\n- Later\n    - [B](b.md)\n";
+        let items = parse_summary(summary).unwrap();
+
+        assert_eq!(sections(&items),
+                   vec![("1.".to_owned(), "A".to_owned()),
+                        ("1.1.".to_owned(), "Future chapter".to_owned()),
+                        ("2.".to_owned(), "Later".to_owned()),
+                        ("2.1.".to_owned(), "B".to_owned())]);
+
+        match items[1] {
+            BookItem::Chapter(_, ref ch) => assert_eq!(ch.path, PathBuf::new()),
+            _ => panic!("Expected a chapter"),
+        }
+    }
+
+    #[test]
+    fn parse_url_entries() {
+        let items = parse_summary("- [A](a.md)\n- [Forum](https://users.rust-lang.org)\n").unwrap();
+
+        match items[1] {
+            BookItem::Chapter(_, ref ch) => assert!(ch.is_url()),
+            _ => panic!("Expected a chapter"),
+        }
+    }
+
+    // Returns the line and the message of the error for the summary
+    fn error(summary: &str) -> (usize, String) {
+        match parse_summary(summary) {
+            Err(MdBookError::SummaryParse { line, message, .. }) => (line, message),
+            other => panic!("Expected a summary error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_nested_affix_is_an_error() {
+        let summary = "- [A](a.md)\n    [B](b.md)\n";
+
+        assert_eq!(error(summary).0, 2);
+    }
+
+    #[test]
+    fn parse_malformed_entries_are_errors() {
+        assert_eq!(error("# Summary\n\n- [A](a.md)\n- [B(b.md)\n"),
+                   (4, "expected '- [Title](path)', found '- [B(b.md)'".to_owned()));
+        assert_eq!(error("- [A]()\n"),
+                   (1, "the link of 'A' has an empty path".to_owned()));
+        assert_eq!(error("- [A](a.md)\n- [B](b.html)\n"),
+                   (2, "expected a link to a '.md' file, found 'b.html'".to_owned()));
+        assert_eq!(error("- [A](a.md)\n    - [B](b.md)\n- [C](c.md)\n        - [D](d.md)\n").0, 4);
+    }
+
+    #[test]
+    fn chapters_outside_of_source_are_an_error() {
+        assert_eq!(error("- [A](a.md)\n- [Passwords](../../etc/passwd.md)\n"),
+                   (2, "'../../etc/passwd.md' is outside of the source directory".to_owned()));
+        assert_eq!(error("[Preface](/tmp/preface.md)\n").0, 1);
+        assert_eq!(error("- [A](guide/../../a.md)\n").0, 1);
+
+        assert!(parse_summary("- [A](guide/../a.md)\n- [B](./b.md)\n").is_ok());
+    }
+
+    #[test]
+    fn parse_several_summary_files() {
+        let summaries = vec![(PathBuf::from("SUMMARY.md"), "# Summary\n\n- [A](a.md)\n- [B](b.md)\n".to_owned()),
+                             (PathBuf::from("reference/SUMMARY.md"),
+                              "# Reference\n\n- [C](c.md)\n    - [D](../d.md)\n".to_owned()),
+                             (PathBuf::from("appendix.md"), "- [E](./e.md)\n".to_owned())];
+        let items = parse_summaries(&summaries).unwrap();
+
+        assert_eq!(sections(&items),
+                   vec![("1.".to_owned(), "A".to_owned()),
+                        ("2.".to_owned(), "B".to_owned()),
+                        ("3.".to_owned(), "C".to_owned()),
+                        ("3.1.".to_owned(), "D".to_owned()),
+                        ("4.".to_owned(), "E".to_owned())]);
+        match (&items[2], &items[4]) {
+            (&BookItem::PartTitle(ref title), &BookItem::Spacer) => assert_eq!(title, "Reference"),
+            _ => panic!("Expected a part title and a spacer, got {:?}", items),
+        }
+        match items[3] {
+            BookItem::Chapter(_, ref ch) => {
+                assert_eq!(ch.path, PathBuf::from("reference/c.md"));
+                match ch.sub_items[0] {
+                    BookItem::Chapter(_, ref ch) => assert_eq!(ch.path, PathBuf::from("d.md")),
+                    _ => panic!("Expected a chapter"),
+                }
+            },
+            _ => panic!("Expected a chapter"),
+        }
+
+        // The errors point to the file of the entry
+        let summaries = vec![(PathBuf::from("SUMMARY.md"), "- [A](a.md)\n".to_owned()),
+                             (PathBuf::from("reference/SUMMARY.md"), "- [B](../../b.md)\n".to_owned())];
+        match parse_summaries(&summaries) {
+            Err(MdBookError::SummaryParse { file, line, .. }) => {
+                assert_eq!((file, line), (PathBuf::from("reference/SUMMARY.md"), 1));
+            },
+            other => panic!("Expected a summary error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn hidden_chapters_take_no_number() {
+        let summary = "# Summary
+
+[Intro](intro.md)
+- [A](a.md)
+- [Glossary](glossary.md) {hidden}
+- [B](b.md)
+    - [B.1](b1.md)
+    - [Terms](terms.md)   {hidden}
+    - [B.2](b2.md)
+";
+        let items = parse_summary(summary).unwrap();
+
+        assert_eq!(sections(&items),
+                   vec![("1.".to_owned(), "A".to_owned()),
+                        ("".to_owned(), "Glossary".to_owned()),
+                        ("2.".to_owned(), "B".to_owned()),
+                        ("2.1.".to_owned(), "B.1".to_owned()),
+                        ("".to_owned(), "Terms".to_owned()),
+                        ("2.2.".to_owned(), "B.2".to_owned())]);
+        match items[2] {
+            BookItem::Chapter(_, ref ch) => {
+                assert!(ch.hidden);
+                assert_eq!(ch.path, PathBuf::from("glossary.md"));
+            },
+            ref other => panic!("expected the hidden chapter, got {:?}", other),
+        }
+
+        let error = parse_summary("- [A](a.md) {hidden}\n    - [B](b.md)\n").unwrap_err();
+        assert_eq!(error.to_string(), "SUMMARY.md:2: hidden chapters can not have sub-chapters");
+        let error = parse_summary("- Future chapter {hidden}\n").unwrap_err();
+        assert_eq!(error.to_string(), "SUMMARY.md:1: the draft 'Future chapter' can not be hidden, it has no page");
+    }
+}
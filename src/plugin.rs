@@ -0,0 +1,268 @@
+//! Plugins, preprocessors and renderers that are external executables
+//!
+//! A plugin named `foo` is an executable named `mdbook-foo` on the `PATH`, written in any
+//! language. It is listed by name in the `preprocessors` or `renderers` of the configuration and
+//! `build()` runs it once for every language of the book.
+//!
+//! mdBook writes a request to the standard input of the plugin, a JSON object, and closes it:
+//!
+//! ```json
+//! {
+//!     "version": 1,
+//!     "mdbook_version": "0.0.12",
+//!     "kind": "preprocessor",
+//!     "root": "/home/me/mybook",
+//!     "src": "/home/me/mybook/src",
+//!     "dest": "/home/me/mybook/book",
+//!     "config": {"title": "My book", "foo": {"option": true}},
+//!     "book": {
+//!         "title": "My book",
+//!         "author": "",
+//!         "description": "",
+//!         "language": "en",
+//!         "items": [
+//!             {"type": "chapter", "section": "1.", "name": "Intro", "path": "intro.md",
+//!              "content": "# Intro", "sub_items": []}
+//!         ]
+//!     }
+//! }
+//! ```
+//!
+//! `version` is the version of the protocol, [`PROTOCOL_VERSION`](constant.PROTOCOL_VERSION.html).
+//! `kind` is `preprocessor` or `renderer`, `dest` is the directory of the output of the renderer.
+//! `config` has all the keys of the configuration file, those that mdBook does not know included,
+//! so that a plugin can read its own settings from them. The options of mdBook are there with the
+//! values used for the build, also when they are not in the file or were changed by the setters. The items are the tree of the
+//! [json renderer](../renderer/struct.JsonRenderer.html), with the markdown of every chapter.
+//!
+//! The plugin answers with a JSON object on its standard output, with the same `version`:
+//!
+//! - a preprocessor returns the modified `items` of the book, `{"version": 1, "items": [...]}`,
+//!   they replace the items of the book for the next preprocessors and the renderers.
+//! - a renderer can return text `files`, keyed by their path relative to `dest`, that mdBook
+//!   writes for it: `{"version": 1, "files": {"index.txt": "..."}}`. It can also write its output
+//!   to `dest` itself.
+//!
+//! A plugin that exits with an error fails the build, with what it wrote to its standard error. On
+//! success, what it wrote to its standard error is logged.
+//!
+//! The executables are looked for in the directories of
+//! [`MDBook::set_plugin_path()`](../book/struct.MDBook.html#method.set_plugin_path) first, then on
+//! the `PATH`.
+
+use std::env;
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::collections::BTreeMap;
+
+use rustc_serialize::json::{Json, ToJson};
+
+use book::MDBook;
+use book::bookitem::items_to_json;
+
+
+/// Version of the protocol between mdBook and the plugins, the `version` of the requests and of
+/// the answers
+pub const PROTOCOL_VERSION: u64 = 1;
+
+/// Prefix of the name of the executable of a plugin
+pub const EXECUTABLE_PREFIX: &'static str = "mdbook-";
+
+
+/// Returns the request of `kind`, `preprocessor` or `renderer`, for the language of `book` being
+/// built
+
+pub fn request(book: &MDBook, kind: &str) -> Json {
+    let mut data = BTreeMap::new();
+    data.insert("title".to_owned(), book.get_title().to_json());
+    data.insert("author".to_owned(), book.get_author().to_json());
+    data.insert("description".to_owned(), book.get_description().to_json());
+    data.insert("language".to_owned(), book.get_language().to_json());
+    data.insert("items".to_owned(), items_to_json(&book.content));
+
+    let mut request = BTreeMap::new();
+    request.insert("version".to_owned(), PROTOCOL_VERSION.to_json());
+    request.insert("mdbook_version".to_owned(), env!("CARGO_PKG_VERSION").to_json());
+    request.insert("kind".to_owned(), kind.to_json());
+    request.insert("root".to_owned(), book.get_root().to_string_lossy().to_json());
+    request.insert("src".to_owned(), book.get_src().to_string_lossy().to_json());
+    request.insert("dest".to_owned(), book.get_dest().to_string_lossy().to_json());
+    request.insert("config".to_owned(), book.config().to_json());
+    request.insert("book".to_owned(), Json::Object(data));
+
+    Json::Object(request)
+}
+
+/// Runs the plugin `name` of `kind` with the request for `book` and returns its answer, once its
+/// version is checked
+
+pub fn run(name: &str, kind: &str, book: &MDBook) -> Result<Json, Box<Error>> {
+    let executable = format!("{}{}", EXECUTABLE_PREFIX, name);
+    debug!("[*]: Running the {} {}", kind, executable);
+
+    // An executable of the plugin path comes before the one on the PATH
+    let program = book.get_plugin_path()
+                      .iter()
+                      .map(|dir| dir.join(format!("{}{}", executable, env::consts::EXE_SUFFIX)))
+                      .find(|path| path.is_file())
+                      .unwrap_or_else(|| PathBuf::from(&executable));
+
+    let mut child = match Command::new(&program)
+                              .current_dir(book.get_root())
+                              .stdin(Stdio::piped())
+                              .stdout(Stdio::piped())
+                              .stderr(Stdio::piped())
+                              .spawn() {
+        Ok(child) => child,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(Box::new(io::Error::new(io::ErrorKind::NotFound,
+                                               format!("The {} `{}` needs an executable named `{}` on the PATH",
+                                                       kind,
+                                                       name,
+                                                       executable))));
+        },
+        Err(e) => return Err(Box::new(io::Error::new(e.kind(), format!("{}: {}", executable, e)))),
+    };
+
+    // The request is written while the answer is read, a plugin can answer before it read it all
+    let input = request(book, kind).to_string();
+    let mut stdin = child.stdin.take().expect("the standard input of the plugin is piped");
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = try!(child.wait_with_output());
+    let written = writer.join().unwrap_or(Ok(()));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if !output.status.success() {
+        let mut message = format!("{} failed ({})", executable, output.status);
+        if !stderr.trim().is_empty() {
+            message.push_str(&format!(":\n{}", stderr.trim_right()));
+        }
+        return Err(Box::new(io::Error::new(io::ErrorKind::Other, message)));
+    }
+    // A plugin that has no use for the request may exit without reading it
+    if let Err(e) = written {
+        debug!("[*]: {} did not read the whole request: {}", executable, e);
+    }
+    for line in stderr.lines() {
+        info!("{}: {}", executable, line);
+    }
+
+    parse_answer(&executable, &String::from_utf8_lossy(&output.stdout))
+}
+
+
+// The answer of the plugin `executable`, an object with the version of the protocol
+fn parse_answer(executable: &str, stdout: &str) -> Result<Json, Box<Error>> {
+    let invalid = |message: String| -> Box<Error> {
+        Box::new(io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", executable, message)))
+    };
+
+    let answer = try!(Json::from_str(stdout).map_err(|e| invalid(format!("the answer is not valid JSON, {}", e))));
+    if !answer.is_object() {
+        return Err(invalid("the answer should be a JSON object".to_owned()));
+    }
+
+    match answer.find("version").and_then(Json::as_u64) {
+        Some(PROTOCOL_VERSION) => Ok(answer),
+        Some(version) => {
+            Err(invalid(format!("the answer is in version {} of the protocol, mdBook speaks version {}",
+                                version,
+                                PROTOCOL_VERSION)))
+        },
+        None => Err(invalid("the answer has no `version`".to_owned())),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use super::*;
+    use book::MDBook;
+
+    use std::fs::{self, File};
+    use std::path::Path;
+
+    #[test]
+    fn version_of_the_answer() {
+        assert_eq!(parse_answer("mdbook-foo", "{\"version\": 1, \"items\": []}").unwrap().find("items"),
+                   Some(&Json::Array(vec![])));
+        assert_eq!(parse_answer("mdbook-foo", "{\"version\": 2}").unwrap_err().to_string(),
+                   "mdbook-foo: the answer is in version 2 of the protocol, mdBook speaks version 1");
+        assert_eq!(parse_answer("mdbook-foo", "{}").unwrap_err().to_string(),
+                   "mdbook-foo: the answer has no `version`");
+        assert_eq!(parse_answer("mdbook-foo", "[1]").unwrap_err().to_string(),
+                   "mdbook-foo: the answer should be a JSON object");
+        assert!(parse_answer("mdbook-foo", "").unwrap_err().to_string().contains("not valid JSON"));
+    }
+
+    // Writes the shell script `mdbook-<name>` in `dir`
+    #[cfg(not(target_os = "windows"))]
+    fn plugin(dir: &Path, name: &str, script: &str) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(format!("mdbook-{}", name));
+        File::create(&path).unwrap().write_all(format!("#!/bin/sh\n{}", script).as_bytes()).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn plugins_of_the_book() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let bin = tmp.path().join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        plugin(&bin,
+               "shout",
+               "cat > request.json\necho run >> runs.txt\necho 'shouting' >&2\n\
+                echo '{\"version\": 1, \"items\": [{\"type\": \"chapter\", \"section\": \"1.\", \"name\": \"Loud\", \
+                \"path\": \"chapter_1.md\", \"content\": \"# LOUD\", \"sub_items\": []}]}'\n");
+        plugin(&bin, "text", "echo '{\"version\": 1, \"files\": {\"book.txt\": \"LOUD\\\\n\"}}'\n");
+        plugin(&bin, "broken", "echo 'no such chapter' >&2\nexit 2\n");
+        let root = tmp.path().join("book");
+        MDBook::create(&root).unwrap();
+        File::create(root.join("book.json"))
+            .unwrap()
+            .write_all(b"{\"preprocessors\": [\"shout\"], \"renderers\": [\"html\", \"text\"], \"text\": {\"wrap\": 80}}")
+            .unwrap();
+        let mut book = MDBook::new(&root).read_config().unwrap().set_plugin_path(&[bin.clone()]).set_title("Set");
+        book.build().unwrap();
+
+        let request = Json::from_str(&String::from_utf8(fs::read(root.join("request.json")).unwrap()).unwrap()).unwrap();
+        assert_eq!(request.find("version").and_then(Json::as_u64), Some(PROTOCOL_VERSION));
+        assert_eq!(request.find("kind").and_then(Json::as_string), Some("preprocessor"));
+        assert_eq!(request.find_path(&["config", "text", "wrap"]).and_then(Json::as_u64), Some(80));
+        assert_eq!(request.find_path(&["config", "title"]).and_then(Json::as_string), Some("Set"));
+        assert_eq!(request.find_path(&["book", "items"]).and_then(Json::as_array).map(|items| items.len()),
+                   Some(1));
+        assert!(fs::read_to_string(root.join("book/html/chapter_1.html")).unwrap().contains("LOUD"));
+        assert_eq!(fs::read_to_string(root.join("book/text/book.txt")).unwrap(), "LOUD\n");
+
+        // Reading the configuration again replaces its plugins, they still run once per build
+        book.read_config_mut().unwrap();
+        book.build().unwrap();
+        assert_eq!(fs::read_to_string(root.join("runs.txt")).unwrap(), "run\nrun\n");
+
+        let mut book = MDBook::new(&root)
+            .read_config()
+            .unwrap()
+            .set_plugin_path(&[bin.clone()])
+            .add_preprocessor(Box::new(::preprocess::ExternalPreprocessor::new("broken")));
+        let error = book.build().unwrap_err().to_string();
+        assert!(error.starts_with("mdbook-broken failed"), "{}", error);
+        assert!(error.ends_with(":\nno such chapter"), "{}", error);
+
+        let mut book = MDBook::new(&root)
+            .read_config()
+            .unwrap()
+            .set_plugin_path(&[bin.clone()])
+            .add_preprocessor(Box::new(::preprocess::ExternalPreprocessor::new("missing")));
+        assert_eq!(book.build().unwrap_err().to_string(),
+                   "The preprocessor `missing` needs an executable named `mdbook-missing` on the PATH");
+    }
+}
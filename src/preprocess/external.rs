@@ -0,0 +1,43 @@
+use std::error::Error;
+use std::io;
+
+use book::MDBook;
+use book::bookitem::items_from_json;
+use preprocess::Preprocessor;
+use plugin;
+
+
+/// Preprocessor that runs the [plugin](../../plugin/index.html) `mdbook-<name>`, the items of the
+/// book are replaced by the ones it returns
+#[derive(Clone)]
+pub struct ExternalPreprocessor {
+    name: String,
+}
+
+impl ExternalPreprocessor {
+    pub fn new(name: &str) -> Self {
+        ExternalPreprocessor { name: name.to_owned() }
+    }
+}
+
+impl Preprocessor for ExternalPreprocessor {
+    fn run(&self, book: &mut MDBook) -> Result<(), Box<Error>> {
+        let answer = try!(plugin::run(&self.name, "preprocessor", book));
+
+        let items = match answer.find("items") {
+            Some(items) => items,
+            None => {
+                return Err(Box::new(io::Error::new(io::ErrorKind::InvalidData,
+                                                   format!("{}{}: the answer has no `items`",
+                                                           plugin::EXECUTABLE_PREFIX,
+                                                           self.name))))
+            },
+        };
+        book.content = try!(items_from_json(items).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData,
+                           format!("{}{}: {}", plugin::EXECUTABLE_PREFIX, self.name, e))
+        }));
+
+        Ok(())
+    }
+}
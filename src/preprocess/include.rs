@@ -0,0 +1,310 @@
+use std::path::{Path, PathBuf};
+use std::error::Error;
+use std::fs;
+use std::io;
+
+use book::{MDBook, BookItemMut};
+use markdown::front_matter;
+use preprocess::Preprocessor;
+use utils;
+
+
+// Maximum depth of the nested `{{#include_md}}` directives
+const MAX_DEPTH: usize = 16;
+
+
+/// Built-in preprocessor that expands the `{{#include file}}` and `{{#include_md file}}` directives
+/// of every chapter, see [`render_includes()`](fn.render_includes.html)
+#[derive(Clone, Copy)]
+pub struct IncludePreprocessor;
+
+impl Preprocessor for IncludePreprocessor {
+    fn run(&self, book: &mut MDBook) -> Result<(), Box<Error>> {
+        let src = book.get_src().to_owned();
+
+        for item in book.iter_mut() {
+            let ch = match item {
+                BookItemMut::Chapter(_, ch) | BookItemMut::Affix(ch) => ch,
+                BookItemMut::Spacer | BookItemMut::PartTitle(_) => continue,
+            };
+
+            *ch.content = try!(render_includes(ch.content, &src.join(&*ch.path)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces every `{{#include file}}` in `s` by the content of `file`. A range of lines can be
+/// selected with `{{#include file:start:end}}`, both bounds are optional and inclusive.
+///
+/// `{{#include_md file.md}}` includes a markdown fragment that is processed first: its front
+/// matter is removed and its own directives are expanded, relative to the fragment. This is meant
+/// for notes shared by several chapters. The fragments can be nested up to 16 levels, a fragment
+/// that includes itself, directly or not, is an error naming the files of the cycle.
+///
+/// The paths are relative to `path`, the file that contains the directives. An error pointing at the
+/// directive is returned when a file can not be read.
+
+pub fn render_includes(s: &str, path: &Path) -> Result<String, Box<Error>> {
+    render_nested_includes(s, path, &mut vec![path.to_owned()])
+}
+
+// `included` holds the files that are being included, from the chapter to `path`
+fn render_nested_includes(s: &str, path: &Path, included: &mut Vec<PathBuf>) -> Result<String, Box<Error>> {
+    let base_path = path.parent().unwrap_or(Path::new(""));
+
+    let mut previous_end_index = 0;
+    let mut replaced = String::new();
+
+    let mut includes = find_includes(s, base_path);
+    includes.extend(find_directives(s, "{{#include_md", true, base_path));
+    includes.sort_by_key(|include| include.start_index);
+
+    for include in includes {
+
+        if include.escaped {
+            replaced.push_str(&s[previous_end_index..include.start_index - 1]);
+            replaced.push_str(&s[include.start_index..include.end_index]);
+            previous_end_index = include.end_index;
+            continue;
+        }
+
+        let line = s[..include.start_index].matches('\n').count() + 1;
+        let error = |message: String| -> Box<Error> {
+            Box::new(io::Error::new(io::ErrorKind::Other, format!("{}:{}: {}", path.display(), line, message)))
+        };
+
+        let mut file_content = match utils::fs::file_to_string(&include.file) {
+            Ok(content) => content,
+            Err(e) => return Err(error(format!("could not include {:?}: {}", include.file, e))),
+        };
+
+        if include.markdown {
+            if let Some(start) = included.iter().position(|file| same_file(file, &include.file)) {
+                let cycle = included[start..]
+                                .iter()
+                                .chain(Some(&include.file))
+                                .map(|file| file.display().to_string())
+                                .collect::<Vec<_>>();
+                return Err(error(format!("include cycle: {}", cycle.join(" -> "))));
+            }
+            if included.len() > MAX_DEPTH {
+                return Err(error(format!("could not include {:?}: more than {} nested includes",
+                                         include.file,
+                                         MAX_DEPTH)));
+            }
+
+            file_content = match front_matter::split(&file_content) {
+                Ok((_, markdown)) => markdown.to_owned(),
+                Err(e) => return Err(error(format!("could not include {:?}: {}", include.file, e))),
+            };
+
+            included.push(include.file.clone());
+            let rendered = render_nested_includes(&file_content, &include.file, included);
+            included.pop();
+            file_content = try!(rendered);
+        }
+
+        replaced.push_str(&s[previous_end_index..include.start_index]);
+        replaced.push_str(&select_lines(&file_content, include.start_line, include.end_line));
+        previous_end_index = include.end_index;
+    }
+
+    replaced.push_str(&s[previous_end_index..]);
+
+    Ok(replaced)
+}
+
+// Whether two paths are the same file, even when they are written differently
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+// Keeps the lines between `start` and `end` (1-based and inclusive)
+fn select_lines(content: &str, start: Option<usize>, end: Option<usize>) -> String {
+    if start.is_none() && end.is_none() {
+        return content.to_owned();
+    }
+
+    let start = start.unwrap_or(1);
+    let end = end.unwrap_or(usize::max_value());
+
+    content.lines()
+           .enumerate()
+           .filter(|&(i, _)| i + 1 >= start && i + 1 <= end)
+           .map(|(_, line)| line)
+           .collect::<Vec<&str>>()
+           .join("\n")
+}
+
+#[derive(PartialOrd, PartialEq, Debug)]
+struct Include {
+    start_index: usize,
+    end_index: usize,
+    file: PathBuf,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+    escaped: bool,
+    // `{{#include_md}}`, the included file is processed before it is included
+    markdown: bool,
+}
+
+fn find_includes(s: &str, base_path: &Path) -> Vec<Include> {
+    find_directives(s, "{{#include", false, base_path)
+}
+
+fn find_directives(s: &str, directive: &str, markdown: bool, base_path: &Path) -> Vec<Include> {
+    let mut includes = vec![];
+    for (i, _) in s.match_indices(directive) {
+        debug!("[*]: find_include");
+
+        // `{{#include` is also the start of `{{#include_md`
+        let next = s[i + directive.len()..].chars().next();
+        if !next.map_or(false, |c| c.is_whitespace() || c == '}') {
+            continue;
+        }
+
+        let escaped = i > 0 && s[..i].ends_with('\\');
+
+        let end_i = if let Some(n) = s[i..].find("}}") {
+            n
+        } else {
+            continue;
+        } + i + 2;
+
+        // If there is nothing between the directive and "}}" skip
+        let param = s[i + directive.len()..end_i - 2].trim();
+        if param.is_empty() {
+            continue;
+        }
+
+        // file.rs:start:end
+        let mut parts = param.split(':');
+        let file = parts.next().expect("split always returns at least one element");
+        let start_line = parts.next().and_then(|n| n.trim().parse().ok());
+        let end_line = parts.next().and_then(|n| n.trim().parse().ok());
+
+        includes.push(Include {
+            start_index: i,
+            end_index: end_i,
+            file: base_path.join(file.trim()),
+            start_line: start_line,
+            end_line: end_line,
+            escaped: escaped,
+            markdown: markdown,
+        })
+    }
+
+    includes
+}
+
+
+
+
+// ---------------------------------------------------------------------------------
+//      Tests
+//
+
+#[cfg(test)]
+extern crate tempdir;
+#[cfg(test)]
+use self::tempdir::TempDir;
+
+#[test]
+fn test_find_includes_simple() {
+    let s = "Some text {{#include file.rs}} and {{#include dir/test.rs:10:20}}...";
+
+    assert_eq!(find_includes(s, Path::new("")),
+               vec![Include {
+                        start_index: 10,
+                        end_index: 30,
+                        file: PathBuf::from("file.rs"),
+                        start_line: None,
+                        end_line: None,
+                        escaped: false,
+                        markdown: false,
+                    },
+                    Include {
+                        start_index: 35,
+                        end_index: 65,
+                        file: PathBuf::from("dir/test.rs"),
+                        start_line: Some(10),
+                        end_line: Some(20),
+                        escaped: false,
+                        markdown: false,
+                    }]);
+}
+
+#[test]
+fn test_find_includes_escaped_and_empty() {
+    let s = "Escaped \\{{#include file.rs}} and empty {{#include }}";
+
+    assert_eq!(find_includes(s, Path::new("")),
+               vec![Include {
+                        start_index: 9,
+                        end_index: 29,
+                        file: PathBuf::from("file.rs"),
+                        start_line: None,
+                        end_line: None,
+                        escaped: true,
+                        markdown: false,
+                    }]);
+}
+
+#[test]
+fn test_select_lines() {
+    let content = "1\n2\n3\n4\n5";
+
+    assert_eq!(select_lines(content, None, None), content);
+    assert_eq!(select_lines(content, Some(2), Some(4)), "2\n3\n4");
+    assert_eq!(select_lines(content, Some(4), None), "4\n5");
+    assert_eq!(select_lines(content, None, Some(2)), "1\n2");
+}
+
+#[test]
+fn test_render_includes_missing_file() {
+    let err = render_includes("text\n{{#include does_not_exist.rs}}", Path::new("chapter.md")).unwrap_err();
+
+    assert!(format!("{}", err).starts_with("chapter.md:2:"));
+}
+
+#[test]
+fn test_find_includes_not_include_md() {
+    let s = "{{#include_md notes.md}} {{#include file.rs}}";
+
+    assert_eq!(find_includes(s, Path::new("")).len(), 1);
+    assert_eq!(find_directives(s, "{{#include_md", true, Path::new(""))[0].file, PathBuf::from("notes.md"));
+}
+
+#[test]
+fn test_render_includes_markdown_fragments() {
+    use std::fs::File;
+    use std::io::Write;
+
+    let tmp = TempDir::new("mdbook").expect("Could not create a temp dir");
+    fs::create_dir_all(tmp.path().join("notes")).unwrap();
+    let write = |path: &str, content: &str| {
+        File::create(tmp.path().join(path)).unwrap().write_all(content.as_bytes()).unwrap();
+    };
+    write("notes/warning.md", "---\ntitle: Warning\n---\n**Warning:** {{#include_md details.md}}");
+    write("notes/details.md", "see `{{#include code.rs}}`");
+    write("notes/code.rs", "{{#include_md warning.md}}");
+
+    let chapter = tmp.path().join("chapter.md");
+    let rendered = render_includes("Text\n\n{{#include_md notes/warning.md}}\n", &chapter).unwrap();
+    assert_eq!(rendered, "Text\n\n**Warning:** see `{{#include_md warning.md}}`\n");
+
+    // A fragment that includes itself
+    write("notes/details.md", "{{#include_md ../notes/warning.md}}");
+    let err = format!("{}", render_includes("{{#include_md notes/warning.md}}", &chapter).unwrap_err());
+    assert!(err.contains("include cycle"), "{}", err);
+    assert!(err.contains(&format!("{} -> {}",
+                                  tmp.path().join("notes/details.md").display(),
+                                  tmp.path().join("notes/../notes/warning.md").display())),
+            "{}",
+            err);
+}
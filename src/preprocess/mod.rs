@@ -0,0 +1,22 @@
+//! Preprocessors modify the book after `SUMMARY.md` is parsed and the chapters are read, before
+//! the renderers are called
+//!
+//! A preprocessor works on the in-memory tree of the book, for example with
+//! [`MDBook::iter_mut()`](../book/struct.MDBook.html#method.iter_mut), so that every renderer sees
+//! the same modified markdown. They are added with
+//! [`MDBook::add_preprocessor()`](../book/struct.MDBook.html#method.add_preprocessor) and run in the
+//! order they were added, after the built-in ones. The [plugins](../plugin/index.html) listed in
+//! the `preprocessors` of the configuration are set by `read_config()` and run last, reading the
+//! configuration again replaces them.
+
+pub use self::include::IncludePreprocessor;
+pub use self::external::ExternalPreprocessor;
+
+pub mod include;
+pub mod external;
+
+use std::error::Error;
+
+pub trait Preprocessor {
+    fn run(&self, book: &mut ::book::MDBook) -> Result<(), Box<Error>>;
+}
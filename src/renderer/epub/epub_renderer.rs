@@ -0,0 +1,453 @@
+use renderer::Renderer;
+use renderer::html_handlebars::{code_options, links};
+use book::MDBook;
+use book::bookitem::{BookItem, Chapter};
+use markdown::highlight::Highlighter;
+use utils::html::escape_html;
+use {utils, theme, markdown};
+
+use std::path::{Path, PathBuf};
+use std::fs::{self, File};
+use std::error::Error;
+use std::io::{self, Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use zip::{ZipWriter, CompressionMethod};
+use zip::write::FileOptions;
+
+
+/// Renders the book to a single `<title>.epub` file in the destination directory.
+///
+/// Every chapter is converted to XHTML, the table of contents is written both as an EPUB 3
+/// navigation document and as an NCX file for older readers, and the css of the theme is embedded.
+/// Images found in the source directory are added to the archive so that they can be referenced
+/// from the chapters.
+pub struct EpubRenderer;
+
+impl EpubRenderer {
+    pub fn new() -> Self {
+        EpubRenderer
+    }
+}
+
+// A chapter that is part of the spine, in reading order
+struct SpineItem {
+    id: String,
+    href: String,
+}
+
+impl Renderer for EpubRenderer {
+    fn name(&self) -> &str {
+        "epub"
+    }
+
+    fn files_to_write(&self, book: &MDBook) -> Result<Option<Vec<PathBuf>>, Box<Error>> {
+        Ok(Some(vec![PathBuf::from(format!("{}.epub", file_name(book.get_title())))]))
+    }
+
+    fn render(&self, book: &MDBook) -> Result<(), Box<Error>> {
+        debug!("[fn]: render (epub)");
+
+        // Load theme
+        let theme = theme::Theme::new(&book.get_theme_path());
+
+        // Check if dest directory exists
+        debug!("[*]: Check if destination directory exists");
+        if let Err(_) = fs::create_dir_all(book.get_dest()) {
+            return Err(Box::new(io::Error::new(io::ErrorKind::Other,
+                                               "Unexpected error when constructing destination path")));
+        }
+
+        let path = book.get_dest().join(format!("{}.epub", file_name(book.get_title())));
+        let mut epub = ZipWriter::new(try!(File::create(&path)));
+        let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+
+        // The mimetype has to be the first file of the archive and can not be compressed
+        try!(epub.start_file("mimetype", stored));
+        try!(epub.write_all(b"application/epub+zip"));
+
+        try!(epub.start_file("META-INF/container.xml", stored));
+        try!(epub.write_all(CONTAINER.as_bytes()));
+
+        // Chapters
+        let highlighter = book.get_highlight_theme().map(|_| Highlighter::new());
+        let mut spine = vec![];
+        for item in book.iter() {
+            match *item {
+                BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) if ch.path != PathBuf::new() && !ch.is_url() => {
+                    let href = try!(xhtml_href(&ch.path));
+                    let xhtml = chapter_xhtml(&ch.name,
+                                              &utils::fs::path_to_root(&ch.path),
+                                              book.get_language(),
+                                              &chapter_html(book, ch, highlighter.as_ref()));
+
+                    try!(epub.start_file(format!("OEBPS/{}", href), stored));
+                    try!(epub.write_all(xhtml.as_bytes()));
+
+                    spine.push(SpineItem {
+                        id: format!("chapter-{}", spine.len() + 1),
+                        href: href,
+                    });
+                },
+                _ => {},
+            }
+        }
+
+        // Images
+        let mut resources = vec![];
+        try!(find_resources(book.get_src(), book.get_dest(), &mut resources));
+
+        for resource in &resources {
+            let relative = match resource.strip_prefix(book.get_src()) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            let href = relative.to_string_lossy().replace("\\", "/");
+
+            debug!("[*]: Adding resource {:?}", resource);
+            let mut data = vec![];
+            try!(try!(File::open(resource)).read_to_end(&mut data));
+
+            try!(epub.start_file(format!("OEBPS/{}", href), stored));
+            try!(epub.write_all(&data));
+        }
+
+        // Css
+        try!(epub.start_file("OEBPS/book.css", stored));
+        try!(epub.write_all(&theme.css));
+
+        // Navigation
+        let mut nav = String::new();
+        try!(nav_list(&book.content, &mut nav));
+        try!(epub.start_file("OEBPS/nav.xhtml", stored));
+        try!(epub.write_all(nav_xhtml(book.get_title(), book.get_language(), &nav).as_bytes()));
+
+        let mut nav_points = String::new();
+        try!(ncx_points(&book.content, &mut 1, &mut nav_points));
+        try!(epub.start_file("OEBPS/toc.ncx", stored));
+        try!(epub.write_all(ncx(book.get_title(), &nav_points).as_bytes()));
+
+        // Package document
+        try!(epub.start_file("OEBPS/content.opf", stored));
+        try!(epub.write_all(opf(book, &spine, &resources).as_bytes()));
+
+        try!(epub.finish());
+        debug!("Creating {:?} ✓", path);
+
+        Ok(())
+    }
+}
+
+
+static CONTAINER: &'static str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+    <rootfiles>
+        <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+    </rootfiles>
+</container>
+"#;
+
+
+fn chapter_xhtml(title: &str, path_to_root: &str, language: &str, content: &str) -> String {
+    format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{language}" lang="{language}">
+<head>
+    <meta charset="UTF-8"/>
+    <title>{title}</title>
+    <link rel="stylesheet" type="text/css" href="{root}book.css"/>
+</head>
+<body>
+{content}
+</body>
+</html>
+"#,
+            title = escape_html(title),
+            root = path_to_root,
+            language = escape_html(language),
+            content = content)
+}
+
+fn nav_xhtml(title: &str, language: &str, list: &str) -> String {
+    format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{language}" lang="{language}">
+<head>
+    <meta charset="UTF-8"/>
+    <title>{title}</title>
+</head>
+<body>
+<nav epub:type="toc" id="toc">
+{list}</nav>
+</body>
+</html>
+"#,
+            title = escape_html(title),
+            language = escape_html(language),
+            list = list)
+}
+
+fn ncx(title: &str, nav_points: &str) -> String {
+    format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+<head>
+    <meta name="dtb:uid" content="{uid}"/>
+</head>
+<docTitle><text>{title}</text></docTitle>
+<navMap>
+{nav_points}</navMap>
+</ncx>
+"#,
+            uid = escape_html(&identifier(title)),
+            title = escape_html(title),
+            nav_points = nav_points)
+}
+
+fn opf(book: &MDBook, spine: &[SpineItem], resources: &[PathBuf]) -> String {
+    let mut manifest = String::new();
+    manifest.push_str("        <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n");
+    manifest.push_str("        <item id=\"ncx\" href=\"toc.ncx\" media-type=\"application/x-dtbncx+xml\"/>\n");
+    manifest.push_str("        <item id=\"css\" href=\"book.css\" media-type=\"text/css\"/>\n");
+
+    for item in spine {
+        manifest.push_str(&format!("        <item id=\"{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+                                   item.id,
+                                   escape_html(&item.href)));
+    }
+
+    for (i, resource) in resources.iter().enumerate() {
+        let href = match resource.strip_prefix(book.get_src()) {
+            Ok(r) => r.to_string_lossy().replace("\\", "/"),
+            Err(_) => continue,
+        };
+        let media_type = media_type(resource).unwrap_or("application/octet-stream");
+
+        manifest.push_str(&format!("        <item id=\"resource-{}\" href=\"{}\" media-type=\"{}\"/>\n",
+                                   i + 1,
+                                   escape_html(&href),
+                                   media_type));
+    }
+
+    let mut itemrefs = String::new();
+    for item in spine {
+        itemrefs.push_str(&format!("        <itemref idref=\"{}\"/>\n", item.id));
+    }
+
+    format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="{language}">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <dc:identifier id="book-id">{identifier}</dc:identifier>
+        <dc:title>{title}</dc:title>
+        <dc:creator>{author}</dc:creator>
+        <dc:description>{description}</dc:description>
+        <dc:language>{language}</dc:language>
+        <meta property="dcterms:modified">{modified}</meta>
+    </metadata>
+    <manifest>
+{manifest}    </manifest>
+    <spine toc="ncx">
+{itemrefs}    </spine>
+</package>
+"#,
+            identifier = escape_html(&identifier(book.get_title())),
+            title = escape_html(book.get_title()),
+            author = escape_html(book.get_author()),
+            description = escape_html(book.get_description()),
+            language = escape_html(book.get_language()),
+            modified = timestamp(),
+            manifest = manifest,
+            itemrefs = itemrefs)
+}
+
+
+// Writes the nested list of the navigation document, chapters without a file are skipped but
+// their sub chapters are kept. The hidden chapters are only in the spine.
+fn nav_list(items: &[BookItem], out: &mut String) -> Result<(), Box<Error>> {
+    let mut list = String::new();
+
+    for item in items {
+        match *item {
+            BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) => {
+                if ch.path == PathBuf::new() || ch.is_url() {
+                    try!(nav_list(&ch.sub_items, &mut list));
+                    continue;
+                }
+                if ch.hidden {
+                    continue;
+                }
+
+                let name = match *item {
+                    BookItem::Chapter(ref s, _) => format!("{} {}", s, ch.name),
+                    _ => ch.name.clone(),
+                };
+
+                list.push_str(&format!("<li><a href=\"{}\">{}</a>\n",
+                                       escape_html(&try!(xhtml_href(&ch.path))),
+                                       escape_html(&name)));
+                try!(nav_list(&ch.sub_items, &mut list));
+                list.push_str("</li>\n");
+            },
+            BookItem::Spacer | BookItem::PartTitle(_) => {},
+        }
+    }
+
+    if !list.is_empty() {
+        out.push_str("<ol>\n");
+        out.push_str(&list);
+        out.push_str("</ol>\n");
+    }
+
+    Ok(())
+}
+
+fn ncx_points(items: &[BookItem], play_order: &mut usize, out: &mut String) -> Result<(), Box<Error>> {
+    for item in items {
+        match *item {
+            BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) => {
+                if ch.path == PathBuf::new() || ch.is_url() {
+                    try!(ncx_points(&ch.sub_items, play_order, out));
+                    continue;
+                }
+
+                out.push_str(&format!("<navPoint id=\"navpoint-{0}\" playOrder=\"{0}\">\n\
+                                       <navLabel><text>{1}</text></navLabel>\n\
+                                       <content src=\"{2}\"/>\n",
+                                      play_order,
+                                      escape_html(&ch.name),
+                                      escape_html(&try!(xhtml_href(&ch.path)))));
+                *play_order += 1;
+
+                try!(ncx_points(&ch.sub_items, play_order, out));
+                out.push_str("</navPoint>\n");
+            },
+            BookItem::Spacer | BookItem::PartTitle(_) => {},
+        }
+    }
+
+    Ok(())
+}
+
+
+// Looks recursively for the files in `dir` that can be embedded in the epub, the destination
+// directory is skipped in case it is inside of the source directory
+fn find_resources(dir: &Path, dest: &Path, resources: &mut Vec<PathBuf>) -> Result<(), Box<Error>> {
+    for entry in try!(fs::read_dir(dir)) {
+        let path = try!(entry).path();
+
+        if path.is_dir() {
+            if path != dest {
+                try!(find_resources(&path, dest, resources));
+            }
+        } else if media_type(&path).is_some() {
+            resources.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn media_type(path: &Path) -> Option<&'static str> {
+    let extension = match path.extension().and_then(|e| e.to_str()) {
+        Some(e) => e.to_lowercase(),
+        None => return None,
+    };
+
+    match &*extension {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "svg" => Some("image/svg+xml"),
+        _ => None,
+    }
+}
+
+// Converts the markdown of a chapter like the html renderer does. The links to the markdown files
+// of other chapters point to their file in the archive, relative to the chapter.
+fn chapter_html(book: &MDBook, ch: &Chapter, highlighter: Option<&Highlighter>) -> String {
+    let (rendered, _) = markdown::render_with_headings(&ch.content, code_options(book, highlighter));
+    let path_to_root = utils::fs::path_to_root(&ch.path);
+    links::markdown_links_to_pages(&rendered, &ch.path, |target| {
+        format!("{}{}", path_to_root, links::to_url(&target.with_extension("xhtml")))
+    })
+}
+
+fn xhtml_href(path: &Path) -> Result<String, Box<Error>> {
+    match path.with_extension("xhtml").to_str() {
+        // Hack for windows who tends to use `\` as separator instead of `/`
+        Some(p) => Ok(p.replace("\\", "/")),
+        None => Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not convert path to str"))),
+    }
+}
+
+// Name of the epub file, derived from the title of the book
+fn file_name(title: &str) -> String {
+    let name: String = title.chars()
+                            .map(|c| match c {
+                                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                                _ => c,
+                            })
+                            .collect();
+
+    match name.trim() {
+        "" => String::from("book"),
+        name => name.to_owned(),
+    }
+}
+
+fn identifier(title: &str) -> String {
+    format!("urn:mdbook:{}", file_name(title).replace(" ", "-").to_lowercase())
+}
+
+// Current time in the `CCYY-MM-DDThh:mm:ssZ` form required by `dcterms:modified`
+fn timestamp() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    utils::format_timestamp(seconds)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use book::bookitem::{BookItem, Chapter};
+    use std::path::PathBuf;
+
+    #[test]
+    fn links_to_chapters_point_to_their_xhtml_file() {
+        let book = MDBook::new(Path::new("book"));
+        let mut chapter = Chapter::new("Setup".to_owned(), PathBuf::from("guide/setup.md"));
+        chapter.content = "[intro](../intro.md#start) [other](other.md) [web](https://x.org/y.md)".to_owned();
+
+        let html = chapter_html(&book, &chapter, None);
+        assert!(html.contains("href=\"../intro.xhtml#start\""), "{}", html);
+        assert!(html.contains("href=\"../guide/other.xhtml\""), "{}", html);
+        assert!(html.contains("href=\"https://x.org/y.md\""), "{}", html);
+    }
+
+    #[test]
+    fn code_options_of_the_book() {
+        let book = MDBook::new(Path::new("book")).set_line_numbers(true);
+        let mut chapter = Chapter::new("Code".to_owned(), PathBuf::from("code.md"));
+        chapter.content = "```\nfn main() {}\n```".to_owned();
+
+        assert!(chapter_html(&book, &chapter, None).contains("<pre class=\"line-numbers\">"));
+    }
+
+    #[test]
+    fn nav_list_follows_chapter_hierarchy() {
+        let mut chapter = Chapter::new("Chapter".to_owned(), PathBuf::from("chapter.md"));
+        chapter.sub_items.push(BookItem::Chapter("1.1.".to_owned(),
+                                                 Chapter::new("Sub".to_owned(), PathBuf::from("dir/sub.md"))));
+        let items = vec![BookItem::Affix(Chapter::new("Intro".to_owned(), PathBuf::from("intro.md"))),
+                         BookItem::Spacer,
+                         BookItem::Chapter("1.".to_owned(), chapter)];
+
+        let mut nav = String::new();
+        nav_list(&items, &mut nav).unwrap();
+
+        assert_eq!(nav,
+                   "<ol>\n<li><a href=\"intro.xhtml\">Intro</a>\n</li>\n\
+                    <li><a href=\"chapter.xhtml\">1. Chapter</a>\n\
+                    <ol>\n<li><a href=\"dir/sub.xhtml\">1.1. Sub</a>\n</li>\n</ol>\n</li>\n</ol>\n");
+    }
+}
@@ -0,0 +1,3 @@
+pub use self::epub_renderer::EpubRenderer;
+
+mod epub_renderer;
@@ -0,0 +1,65 @@
+use renderer::Renderer;
+use book::MDBook;
+use {plugin, utils};
+
+use std::path::{Component, Path};
+use std::fs;
+use std::error::Error;
+use std::io::{self, Write};
+
+
+/// Renderer that runs the [plugin](../../plugin/index.html) `mdbook-<name>`. The plugin writes its
+/// output to the destination directory itself or returns the text files that are written for it.
+#[derive(Clone)]
+pub struct ExternalRenderer {
+    name: String,
+}
+
+impl ExternalRenderer {
+    pub fn new(name: &str) -> Self {
+        ExternalRenderer { name: name.to_owned() }
+    }
+}
+
+impl Renderer for ExternalRenderer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn render(&self, book: &MDBook) -> Result<(), Box<Error>> {
+        debug!("[fn]: render ({})", self.name);
+
+        try!(fs::create_dir_all(book.get_dest()));
+        let answer = try!(plugin::run(&self.name, "renderer", book));
+
+        let files = match answer.find("files") {
+            Some(files) => files,
+            None => return Ok(()),
+        };
+        let invalid = |message: String| -> Box<Error> {
+            Box::new(io::Error::new(io::ErrorKind::InvalidData,
+                                    format!("{}{}: {}", plugin::EXECUTABLE_PREFIX, self.name, message)))
+        };
+        let files = try!(files.as_object().ok_or_else(|| invalid("`files` should be an object".to_owned())));
+
+        for (path, content) in files {
+            // The files stay in the destination directory
+            let inside = Path::new(path).components().all(|c| match c {
+                Component::Normal(_) | Component::CurDir => true,
+                _ => false,
+            });
+            if !inside || path.is_empty() {
+                return Err(invalid(format!("`{}` is not a path relative to the destination directory", path)));
+            }
+            let content = try!(content.as_string()
+                                      .ok_or_else(|| invalid(format!("the content of `{}` should be a string", path))));
+
+            let path = book.get_dest().join(path);
+            let mut file = try!(utils::fs::create_file(&path));
+            try!(file.write_all(content.as_bytes()));
+            debug!("Creating {:?} ✓", path);
+        }
+
+        Ok(())
+    }
+}
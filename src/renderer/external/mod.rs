@@ -0,0 +1,3 @@
+pub use self::external_renderer::ExternalRenderer;
+
+mod external_renderer;
@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use rustc_serialize::json::{Json, ToJson};
+
+use markdown::Heading;
+use utils;
+
+/// Name of the cache file, in the output directory of the html renderer
+pub const CACHE_FILE: &'static str = ".mdbook-cache.json";
+
+/// Rendered html of the chapters, kept in the output directory between two builds so that the
+/// chapters that did not change are not converted again.
+///
+/// A chapter is found in the cache by its source path and the hash of its markdown. The `layout`
+/// is a fingerprint of everything else the pages depend on (the sidebar, the previous / next
+/// links, the template, the configuration...), the pages of unchanged chapters are only kept as
+/// they are when it is the same as in the previous build.
+///
+/// The cache can be deleted at any time, the next build then renders every chapter again.
+///
+/// It also lists the output files whose content was changed by the build, which `mdbook serve` uses
+/// to only reload the pages that need it.
+pub struct RenderCache {
+    pub layout: u64,
+    /// Output files created, modified or removed by the build, relative to the output directory
+    pub changed: Vec<String>,
+    chapters: BTreeMap<String, CachedChapter>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedChapter {
+    /// Hash of the markdown of the chapter
+    pub hash: u64,
+    pub html: String,
+    pub headings: Vec<Heading>,
+}
+
+impl RenderCache {
+    pub fn new(layout: u64) -> Self {
+        RenderCache {
+            layout: layout,
+            changed: vec![],
+            chapters: BTreeMap::new(),
+        }
+    }
+
+    /// Reads the cache of the previous build from `dir`. Returns `None` when there is no cache or
+    /// when it can not be read, the book is then fully rebuilt.
+    pub fn load(dir: &Path) -> Option<Self> {
+        let content = match utils::fs::file_to_string(&dir.join(CACHE_FILE)) {
+            Ok(content) => content,
+            Err(_) => return None,
+        };
+
+        let json = match Json::from_str(&content) {
+            Ok(json) => json,
+            Err(e) => {
+                debug!("[*]: Ignoring the invalid cache: {}", e);
+                return None;
+            },
+        };
+
+        let layout = match json.find("layout").and_then(Json::as_u64) {
+            Some(layout) => layout,
+            None => return None,
+        };
+
+        let mut cache = RenderCache::new(layout);
+        if let Some(changed) = json.find("changed").and_then(Json::as_array) {
+            cache.changed = changed.iter().filter_map(|path| path.as_string().map(|path| path.to_owned())).collect();
+        }
+        if let Some(chapters) = json.find("chapters").and_then(Json::as_object) {
+            for (path, chapter) in chapters {
+                if let Some(chapter) = chapter_from_json(chapter) {
+                    cache.chapters.insert(path.clone(), chapter);
+                }
+            }
+        }
+
+        Some(cache)
+    }
+
+    /// Writes the cache to `dir`
+    pub fn save(&self, dir: &Path) -> Result<(), Box<Error>> {
+        let mut file = try!(utils::fs::create_file(&dir.join(CACHE_FILE)));
+        try!(file.write_all(self.to_json().to_string().as_bytes()));
+        Ok(())
+    }
+
+    /// Removes the cache from `dir`, if there is one
+    pub fn remove(dir: &Path) -> Result<(), Box<Error>> {
+        let path = dir.join(CACHE_FILE);
+        if path.exists() {
+            try!(fs::remove_file(path));
+        }
+        Ok(())
+    }
+
+    /// Returns the cached chapter at `path` if its markdown still has the same `hash`
+    pub fn get(&self, path: &Path, hash: u64) -> Option<&CachedChapter> {
+        self.chapters.get(&*path.to_string_lossy()).and_then(|ch| if ch.hash == hash { Some(ch) } else { None })
+    }
+
+    pub fn insert(&mut self, path: &Path, chapter: CachedChapter) {
+        self.chapters.insert(path.to_string_lossy().into_owned(), chapter);
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.chapters.contains_key(path)
+    }
+
+    /// Source paths of the cached chapters
+    pub fn paths(&self) -> Vec<&str> {
+        self.chapters.keys().map(|path| path.as_str()).collect()
+    }
+}
+
+impl ToJson for RenderCache {
+    fn to_json(&self) -> Json {
+        let mut chapters = BTreeMap::new();
+        for (path, chapter) in &self.chapters {
+            let headings = chapter.headings
+                                  .iter()
+                                  .map(|h| {
+                                      let mut heading = BTreeMap::new();
+                                      heading.insert("level".to_owned(), h.level.to_json());
+                                      heading.insert("text".to_owned(), h.text.to_json());
+                                      heading.insert("id".to_owned(), h.id.to_json());
+                                      Json::Object(heading)
+                                  })
+                                  .collect::<Vec<_>>();
+
+            let mut object = BTreeMap::new();
+            object.insert("hash".to_owned(), chapter.hash.to_json());
+            object.insert("html".to_owned(), chapter.html.to_json());
+            object.insert("headings".to_owned(), Json::Array(headings));
+            chapters.insert(path.clone(), Json::Object(object));
+        }
+
+        let mut cache = BTreeMap::new();
+        cache.insert("layout".to_owned(), self.layout.to_json());
+        cache.insert("changed".to_owned(), self.changed.to_json());
+        cache.insert("chapters".to_owned(), Json::Object(chapters));
+        Json::Object(cache)
+    }
+}
+
+fn chapter_from_json(json: &Json) -> Option<CachedChapter> {
+    let hash = json.find("hash").and_then(Json::as_u64);
+    let html = json.find("html").and_then(Json::as_string);
+    let headings = json.find("headings")
+                       .and_then(Json::as_array)
+                       .and_then(|headings| headings.iter().map(heading_from_json).collect::<Option<Vec<_>>>());
+
+    match (hash, html, headings) {
+        (Some(hash), Some(html), Some(headings)) => {
+            Some(CachedChapter {
+                hash: hash,
+                html: html.to_owned(),
+                headings: headings,
+            })
+        },
+        _ => None,
+    }
+}
+
+fn heading_from_json(json: &Json) -> Option<Heading> {
+    let level = json.find("level").and_then(Json::as_i64);
+    let text = json.find("text").and_then(Json::as_string);
+    let id = json.find("id").and_then(Json::as_string);
+
+    match (level, text, id) {
+        (Some(level), Some(text), Some(id)) => {
+            Some(Heading {
+                level: level as i32,
+                text: text.to_owned(),
+                id: id.to_owned(),
+            })
+        },
+        _ => None,
+    }
+}
+
+/// Output files changed by the last build of the html renderer in `dir`, relative to it. `None`
+/// when there is no cache, the book was never built or its last build failed.
+pub fn changed_files(dir: &Path) -> Option<Vec<PathBuf>> {
+    RenderCache::load(dir).map(|cache| cache.changed.iter().map(PathBuf::from).collect())
+}
+
+/// Hash used for the cache, it only has to be stable between two builds with the same mdBook
+pub fn hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+
+// ---------------------------------------------------------------------------------
+//      Tests
+//
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use super::*;
+    use markdown::Heading;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn cache_round_trip() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+
+        let chapter = CachedChapter {
+            hash: hash("# Intro"),
+            html: "<h1 id=\"intro\">Intro</h1>".to_owned(),
+            headings: vec![Heading { level: 1, text: "Intro".to_owned(), id: "intro".to_owned() }],
+        };
+        let mut cache = RenderCache::new(42);
+        cache.insert(Path::new("intro.md"), chapter.clone());
+        cache.changed = vec!["intro.html".to_owned(), "print.html".to_owned()];
+        cache.save(tmp.path()).unwrap();
+
+        let loaded = RenderCache::load(tmp.path()).expect("The cache could not be loaded");
+        assert_eq!(loaded.layout, 42);
+        assert_eq!(loaded.get(Path::new("intro.md"), hash("# Intro")), Some(&chapter));
+        assert_eq!(loaded.get(Path::new("intro.md"), hash("# Changed")), None);
+        assert_eq!(loaded.paths(), vec!["intro.md"]);
+        assert_eq!(changed_files(tmp.path()),
+                   Some(vec![PathBuf::from("intro.html"), PathBuf::from("print.html")]));
+
+        RenderCache::remove(tmp.path()).unwrap();
+        assert!(RenderCache::load(tmp.path()).is_none());
+        assert!(changed_files(tmp.path()).is_none());
+    }
+}
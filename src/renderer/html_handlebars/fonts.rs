@@ -0,0 +1,130 @@
+//! `fonts.css`, the fonts bundled with the book
+//!
+//! Every font file gets an `@font-face` rule. The family, the weight and the style of the face come
+//! from the name of the file, `<family>-<weight><style>`: `Brand-SemiBoldItalic.woff2` is the
+//! italic face of `Brand` with a weight of 600. A name without a known weight is the regular face
+//! of a family named like the whole name. The files of the same face, in different formats, are
+//! the sources of the same rule, in the order they are listed.
+//!
+//! The stylesheet is loaded before the one of the theme, its rules for the families of the text
+//! are more specific than the ones of the theme so that they win.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+
+/// Returns `fonts.css` for the font files at `links`, relative to the root of the output, and the
+/// families of the text keyed by `body`, `heading` or `code`. The files must be `woff2`, `woff`,
+/// `ttf` or `otf` files.
+
+pub fn fonts_css(links: &[String], font_family: &BTreeMap<String, String>) -> Result<String, String> {
+    let mut faces: Vec<((String, u32, &str), Vec<String>)> = vec![];
+
+    for link in links {
+        let path = Path::new(link);
+        let format = match path.extension().map(|ext| ext.to_string_lossy().to_lowercase()) {
+            Some(ref ext) if ext == "woff2" => "woff2",
+            Some(ref ext) if ext == "woff" => "woff",
+            Some(ref ext) if ext == "ttf" => "truetype",
+            Some(ref ext) if ext == "otf" => "opentype",
+            _ => return Err(format!("{} is not a woff2, woff, ttf or otf font", link)),
+        };
+        let stem = path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+        let face = face(&stem);
+        let source = format!("url(\"{}\") format(\"{}\")", link, format);
+
+        match faces.iter().position(|&(ref other, _)| *other == face) {
+            Some(i) => faces[i].1.push(source),
+            None => faces.push((face, vec![source])),
+        }
+    }
+
+    let mut css = String::new();
+    for ((family, weight, style), sources) in faces {
+        css.push_str(&format!("@font-face {{\n    font-family: \"{}\";\n    font-style: {};\n    \
+                               font-weight: {};\n    src: {};\n}}\n\n",
+                              family,
+                              style,
+                              weight,
+                              sources.join(", ")));
+    }
+
+    let stacks = [("body", "html body", "sans-serif"),
+                  ("heading", "h1, h2, h3, h4, h5, h6", "sans-serif"),
+                  ("code", "code, pre, kbd", "monospace")];
+    for &(key, selector, generic) in &stacks {
+        if let Some(family) = font_family.get(key) {
+            css.push_str(&format!("{} {{\n    font-family: {}, {};\n}}\n\n", selector, family, generic));
+        }
+    }
+
+    Ok(css)
+}
+
+
+// Family, weight and style of the font file named `stem`
+fn face(stem: &str) -> (String, u32, &'static str) {
+    let (family, suffix) = match stem.rfind('-') {
+        Some(i) => (&stem[..i], stem[i + 1..].to_lowercase()),
+        None => return (stem.to_owned(), 400, "normal"),
+    };
+
+    let (weight, style) = if suffix.ends_with("italic") {
+        (&suffix[..suffix.len() - "italic".len()], "italic")
+    } else {
+        (&suffix[..], "normal")
+    };
+    let weight = match weight {
+        "thin" | "hairline" => 100,
+        "extralight" | "ultralight" => 200,
+        "light" => 300,
+        "" | "regular" | "normal" | "book" => 400,
+        "medium" => 500,
+        "semibold" | "demibold" => 600,
+        "bold" => 700,
+        "extrabold" | "ultrabold" => 800,
+        "black" | "heavy" => 900,
+        _ => return (stem.to_owned(), 400, "normal"),
+    };
+
+    (family.to_owned(), weight, style)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn faces_from_file_names() {
+        assert_eq!(face("Brand-Regular"), ("Brand".to_owned(), 400, "normal"));
+        assert_eq!(face("Brand-SemiBoldItalic"), ("Brand".to_owned(), 600, "italic"));
+        assert_eq!(face("Brand-Italic"), ("Brand".to_owned(), 400, "italic"));
+        assert_eq!(face("Fira-Code-Bold"), ("Fira-Code".to_owned(), 700, "normal"));
+        assert_eq!(face("Fira-Code"), ("Fira-Code".to_owned(), 400, "normal"));
+        assert_eq!(face("Brand"), ("Brand".to_owned(), 400, "normal"));
+    }
+
+    #[test]
+    fn font_faces_and_families() {
+        let links = vec!["fonts/Brand-Bold.woff2".to_owned(),
+                         "fonts/Mono.ttf".to_owned(),
+                         "fonts/Brand-Bold.woff".to_owned()];
+        let mut font_family = BTreeMap::new();
+        font_family.insert("body".to_owned(), "Brand".to_owned());
+        font_family.insert("code".to_owned(), "Mono".to_owned());
+
+        assert_eq!(fonts_css(&links, &font_family).unwrap(),
+                   "@font-face {\n    font-family: \"Brand\";\n    font-style: normal;\n    font-weight: 700;\n    \
+                    src: url(\"fonts/Brand-Bold.woff2\") format(\"woff2\"), \
+                    url(\"fonts/Brand-Bold.woff\") format(\"woff\");\n}\n\n\
+                    @font-face {\n    font-family: \"Mono\";\n    font-style: normal;\n    font-weight: 400;\n    \
+                    src: url(\"fonts/Mono.ttf\") format(\"truetype\");\n}\n\n\
+                    html body {\n    font-family: Brand, sans-serif;\n}\n\n\
+                    code, pre, kbd {\n    font-family: Mono, monospace;\n}\n\n");
+
+        assert!(fonts_css(&["logo.png".to_owned()], &BTreeMap::new()).unwrap_err().contains("logo.png"));
+    }
+}
@@ -1,143 +1,342 @@
 use renderer::html_handlebars::helpers;
+use renderer::html_handlebars::search::SearchIndex;
+use renderer::html_handlebars::sitemap::Sitemap;
+use renderer::html_handlebars::print;
+use renderer::html_handlebars::links;
+use renderer::html_handlebars::minify;
+use renderer::html_handlebars::redirect;
+use renderer::html_handlebars::fonts;
+use renderer::html_handlebars::standalone;
+use renderer::html_handlebars::cache::{self, RenderCache, CachedChapter};
 use renderer::Renderer;
-use book::MDBook;
-use book::bookitem::BookItem;
+use book::{MDBook, Progress, NoProgress};
+use book::bookitem::{BookItem, Chapter};
 use {utils, theme, markdown};
+use markdown::Heading;
+use markdown::highlight::Highlighter;
+use utils::html::escape_html;
 
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::str;
 use std::fs::{self, File};
 use std::error::Error;
 use std::io::{self, Read, Write};
-use std::collections::BTreeMap;
+use std::time::UNIX_EPOCH;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use handlebars::{Handlebars, JsonRender};
 use rustc_serialize::json::{Json, ToJson};
 
 
+// Extensions of the generated files whose line endings are the ones of `line_endings`
+const TEXT_EXTENSIONS: &'static [&'static str] = &["html", "css", "js", "json", "xml"];
+
 pub struct HtmlHandlebars;
 
 impl HtmlHandlebars {
     pub fn new() -> Self {
         HtmlHandlebars
     }
-}
 
-impl Renderer for HtmlHandlebars {
-    fn render(&self, book: &MDBook) -> Result<(), Box<Error>> {
-        debug!("[fn]: render");
-        let mut handlebars = Handlebars::new();
+    /// Renders the book like [`render()`](../trait.Renderer.html#tymethod.render) but returns the
+    /// generated files, keyed by their path relative to the output directory, instead of writing them.
+    ///
+    /// Nothing is written to disk: the cache of the previous build is not used, every page is
+    /// rendered, and the static files of the source directory are read into the map.
 
-        // Load theme
-        let theme = theme::Theme::new(book.get_src());
+    pub fn render_to_map(&self, book: &MDBook) -> Result<HashMap<PathBuf, Vec<u8>>, Box<Error>> {
+        debug!("[fn]: render_to_map");
+
+        let (files, _) = try!(self.render_files(book, None, &NoProgress));
+        let mut files = files.into_iter().collect::<HashMap<_, _>>();
+
+        for path in try!(static_files(book)) {
+            if files.contains_key(&path) {
+                continue;
+            }
+            let mut content = vec![];
+            try!(try!(File::open(book.get_src().join(&path))).read_to_end(&mut content));
+            files.insert(path, content);
+        }
+
+        Ok(files)
+    }
+
+    /// Paths, relative to the output directory, of the files that [`render()`](../trait.Renderer.html#tymethod.render)
+    /// would write. The chapters are rendered with the cache of the previous build like in a
+    /// render, the pages it keeps as they are are left out. Nothing is written to disk.
 
-        // Register template
-        debug!("[*]: Register handlebars template");
-        try!(handlebars.register_template_string("index", try!(String::from_utf8(theme.index))));
+    pub fn files_to_write(&self, book: &MDBook) -> Result<Vec<PathBuf>, Box<Error>> {
+        debug!("[fn]: files_to_write");
 
-        // Register helpers
-        debug!("[*]: Register handlebars helpers");
-        handlebars.register_helper("toc", Box::new(helpers::toc::RenderToc));
-        handlebars.register_helper("previous", Box::new(helpers::navigation::previous));
-        handlebars.register_helper("next", Box::new(helpers::navigation::next));
+        let previous_cache = RenderCache::load(book.get_dest());
+        let (files, render_cache) = try!(self.render_files(book, previous_cache.as_ref(), &NoProgress));
+        let mut paths = files.into_iter().map(|(path, _)| path).collect::<Vec<_>>();
 
+        // The static files are not copied over the generated ones, the pages kept as they are included
+        let mut generated = paths.iter().cloned().collect::<HashSet<_>>();
+        generated.extend(render_cache.paths().iter().map(|path| book.page_path(Path::new(path))));
+        generated.insert(PathBuf::from("index.html"));
+        for path in try!(static_files(book)) {
+            if !generated.contains(&path) {
+                paths.push(path);
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Renders the page of a single chapter of `book`, for example to preview it in an editor, and
+    /// returns its html. The markdown is the `content` of the chapter as it is, nothing is read
+    /// from its file. The page is the one of a full build, sidebar included, but without the
+    /// links to the previous and next chapters. Nothing is written to disk.
+
+    pub fn render_chapter(&self, book: &MDBook, ch: &Chapter) -> Result<String, Box<Error>> {
+        debug!("[fn]: render_chapter");
+
+        let theme = theme::Theme::new(&book.get_theme_path());
+        let handlebars = try!(make_handlebars(&theme));
         let mut data = try!(make_data(book));
 
-        // Print version
+        let highlighter = book.get_highlight_theme().map(|_| Highlighter::new());
+        let (rendered, headings) = convert_chapter(book,
+                                                   &chapter_markdown(book, ch),
+                                                   code_options(book, highlighter.as_ref()));
+        let updated = last_updated(book, &ch.path);
+        try!(insert_chapter_data(book, &mut data, ch, &rendered, &headings, updated.as_ref().map(String::as_str)));
+
+        Ok(try!(handlebars.render("index", &data)))
+    }
+
+    // Renders the files generated from the book, in the order they are created, with their path
+    // relative to the output directory. The chapters found in `previous_cache` are not converted
+    // again and their pages are left out when the page of the previous build is still up to date.
+    fn render_files(&self,
+                    book: &MDBook,
+                    previous_cache: Option<&RenderCache>,
+                    progress: &Progress)
+                    -> Result<(Vec<(PathBuf, Vec<u8>)>, RenderCache), Box<Error>> {
+        let mut files = vec![];
+        // Load theme
+        let theme = theme::Theme::new(&book.get_theme_path());
+        let handlebars = try!(make_handlebars(&theme));
+
+        let mut data = try!(make_data(book));
+
+        // Print version, the links between the chapters point inside of the page
         let mut print_content: String = String::new();
+        let print_chapters = book.iter()
+                                 .filter_map(|item| match *item {
+                                     BookItem::Chapter(_, ref ch) |
+                                     BookItem::Affix(ref ch) if ch.path != PathBuf::new() && !ch.is_url() => {
+                                         Some(ch.path.clone())
+                                     },
+                                     _ => None,
+                                 })
+                                 .collect::<HashSet<_>>();
 
-        // Check if dest directory exists
-        debug!("[*]: Check if destination directory exists");
-        if let Err(_) = fs::create_dir_all(book.get_dest()) {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other,
-                                               "Unexpected error when constructing destination path")));
-        }
+        let mut search_index = SearchIndex::new();
+
+        // A sitemap needs absolute urls, it is only written when the site url is one
+        let mut sitemap = match book.get_site_url() {
+            Some(site_url) if site_url.contains("://") => Some(Sitemap::new()),
+            _ => None,
+        };
+
+        // Code blocks are highlighted at build time when a highlight theme is set
+        let syntax_css = match book.get_highlight_theme() {
+            Some(name) => Some(try!(markdown::highlight::theme_css(name))),
+            None => None,
+        };
+        let highlighter = syntax_css.as_ref().map(|_| Highlighter::new());
+        let code_options = code_options(book, highlighter.as_ref());
+
+        // Options the conversion of the markdown depends on, the chapters converted with other
+        // options are not taken from the cache
+        let conversion = cache::hash(&format!("{:?}{}{}{:?}{}{}{}",
+                                              book.get_highlight_theme(),
+                                              book.get_line_numbers(),
+                                              book.get_mermaid(),
+                                              book.get_language_aliases(),
+                                              book.get_mathjax_support(),
+                                              book.get_smart_punctuation(),
+                                              book.get_emoji()));
+
+        // Chapters that get a page, in the order of the book, for the previous / next links
+        let navigation = try!(make_navigation(book));
+        let mut current_page = 0;
+
+        // The chapters that did not change since the previous build are not converted again, and
+        // their pages are not rewritten when nothing else they depend on changed
+        let translations = book.iter()
+                               .filter_map(|item| match *item {
+                                   BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) => {
+                                       Some(language_links(book, &ch.path))
+                                   },
+                                   _ => None,
+                               })
+                               .collect::<Vec<_>>();
+        let layout = cache::hash(&format!("{}{:?}{:?}{:?}{:?}{}{}{}{}{:?}{}{:?}{:?}{}{}{:?}{}{}{:?}",
+                                          data.to_json(),
+                                          theme.index,
+                                          theme.partials,
+                                          navigation,
+                                          translations,
+                                          book.get_line_numbers(),
+                                          book.get_toc_depth(),
+                                          book.get_reading_time(),
+                                          book.get_words_per_minute(),
+                                          book.get_edit_url_template(),
+                                          book.get_clean_urls(),
+                                          book.get_site_url(),
+                                          book.get_index(),
+                                          book.get_line_endings(),
+                                          book.get_minify(),
+                                          book.get_minify_exclude(),
+                                          book.get_navigate_hidden(),
+                                          book.get_git_dates(),
+                                          book.get_redirects()));
+        let same_layout = previous_cache.map_or(false, |c| c.layout == layout);
+        let mut render_cache = RenderCache::new(layout);
+
+        // Ids of every chapter and relative links of the chapters, when the links are checked
+        let mut anchors = HashMap::new();
+        let mut chapter_links = vec![];
+
+        // The chapter that is also rendered as the landing page
+        let index = try!(index_chapter(book));
 
         // Render a file for every entry in the book
-        let mut index = true;
         for item in book.iter() {
 
             match *item {
                 BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) => {
-                    if ch.path != PathBuf::new() {
+                    if ch.path != PathBuf::new() && !ch.is_url() {
 
                         let path = book.get_src().join(&ch.path);
+                        let section = match *item {
+                            BookItem::Chapter(ref s, _) if book.get_number_sections() && !ch.hidden => Some(s.as_str()),
+                            _ => None,
+                        };
+
+                        let content = chapter_markdown(book, ch);
+                        let is_index = index.as_ref() == links::resolve(Path::new(""), &ch.path.to_string_lossy()).as_ref();
+
+                        if let Some(ref mut sitemap) = sitemap {
+                            let link = page_link(book, &ch.path);
+                            let url = format!("{}{}", site_root(book), link.trim_left_matches("./"));
+                            sitemap.add_page(&url, fs::metadata(&path).and_then(|m| m.modified()).ok());
+                        }
 
-                        debug!("[*]: Opening file: {:?}", path);
-                        let mut f = try!(File::open(&path));
-                        let mut content: String = String::new();
+                        // The front matter is part of the page, through its title and description. A
+                        // commit changes the date of the page without changing its file.
+                        let updated = last_updated(book, &ch.path);
+                        let hash = cache::hash(&(&content, ch.metadata.to_json().to_string(), conversion, &updated));
+                        let cached = previous_cache.and_then(|c| c.get(&ch.path, hash)).cloned();
+                        let unchanged = cached.is_some();
 
-                        debug!("[*]: Reading file");
-                        try!(f.read_to_string(&mut content));
+                        // Render markdown using the pulldown-cmark crate, math is kept out of its way
+                        let (rendered, headings) = if let Some(cached) = cached {
+                            debug!("[*]: {:?} did not change, using the cache", ch.path);
+                            (cached.html, cached.headings)
+                        } else {
+                            convert_chapter(book, &content, code_options)
+                        };
 
-                        // Parse for playpen links
-                        if let Some(p) = path.parent() {
-                            content = helpers::playpen::render_playpen(&content, p);
+                        if book.get_search() {
+                            let link = page_link(book, &ch.path);
+                            search_index.add_chapter(section, &ch.name, &link, &content, &headings);
                         }
 
-                        // Render markdown using the pulldown-cmark crate
-                        content = markdown::render(&content);
-                        print_content.push_str(&content);
-
-                        // Remove content from previous file and render content for this one
-                        data.remove("path");
-                        match ch.path.to_str() {
-                            Some(p) => {
-                                data.insert("path".to_owned(), p.to_json());
-                            },
-                            None => {
-                                return Err(Box::new(io::Error::new(io::ErrorKind::Other,
-                                                                   "Could not convert path to str")))
-                            },
+                        render_cache.insert(&ch.path,
+                                            CachedChapter {
+                                                hash: hash,
+                                                html: rendered.clone(),
+                                                headings: headings.clone(),
+                                            });
+                        print_content.push_str(&print::render_chapter(&rendered, &ch.path, &print_chapters));
+
+                        if book.get_check_links() {
+                            if let Some(chapter) = links::resolve(Path::new(""), &ch.path.to_string_lossy()) {
+                                anchors.insert(chapter, links::ids(&rendered));
+                            }
+                            for (attribute, link) in links::relative_links(&rendered) {
+                                chapter_links.push((ch.path.clone(), attribute, link));
+                            }
                         }
 
+                        try!(insert_chapter_data(book,
+                                                 &mut data,
+                                                 ch,
+                                                 &rendered,
+                                                 &headings,
+                                                 updated.as_ref().map(String::as_str)));
+                        let page = book.page_path(&ch.path);
 
-                        // Remove content from previous file and render content for this one
-                        data.remove("content");
-                        data.insert("content".to_owned(), content.to_json());
+                        // Links to the neighbouring chapters, the hidden ones are skipped unless they are
+                        // navigated
+                        let navigable = |i: &usize| book.get_navigate_hidden() || !navigation[*i].1;
+                        data.remove("previous");
+                        if let Some(previous) = (0..current_page).rev().find(&navigable) {
+                            data.insert("previous".to_owned(), navigation[previous].0.to_json());
+                        }
+                        data.remove("next");
+                        if let Some(next) = (current_page + 1..navigation.len()).find(&navigable) {
+                            data.insert("next".to_owned(), navigation[next].0.to_json());
+                        }
+                        current_page += 1;
+                        progress.chapter(&ch.path, current_page, navigation.len());
 
-                        // Remove path to root from previous file and render content for this one
-                        data.remove("path_to_root");
-                        data.insert("path_to_root".to_owned(), utils::fs::path_to_root(&ch.path).to_json());
+                        // The page of an unchanged chapter is kept when nothing else it depends on changed
+                        if unchanged && same_layout && book.get_dest().join(&page).exists() &&
+                           (!is_index || book.get_dest().join("index.html").exists()) {
+                            debug!("[*]: {:?} is up to date", page);
+                            continue;
+                        }
 
                         // Rendere the handlebars template with the data
                         debug!("[*]: Render template");
                         let rendered = try!(handlebars.render("index", &data));
 
-                        debug!("[*]: Create file {:?}", &book.get_dest().join(&ch.path).with_extension("html"));
-                        // Write to file
-                        let mut file = try!(utils::fs::create_file(&book.get_dest().join(&ch.path).with_extension("html")));
-                        output!("[*] Creating {:?} ✓", &book.get_dest().join(&ch.path).with_extension("html"));
-
-                        try!(file.write_all(&rendered.into_bytes()));
-
-                        // Create an index.html from the first element in SUMMARY.md
-                        if index {
+                        // Create an index.html from the landing chapter, the links of the pages start at the
+                        // root because of their <base> so they work from there whatever the chapter is
+                        if is_index {
                             debug!("[*]: index.html");
 
-                            let mut index_file = try!(File::create(book.get_dest().join("index.html")));
-                            let mut content = String::new();
-                            let _source = try!(File::open(book.get_dest().join(&ch.path.with_extension("html"))))
-                                              .read_to_string(&mut content);
-
                             // This could cause a problem when someone displays code containing <base href=...>
                             // on the front page, however this case should be very very rare...
-                            content = content.lines()
-                                             .filter(|line| !line.contains("<base href="))
-                                             .collect::<Vec<&str>>()
-                                             .join("\n");
+                            let content = rendered.lines()
+                                                  .filter(|line| !line.contains("<base href="))
+                                                  .collect::<Vec<&str>>()
+                                                  .join("\n");
 
-                            try!(index_file.write_all(content.as_bytes()));
-
-                            output!("[*] Creating index.html from {:?} ✓",
-                                    book.get_dest().join(&ch.path.with_extension("html")));
-                            index = false;
+                            files.push((PathBuf::from("index.html"), content.into_bytes()));
                         }
+
+                        files.push((page, rendered.into_bytes()));
                     }
                 },
-                _ => {},
+                // Part titles and spacers separate the chapters of the print version
+                BookItem::PartTitle(ref title) => {
+                    print_content.push_str(&format!("<h1 class=\"print-part-title\">{}</h1>\n", escape_html(title)));
+                },
+                BookItem::Spacer => print_content.push_str("<hr class=\"print-spacer\">\n"),
             }
         }
 
+        // Links to chapters, anchors and files that do not exist
+        let broken = chapter_links.iter()
+                                  .filter_map(|&(ref path, ref attribute, ref link)| {
+                                      links::check_link(attribute, link, path, &anchors, book.get_src())
+                                          .map(|reason| format!("- {}: {}, {}", path.display(), link, reason))
+                                  })
+                                  .collect::<Vec<_>>();
+        if !broken.is_empty() {
+            return Err(Box::new(io::Error::new(io::ErrorKind::Other,
+                                               format!("Some links of the chapters are broken:\n{}",
+                                                       broken.join("\n")))));
+        }
+
         // Print version
 
         // Remove content from previous file and render content for this one
@@ -147,146 +346,833 @@ impl Renderer for HtmlHandlebars {
         // Remove content from previous file and render content for this one
         data.remove("content");
         data.insert("content".to_owned(), print_content.to_json());
+        data.remove("mermaid");
+        if has_diagram(book, &print_content) {
+            data.insert("mermaid".to_owned(), true.to_json());
+        }
 
         // Remove path to root from previous file and render content for this one
         data.remove("path_to_root");
-        data.insert("path_to_root".to_owned(), utils::fs::path_to_root(Path::new("print.md")).to_json());
+        data.insert("path_to_root".to_owned(), path_to_root(book, Path::new("print.html")).to_json());
+
+        // The print version has no neighbours, no table of contents and no source to edit
+        data.remove("chapter_title");
+        data.remove("chapter_description");
+        data.remove("page_url");
+        data.remove("word_count");
+        data.remove("reading_time");
+        data.remove("edit_url");
+        data.remove("previous");
+        data.remove("next");
+        data.remove("page_toc");
 
         // Rendere the handlebars template with the data
         debug!("[*]: Render template");
         let rendered = try!(handlebars.render("index", &data));
-        let mut file = try!(utils::fs::create_file(&book.get_dest().join("print").with_extension("html")));
-        try!(file.write_all(&rendered.into_bytes()));
-        output!("[*] Creating print.html ✓");
+        files.push((PathBuf::from("print.html"), rendered.into_bytes()));
 
-        // Copy static files (js, css, images, ...)
-
-        debug!("[*] Copy static files");
-        // JavaScript
-        let mut js_file = if let Ok(f) = File::create(book.get_dest().join("book.js")) {
-            f
+        // The standalone version of the print page loads its assets from the book, they are embedded
+        // once all the files are known
+        let standalone_page = if book.get_standalone() {
+            data.insert("standalone".to_owned(), true.to_json());
+            let rendered = try!(handlebars.render("index", &data));
+            data.remove("standalone");
+            Some(rendered)
         } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create book.js")));
+            None
         };
-        try!(js_file.write_all(&theme.js));
 
-        // Css
-        let mut css_file = if let Ok(f) = File::create(book.get_dest().join("book.css")) {
-            f
+        // 404 page, from `404.md` in the source directory when there is one. The host serves it
+        // at the address of the missing page, whatever its depth, so its links start at the url
+        // of the site instead of being relative.
+        let not_found = book.get_src().join("404.md");
+        let not_found = if not_found.is_file() {
+            try!(utils::fs::file_to_string(&not_found))
         } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create book.css")));
+            NOT_FOUND.to_owned()
         };
-        try!(css_file.write_all(&theme.css));
 
-        // Favicon
-        let mut favicon_file = if let Ok(f) = File::create(book.get_dest().join("favicon.png")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create favicon.png")));
-        };
-        try!(favicon_file.write_all(&theme.favicon));
+        data.remove("path");
+        data.insert("path".to_owned(), "404.md".to_json());
+        data.remove("content");
+        let not_found = markdown::render_with_headings(&not_found, code_options).0;
+        data.insert("content".to_owned(), not_found.to_json());
+        data.remove("path_to_root");
+        data.insert("path_to_root".to_owned(), site_root(book).to_json());
+        data.remove("languages");
+        data.remove("mermaid");
+        if has_diagram(book, &not_found) {
+            data.insert("mermaid".to_owned(), true.to_json());
+        }
 
-        // JQuery local fallback
-        let mut jquery = if let Ok(f) = File::create(book.get_dest().join("jquery.js")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create jquery.js")));
-        };
-        try!(jquery.write_all(&theme.jquery));
+        let rendered = try!(handlebars.render("index", &data));
+        files.push((PathBuf::from("404.html"), rendered.into_bytes()));
+
+        // Pages at the old paths of the moved chapters, that redirect to their new path. They must
+        // not replace a page of the book, one kept from the previous build included, or a file
+        // copied from the source directory.
+        let mut taken = files.iter().map(|&(ref file, _)| file.clone()).collect::<HashSet<_>>();
+        taken.extend(render_cache.paths().iter().map(|path| book.page_path(Path::new(path))));
+        taken.extend(try!(static_files(book)));
+        for (from, to) in book.get_redirects() {
+            let mut path = match links::resolve(Path::new(""), from.trim_left_matches('/')) {
+                Some(ref path) if *path == PathBuf::new() => PathBuf::from("index.html"),
+                Some(path) => path,
+                None => {
+                    return Err(Box::new(io::Error::new(io::ErrorKind::Other,
+                                                       format!("The redirect from {} goes above the root of the book",
+                                                               from))));
+                },
+            };
+            if from.ends_with('/') {
+                path.push("index.html");
+            }
+            if taken.contains(&path) {
+                return Err(Box::new(io::Error::new(io::ErrorKind::Other,
+                                                   format!("The redirect from {} would overwrite a page of the book",
+                                                           from))));
+            }
+
+            let (url, canonical) = redirect_urls(book, &path, to);
+            files.push((path, redirect::redirect_page(&url, &canonical).into_bytes()));
+        }
+
+        // Search index
+        if book.get_search() {
+            files.push((PathBuf::from("searchindex.json"), search_index.to_json().to_string().into_bytes()));
+        }
+
+        if let Some(sitemap) = sitemap {
+            files.push((PathBuf::from("sitemap.xml"), sitemap.to_xml().into_bytes()));
+        }
 
+        // Static files (js, css, images, ...)
+        debug!("[*] Copy static files");
+        files.push((PathBuf::from("book.js"), theme.js.clone()));
+        files.push((PathBuf::from("book.css"), theme.css.clone()));
+        match book.get_favicon() {
+            Some(favicon) => {
+                files.push((additional_file_path(book, favicon), try!(read_book_file(favicon, "favicon"))));
+            },
+            None => files.push((PathBuf::from("favicon.png"), theme.favicon.clone())),
+        }
+        if let Some(logo) = book.get_logo() {
+            files.push((additional_file_path(book, logo), try!(read_book_file(logo, "logo"))));
+        }
+        // JQuery local fallback
+        files.push((PathBuf::from("jquery.js"), theme.jquery.clone()));
         // syntax highlighting
-        let mut highlight_css = if let Ok(f) = File::create(book.get_dest().join("highlight.css")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create highlight.css")));
-        };
-        try!(highlight_css.write_all(&theme.highlight_css));
+        files.push((PathBuf::from("highlight.css"), theme.highlight_css.clone()));
+        files.push((PathBuf::from("tomorrow-night.css"), theme.tomorrow_night_css.clone()));
+        if let Some(syntax_css) = syntax_css {
+            files.push((PathBuf::from("syntax-highlight.css"), syntax_css.into_bytes()));
+        }
 
-        let mut tomorrow_night_css = if let Ok(f) = File::create(book.get_dest().join("tomorrow-night.css")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create tomorrow-night.css")));
-        };
-        try!(tomorrow_night_css.write_all(&theme.tomorrow_night_css));
+        // Additional stylesheets and scripts of the book
+        for path in book.get_additional_css().iter().chain(book.get_additional_js()) {
+            files.push((additional_file_path(book, path), try!(read_book_file(path, "additional file"))));
+        }
 
-        let mut highlight_js = if let Ok(f) = File::create(book.get_dest().join("highlight.js")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create highlight.js")));
-        };
-        try!(highlight_js.write_all(&theme.highlight_js));
+        // Fonts bundled with the book and the stylesheet that declares them
+        if has_fonts(book) {
+            let mut font_links = vec![];
+            for path in book.get_fonts() {
+                let font_path = additional_file_path(book, path);
+                font_links.push(links::to_url(&font_path));
+                files.push((font_path, try!(read_book_file(path, "font"))));
+            }
+            let css = try!(fonts::fonts_css(&font_links, book.get_font_family())
+                               .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)));
+            files.push((PathBuf::from("fonts.css"), css.into_bytes()));
+        }
+
+        files.push((PathBuf::from("highlight.js"), theme.highlight_js.clone()));
 
         // Font Awesome local fallback
-        let mut font_awesome = if let Ok(f) = utils::fs::create_file(&book.get_dest()
-                                                                      .join("_FontAwesome/css/font-awesome.css")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create font-awesome.css")));
-        };
-        try!(font_awesome.write_all(theme::FONT_AWESOME));
-        let mut font_awesome = if let Ok(f) = utils::fs::create_file(&book.get_dest()
-                                                                      .join("_FontAwesome/fonts/fontawesome-webfon\
-                                                                             t.eot")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create fontawesome-webfont.eot")));
-        };
-        try!(font_awesome.write_all(theme::FONT_AWESOME_EOT));
-        let mut font_awesome = if let Ok(f) = utils::fs::create_file(&book.get_dest()
-                                                                      .join("_FontAwesome/fonts/fontawesome-webfon\
-                                                                             t.svg")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create fontawesome-webfont.svg")));
-        };
-        try!(font_awesome.write_all(theme::FONT_AWESOME_SVG));
-        let mut font_awesome = if let Ok(f) = utils::fs::create_file(&book.get_dest()
-                                                                      .join("_FontAwesome/fonts/fontawesome-webfon\
-                                                                             t.ttf")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create fontawesome-webfont.ttf")));
-        };
-        try!(font_awesome.write_all(theme::FONT_AWESOME_TTF));
-        let mut font_awesome = if let Ok(f) = utils::fs::create_file(&book.get_dest()
-                                                                      .join("_FontAwesome/fonts/fontawesome-webfon\
-                                                                             t.woff")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create fontawesome-webfont.woff")));
-        };
-        try!(font_awesome.write_all(theme::FONT_AWESOME_WOFF));
-        let mut font_awesome = if let Ok(f) = utils::fs::create_file(&book.get_dest()
-                                                                      .join("_FontAwesome/fonts/fontawesome-webfon\
-                                                                             t.woff2")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create fontawesome-webfont.woff2")));
-        };
-        try!(font_awesome.write_all(theme::FONT_AWESOME_WOFF2));
-        let mut font_awesome = if let Ok(f) = utils::fs::create_file(&book.get_dest()
-                                                                      .join("_FontAwesome/fonts/FontAwesome.ttf")) {
-            f
-        } else {
-            return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not create FontAwesome.ttf")));
-        };
-        try!(font_awesome.write_all(theme::FONT_AWESOME_TTF));
+        let font_awesome = [("_FontAwesome/css/font-awesome.css", theme::FONT_AWESOME),
+                            ("_FontAwesome/fonts/fontawesome-webfont.eot", theme::FONT_AWESOME_EOT),
+                            ("_FontAwesome/fonts/fontawesome-webfont.svg", theme::FONT_AWESOME_SVG),
+                            ("_FontAwesome/fonts/fontawesome-webfont.ttf", theme::FONT_AWESOME_TTF),
+                            ("_FontAwesome/fonts/fontawesome-webfont.woff", theme::FONT_AWESOME_WOFF),
+                            ("_FontAwesome/fonts/fontawesome-webfont.woff2", theme::FONT_AWESOME_WOFF2),
+                            ("_FontAwesome/fonts/FontAwesome.ttf", theme::FONT_AWESOME_TTF)];
+        for &(path, content) in &font_awesome {
+            files.push((PathBuf::from(path), content.to_vec()));
+        }
+
+        if let Some(standalone_page) = standalone_page {
+            let mut pages = print_chapters.iter()
+                                          .map(|path| (page_link(book, path), format!("#{}", print::chapter_id(path))))
+                                          .collect::<HashMap<_, _>>();
+            pages.entry("./".to_owned()).or_insert("#".to_owned());
+
+            let (page, external) = {
+                // The generated files, then the static files of the source directory
+                let read = |path: &Path| {
+                    if let Some(&(_, ref content)) = files.iter().find(|&&(ref file, _)| file == path) {
+                        return Some(content.clone());
+                    }
+                    let mut content = vec![];
+                    let source = book.get_src().join(path);
+                    match File::open(&source).and_then(|mut file| file.read_to_end(&mut content)) {
+                        Ok(_) if source.is_file() => Some(content),
+                        _ => None,
+                    }
+                };
+                standalone::inline_assets(&standalone_page, &pages, standalone::INLINE_MAX_SIZE, read)
+            };
+            for path in external {
+                warn!("{:?} is larger than {} KiB, standalone.html loads it from next to the page instead of embedding it",
+                      path,
+                      standalone::INLINE_MAX_SIZE / 1024);
+            }
+            files.push((PathBuf::from("standalone.html"), page.into_bytes()));
+        }
+
+        // Minified html, css and js, except for the files excluded from it
+        if book.get_minify() {
+            for &mut (ref path, ref mut content) in &mut files {
+                let url = links::to_url(path);
+                if book.get_minify_exclude().iter().any(|pattern| utils::fs::glob_match(pattern, &url)) {
+                    continue;
+                }
+                let extension = path.extension().map_or(String::new(), |ext| ext.to_string_lossy().to_lowercase());
+                let minified = str::from_utf8(content).ok().and_then(|text| minify::minify(&extension, text));
+                if let Some(minified) = minified {
+                    *content = minified.into_bytes();
+                }
+            }
+        }
+
+        // The templates and the sources may have Windows line endings, the output has the same ones
+        // everywhere
+        let line_ending = if book.get_line_endings() == "crlf" { "\r\n" } else { "\n" };
+        for &mut (ref path, ref mut content) in &mut files {
+            let text = path.extension().map_or(false, |ext| TEXT_EXTENSIONS.iter().any(|text| ext == *text));
+            if !text {
+                continue;
+            }
+            if let Ok(converted) = String::from_utf8(content.clone()) {
+                *content = utils::normalize_line_endings(&converted, line_ending).into_bytes();
+            }
+        }
+
+        Ok((files, render_cache))
+    }
+}
+
+impl Renderer for HtmlHandlebars {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn render(&self, book: &MDBook) -> Result<(), Box<Error>> {
+        self.render_with_progress(book, &NoProgress)
+    }
+
+    fn files_to_write(&self, book: &MDBook) -> Result<Option<Vec<PathBuf>>, Box<Error>> {
+        HtmlHandlebars::files_to_write(self, book).map(Some)
+    }
+
+    fn render_with_progress(&self, book: &MDBook, progress: &Progress) -> Result<(), Box<Error>> {
+        debug!("[fn]: render");
+
+        let previous_cache = RenderCache::load(book.get_dest());
+
+        // A build that fails half way must not leave a cache that does not match the output
+        try!(RenderCache::remove(book.get_dest()));
+
+        let (files, mut render_cache) = try!(self.render_files(book, previous_cache.as_ref(), progress));
+
+        // Check if dest directory exists
+        debug!("[*]: Check if destination directory exists");
+        if let Err(_) = fs::create_dir_all(book.get_dest()) {
+            return Err(Box::new(io::Error::new(io::ErrorKind::Other,
+                                               "Unexpected error when constructing destination path")));
+        }
 
-        // Copy all remaining files
-        try!(utils::fs::copy_files_except_ext(book.get_src(), book.get_dest(), true, &["md"]));
+        // The files generated by the build, including the pages that are up to date
+        let mut generated = files.iter().map(|&(ref path, _)| path.clone()).collect::<HashSet<_>>();
+        generated.extend(render_cache.paths().iter().map(|path| book.page_path(Path::new(path))));
+        generated.insert(PathBuf::from("index.html"));
+
+        // Output files whose content is not the same as before the build
+        let mut changed = vec![];
+
+        for (path, content) in files {
+            let path = book.get_dest().join(path);
+            if !has_content(&path, &content) {
+                changed.push(path.clone());
+            }
+            debug!("[*]: Create file {:?}", path);
+            let mut file = try!(utils::fs::create_file(&path));
+            try!(file.write_all(&content));
+            debug!("Creating {:?} ✓", path);
+        }
+
+        // Pages of the chapters that were removed from the book since the previous build
+        if let Some(ref previous_cache) = previous_cache {
+            for path in previous_cache.paths() {
+                let page = book.get_dest().join(book.page_path(Path::new(path)));
+                if !render_cache.contains(path) && page.exists() {
+                    try!(fs::remove_file(&page));
+                    debug!("Removing {:?} ✓", page);
+                    changed.push(page);
+                }
+            }
+        }
+
+        // Static files of the source directory (images, downloads...), next to the pages
+        for path in try!(static_files(book)) {
+            if generated.contains(&path) {
+                warn!("{:?} is not copied, it would overwrite a generated file", book.get_src().join(&path));
+                continue;
+            }
+
+            let output_path = book.get_dest().join(&path);
+            if let Some(parent) = output_path.parent() {
+                try!(fs::create_dir_all(parent));
+            }
+            let content = try!(read_book_file(&book.get_src().join(&path), "file"));
+            if !has_content(&output_path, &content) {
+                changed.push(output_path.clone());
+            }
+            try!(fs::copy(book.get_src().join(&path), &output_path));
+            debug!("Copying {:?} ✓", output_path);
+        }
+
+        render_cache.changed = changed.iter()
+                                      .filter_map(|path| path.strip_prefix(book.get_dest()).ok())
+                                      .map(links::to_url)
+                                      .collect();
+        try!(render_cache.save(book.get_dest()));
 
         Ok(())
     }
 }
 
+// Keys of the data of the templates that are set by the renderer, for the whole book or for every page
+const RESERVED_KEYS: &'static [&'static str] = &["additional_css", "additional_js", "body_font",
+                                                 "chapter_description", "chapter_title", "chapters",
+                                                 "content", "default_theme", "description", "edit_url",
+                                                 "favicon", "fold_level", "fonts_css", "head",
+                                                 "highlight_theme", "language", "languages", "last_updated",
+                                                 "last_updated_date", "livereload", "logo", "mathjax_support",
+                                                 "mermaid", "next", "number_sections", "page_toc", "page_url",
+                                                 "path", "path_to_root", "previous", "reading_time", "search",
+                                                 "standalone", "title", "word_count"];
+
+// Length of the description of a page taken from the first paragraph of its chapter, the previews
+// of the links show about as many characters
+const DESCRIPTION_MAX_CHARS: usize = 160;
+
+// Content of the 404 page of the books without a `404.md`
+static NOT_FOUND: &'static str = "# Document not found (404)\n\n\
+                                  This page does not exist, it may have been moved or removed.\n\n\
+                                  [Go back to the book](index.html)\n";
+
+// Template of an entry in the table of contents of a chapter, used as `{{> page_toc_item}}` in
+// `index.hbs`. It includes itself for the nested entries.
+static PAGE_TOC_ITEM: &'static str = "<li><a href=\"#{{id}}\">{{title}}</a>\
+                                        {{#if children}}<ul>{{#each children}}{{> page_toc_item}}{{/each}}</ul>{{/if}}\
+                                        </li>";
+
+// Returns the table of contents of a chapter as a nested list of `{"title", "id", "children"}`
+// objects. The chapter title (`h1`) is left out and only `depth` levels are kept, starting at `h2`.
+// Chapters with less than two of these headings don't get a table of contents.
+fn make_page_toc(headings: &[markdown::Heading], depth: usize) -> Option<Vec<Json>> {
+    let max_level = 1 + depth as i32;
+    let kept: Vec<&markdown::Heading> = headings.iter()
+                                                .filter(|h| h.level > 1 && h.level <= max_level)
+                                                .collect();
+
+    if kept.len() < 2 {
+        return None;
+    }
+
+    Some(nest_headings(&kept))
+}
+
+fn nest_headings(headings: &[&markdown::Heading]) -> Vec<Json> {
+    let mut entries = vec![];
+    let mut i = 0;
+
+    while i < headings.len() {
+        // The headings that follow with a higher level are the children of this one
+        let mut end = i + 1;
+        while end < headings.len() && headings[end].level > headings[i].level {
+            end += 1;
+        }
+
+        entries.push(page_toc_entry(headings[i], nest_headings(&headings[i + 1..end])));
+        i = end;
+    }
+
+    entries
+}
+
+fn page_toc_entry(heading: &markdown::Heading, children: Vec<Json>) -> Json {
+    let mut entry = BTreeMap::new();
+    entry.insert("title".to_owned(), heading.text.to_json());
+    entry.insert("id".to_owned(), heading.id.to_json());
+    if !children.is_empty() {
+        entry.insert("children".to_owned(), children.to_json());
+    }
+    entry.to_json()
+}
+
+// Path of the chapter rendered as the `index.html` of the output, relative to the source directory:
+// the `index` of the book or its first chapter with a page
+fn index_chapter(book: &MDBook) -> Result<Option<PathBuf>, Box<Error>> {
+    let mut chapters = book.iter().filter_map(|item| match *item {
+        BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) if ch.path != PathBuf::new() && !ch.is_url() => {
+            links::resolve(Path::new(""), &ch.path.to_string_lossy())
+        },
+        _ => None,
+    });
+
+    match book.get_index() {
+        None => Ok(chapters.next()),
+        Some(index) => {
+            match links::resolve(Path::new(""), &index.to_string_lossy()) {
+                Some(ref path) if chapters.any(|chapter| chapter == *path) => Ok(Some(path.clone())),
+                _ => {
+                    Err(Box::new(io::Error::new(io::ErrorKind::Other,
+                                                format!("The index {:?} is not a chapter of the book", index))))
+                },
+            }
+        },
+    }
+}
+
+// Returns the title and the link of every chapter that is rendered to a page, spacers are skipped
+fn make_navigation(book: &MDBook) -> Result<Vec<(BTreeMap<String, String>, bool)>, Box<Error>> {
+    let mut navigation = vec![];
+
+    for item in book.iter() {
+        match *item {
+            BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) if ch.path != PathBuf::new() && !ch.is_url() => {
+                if ch.path.to_str().is_none() {
+                    return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not convert path to str")));
+                }
+
+                let mut chapter = BTreeMap::new();
+                chapter.insert("title".to_owned(), ch.name.clone());
+                chapter.insert("link".to_owned(), page_link(book, &ch.path));
+                navigation.push((chapter, ch.hidden));
+            },
+            _ => {},
+        }
+    }
+
+    Ok(navigation)
+}
+
+// Files of the source directory that are copied as they are to the output, with their path
+// relative to the source directory: everything but the markdown, the theme and the output itself
+fn static_files(book: &MDBook) -> Result<Vec<PathBuf>, Box<Error>> {
+    let theme = book.get_theme_path();
+    utils::fs::list_files_except_ext(book.get_src(), &[&theme, book.get_dest()], &["md"])
+}
+
+// Link to the page of a chapter, relative to the root of the output. With clean urls it is the
+// directory of the page.
+fn page_link(book: &MDBook, path: &Path) -> String {
+    // Hack for windows who tends to use `\` as separator instead of `/`
+    let page = book.page_path(path).to_string_lossy().replace("\\", "/");
+
+    if !book.get_clean_urls() {
+        return page;
+    }
+    match page.rfind('/') {
+        Some(i) => page[..i + 1].to_owned(),
+        None => "./".to_owned(),
+    }
+}
+
+// Link of a chapter in the sidebar: the link to its page, the url of a web page, or nothing for a
+// chapter without a file
+fn chapter_link(book: &MDBook, path: &Path) -> String {
+    let p = path.to_string_lossy();
+    if p.is_empty() || p.starts_with("http://") || p.starts_with("https://") {
+        p.into_owned()
+    } else {
+        page_link(book, path)
+    }
+}
+
+// Link from a page to the root of the output, used as the `<base>` of the page: the url of the
+// site when the book has one, a relative path otherwise
+fn path_to_root(book: &MDBook, page: &Path) -> String {
+    if book.get_site_url().is_some() {
+        site_root(book)
+    } else {
+        utils::fs::path_to_root(page)
+    }
+}
+
+// Absolute url of the root of the output of the language being built
+fn site_root(book: &MDBook) -> String {
+    let mut root = book.get_site_url().unwrap_or("/").to_owned();
+    if book.get_language() != book.get_default_language() {
+        root.push_str(book.get_language());
+        root.push('/');
+    }
+    root
+}
+
+// Url the redirect page at `path` sends the browser to, and its canonical url. The relative targets
+// start at the root of the book, a markdown file is the page of the chapter.
+fn redirect_urls(book: &MDBook, path: &Path, target: &str) -> (String, String) {
+    if !links::is_relative(target) {
+        return (target.to_owned(), target.to_owned());
+    }
+
+    let (file, fragment) = match target.find('#') {
+        Some(i) => (&target[..i], &target[i..]),
+        None => (target, ""),
+    };
+    let link = if file.ends_with(".md") {
+        format!("{}{}", page_link(book, Path::new(file)), fragment)
+    } else {
+        target.to_owned()
+    };
+
+    let url = format!("{}{}", path_to_root(book, path), link.trim_left_matches("./"));
+    let canonical = match book.get_site_url() {
+        Some(site_url) if site_url.contains("://") => format!("{}{}", site_root(book), link.trim_left_matches("./")),
+        _ => url.clone(),
+    };
+    (url, canonical)
+}
+
+// Path of an additional css or js file in the destination. Files inside of the root of the book keep
+// their relative path, the others are put at the top of the destination.
+fn additional_file_path(book: &MDBook, path: &Path) -> PathBuf {
+    if let Ok(relative) = path.strip_prefix(book.get_root()) {
+        if relative.components().all(|c| c != Component::ParentDir) {
+            return relative.to_owned();
+        }
+    }
+
+    match path.file_name() {
+        Some(name) => PathBuf::from(name),
+        None => path.to_owned(),
+    }
+}
+
+// Whether the book has fonts or font families of its own, in a `fonts.css`
+fn has_fonts(book: &MDBook) -> bool {
+    !book.get_fonts().is_empty() || !book.get_font_family().is_empty()
+}
+
+// Whether the rendered `html` of a page contains a Mermaid diagram
+fn has_diagram(book: &MDBook, html: &str) -> bool {
+    book.get_mermaid() && html.contains("<div class=\"mermaid\">")
+}
+
+// Content of a file of the book that is copied to the destination, `what` names it in the error
+// when it does not exist
+fn read_book_file(path: &Path, what: &str) -> Result<Vec<u8>, Box<Error>> {
+    if !path.is_file() {
+        return Err(Box::new(io::Error::new(io::ErrorKind::NotFound,
+                                           format!("The {} {:?} does not exist", what, path))));
+    }
+
+    let mut content = vec![];
+    try!(try!(File::open(path)).read_to_end(&mut content));
+    Ok(content)
+}
+
+// Whether the file at `path` exists with exactly `content`, a file that would be rewritten as it is
+// is not a change
+fn has_content(path: &Path, content: &[u8]) -> bool {
+    let mut existing = vec![];
+    match File::open(path).and_then(|mut file| file.read_to_end(&mut existing)) {
+        Ok(_) => existing == content,
+        Err(_) => false,
+    }
+}
+
+// Url of the page to edit the source of a chapter, `None` when the book has no repository
+fn edit_url(book: &MDBook, path: &Path) -> Option<String> {
+    // Hack for windows who tends to use `\` as separator instead of `/`
+    let path = path.to_string_lossy().replace("\\", "/");
+
+    if let Some(template) = book.get_edit_url_template() {
+        return Some(template.replace("{path}", &path));
+    }
+
+    book.get_git_repository_url().map(|url| {
+        let src = match book.get_src().strip_prefix(book.get_root()) {
+            Ok(src) if src != Path::new("") => src.to_string_lossy().replace("\\", "/"),
+            _ => "src".to_owned(),
+        };
+        format!("{}/edit/master/{}/{}", url.trim_right_matches('/'), src, path)
+    })
+}
+
+// Links to the same chapter in every language of a translated book, relative to the root of the
+// output. A language without a translation of the chapter links to its first page. `None` when the
+// book is not translated.
+fn language_links(book: &MDBook, path: &Path) -> Option<Json> {
+    let languages = book.get_languages();
+    if languages.len() < 2 {
+        return None;
+    }
+
+    // The default language is at the root of the output, the other ones in a sub directory
+    let to_default = if book.get_language() == book.get_default_language() { "" } else { "../" };
+
+    let links = languages.iter()
+                         .map(|language| {
+                             let dir = if language == book.get_default_language() {
+                                 String::new()
+                             } else {
+                                 format!("{}/", language)
+                             };
+                             let translated = book.get_book(language).map_or(false, |b| b.contains_file(path));
+                             let page = if translated {
+                                 page_link(book, path)
+                             } else {
+                                 "index.html".to_owned()
+                             };
+
+                             let mut link = BTreeMap::new();
+                             link.insert("code".to_owned(), language.to_json());
+                             link.insert("link".to_owned(), format!("{}{}{}", to_default, dir, page).to_json());
+                             link.insert("current".to_owned(), (language == book.get_language()).to_json());
+                             link.insert("missing".to_owned(), (!translated).to_json());
+                             Json::Object(link)
+                         })
+                         .collect();
+
+    Some(Json::Array(links))
+}
+
+// Links to the additional css or js files, relative to the root of the output
+fn additional_file_links(book: &MDBook, paths: &[PathBuf]) -> Vec<String> {
+    paths.iter()
+         // Hack for windows who tends to use `\` as separator instead of `/`
+         .map(|path| additional_file_path(book, path).to_string_lossy().replace("\\", "/"))
+         .collect()
+}
+
+// Handlebars with the templates and the helpers of the theme
+fn make_handlebars(theme: &theme::Theme) -> Result<Handlebars, Box<Error>> {
+    let mut handlebars = Handlebars::new();
+
+    // Register templates, the partials of the theme come after the built-in ones to replace them
+    debug!("[*]: Register handlebars template");
+    try!(handlebars.register_template_string("page_toc_item", PAGE_TOC_ITEM.to_owned()));
+    for (name, partial) in &theme.partials {
+        let partial = try!(String::from_utf8(partial.clone())
+                               .map_err(|_| format!("The partial {}.hbs of the theme is not valid UTF-8", name)));
+        try!(handlebars.register_template_string(name, partial));
+    }
+    try!(handlebars.register_template_string("index", try!(String::from_utf8(theme.index.clone()))));
+
+    // Register helpers
+    debug!("[*]: Register handlebars helpers");
+    handlebars.register_helper("toc", Box::new(helpers::toc::RenderToc));
+    handlebars.register_helper("previous", Box::new(helpers::navigation::previous));
+    handlebars.register_helper("next", Box::new(helpers::navigation::next));
+    handlebars.register_helper("ifeq", Box::new(helpers::compare::ifeq));
+
+    Ok(handlebars)
+}
+
+/// How the markdown of the chapters is converted, according to the configuration of the book
+
+pub fn code_options<'a>(book: &'a MDBook, highlighter: Option<&'a Highlighter>) -> markdown::CodeOptions<'a> {
+    markdown::CodeOptions {
+        highlighter: highlighter,
+        line_numbers: book.get_line_numbers(),
+        mermaid: book.get_mermaid(),
+        language_aliases: Some(book.get_language_aliases()),
+        smart_punctuation: book.get_smart_punctuation(),
+        emoji: book.get_emoji(),
+    }
+}
+
+// The markdown of a chapter, read and preprocessed by the book, with its playpen links included
+fn chapter_markdown(book: &MDBook, ch: &Chapter) -> String {
+    match book.get_src().join(&ch.path).parent() {
+        Some(p) => helpers::playpen::render_playpen(&ch.content, p),
+        None => ch.content.clone(),
+    }
+}
+
+// Renders the markdown of a chapter using the pulldown-cmark crate and returns the html with its
+// headings. The math is kept out of its way.
+fn convert_chapter(book: &MDBook, content: &str, code_options: markdown::CodeOptions) -> (String, Vec<Heading>) {
+    if book.get_mathjax_support() {
+        let (protected, math) = markdown::math::protect(content);
+        let (rendered, mut headings) = markdown::render_with_headings(&protected, code_options);
+        for heading in &mut headings {
+            heading.text = markdown::math::restore(&heading.text, &math);
+        }
+        (markdown::math::restore(&rendered, &math), headings)
+    } else {
+        markdown::render_with_headings(content, code_options)
+    }
+}
+
+// Date of the last change of the chapter at `path`, when the dates are shown: the date of the
+// last commit of its file or, when it is not committed, its modification time. `None` for a
+// chapter without a file.
+fn last_updated(book: &MDBook, path: &Path) -> Option<String> {
+    if !book.get_git_dates() {
+        return None;
+    }
+
+    let file = book.get_src().join(path);
+    utils::git_commit_date(&file).or_else(|| {
+        fs::metadata(&file)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| utils::format_timestamp(duration.as_secs()))
+    })
+}
+
+// Sets the keys of `data` that belong to the page of a chapter, whose markdown was converted to
+// `rendered` with `headings` and that was last changed at `last_updated`. The links to the
+// neighbouring chapters are left to the caller.
+fn insert_chapter_data(book: &MDBook,
+                       data: &mut BTreeMap<String, Json>,
+                       ch: &Chapter,
+                       rendered: &str,
+                       headings: &[Heading],
+                       last_updated: Option<&str>)
+                       -> Result<(), Box<Error>> {
+    // Links to the markdown files of other chapters point to their page, the images are found from
+    // the root of the book
+    let content = links::markdown_links_to_pages(rendered, &ch.path, |target| page_link(book, target));
+    let content = links::sources_from_root(&content, &ch.path);
+
+    // The Mermaid script is only loaded by the pages with a diagram
+    data.remove("mermaid");
+    if has_diagram(book, &content) {
+        data.insert("mermaid".to_owned(), true.to_json());
+    }
+
+    // Table of contents of the chapter
+    data.remove("page_toc");
+    if let Some(page_toc) = make_page_toc(headings, book.get_toc_depth()) {
+        data.insert("page_toc".to_owned(), page_toc.to_json());
+    }
+
+    // Remove content from previous file and render content for this one
+    data.remove("path");
+    match ch.path.to_str() {
+        Some(p) => {
+            data.insert("path".to_owned(), p.to_json());
+        },
+        None => return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not convert path to str"))),
+    }
+
+    // Title and description of the page, from the front matter of the chapter. Without a
+    // description in the front matter, the first paragraph of the chapter is used for the previews
+    // of the links to the page.
+    data.remove("chapter_title");
+    data.remove("chapter_description");
+    let title = ch.metadata.get("title").and_then(Json::as_string).unwrap_or(&ch.name);
+    data.insert("chapter_title".to_owned(), title.to_json());
+    let description = match ch.metadata.get("description").and_then(Json::as_string) {
+        Some(description) => Some(description.to_owned()),
+        None => utils::first_paragraph(&ch.content, DESCRIPTION_MAX_CHARS),
+    };
+    if let Some(description) = description {
+        data.insert("chapter_description".to_owned(), description.to_json());
+    }
+
+    // Absolute url of the page, when the book knows where it is served
+    data.remove("page_url");
+    if let Some(site_url) = book.get_site_url() {
+        if site_url.contains("://") {
+            let url = format!("{}{}", site_root(book), page_link(book, &ch.path).trim_left_matches("./"));
+            data.insert("page_url".to_owned(), url.to_json());
+        }
+    }
+
+    // Reading time of the chapter
+    data.remove("word_count");
+    data.remove("reading_time");
+    if book.get_reading_time() {
+        let words = utils::word_count(&ch.content);
+        data.insert("word_count".to_owned(), words.to_json());
+        data.insert("reading_time".to_owned(),
+                    utils::reading_time(words, book.get_words_per_minute()).to_json());
+    }
+
+    // Link to edit the source of the chapter
+    data.remove("edit_url");
+    if let Some(edit_url) = edit_url(book, &ch.path) {
+        data.insert("edit_url".to_owned(), edit_url.to_json());
+    }
+
+    // Date of the last change of the chapter, in full for machines and as a day for readers
+    data.remove("last_updated");
+    data.remove("last_updated_date");
+    if let Some(last_updated) = last_updated {
+        data.insert("last_updated".to_owned(), last_updated.to_json());
+        data.insert("last_updated_date".to_owned(), last_updated.chars().take(10).collect::<String>().to_json());
+    }
+
+    // Language switcher of a translated book
+    data.remove("languages");
+    if let Some(languages) = language_links(book, &ch.path) {
+        data.insert("languages".to_owned(), languages);
+    }
+
+    // Remove content from previous file and render content for this one
+    data.remove("content");
+    data.insert("content".to_owned(), content.to_json());
+
+    // Remove path to root from previous file and render content for this one
+    data.remove("path_to_root");
+    let page = book.page_path(&ch.path);
+    data.insert("path_to_root".to_owned(), path_to_root(book, &page).to_json());
+
+    Ok(())
+}
+
 fn make_data(book: &MDBook) -> Result<BTreeMap<String, Json>, Box<Error>> {
     debug!("[fn]: make_data");
 
     let mut data = BTreeMap::new();
-    data.insert("language".to_owned(), "en".to_json());
+    data.insert("language".to_owned(), book.get_language().to_json());
     data.insert("title".to_owned(), book.get_title().to_json());
     data.insert("description".to_owned(), book.get_description().to_json());
-    data.insert("favicon".to_owned(), "favicon.png".to_json());
+    let favicon = match book.get_favicon() {
+        Some(favicon) => links::to_url(&additional_file_path(book, favicon)),
+        None => "favicon.png".to_owned(),
+    };
+    data.insert("favicon".to_owned(), favicon.to_json());
+    if let Some(logo) = book.get_logo() {
+        data.insert("logo".to_owned(), links::to_url(&additional_file_path(book, logo)).to_json());
+    }
+    data.insert("search".to_owned(), book.get_search().to_json());
+    data.insert("number_sections".to_owned(), book.get_number_sections().to_json());
+    data.insert("fold_level".to_owned(), book.get_fold_level().to_json());
+    data.insert("mathjax_support".to_owned(), book.get_mathjax_support().to_json());
+    if let Some(highlight_theme) = book.get_highlight_theme() {
+        data.insert("highlight_theme".to_owned(), highlight_theme.to_json());
+    }
+    data.insert("additional_css".to_owned(),
+                additional_file_links(book, book.get_additional_css()).to_json());
+    data.insert("additional_js".to_owned(),
+                additional_file_links(book, book.get_additional_js()).to_json());
+    if has_fonts(book) {
+        data.insert("fonts_css".to_owned(), true.to_json());
+    }
+    // The font of the theme is not loaded when the text has another one
+    if book.get_font_family().contains_key("body") {
+        data.insert("body_font".to_owned(), true.to_json());
+    }
+    data.insert("head".to_owned(), book.get_head().to_json());
+    data.insert("default_theme".to_owned(), book.get_default_theme().to_json());
     if let Some(livereload) = book.get_livereload() {
         data.insert("livereload".to_owned(), livereload.to_json());
     }
@@ -298,11 +1184,14 @@ fn make_data(book: &MDBook) -> Result<BTreeMap<String, Json>, Box<Error>> {
         let mut chapter = BTreeMap::new();
 
         match *item {
+            // The hidden chapters have a page but are not in the table of contents
+            BookItem::Chapter(_, ref ch) | BookItem::Affix(ref ch) if ch.hidden => continue,
             BookItem::Affix(ref ch) => {
                 chapter.insert("name".to_owned(), ch.name.to_json());
                 match ch.path.to_str() {
                     Some(p) => {
                         chapter.insert("path".to_owned(), p.to_json());
+                        chapter.insert("link".to_owned(), chapter_link(book, &ch.path).to_json());
                     },
                     None => return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not convert path to str"))),
                 }
@@ -313,6 +1202,7 @@ fn make_data(book: &MDBook) -> Result<BTreeMap<String, Json>, Box<Error>> {
                 match ch.path.to_str() {
                     Some(p) => {
                         chapter.insert("path".to_owned(), p.to_json());
+                        chapter.insert("link".to_owned(), chapter_link(book, &ch.path).to_json());
                     },
                     None => return Err(Box::new(io::Error::new(io::ErrorKind::Other, "Could not convert path to str"))),
                 }
@@ -320,6 +1210,9 @@ fn make_data(book: &MDBook) -> Result<BTreeMap<String, Json>, Box<Error>> {
             BookItem::Spacer => {
                 chapter.insert("spacer".to_owned(), "_spacer_".to_json());
             },
+            BookItem::PartTitle(ref title) => {
+                chapter.insert("part".to_owned(), title.to_json());
+            },
 
         }
 
@@ -328,6 +1221,504 @@ fn make_data(book: &MDBook) -> Result<BTreeMap<String, Json>, Box<Error>> {
 
     data.insert("chapters".to_owned(), chapters.to_json());
 
+    // Values of the book for the templates, they can not replace the ones of the renderer
+    for (key, value) in book.get_template_data() {
+        if RESERVED_KEYS.contains(&key.as_str()) {
+            warn!("The template data `{}` is ignored, the renderer uses this key", key);
+            continue;
+        }
+        data.insert(key.clone(), value.clone());
+    }
+
     debug!("[*]: JSON constructed");
     Ok(data)
 }
+
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use super::*;
+
+    // Writes the files, with paths relative to the root of the book, in a temp dir and reads the
+    // configuration of the book
+    fn book_with(files: &[(&str, &str)]) -> (tempdir::TempDir, MDBook) {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        for &(path, content) in files {
+            let path = tmp.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            File::create(&path).unwrap().write_all(content.as_bytes()).unwrap();
+        }
+        let book = MDBook::new(tmp.path()).read_config().unwrap();
+        (tmp, book)
+    }
+
+    #[test]
+    fn edit_url_from_repository_or_template() {
+        let path = Path::new("format/config.md");
+
+        let book = MDBook::new(Path::new("book"));
+        assert_eq!(edit_url(&book, path), None);
+
+        let book = book.set_git_repository_url("https://github.com/azerupi/mdBook/");
+        assert_eq!(edit_url(&book, path),
+                   Some("https://github.com/azerupi/mdBook/edit/master/src/format/config.md".to_owned()));
+
+        let book = book.set_edit_url_template("https://example.com/{path}?edit");
+        assert_eq!(edit_url(&book, path), Some("https://example.com/format/config.md?edit".to_owned()));
+    }
+
+    #[test]
+    fn page_paths_and_links_with_clean_urls() {
+        let book = MDBook::new(Path::new("book"));
+        assert_eq!(book.page_path(Path::new("cli/build.md")), PathBuf::from("cli/build.html"));
+        assert_eq!(page_link(&book, Path::new("cli/build.md")), "cli/build.html");
+
+        let book = book.set_clean_urls(true);
+        assert_eq!(book.page_path(Path::new("cli/build.md")), PathBuf::from("cli/build/index.html"));
+        assert_eq!(page_link(&book, Path::new("cli/build.md")), "cli/build/");
+        assert_eq!(book.page_path(Path::new("cli/index.md")), PathBuf::from("cli/index.html"));
+        assert_eq!(page_link(&book, Path::new("cli/index.md")), "cli/");
+        assert_eq!(page_link(&book, Path::new("index.md")), "./");
+    }
+
+    #[test]
+    fn path_to_root_is_the_site_url() {
+        let book = MDBook::new(Path::new("book"));
+        assert_eq!(path_to_root(&book, Path::new("cli/build/index.html")), "../../");
+
+        let book = book.set_site_url("/docs/");
+        assert_eq!(path_to_root(&book, Path::new("cli/build/index.html")), "/docs/");
+        assert_eq!(path_to_root(&book, Path::new("print.html")), "/docs/");
+    }
+
+    #[test]
+    fn site_root_ends_with_slash() {
+        let book = MDBook::new(Path::new("book"));
+        assert_eq!(site_root(&book), "/");
+
+        let book = book.set_site_url("https://example.com/docs");
+        assert_eq!(site_root(&book), "https://example.com/docs/");
+    }
+
+    #[test]
+    fn render_to_map_writes_nothing() {
+        let (tmp, mut book) = book_with(&[("src/SUMMARY.md", "# Summary\n\n- [Intro](intro.md)\n"),
+                                          ("src/intro.md", "---\ntitle: Introduction\n---\n# Intro"),
+                                          ("src/images/logo.png", "png")]);
+        let files = book.render_to_map().unwrap();
+
+        let intro = String::from_utf8_lossy(&files[Path::new("intro.html")]).into_owned();
+        assert!(intro.contains("<h1 id=\"intro\">Intro</h1>"));
+        assert!(intro.contains("<title>Introduction - </title>"));
+        assert!(!intro.contains("title: Introduction"));
+        assert!(files.contains_key(Path::new("index.html")));
+        assert!(files.contains_key(Path::new("print.html")));
+        assert!(String::from_utf8_lossy(&files[Path::new("404.html")]).contains("<base href=\"/\">"));
+        assert!(files.contains_key(Path::new("_FontAwesome/css/font-awesome.css")));
+        assert_eq!(files[Path::new("images/logo.png")], b"png");
+        assert!(!files.contains_key(Path::new("intro.md")));
+        assert!(!files.contains_key(Path::new("sitemap.xml")));
+        assert!(!intro.contains("min read"));
+        assert!(!tmp.path().join("book").exists());
+
+        // With an absolute site url the pages are listed in a sitemap
+        let mut book = book.set_site_url("https://example.com/docs").set_clean_urls(true);
+        let files = book.render_to_map().unwrap();
+        let sitemap = String::from_utf8_lossy(&files[Path::new("sitemap.xml")]).into_owned();
+        assert!(sitemap.contains("<loc>https://example.com/docs/intro/</loc>"), "{}", sitemap);
+
+        let mut book = book.set_reading_time(true);
+        let files = book.render_to_map().unwrap();
+        let intro = String::from_utf8_lossy(&files[Path::new("intro/index.html")]).into_owned();
+        assert!(intro.contains("<p class=\"reading-time\">~1 min read</p>"), "{}", intro);
+    }
+
+    #[test]
+    fn line_endings_of_the_output() {
+        let (_tmp, mut book) =
+            book_with(&[("src/SUMMARY.md", "# Summary\r\n\r\n- [Intro](intro.md)\r\n"),
+                        ("src/intro.md",
+                         "---\r\ntitle: Introduction\r\n---\r\n# Intro\r\n\r\n```\r\nfn main() {\r\n}\r\n```\r\n")]);
+        let files = book.render_to_map().unwrap();
+        let intro = String::from_utf8(files[Path::new("intro.html")].clone()).unwrap();
+        assert!(intro.contains("<pre><code>fn main() {\n}\n</code></pre>"), "{}", intro);
+        assert!(!intro.contains('\r'));
+        assert!(!String::from_utf8_lossy(&files[Path::new("book.css")]).contains('\r'));
+
+        let mut book = book.set_line_endings("crlf");
+        let files = book.render_to_map().unwrap();
+        let intro = String::from_utf8(files[Path::new("intro.html")].clone()).unwrap();
+        assert!(intro.contains("<pre><code>fn main() {\r\n}\r\n</code></pre>"), "{}", intro);
+        assert_eq!(intro.matches('\n').count(), intro.matches("\r\n").count());
+    }
+
+    #[test]
+    fn minified_output() {
+        let (_tmp, mut book) = book_with(&[("src/SUMMARY.md", "# Summary\n\n- [Intro](intro.md)\n"),
+                                           ("src/intro.md",
+                                            "# Intro\n\n```text\n  indented   code\n\n  block\n```\n")]);
+        let files = book.render_to_map().unwrap();
+        let mut book = book.set_minify(true).set_minify_exclude(&["book.js".to_owned()]);
+        let minified = book.render_to_map().unwrap();
+
+        let intro = String::from_utf8(minified[Path::new("intro.html")].clone()).unwrap();
+        assert!(intro.contains("<code class=\"language-text\">  indented   code\n\n  block\n</code>"), "{}", intro);
+        assert!(!intro.contains("\n    "), "{}", intro);
+        assert!(minified[Path::new("intro.html")].len() < files[Path::new("intro.html")].len());
+        assert!(minified[Path::new("book.css")].len() < files[Path::new("book.css")].len());
+        assert_eq!(minified[Path::new("book.js")], files[Path::new("book.js")]);
+    }
+
+    #[test]
+    fn hidden_chapter_between_two_visible_ones() {
+        let (_tmp, mut book) =
+            book_with(&[("src/SUMMARY.md", "# Summary\n\n- [A](a.md)\n- [Glossary](glossary.md) {hidden}\n- [B](b.md)\n"),
+                        ("src/a.md", "# Title"),
+                        ("src/glossary.md", "# Title"),
+                        ("src/b.md", "# Title")]);
+        let files = book.render_to_map().unwrap();
+        let page = |files: &HashMap<PathBuf, Vec<u8>>, name: &str| String::from_utf8(files[Path::new(name)].clone()).unwrap();
+
+        let a = page(&files, "a.html");
+        assert!(!a.contains("<a href=\"glossary.html\""), "{}", a);
+        assert!(a.contains("<strong>2.</strong> B"), "{}", a);
+        assert!(a.contains("<a href=\"b.html\" class=\"nav-chapters next\""));
+        let glossary = page(&files, "glossary.html");
+        assert!(glossary.contains("<a href=\"a.html\" class=\"nav-chapters previous\""));
+        assert!(glossary.contains("<a href=\"b.html\" class=\"nav-chapters next\""));
+        assert!(page(&files, "b.html").contains("<a href=\"a.html\" class=\"nav-chapters previous\""));
+
+        let mut book = book.set_navigate_hidden(true);
+        let files = book.render_to_map().unwrap();
+        assert!(page(&files, "a.html").contains("<a href=\"glossary.html\" class=\"nav-chapters next\""));
+        assert!(page(&files, "b.html").contains("<a href=\"glossary.html\" class=\"nav-chapters previous\""));
+    }
+
+    #[test]
+    fn last_updated_from_git_or_from_the_file() {
+        use std::process::Command;
+
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let book = MDBook::create(tmp.path()).unwrap();
+        let path = Path::new("chapter_1.md");
+        assert_eq!(last_updated(&book, path), None);
+
+        // Outside of a repository, the date is the modification time of the file
+        let book = book.set_git_dates(true);
+        let modified = last_updated(&book, path).unwrap();
+        assert!(modified.ends_with('Z'), "{}", modified);
+        assert_eq!(last_updated(&book, Path::new("missing.md")), None);
+
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .env("GIT_AUTHOR_NAME", "mdBook")
+                .env("GIT_AUTHOR_EMAIL", "mdbook@example.com")
+                .env("GIT_COMMITTER_NAME", "mdBook")
+                .env("GIT_COMMITTER_EMAIL", "mdbook@example.com")
+                .env("GIT_COMMITTER_DATE", "2017-05-03T10:20:30+02:00")
+                .output()
+                .map(|output| output.status.success())
+                .unwrap_or(false)
+        };
+        if !git(&["init", "-q"]) {
+            return;
+        }
+
+        // A file of the repository that is not committed keeps its modification time
+        assert_eq!(last_updated(&book, path), Some(modified));
+
+        assert!(git(&["add", "src/chapter_1.md"]));
+        assert!(git(&["commit", "-q", "-m", "Chapter 1"]));
+        assert_eq!(last_updated(&book, path), Some("2017-05-03T10:20:30+02:00".to_owned()));
+
+        let mut book = book;
+        let files = book.render_to_map().unwrap();
+        let page = String::from_utf8(files[Path::new("./chapter_1.html")].clone()).unwrap();
+        assert!(page.contains("Last updated: <time datetime=\"2017-05-03T10:20:30+02:00\">2017-05-03</time>"),
+                "{}",
+                page);
+    }
+
+    #[test]
+    fn index_page_of_the_book() {
+        let (_tmp, mut book) = book_with(&[("src/SUMMARY.md",
+                                            "# Summary\n\n- [Intro](intro.md)\n- [Welcome](guide/README.md)\n"),
+                                           ("src/intro.md", "# Intro"),
+                                           ("src/guide/README.md", "# Welcome")]);
+        let files = book.render_to_map().unwrap();
+        assert!(String::from_utf8_lossy(&files[Path::new("index.html")]).contains("<h1 id=\"intro\">Intro</h1>"));
+
+        // The links of a nested chapter start at the root on the landing page too
+        let mut book = book.set_index(Path::new("./guide/README.md"));
+        let files = book.render_to_map().unwrap();
+        let index = String::from_utf8_lossy(&files[Path::new("index.html")]).into_owned();
+        assert!(index.contains("<h1 id=\"welcome\">Welcome</h1>"));
+        assert!(!index.contains("<base href="));
+        assert!(files.contains_key(Path::new("guide/README.html")));
+
+        let mut book = book.set_index(Path::new("missing.md"));
+        assert!(book.render_to_map().unwrap_err().to_string().contains("is not a chapter of the book"));
+    }
+
+    #[test]
+    fn standalone_page_embeds_its_assets() {
+        let (_tmp, mut book) = book_with(&[("src/SUMMARY.md",
+                                            "# Summary\n\n- [Intro](intro.md)\n- [Setup](guide/setup.md)\n"),
+                                           ("src/intro.md", "# Intro\n\n[Setup](guide/setup.md)"),
+                                           ("src/guide/setup.md", "# Setup\n\n![diagram](img/a.png)"),
+                                           ("src/guide/img/a.png", "png")]);
+        assert!(!book.render_to_map().unwrap().contains_key(Path::new("standalone.html")));
+
+        let mut book = book.set_standalone(true);
+        let files = book.render_to_map().unwrap();
+        let page = String::from_utf8_lossy(&files[Path::new("standalone.html")]).into_owned();
+        assert!(!page.contains("<base href="));
+        assert!(!page.contains("href=\"book.css\""));
+        assert!(!page.contains("src=\"book.js\""));
+        assert!(!page.contains("https://code.jquery.com"));
+        assert!(page.contains("src=\"data:image/png;base64,cG5n\""));
+        assert!(page.contains("url(data:font/woff2;base64,"));
+        assert!(page.contains("<a href=\"#guide-setup\">Setup</a>"), "{}", page);
+        assert!(page.contains("href=\"#intro\""));
+    }
+
+    #[test]
+    fn fonts_of_the_book() {
+        let (_tmp, mut book) = book_with(&[("src/SUMMARY.md", "# Summary\n\n- [Intro](intro.md)\n"),
+                                           ("src/intro.md", "# Intro"),
+                                           ("fonts/Brand-Bold.woff2", "font")]);
+        let files = book.render_to_map().unwrap();
+        assert!(!files.contains_key(Path::new("fonts.css")));
+        assert!(!String::from_utf8_lossy(&files[Path::new("intro.html")]).contains("fonts.css"));
+
+        let mut font_family = BTreeMap::new();
+        font_family.insert("body".to_owned(), "Brand".to_owned());
+        let mut book = book.set_fonts(&[PathBuf::from("fonts/Brand-Bold.woff2")]).set_font_family(font_family);
+        let files = book.render_to_map().unwrap();
+        assert_eq!(files[Path::new("fonts/Brand-Bold.woff2")], b"font");
+        let css = String::from_utf8_lossy(&files[Path::new("fonts.css")]).into_owned();
+        assert!(css.contains("src: url(\"fonts/Brand-Bold.woff2\") format(\"woff2\");"));
+        assert!(css.contains("font-family: Brand, sans-serif;"));
+        let intro = String::from_utf8_lossy(&files[Path::new("intro.html")]).into_owned();
+        assert!(intro.find("href=\"fonts.css\"").unwrap() < intro.find("href=\"book.css\"").unwrap());
+        assert!(!intro.contains("fonts.googleapis.com"));
+
+        let mut book = book.set_fonts(&[PathBuf::from("fonts/Missing.woff2")]);
+        assert!(book.render_to_map().unwrap_err().to_string().contains("does not exist"));
+    }
+
+    #[test]
+    fn sidebar_sections_deeper_than_the_fold_level_are_collapsed() {
+        let (_tmp, mut book) =
+            book_with(&[("src/SUMMARY.md", "# Summary\n\n- [A](a.md)\n    - [A.1](a1.md)\n- [B](b.md)\n    - [B.1](b1.md)\n"),
+                        ("src/a.md", "# Title"),
+                        ("src/a1.md", "# Title"),
+                        ("src/b.md", "# Title"),
+                        ("src/b1.md", "# Title")]);
+        let files = book.render_to_map().unwrap();
+        assert!(!String::from_utf8_lossy(&files[Path::new("a.html")]).contains("collapsed"));
+
+        let mut book = book.set_fold_level(1);
+        let files = book.render_to_map().unwrap();
+        let page = String::from_utf8_lossy(&files[Path::new("a.html")]).into_owned();
+        assert!(page.contains("<ul class=\"section\" data-section=\"1.\">"), "{}", page);
+        assert!(page.contains("<ul class=\"section collapsed\" data-section=\"2.\">"));
+        let page = String::from_utf8_lossy(&files[Path::new("b1.html")]).into_owned();
+        assert!(page.contains("<ul class=\"section collapsed\" data-section=\"1.\">"));
+        assert!(page.contains("<ul class=\"section\" data-section=\"2.\">"));
+    }
+
+    #[test]
+    fn description_and_open_graph_of_the_pages() {
+        let (_tmp, book) = book_with(&[("src/SUMMARY.md",
+                                        "# Summary\n\n- [Intro](intro.md)\n- [Setup](setup.md)\n- [Code](code.md)\n"),
+                                       ("src/intro.md", "---\ndescription: All about mdBook\n---\n# Intro\n\nText."),
+                                       ("src/setup.md", "# Setup\n\nInstall *mdBook* & run it."),
+                                       ("src/code.md", "# Code\n\n    fn main() {}\n")]);
+        let mut book = book.set_site_url("https://example.com/docs");
+        book.set_description_mut("A book");
+        let files = book.render_to_map().unwrap();
+        let page = |path| String::from_utf8_lossy(&files[Path::new(path)]).into_owned();
+
+        assert!(page("intro.html").contains("<meta property=\"og:description\" content=\"All about mdBook\">"));
+        assert!(page("intro.html").contains("<meta property=\"og:title\" content=\"Intro\">"));
+        assert!(page("intro.html").contains("<meta property=\"og:url\" content=\"https://example.com/docs/intro.html\">"));
+        assert!(page("setup.html").contains("<meta name=\"description\" content=\"Install mdBook &amp; run it.\">"));
+        assert!(page("code.html").contains("<meta property=\"og:description\" content=\"A book\">"));
+        assert!(!page("print.html").contains("og:url"));
+    }
+
+    #[test]
+    fn template_data_of_the_book() {
+        let (tmp, book) = book_with(&[("src/SUMMARY.md", "# Summary\n\n- [Intro](intro.md)\n"),
+                                      ("src/intro.md", "# Intro"),
+                                      ("theme/index.hbs", "<title>{{ title }}</title><p>{{ version }} {{ build.sha }}</p>")]);
+
+        let mut build = BTreeMap::new();
+        build.insert("sha".to_owned(), "abc123".to_json());
+        let mut book = book.set_theme_path(&tmp.path().join("theme"))
+                           .set_title("Book")
+                           .add_template_data("version", "1.2.0".to_json())
+                           .add_template_data("build", build.to_json())
+                           .add_template_data("title", "Replaced".to_json());
+        let files = book.render_to_map().unwrap();
+        assert_eq!(String::from_utf8_lossy(&files[Path::new("intro.html")]),
+                   "<title>Book</title><p>1.2.0 abc123</p>");
+    }
+
+    #[test]
+    fn favicon_and_logo_of_the_book() {
+        let (tmp, book) = book_with(&[("src/SUMMARY.md", "# Summary\n\n- [Intro](intro.md)\n"),
+                                      ("src/intro.md", "# Intro"),
+                                      ("assets/icon.png", "icon")]);
+        let mut book = book.set_favicon(Path::new("assets/icon.png")).set_logo(Path::new("assets/logo.svg"));
+
+        // The logo does not exist
+        let err = format!("{}", book.render_to_map().unwrap_err());
+        assert!(err.contains("logo.svg"), "{}", err);
+
+        File::create(tmp.path().join("assets/logo.svg")).unwrap().write_all(b"<svg/>").unwrap();
+        let files = book.render_to_map().unwrap();
+        let page = String::from_utf8_lossy(&files[Path::new("intro.html")]).into_owned();
+
+        assert_eq!(files[Path::new("assets/icon.png")], b"icon");
+        assert_eq!(files[Path::new("assets/logo.svg")], b"<svg/>");
+        assert!(!files.contains_key(Path::new("favicon.png")));
+        assert!(page.contains("<link rel=\"shortcut icon\" href=\"assets/icon.png\">"));
+        assert!(page.contains("<img src=\"assets/logo.svg\""));
+    }
+
+    #[test]
+    fn broken_links_fail_the_rendering() {
+        let (_tmp, mut book) =
+            book_with(&[("src/SUMMARY.md", "# Summary\n\n- [Intro](intro.md)\n- [Setup](guide/setup.md)\n"),
+                        ("src/intro.md",
+                         "# Intro\n\n[setup](guide/setup.md#install) [old](old.md) [top](#intro) [web](https://x.org/y.md)"),
+                        ("src/guide/setup.md",
+                         "# Setup\n\n## Install\n\n[back](../intro.md#nowhere) ![logo](images/logo.png)")]);
+        assert!(book.render_to_map().is_ok());
+
+        let mut book = book.set_check_links(true);
+        let err = format!("{}", book.render_to_map().unwrap_err());
+        assert_eq!(err,
+                   format!("Some links of the chapters are broken:\n\
+                            - intro.md: old.md, it is not a chapter of the book\n\
+                            - {}: ../intro.md#nowhere, the anchor does not exist\n\
+                            - {}: images/logo.png, the file does not exist",
+                           Path::new("guide/setup.md").display(),
+                           Path::new("guide/setup.md").display()));
+    }
+
+    #[test]
+    fn images_relative_to_nested_chapters() {
+        let (_tmp, book) =
+            book_with(&[("src/SUMMARY.md",
+                         "# Summary\n\n- [Intro](intro.md)\n- [Guide](guide/index.md)\n- [Deep](guide/deep/setup.md)\n"),
+                        ("src/intro.md", "# Intro\n\n![](shared/logo.png) ![](guide/deep/img/foo.png)"),
+                        ("src/guide/index.md", "# Guide\n\n![](../shared/logo.png) ![](deep/img/foo.png)"),
+                        ("src/guide/deep/setup.md", "# Deep\n\n![](../../shared/logo.png) ![](img/foo.png)"),
+                        ("src/shared/logo.png", ""),
+                        ("src/guide/deep/img/foo.png", "")]);
+        let mut book = book.set_check_links(true);
+        let files = book.render_to_map().unwrap();
+        assert!(files.contains_key(Path::new("shared/logo.png")));
+        assert!(files.contains_key(Path::new("guide/deep/img/foo.png")));
+
+        // The pages resolve the sources from the root of the book because of their `<base>`
+        for page in &["intro.html", "guide/index.html", "guide/deep/setup.html"] {
+            let html = String::from_utf8_lossy(&files[Path::new(page)]).into_owned();
+            assert!(html.contains("<img src=\"shared/logo.png\""), "{}", page);
+            assert!(html.contains("<img src=\"guide/deep/img/foo.png\""), "{}", page);
+        }
+        let print = String::from_utf8_lossy(&files[Path::new("print.html")]).into_owned();
+        assert_eq!(print.matches("<img src=\"shared/logo.png\"").count(), 3);
+    }
+
+    #[test]
+    fn redirects_of_moved_pages() {
+        let (_tmp, book) = book_with(&[("src/SUMMARY.md", "# Summary\n\n- [Setup](guide/setup.md)\n"),
+                                       ("src/guide/setup.md", "# Setup")]);
+
+        let mut redirects = BTreeMap::new();
+        redirects.insert("old/setup.html".to_owned(), "guide/setup.md#install".to_owned());
+        redirects.insert("/install/".to_owned(), "guide/setup.html".to_owned());
+        redirects.insert("rust.html".to_owned(), "https://www.rust-lang.org".to_owned());
+        let mut book = book.set_redirects(redirects.clone());
+        let files = book.render_to_map().unwrap();
+
+        let page = |path: &str| String::from_utf8_lossy(&files[Path::new(path)]).into_owned();
+        assert!(page("old/setup.html").contains("content=\"0; url=../guide/setup.html#install\""));
+        assert!(page("old/setup.html").contains("<link rel=\"canonical\" href=\"../guide/setup.html#install\">"));
+        assert!(page("install/index.html").contains("content=\"0; url=../guide/setup.html\""));
+        assert!(page("rust.html").contains("content=\"0; url=https://www.rust-lang.org\""));
+
+        let mut book = book.set_site_url("https://example.com/book");
+        let files = book.render_to_map().unwrap();
+        let page = String::from_utf8_lossy(&files[Path::new("old/setup.html")]).into_owned();
+        assert!(page.contains("content=\"0; url=https://example.com/book/guide/setup.html#install\""));
+        assert!(page.contains("href=\"https://example.com/book/guide/setup.html#install\""));
+
+        redirects.insert("guide/setup.html".to_owned(), "rust.html".to_owned());
+        let mut book = book.set_redirects(redirects);
+        assert!(book.render_to_map().is_err());
+    }
+
+    #[test]
+    fn redirects_do_not_overwrite_kept_pages_or_static_files() {
+        let (_tmp, mut book) = book_with(&[("src/SUMMARY.md", "# Summary\n\n- [Intro](intro.md)\n- [Setup](setup.md)\n"),
+                                           ("src/intro.md", "# Intro"),
+                                           ("src/setup.md", "# Setup"),
+                                           ("src/notes.txt", "notes")]);
+        book.build().unwrap();
+
+        // The page of the unchanged chapter is kept by the second build
+        let mut redirects = BTreeMap::new();
+        redirects.insert("setup.html".to_owned(), "intro.html".to_owned());
+        book.set_redirects_mut(redirects);
+        assert!(book.build().is_err());
+        assert!(fs::read_to_string(book.get_dest().join("setup.html")).unwrap().contains("Setup</h1>"));
+
+        book.set_redirects_mut(BTreeMap::new());
+        book.build().unwrap();
+        let mut redirects = BTreeMap::new();
+        redirects.insert("notes.txt".to_owned(), "intro.html".to_owned());
+        book.set_redirects_mut(redirects);
+        assert!(book.build().is_err());
+        assert_eq!(fs::read_to_string(book.get_dest().join("notes.txt")).unwrap(), "notes");
+    }
+
+    #[test]
+    fn sidebar_without_section_numbers() {
+        let (_tmp, mut book) = book_with(&[("src/SUMMARY.md", "# Summary\n\n- [Intro](intro.md)\n"),
+                                           ("src/intro.md", "# Intro")]);
+        let files = book.render_to_map().unwrap();
+        assert!(String::from_utf8_lossy(&files[Path::new("intro.html")]).contains("<strong>1.</strong> Intro"));
+        assert!(String::from_utf8_lossy(&files[Path::new("searchindex.json")]).contains("\"section\":\"1.\""));
+
+        let mut book = book.set_number_sections(false);
+        let files = book.render_to_map().unwrap();
+        assert!(String::from_utf8_lossy(&files[Path::new("intro.html")]).contains("class=\"active\">Intro</a>"));
+        assert!(!String::from_utf8_lossy(&files[Path::new("searchindex.json")]).contains("\"section\""));
+    }
+
+    #[test]
+    fn partials_and_helpers_of_the_theme() {
+        let (tmp, book) = book_with(&[("src/SUMMARY.md", "# Summary\n\n- [Intro](intro.md)\n- [Next](next.md)\n"),
+                                      ("src/intro.md", "# Intro\n## A\n## B"),
+                                      ("src/next.md", "# Next"),
+                                      ("theme/index.hbs",
+                                       "{{> header}}{{#ifeq path \"intro.md\"}}first{{else}}other{{/ifeq}}\
+                                        {{#if page_toc}}{{#each page_toc}}{{> page_toc_item}}{{/each}}{{/if}}"),
+                                      ("theme/header.hbs", "<header>{{ chapter_title }}</header>"),
+                                      ("theme/page_toc_item.hbs", "[{{ title }}]")]);
+        let mut book = book.set_theme_path(&tmp.path().join("theme"));
+        let files = book.render_to_map().unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&files[Path::new("intro.html")]), "<header>Intro</header>first[A][B]");
+        assert_eq!(String::from_utf8_lossy(&files[Path::new("next.html")]), "<header>Next</header>other");
+    }
+}
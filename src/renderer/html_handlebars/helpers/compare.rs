@@ -0,0 +1,34 @@
+use handlebars::{Handlebars, RenderError, RenderContext, Helper, Context, Renderable};
+use rustc_serialize::json::Json;
+
+// Handlebars helper to compare two values
+//
+// `{{#ifeq path "intro.md"}}...{{else}}...{{/ifeq}}` renders its template when both values are
+// equal and its `{{else}}` part otherwise. Literals (`"a string"`, `2`, `true`) are used as they
+// are, the other parameters are looked up in the context like the one of `{{#if}}`.
+
+pub fn ifeq(c: &Context, h: &Helper, r: &Handlebars, rc: &mut RenderContext) -> Result<(), RenderError> {
+    debug!("[fn]: ifeq (handlebars helper)");
+    let equal = match (h.param(0), h.param(1)) {
+        (Some(left), Some(right)) => value(left, c, rc) == value(right, c, rc),
+        _ => return Err(RenderError { desc: "The ifeq helper needs two parameters".to_owned() }),
+    };
+
+    let template = if equal { h.template() } else { h.inverse() };
+    match template {
+        Some(t) => t.render(c, r, rc),
+        None => Ok(()),
+    }
+}
+
+fn value(param: &String, c: &Context, rc: &RenderContext) -> Json {
+    if let Ok(literal) = Json::from_str(param) {
+        return literal;
+    }
+
+    if param.starts_with('@') {
+        rc.get_local_var(param).clone()
+    } else {
+        c.navigate(rc.get_path(), param).clone()
+    }
+}
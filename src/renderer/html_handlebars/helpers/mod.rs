@@ -1,3 +1,4 @@
 pub mod navigation;
 pub mod toc;
 pub mod playpen;
+pub mod compare;
@@ -1,205 +1,41 @@
-use std::path::Path;
-use std::collections::BTreeMap;
-
-use rustc_serialize::json::{self, ToJson};
 use handlebars::{Handlebars, RenderError, RenderContext, Helper, Context, Renderable};
 
-// Handlebars helper for navigation
+// Handlebars helpers for navigation
+//
+// The neighbours of the current chapter are computed by the renderer and stored in the `previous`
+// and `next` keys of the context. These block helpers render their template with the `title` and
+// `link` of the neighbour, or render nothing if there is none.
 
-pub fn previous(c: &Context, _h: &Helper, r: &Handlebars, rc: &mut RenderContext) -> Result<(), RenderError> {
+pub fn previous(c: &Context, h: &Helper, r: &Handlebars, rc: &mut RenderContext) -> Result<(), RenderError> {
     debug!("[fn]: previous (handlebars helper)");
-
-    debug!("[*]: Get data from context");
-    // get value from context data
-    // rc.get_path() is current json parent path, you should always use it like this
-    // param is the key of value you want to display
-    let chapters = c.navigate(rc.get_path(), "chapters");
-
-    let current = c.navigate(rc.get_path(), "path")
-                   .to_string()
-                   .replace("\"", "");
-
-
-    debug!("[*]: Decode chapters from JSON");
-    // Decode json format
-    let decoded: Vec<BTreeMap<String, String>> = match json::decode(&chapters.to_string()) {
-        Ok(data) => data,
-        Err(_) => return Err(RenderError { desc: "Could not decode the JSON data".to_owned() }),
-    };
-    let mut previous: Option<BTreeMap<String, String>> = None;
-
-
-    debug!("[*]: Search for current Chapter");
-    // Search for current chapter and return previous entry
-    for item in decoded {
-
-        match item.get("path") {
-            Some(path) if !path.is_empty() => {
-                if path == &current {
-
-                    debug!("[*]: Found current chapter");
-                    if let Some(previous) = previous {
-
-                        debug!("[*]: Creating BTreeMap to inject in context");
-                        // Create new BTreeMap to extend the context: 'title' and 'link'
-                        let mut previous_chapter = BTreeMap::new();
-
-                        // Chapter title
-                        match previous.get("name") {
-                            Some(n) => {
-                                debug!("[*]: Inserting title: {}", n);
-                                previous_chapter.insert("title".to_owned(), n.to_json())
-                            },
-                            None => {
-                                debug!("[*]: No title found for chapter");
-                                return Err(RenderError { desc: "No title found for chapter in JSON data".to_owned() });
-                            },
-                        };
-
-                        // Chapter link
-
-                        match previous.get("path") {
-                            Some(p) => {
-                                // Hack for windows who tends to use `\` as separator instead of `/`
-                                let path = Path::new(p).with_extension("html");
-                                debug!("[*]: Inserting link: {:?}", path);
-
-                                match path.to_str() {
-                                    Some(p) => {
-                                        previous_chapter.insert("link".to_owned(), p.replace("\\", "/").to_json());
-                                    },
-                                    None => {
-                                        return Err(RenderError {
-                                            desc: "Link could not be converted to str".to_owned(),
-                                        })
-                                    },
-                                }
-                            },
-                            None => {
-                                return Err(RenderError { desc: "No path found for chapter in JSON data".to_owned() })
-                            },
-                        }
-
-                        debug!("[*]: Inject in context");
-                        // Inject in current context
-                        let updated_context = c.extend(&previous_chapter);
-
-                        debug!("[*]: Render template");
-                        // Render template
-                        match _h.template() {
-                            Some(t) => {
-                                try!(t.render(&updated_context, r, rc));
-                            },
-                            None => return Err(RenderError { desc: "Error with the handlebars template".to_owned() }),
-                        }
-
-                    }
-
-                    break;
-                } else {
-                    previous = Some(item.clone());
-                }
-            },
-            _ => continue,
-
-        }
-
-    }
-
-    Ok(())
+    render_neighbour("previous", c, h, r, rc)
 }
 
-
-
-
-pub fn next(c: &Context, _h: &Helper, r: &Handlebars, rc: &mut RenderContext) -> Result<(), RenderError> {
+pub fn next(c: &Context, h: &Helper, r: &Handlebars, rc: &mut RenderContext) -> Result<(), RenderError> {
     debug!("[fn]: next (handlebars helper)");
+    render_neighbour("next", c, h, r, rc)
+}
 
+fn render_neighbour(key: &str, c: &Context, h: &Helper, r: &Handlebars, rc: &mut RenderContext)
+                    -> Result<(), RenderError> {
     debug!("[*]: Get data from context");
-    // get value from context data
-    // rc.get_path() is current json parent path, you should always use it like this
-    // param is the key of value you want to display
-    let chapters = c.navigate(rc.get_path(), "chapters");
-
-    let current = c.navigate(rc.get_path(), "path")
-                   .to_string()
-                   .replace("\"", "");
-
-    debug!("[*]: Decode chapters from JSON");
-    // Decode json format
-    let decoded: Vec<BTreeMap<String, String>> = match json::decode(&chapters.to_string()) {
-        Ok(data) => data,
-        Err(_) => return Err(RenderError { desc: "Could not decode the JSON data".to_owned() }),
+    let neighbour = c.navigate(rc.get_path(), key);
+
+    let chapter = match neighbour.as_object() {
+        Some(chapter) => chapter,
+        None => {
+            debug!("[*]: No {} chapter", key);
+            return Ok(());
+        },
     };
-    let mut previous: Option<BTreeMap<String, String>> = None;
-
-    debug!("[*]: Search for current Chapter");
-    // Search for current chapter and return previous entry
-    for item in decoded {
-
-        match item.get("path") {
-
-            Some(path) if !path.is_empty() => {
-
-                if let Some(previous) = previous {
-
-                    let previous_path = match previous.get("path") {
-                        Some(p) => p,
-                        None => return Err(RenderError { desc: "No path found for chapter in JSON data".to_owned() }),
-                    };
-
-                    if previous_path == &current {
-
-                        debug!("[*]: Found current chapter");
-                        debug!("[*]: Creating BTreeMap to inject in context");
-                        // Create new BTreeMap to extend the context: 'title' and 'link'
-                        let mut next_chapter = BTreeMap::new();
-
-                        match item.get("name") {
-                            Some(n) => {
-                                debug!("[*]: Inserting title: {}", n);
-                                next_chapter.insert("title".to_owned(), n.to_json());
-                            },
-                            None => {
-                                return Err(RenderError { desc: "No title found for chapter in JSON data".to_owned() })
-                            },
-                        }
-
-
-                        let link = Path::new(path).with_extension("html");
-                        debug!("[*]: Inserting link: {:?}", link);
-
-                        match link.to_str() {
-                            Some(l) => {
-                                // Hack for windows who tends to use `\` as separator instead of `/`
-                                next_chapter.insert("link".to_owned(), l.replace("\\", "/").to_json());
-                            },
-                            None => return Err(RenderError { desc: "Link could not converted to str".to_owned() }),
-                        }
-
-                        debug!("[*]: Inject in context");
-                        // Inject in current context
-                        let updated_context = c.extend(&next_chapter);
-
-                        debug!("[*]: Render template");
-
-                        // Render template
-                        match _h.template() {
-                            Some(t) => {
-                                try!(t.render(&updated_context, r, rc));
-                            },
-                            None => return Err(RenderError { desc: "Error with the handlebars template".to_owned() }),
-                        }
-
-                        break;
-                    }
-                }
 
-                previous = Some(item.clone());
-            },
+    debug!("[*]: Inject in context");
+    // Inject 'title' and 'link' in current context
+    let updated_context = c.extend(chapter);
 
-            _ => continue,
-        }
+    debug!("[*]: Render template");
+    match h.template() {
+        Some(t) => t.render(&updated_context, r, rc),
+        None => Err(RenderError { desc: "Error with the handlebars template".to_owned() }),
     }
-    Ok(())
 }
@@ -2,6 +2,8 @@ use std::path::{Path, PathBuf};
 use std::fs::File;
 use std::io::Read;
 
+use utils;
+
 
 pub fn render_playpen(s: &str, path: &Path) -> String {
     // When replacing one thing in a string by something with a different length, the indices
@@ -20,7 +22,7 @@ pub fn render_playpen(s: &str, path: &Path) -> String {
 
         // Check if the file exists
         if !playpen.rust_file.exists() || !playpen.rust_file.is_file() {
-            output!("[-] No file exists for {{{{#playpen }}}}\n    {}", playpen.rust_file.to_str().unwrap());
+            warn!("No file exists for {{{{#playpen }}}}\n    {}", playpen.rust_file.to_str().unwrap());
             continue;
         }
 
@@ -35,8 +37,17 @@ pub fn render_playpen(s: &str, path: &Path) -> String {
             continue;
         };
 
-        let replacement = String::new() + "<pre class=\"playpen\"><code class=\"language-rust\">" + &file_content +
-                          "</code></pre>";
+        // The flags are passed to book.js through data attributes
+        let mut attributes = String::new();
+        if playpen.editable {
+            attributes.push_str(" data-editable=\"true\"");
+        }
+        if playpen.no_run {
+            attributes.push_str(" data-no-run=\"true\"");
+        }
+
+        let replacement = String::new() + "<pre class=\"playpen\"" + &attributes + "><code class=\"language-rust\">" +
+                          &utils::html::escape_html(&file_content) + "</code></pre>";
 
         replaced.push_str(&s[previous_end_index..playpen.start_index]);
         replaced.push_str(&replacement);
@@ -56,6 +67,7 @@ struct Playpen {
     end_index: usize,
     rust_file: PathBuf,
     editable: bool,
+    no_run: bool,
     escaped: bool,
 }
 
@@ -94,21 +106,17 @@ fn find_playpens(s: &str, base_path: &Path) -> Vec<Playpen> {
 
         // Split on whitespaces
         let params: Vec<&str> = s[i + 10..end_i - 2].split_whitespace().collect();
-        let mut editable = false;
-
-        if params.len() > 1 {
-            editable = if let Some(_) = params[1].find("editable") {
-                true
-            } else {
-                false
-            };
-        }
+
+        // Flags following the file name
+        let editable = params[1..].contains(&"editable");
+        let no_run = params[1..].contains(&"no_run");
 
         playpens.push(Playpen {
             start_index: i,
             end_index: end_i,
             rust_file: base_path.join(PathBuf::from(params[0])),
             editable: editable,
+            no_run: no_run,
             escaped: escaped,
         })
     }
@@ -153,6 +161,7 @@ fn test_find_playpens_simple_playpen() {
                      end_index: 42,
                      rust_file: PathBuf::from("file.rs"),
                      editable: false,
+                     no_run: false,
                      escaped: false,
                  },
                  Playpen {
@@ -160,6 +169,7 @@ fn test_find_playpens_simple_playpen() {
                      end_index: 68,
                      rust_file: PathBuf::from("test.rs"),
                      editable: false,
+                     no_run: false,
                      escaped: false,
                  }]);
 }
@@ -176,6 +186,7 @@ fn test_find_playpens_complex_playpen() {
                      end_index: 51,
                      rust_file: PathBuf::from("dir/file.rs"),
                      editable: true,
+                     no_run: false,
                      escaped: false,
                  },
                  Playpen {
@@ -183,6 +194,7 @@ fn test_find_playpens_complex_playpen() {
                      end_index: 86,
                      rust_file: PathBuf::from("dir/test.rs"),
                      editable: true,
+                     no_run: false,
                      escaped: false,
                  }]);
 }
@@ -195,6 +207,21 @@ fn test_find_playpens_escaped_playpen() {
 
     assert!(find_playpens(s, Path::new("")) ==
             vec![
-        Playpen{start_index: 39, end_index: 68, rust_file: PathBuf::from("file.rs"), editable: true, escaped: true},
+        Playpen{start_index: 39, end_index: 68, rust_file: PathBuf::from("file.rs"), editable: true, no_run: false, escaped: true},
     ]);
 }
+
+#[test]
+fn test_find_playpens_no_run_playpen() {
+    let s = "Some random text with {{#playpen file.rs no_run editable}}...";
+
+    assert!(find_playpens(s, Path::new("")) ==
+            vec![Playpen {
+                     start_index: 22,
+                     end_index: 58,
+                     rust_file: PathBuf::from("file.rs"),
+                     editable: true,
+                     no_run: true,
+                     escaped: false,
+                 }]);
+}
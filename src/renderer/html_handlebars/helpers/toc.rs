@@ -17,12 +17,23 @@ impl HelperDef for RenderToc {
         // param is the key of value you want to display
         let chapters = c.navigate(rc.get_path(), "chapters");
         let current = c.navigate(rc.get_path(), "path").to_string().replace("\"", "");
+        // The numbers are still needed for the nesting of the sections
+        let number_sections = c.navigate(rc.get_path(), "number_sections").as_boolean().unwrap_or(true);
+        // Sections deeper than this are folded, except the ones of the current chapter
+        let fold_level = c.navigate(rc.get_path(), "fold_level").as_u64().unwrap_or(0) as usize;
         try!(rc.writer.write("<ul class=\"chapter\">".as_bytes()));
 
         // Decode json format
         let decoded: Vec<BTreeMap<String, String>> = json::decode(&chapters.to_string()).unwrap();
+        let current_section = decoded.iter()
+                                     .find(|item| item.get("path") == Some(&current))
+                                     .and_then(|item| item.get("section"))
+                                     .cloned()
+                                     .unwrap_or_default();
 
         let mut current_level = 1;
+        // Number of the last chapter, the sections are identified by the number of their parent
+        let mut parent_section = String::new();
 
         for item in decoded {
 
@@ -32,15 +43,36 @@ impl HelperDef for RenderToc {
                 continue;
             }
 
+            // Part title, closes the open sections so that it sits on the root level
+            if let Some(title) = item.get("part") {
+                while current_level > 1 {
+                    try!(rc.writer.write("</ul>".as_bytes()));
+                    try!(rc.writer.write("</li>".as_bytes()));
+                    current_level = current_level - 1;
+                }
+                try!(rc.writer.write("<li class=\"part-title\">".as_bytes()));
+                try!(rc.writer.write(render_name(title).as_bytes()));
+                try!(rc.writer.write("</li>".as_bytes()));
+                continue;
+            }
+
             let level = if let Some(s) = item.get("section") {
-                s.len() / 2
+                s.matches('.').count()
             } else {
                 1
             };
 
             if level > current_level {
+                let folded = fold_level > 0 && level > fold_level && !current_section.starts_with(&parent_section);
                 try!(rc.writer.write("<li>".as_bytes()));
-                try!(rc.writer.write("<ul class=\"section\">".as_bytes()));
+                try!(rc.writer.write(if folded {
+                                         "<ul class=\"section collapsed\" data-section=\""
+                                     } else {
+                                         "<ul class=\"section\" data-section=\""
+                                     }
+                                     .as_bytes()));
+                try!(rc.writer.write(parent_section.as_bytes()));
+                try!(rc.writer.write("\">".as_bytes()));
                 try!(rc.writer.write("<li>".as_bytes()));
             } else if level < current_level {
                 while level < current_level {
@@ -62,15 +94,20 @@ impl HelperDef for RenderToc {
                 if !path.is_empty() {
                     try!(rc.writer.write("<a href=\"".as_bytes()));
 
-                    // Add link
-                    try!(rc.writer.write(Path::new(item.get("path")
-                                                       .expect("Error: path should be Some(_)"))
-                                             .with_extension("html")
-                                             .to_str()
-                                             .unwrap()
-                                             // Hack for windows who tends to use `\` as separator instead of `/`
-                                             .replace("\\", "/")
-                                             .as_bytes()));
+                    // Add link, web pages are linked as they are
+                    if let Some(link) = item.get("link") {
+                        try!(rc.writer.write(link.as_bytes()));
+                    } else if path.starts_with("http://") || path.starts_with("https://") {
+                        try!(rc.writer.write(path.as_bytes()));
+                    } else {
+                        try!(rc.writer.write(Path::new(path)
+                                                 .with_extension("html")
+                                                 .to_str()
+                                                 .unwrap()
+                                                 // Hack for windows who tends to use `\` as separator instead of `/`
+                                                 .replace("\\", "/")
+                                                 .as_bytes()));
+                    }
 
                     try!(rc.writer.write("\"".as_bytes()));
 
@@ -89,31 +126,17 @@ impl HelperDef for RenderToc {
 
             // Section does not necessarily exist
             if let Some(section) = item.get("section") {
-                try!(rc.writer.write("<strong>".as_bytes()));
-                try!(rc.writer.write(section.as_bytes()));
-                try!(rc.writer.write("</strong> ".as_bytes()));
+                if number_sections {
+                    try!(rc.writer.write("<strong>".as_bytes()));
+                    try!(rc.writer.write(section.as_bytes()));
+                    try!(rc.writer.write("</strong> ".as_bytes()));
+                }
+                parent_section = section.clone();
             }
 
             if let Some(name) = item.get("name") {
-                // Render only inline code blocks
-
-                // filter all events that are not inline code blocks
-                let parser = Parser::new(&name).filter(|event| {
-                    match event {
-                        &Event::Start(Tag::Code) |
-                        &Event::End(Tag::Code) => true,
-                        &Event::InlineHtml(_) => true,
-                        &Event::Text(_) => true,
-                        _ => false,
-                    }
-                });
-
-                // render markdown to html
-                let mut markdown_parsed_name = String::with_capacity(name.len() * 3 / 2);
-                html::push_html(&mut markdown_parsed_name, parser);
-
                 // write to the handlebars template
-                try!(rc.writer.write(markdown_parsed_name.as_bytes()));
+                try!(rc.writer.write(render_name(name).as_bytes()));
             }
 
             if path_exists {
@@ -129,3 +152,22 @@ impl HelperDef for RenderToc {
         Ok(())
     }
 }
+
+// Renders the markdown of a name, only inline code blocks are kept
+fn render_name(name: &str) -> String {
+    // filter all events that are not inline code blocks
+    let parser = Parser::new(name).filter(|event| {
+        match event {
+            &Event::Start(Tag::Code) |
+            &Event::End(Tag::Code) => true,
+            &Event::InlineHtml(_) => true,
+            &Event::Text(_) => true,
+            _ => false,
+        }
+    });
+
+    // render markdown to html
+    let mut markdown_parsed_name = String::with_capacity(name.len() * 3 / 2);
+    html::push_html(&mut markdown_parsed_name, parser);
+    markdown_parsed_name
+}
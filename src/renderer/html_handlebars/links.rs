@@ -0,0 +1,313 @@
+//! Rewriting of the links of the rendered chapters
+//!
+//! The links of the markdown are relative to the chapter, the pages of the html output resolve them
+//! from the root of the book because of their `<base>`. A link to the markdown file of another
+//! chapter, `[see](../other.md#anchor)`, is turned into a link to its page so that the same
+//! markdown works on the rendered book and when browsing the sources, for example on GitHub. For
+//! the same reason the relative sources of the images, `![logo](../images/logo.png)`, are resolved
+//! from the chapter.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+
+/// Rewrites the links to markdown files in the html of the chapter at `path` (relative to the
+/// source directory) into links to their page. `page_link` returns the link to the page of a
+/// markdown file, relative to the root of the book. Links with a scheme, absolute links, links to
+/// other files and links that go above the root are left as they are.
+
+pub fn markdown_links_to_pages<F>(html: &str, path: &Path, page_link: F) -> String
+    where F: Fn(&Path) -> String
+{
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    rewrite_attributes(html, |name, value| {
+        if name != "href" || !is_relative(value) {
+            return value.to_owned();
+        }
+
+        let (target, fragment) = match value.find('#') {
+            Some(i) => (&value[..i], &value[i..]),
+            None => (value, ""),
+        };
+
+        if !target.ends_with(".md") {
+            return value.to_owned();
+        }
+
+        match resolve(directory, target) {
+            Some(target) => format!("{}{}", page_link(&target), fragment),
+            None => value.to_owned(),
+        }
+    })
+}
+
+/// Resolves the relative `src` attributes (images, videos, ...) of the html of the chapter at `path`
+/// from its directory, so that the page finds them from the root of the book. Sources that go above
+/// the root are left as they are.
+
+pub fn sources_from_root(html: &str, path: &Path) -> String {
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    rewrite_attributes(html, |name, value| {
+        if name != "src" || !is_relative(value) {
+            return value.to_owned();
+        }
+        match resolve(directory, value) {
+            Some(source) => to_url(&source),
+            None => value.to_owned(),
+        }
+    })
+}
+
+/// Values of the `id` attributes of `html`, the anchors a link to the page can point to
+
+pub fn ids(html: &str) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    rewrite_attributes(html, |name, value| {
+        if name == "id" {
+            ids.insert(value.to_owned());
+        }
+        value.to_owned()
+    });
+    ids
+}
+
+/// Relative links of `html`, the values of its `href` and `src` attributes with the name of the
+/// attribute
+
+pub fn relative_links(html: &str) -> Vec<(String, String)> {
+    let mut links = vec![];
+    rewrite_attributes(html, |name, value| {
+        if (name == "href" || name == "src") && is_relative(value) {
+            links.push((name.to_owned(), value.to_owned()));
+        }
+        value.to_owned()
+    });
+    links
+}
+
+/// Checks a relative link of the chapter at `path`, the value of its `attribute`, before the links
+/// are rewritten. `anchors` contains the ids of every chapter, keyed by their path relative to the
+/// source directory. Returns why the link is broken, if it is.
+///
+/// Links to markdown files are relative to the chapter and must point to a chapter of the book, like
+/// the sources of the images. The other links are resolved from the root of the book, like the
+/// browser does because of the `<base>` of the pages. They must point to a file of the source
+/// directory or to the page of a chapter. The anchors of links to chapters must be one of their ids.
+
+pub fn check_link(attribute: &str,
+                  link: &str,
+                  path: &Path,
+                  anchors: &HashMap<PathBuf, HashSet<String>>,
+                  src: &Path)
+                  -> Option<&'static str> {
+    let (target, fragment) = match link.find('#') {
+        Some(i) => (&link[..i], Some(&link[i + 1..])),
+        None => (link, None),
+    };
+    let target = target.split('?').next().unwrap_or("");
+
+    let above = "it goes above the root of the book";
+    let chapter = if target.is_empty() {
+        // An anchor of the chapter itself
+        match resolve(Path::new(""), &path.to_string_lossy()) {
+            Some(chapter) => chapter,
+            None => return Some(above),
+        }
+    } else if target.ends_with(".md") {
+        let directory = path.parent().unwrap_or(Path::new(""));
+        match resolve(directory, target) {
+            Some(ref target) if !anchors.contains_key(target) => return Some("it is not a chapter of the book"),
+            Some(target) => target,
+            None => return Some(above),
+        }
+    } else {
+        let directory = if attribute == "src" {
+            path.parent().unwrap_or(Path::new(""))
+        } else {
+            Path::new("")
+        };
+        let target = match resolve(directory, target) {
+            Some(target) => target,
+            None => return Some(above),
+        };
+        let page = target.with_extension("md");
+        if target.extension().map_or(false, |ext| ext == "html") && anchors.contains_key(&page) {
+            page
+        } else if src.join(&target).exists() {
+            return None;
+        } else {
+            return Some("the file does not exist");
+        }
+    };
+
+    match fragment {
+        Some(fragment) if !fragment.is_empty() && !anchors[&chapter].contains(fragment) => {
+            Some("the anchor does not exist")
+        },
+        _ => None,
+    }
+}
+
+/// Calls `f` with the name and the value of every attribute of `html` and replaces the value with
+/// what it returns
+
+pub fn rewrite_attributes<F>(html: &str, mut f: F) -> String
+    where F: FnMut(&str, &str) -> String
+{
+    let mut rendered = String::with_capacity(html.len());
+
+    let mut rest = html;
+    while let Some(start) = rest.find("=\"") {
+        let value_start = start + 2;
+        let value_end = match rest[value_start..].find('"') {
+            Some(end) => value_start + end,
+            None => break,
+        };
+
+        let name = attribute_name(&rest[..start]);
+        let value = f(name, &rest[value_start..value_end]);
+
+        rendered.push_str(&rest[..value_start]);
+        rendered.push_str(&value);
+        rest = &rest[value_end..];
+    }
+    rendered.push_str(rest);
+
+    rendered
+}
+
+/// Links with a scheme (`https:`, `mailto:`) and absolute links are not relative
+
+pub fn is_relative(link: &str) -> bool {
+    let scheme = link.find(':').map_or(false, |colon| !link[..colon].contains('/'));
+    !link.is_empty() && !scheme && !link.starts_with('/')
+}
+
+/// Path of `link`, relative to `directory`, from the root of the book. `None` if it goes above
+/// the root.
+
+pub fn resolve(directory: &Path, link: &str) -> Option<PathBuf> {
+    let mut resolved = PathBuf::new();
+    for component in directory.join(link).components() {
+        match component {
+            Component::Normal(name) => resolved.push(name),
+            Component::ParentDir => {
+                if !resolved.pop() {
+                    return None;
+                }
+            },
+            _ => {},
+        }
+    }
+    Some(resolved)
+}
+
+/// Hack for windows who tends to use `\` as separator instead of `/`
+
+pub fn to_url(path: &Path) -> String {
+    path.to_string_lossy().replace("\\", "/")
+}
+
+
+// Name of the attribute at the end of `html`, empty if it is not preceded by whitespace
+fn attribute_name(html: &str) -> &str {
+    let start = html.rfind(|c: char| !c.is_alphanumeric() && c != '-').map_or(0, |i| i + 1);
+    if start == 0 || !html[..start].ends_with(char::is_whitespace) {
+        return "";
+    }
+    &html[start..]
+}
+
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use super::*;
+
+    use std::collections::{HashMap, HashSet};
+    use std::fs::{self, File};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn check_links_to_chapters_and_anchors() {
+        let mut anchors = HashMap::new();
+        anchors.insert(PathBuf::from("intro.md"), vec!["intro".to_owned()].into_iter().collect::<HashSet<_>>());
+        anchors.insert(PathBuf::from("format/config.md"),
+                       vec!["config".to_owned(), "options".to_owned()].into_iter().collect());
+        let path = Path::new("format/config.md");
+        let src = Path::new("/does/not/exist");
+
+        let check = |link| check_link("href", link, path, &anchors, src);
+        assert_eq!(check("../intro.md"), None);
+        assert_eq!(check("../intro.md#intro"), None);
+        assert_eq!(check("#options"), None);
+        assert_eq!(check("config.md#options"), None);
+        assert_eq!(check("intro.html#intro"), None);
+        assert_eq!(check("missing.md"), Some("it is not a chapter of the book"));
+        assert_eq!(check("../intro.md#nowhere"), Some("the anchor does not exist"));
+        assert_eq!(check("#nowhere"), Some("the anchor does not exist"));
+        assert_eq!(check("../../up.md"), Some("it goes above the root of the book"));
+        assert_eq!(check("images/logo.png"), Some("the file does not exist"));
+    }
+
+    #[test]
+    fn check_sources_from_the_chapter() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let src = tmp.path();
+        fs::create_dir_all(src.join("guide/img")).unwrap();
+        fs::create_dir_all(src.join("shared")).unwrap();
+        File::create(src.join("guide/img/foo.png")).unwrap();
+        File::create(src.join("shared/logo.png")).unwrap();
+        let anchors = HashMap::new();
+
+        let check = |link, path| check_link("src", link, Path::new(path), &anchors, src);
+        assert_eq!(check("shared/logo.png", "intro.md"), None);
+        assert_eq!(check("img/foo.png", "guide/intro.md"), None);
+        assert_eq!(check("../shared/logo.png", "guide/intro.md"), None);
+        assert_eq!(check("../../shared/logo.png", "guide/deep/setup.md"), None);
+        assert_eq!(check("../img/foo.png", "guide/deep/setup.md"), None);
+        assert_eq!(check("shared/logo.png", "guide/intro.md"), Some("the file does not exist"));
+        assert_eq!(check("../../logo.png", "guide/intro.md"), Some("it goes above the root of the book"));
+    }
+
+    #[test]
+    fn ids_and_relative_links() {
+        let html = "<h2 id=\"a\">A</h2><a href=\"b.md\">b</a><img src=\"c.png\"><a href=\"https://d\">d</a>";
+        assert_eq!(ids(html), vec!["a".to_owned()].into_iter().collect());
+        assert_eq!(relative_links(html),
+                   vec![("href".to_owned(), "b.md".to_owned()), ("src".to_owned(), "c.png".to_owned())]);
+    }
+
+    #[test]
+    fn sources_are_resolved_from_the_chapter() {
+        let html = "<p><img src=\"img/a.png\"> <img src=\"../shared/b.png\"> <img src=\"https://x.org/c.png\"> \
+                    <img src=\"/d.png\"> <a href=\"img/e.png\">e</a></p>\n";
+        let sources = |path| sources_from_root(html, Path::new(path));
+
+        assert_eq!(sources("intro.md"), html);
+        assert_eq!(sources("guide/intro.md"),
+                   "<p><img src=\"guide/img/a.png\"> <img src=\"shared/b.png\"> <img src=\"https://x.org/c.png\"> \
+                    <img src=\"/d.png\"> <a href=\"img/e.png\">e</a></p>\n");
+        assert_eq!(sources("guide/deep/setup.md"),
+                   "<p><img src=\"guide/deep/img/a.png\"> <img src=\"guide/shared/b.png\"> \
+                    <img src=\"https://x.org/c.png\"> <img src=\"/d.png\"> <a href=\"img/e.png\">e</a></p>\n");
+        assert_eq!(sources_from_root("<img src=\"../../up.png\">", Path::new("guide/intro.md")),
+                   "<img src=\"../../up.png\">");
+    }
+
+    #[test]
+    fn markdown_links_point_to_pages() {
+        let html = "<p><a href=\"config.md\">a</a> <a href=\"../intro.md#b\">b</a> <a href=\"./notes.txt\">c</a> \
+                    <a href=\"https://example.com/x.md\">d</a> <a href=\"../../up.md\">e</a> \
+                    <a href=\"/abs.md\">f</a> <a href=\"#g\">g</a></p>\n";
+
+        let page_link = |path: &Path| to_url(&path.with_extension("html"));
+        assert_eq!(markdown_links_to_pages(html, Path::new("format/summary.md"), page_link),
+                   "<p><a href=\"format/config.html\">a</a> <a href=\"intro.html#b\">b</a> \
+                    <a href=\"./notes.txt\">c</a> <a href=\"https://example.com/x.md\">d</a> \
+                    <a href=\"../../up.md\">e</a> <a href=\"/abs.md\">f</a> <a href=\"#g\">g</a></p>\n");
+    }
+}
@@ -0,0 +1,352 @@
+//! Minification of the html, css and js files of the output
+//!
+//! The minifiers are conservative, they only remove what can not change how a file is read:
+//!
+//! - html: the runs of whitespace between and inside of the elements become a single space, or a
+//!   line break when they contain one, and the comments are removed. The content of `<pre>`,
+//!   `<code>` and `<textarea>` and the values of the attributes are kept as they are, the content
+//!   of `<script>` and `<style>` is minified like the js and css files.
+//! - css: the comments and the whitespace around `{`, `}`, `;` and `,` are removed, the other runs
+//!   of whitespace become a single space. The strings are kept as they are.
+//! - js: the indentation, the empty lines and the lines that are only a `//` comment are removed,
+//!   except in the template literals.
+
+// Elements whose content is kept as it is
+const PRESERVED_ELEMENTS: &'static [&'static str] = &["pre", "code", "textarea", "script", "style"];
+
+// Preserved elements whose content is text, not html
+const RAW_TEXT_ELEMENTS: &'static [&'static str] = &["textarea", "script", "style"];
+
+
+/// Returns the minified version of a file of the output, according to its extension, or `None`
+/// when it is not an html, css or js file
+
+pub fn minify(extension: &str, content: &str) -> Option<String> {
+    match extension {
+        "html" => Some(minify_html(content)),
+        "css" => Some(minify_css(content)),
+        "js" => Some(minify_js(content)),
+        _ => None,
+    }
+}
+
+/// Minifies an html page
+
+pub fn minify_html(html: &str) -> String {
+    let mut minified = String::with_capacity(html.len());
+    // Elements with a preserved content that are open, by name
+    let mut preserved: Vec<String> = vec![];
+
+    let mut rest = html;
+    while !rest.is_empty() {
+        // The text of the scripts, the stylesheets and the text areas has no tags, it goes on until
+        // the closing tag
+        let raw_text = preserved.last().map_or(false, |name| RAW_TEXT_ELEMENTS.contains(&name.as_str()));
+        if raw_text && !rest.starts_with("</") {
+            let name = preserved[preserved.len() - 1].clone();
+            let end = rest.to_ascii_lowercase().find(&format!("</{}", name)).unwrap_or(rest.len());
+            match name.as_str() {
+                "script" => minified.push_str(&minify_js(&rest[..end])),
+                "style" => minified.push_str(&minify_css(&rest[..end])),
+                _ => minified.push_str(&rest[..end]),
+            }
+            rest = &rest[end..];
+            continue;
+        }
+
+        if rest.starts_with("<!--") && preserved.is_empty() {
+            let end = rest.find("-->").map_or(rest.len(), |i| i + 3);
+            // The conditional comments of old browsers are not only comments
+            if rest.starts_with("<!--[if") {
+                minified.push_str(&rest[..end]);
+            }
+            rest = &rest[end..];
+            continue;
+        }
+
+        if rest.starts_with('<') {
+            let end = tag_end(rest);
+            let tag = &rest[..end];
+            rest = &rest[end..];
+
+            let name = tag_name(tag);
+            if PRESERVED_ELEMENTS.contains(&name.as_str()) {
+                if tag.starts_with("</") {
+                    if let Some(i) = preserved.iter().rposition(|open| *open == name) {
+                        preserved.truncate(i);
+                    }
+                } else if !tag.ends_with("/>") {
+                    preserved.push(name);
+                }
+            }
+
+            push_tag(&mut minified, tag);
+            continue;
+        }
+
+        let end = rest.find('<').unwrap_or(rest.len());
+        let text = &rest[..end];
+        rest = &rest[end..];
+
+        if preserved.is_empty() {
+            push_collapsed(&mut minified, text);
+        } else {
+            minified.push_str(text);
+        }
+    }
+
+    minified
+}
+
+/// Minifies a stylesheet
+
+pub fn minify_css(css: &str) -> String {
+    let mut minified = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    let mut whitespace = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                while let Some(c) = chars.next() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                continue;
+            },
+            '"' | '\'' => {
+                push_css_whitespace(&mut minified, whitespace, c);
+                whitespace = false;
+                minified.push(c);
+                let mut escaped = false;
+                while let Some(next) = chars.next() {
+                    minified.push(next);
+                    if escaped {
+                        escaped = false;
+                    } else if next == '\\' {
+                        escaped = true;
+                    } else if next == c {
+                        break;
+                    }
+                }
+                continue;
+            },
+            c if c.is_whitespace() => {
+                whitespace = true;
+                continue;
+            },
+            _ => {},
+        }
+
+        if c == '}' && minified.ends_with(';') {
+            minified.pop();
+        }
+        push_css_whitespace(&mut minified, whitespace, c);
+        whitespace = false;
+        minified.push(c);
+    }
+
+    minified
+}
+
+/// Minifies a script
+
+pub fn minify_js(js: &str) -> String {
+    let mut minified = String::with_capacity(js.len());
+    // Whether the start of the line is in a template literal
+    let mut in_template = false;
+
+    for line in js.lines() {
+        if in_template {
+            minified.push_str(line);
+            minified.push('\n');
+        } else {
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with("//") {
+                minified.push_str(trimmed);
+                minified.push('\n');
+            }
+        }
+        in_template = ends_in_template(line, in_template);
+    }
+
+    minified
+}
+
+
+// Length of the tag at the start of `html`, the quoted values of its attributes can contain `>`
+fn tag_end(html: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in html.char_indices().skip(1) {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {},
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return i + 1,
+            None => {},
+        }
+    }
+    html.len()
+}
+
+// Lowercase name of the element of a tag, without the `/` of a closing tag
+fn tag_name(tag: &str) -> String {
+    tag.trim_left_matches('<')
+       .trim_left_matches('/')
+       .chars()
+       .take_while(|c| c.is_alphanumeric() || *c == '-')
+       .collect::<String>()
+       .to_lowercase()
+}
+
+// Pushes `tag` with its whitespace collapsed, except in the quoted values of its attributes
+fn push_tag(minified: &mut String, tag: &str) {
+    let mut quote = None;
+    let mut whitespace = false;
+
+    for c in tag.chars() {
+        match quote {
+            Some(q) => {
+                minified.push(c);
+                if c == q {
+                    quote = None;
+                }
+                continue;
+            },
+            None if c.is_whitespace() => {
+                whitespace = true;
+                continue;
+            },
+            None => {},
+        }
+
+        if whitespace && c != '>' {
+            minified.push(' ');
+        }
+        whitespace = false;
+        if c == '"' || c == '\'' {
+            quote = Some(c);
+        }
+        minified.push(c);
+    }
+}
+
+// Pushes `text` with every run of whitespace replaced by a space, or by a line break when it
+// contains one. A run right after the whitespace of a removed comment is merged with it.
+fn push_collapsed(minified: &mut String, text: &str) {
+    let mut run: Option<bool> = None;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            run = Some(run.unwrap_or(false) || c == '\n');
+            continue;
+        }
+        if let Some(line_break) = run.take() {
+            push_whitespace(minified, line_break);
+        }
+        minified.push(c);
+    }
+    if let Some(line_break) = run {
+        push_whitespace(minified, line_break);
+    }
+}
+
+fn push_whitespace(minified: &mut String, line_break: bool) {
+    if minified.ends_with('\n') {
+        return;
+    }
+    if minified.ends_with(' ') {
+        if !line_break {
+            return;
+        }
+        minified.pop();
+    }
+    minified.push(if line_break { '\n' } else { ' ' });
+}
+
+// Pushes the whitespace that was skipped before `c`, if it is needed
+fn push_css_whitespace(minified: &mut String, whitespace: bool, c: char) {
+    let around = |c: char| c == '{' || c == '}' || c == ';' || c == ',';
+    if whitespace && !minified.is_empty() && !around(c) && !minified.ends_with(around) {
+        minified.push(' ');
+    }
+}
+
+// Whether the end of `line` is in a template literal, given whether its start is. The quoted
+// strings and the comments end with the line, a `` ` `` in them does not start a template.
+fn ends_in_template(line: &str, in_template: bool) -> bool {
+    let mut in_template = in_template;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut previous = ' ';
+
+    for c in line.chars() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if in_template {
+            in_template = c != '`';
+        } else if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+        } else if c == '/' && previous == '/' {
+            break;
+        } else if c == '"' || c == '\'' {
+            quote = Some(c);
+        } else if c == '`' {
+            in_template = true;
+        }
+        previous = c;
+    }
+
+    in_template
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_whitespace_outside_of_code() {
+        let html = "<!DOCTYPE html>\n<html>\n    <head>\n        <!-- comment -->\n        \
+                    <meta   name=\"description\"  content=\"a  b\">\n    </head>\n    <body>\n        \
+                    <p>Some   <em>text</em>\n        here</p>\n        \
+                    <pre><code class=\"language-rust\">fn main() {\n    let  x = 1;\n}\n</code></pre>\n        \
+                    <p><code>a  b</code> <br/> </p>\n        <script>\n    if (a  <b) {}\n</script>\n    \
+                    </body>\n</html>\n";
+
+        assert_eq!(minify_html(html),
+                   "<!DOCTYPE html>\n<html>\n<head>\n<meta name=\"description\" content=\"a  b\">\n</head>\n\
+                    <body>\n<p>Some <em>text</em>\nhere</p>\n\
+                    <pre><code class=\"language-rust\">fn main() {\n    let  x = 1;\n}\n</code></pre>\n\
+                    <p><code>a  b</code> <br/> </p>\n<script>if (a  <b) {}\n</script>\n</body>\n</html>\n");
+    }
+
+    #[test]
+    fn css_comments_and_whitespace() {
+        let css = "/* Theme */\n.sidebar a:hover,\n.chapter   li > a {\n    color: red;\n    \
+                   font-family: \"Open  Sans\", sans-serif;\n}\n\n@media (max-width: 600px) {\n    \
+                   .x { margin: 0 auto; }\n}\n";
+
+        assert_eq!(minify_css(css),
+                   ".sidebar a:hover,.chapter li > a{color: red;font-family: \"Open  Sans\",sans-serif}\
+                    @media (max-width: 600px){.x{margin: 0 auto}}");
+    }
+
+    #[test]
+    fn js_indentation_and_comments() {
+        let js = "// Theme\nfunction f() {\n    // Comment\n    var url = 'http://x.org'; // end\n\n    \
+                  var t = `a\n    b`;\n    return \"`\";\n}\n";
+
+        assert_eq!(minify_js(js),
+                   "function f() {\nvar url = 'http://x.org'; // end\nvar t = `a\n    b`;\nreturn \"`\";\n}\n");
+        assert_eq!(minify("json", "{ }"), None);
+    }
+}
@@ -1,4 +1,14 @@
-pub use self::hbs_renderer::HtmlHandlebars;
+pub use self::hbs_renderer::{HtmlHandlebars, code_options};
+pub use self::cache::{CACHE_FILE, changed_files};
 
 mod hbs_renderer;
 mod helpers;
+mod search;
+mod sitemap;
+mod redirect;
+mod print;
+mod fonts;
+mod standalone;
+mod cache;
+pub mod links;
+mod minify;
@@ -0,0 +1,119 @@
+//! Content of `print.html`, the whole book on a single page
+//!
+//! Every chapter is put in a `<div class="print-chapter">` whose id is derived from the path of the
+//! chapter. The ids of the elements of the chapter are prefixed with it, so that the headings of
+//! different chapters can have the same text, and the links are rewritten for the page: a link to
+//! another chapter of the book becomes a link to its `<div>`, the other relative links and images
+//! are made relative to the root of the book, where `print.html` is.
+
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use renderer::html_handlebars::links::{rewrite_attributes, is_relative, resolve, to_url};
+
+
+/// Returns the id of the `<div>` of the chapter at `path` in the print page: `format/config.md`
+/// becomes `format-config`
+
+pub fn chapter_id(path: &Path) -> String {
+    path.with_extension("")
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Returns the html of a chapter for the print page. `path` is the path of the chapter relative to
+/// the source directory and `chapters` contains the paths of all the chapters of the book.
+
+pub fn render_chapter(html: &str, path: &Path, chapters: &HashSet<PathBuf>) -> String {
+    let id = chapter_id(path);
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    let mut rendered = String::with_capacity(html.len() + 64);
+    rendered.push_str(&format!("<div class=\"print-chapter\" id=\"{}\">\n", id));
+
+    rendered.push_str(&rewrite_attributes(html, |name, value| match name {
+        "id" => format!("{}-{}", id, value),
+        "href" => rewrite_link(value, &id, directory, chapters),
+        "src" => rewrite_source(value, directory),
+        _ => value.to_owned(),
+    }));
+    rendered.push_str("</div>\n");
+    rendered
+}
+
+
+fn rewrite_link(link: &str, id: &str, directory: &Path, chapters: &HashSet<PathBuf>) -> String {
+    if link.starts_with('#') {
+        return format!("#{}-{}", id, &link[1..]);
+    }
+    if !is_relative(link) {
+        return link.to_owned();
+    }
+
+    let (target, fragment) = match link.find('#') {
+        Some(i) => (&link[..i], Some(&link[i + 1..])),
+        None => (link, None),
+    };
+
+    let target = match resolve(directory, target) {
+        Some(target) => target,
+        None => return link.to_owned(),
+    };
+
+    let is_page = target.extension().map_or(false, |ext| ext == "html" || ext == "md");
+    if is_page && chapters.contains(&target.with_extension("md")) {
+        let target_id = chapter_id(&target);
+        return match fragment {
+            Some(fragment) => format!("#{}-{}", target_id, fragment),
+            None => format!("#{}", target_id),
+        };
+    }
+
+    match fragment {
+        Some(fragment) => format!("{}#{}", to_url(&target), fragment),
+        None => to_url(&target),
+    }
+}
+
+fn rewrite_source(source: &str, directory: &Path) -> String {
+    if !is_relative(source) {
+        return source.to_owned();
+    }
+
+    match resolve(directory, source) {
+        Some(source) => to_url(&source),
+        None => source.to_owned(),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn links_point_inside_of_the_print_page() {
+        let mut chapters = HashSet::new();
+        chapters.insert(PathBuf::from("intro.md"));
+        chapters.insert(PathBuf::from("format/config.md"));
+
+        let html = "<h1 id=\"usage\">Usage</h1>\n<p><a href=\"#usage\">a</a> <a href=\"../intro.html#b\">b</a> \
+                    <a href=\"config.md\">c</a> <a href=\"notes.txt\">d</a> <a href=\"https://rust-lang.org\">e</a> \
+                    <img src=\"images/f.png\" alt=\"f\"></p>\n";
+
+        assert_eq!(render_chapter(html, Path::new("format/summary.md"), &chapters),
+                   "<div class=\"print-chapter\" id=\"format-summary\">\n\
+                    <h1 id=\"format-summary-usage\">Usage</h1>\n<p><a href=\"#format-summary-usage\">a</a> \
+                    <a href=\"#intro-b\">b</a> <a href=\"#format-config\">c</a> <a href=\"format/notes.txt\">d</a> \
+                    <a href=\"https://rust-lang.org\">e</a> <img src=\"format/images/f.png\" alt=\"f\"></p>\n\
+                    </div>\n");
+    }
+}
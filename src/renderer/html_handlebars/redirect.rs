@@ -0,0 +1,37 @@
+use utils::html::escape_html;
+
+/// Page written at the old path of a moved page by the html renderer, see
+/// [`MDBook::set_redirects()`](../../../book/struct.MDBook.html#method.set_redirects). It sends
+/// the browser to `url` right away, and tells the search engines that `canonical` is the address
+/// of its content.
+pub fn redirect_page(url: &str, canonical: &str) -> String {
+    let url = escape_html(url);
+    format!("<!DOCTYPE html>\n\
+             <html>\n\
+             <head>\n\
+             <meta charset=\"utf-8\">\n\
+             <title>Redirecting...</title>\n\
+             <meta http-equiv=\"refresh\" content=\"0; url={url}\">\n\
+             <link rel=\"canonical\" href=\"{canonical}\">\n\
+             </head>\n\
+             <body>\n\
+             <p>This page has moved to <a href=\"{url}\">{url}</a>.</p>\n\
+             </body>\n\
+             </html>\n",
+            url = url,
+            canonical = escape_html(canonical))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirect_to_the_new_page() {
+        let html = redirect_page("../intro.html?a&b", "https://example.com/intro.html");
+        assert!(html.contains("<meta http-equiv=\"refresh\" content=\"0; url=../intro.html?a&amp;b\">"));
+        assert!(html.contains("<link rel=\"canonical\" href=\"https://example.com/intro.html\">"));
+        assert!(html.contains("<a href=\"../intro.html?a&amp;b\">../intro.html?a&amp;b</a>"));
+    }
+}
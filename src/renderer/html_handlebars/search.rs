@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+
+use rustc_serialize::json::{Json, ToJson};
+use pulldown_cmark::{Parser, Event, Tag, Options, OPTION_ENABLE_TABLES, OPTION_ENABLE_FOOTNOTES};
+
+use markdown::Heading;
+
+/// Search index of the book, written to `searchindex.json` by the html renderer.
+///
+/// Every chapter is split into documents at its headings, so that a search result can point to the
+/// right part of the page. The documents of a chapter only depend on the chapter itself.
+pub struct SearchIndex {
+    documents: Vec<Json>,
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        SearchIndex { documents: vec![] }
+    }
+
+    /// Adds the documents of a chapter. `link` is the url of the rendered chapter, `content` its
+    /// markdown source and `headings` the headings of its page, in order. Their text and id are the
+    /// ones of the page, so that the url of a document points to its heading.
+    pub fn add_chapter(&mut self,
+                       section: Option<&str>,
+                       name: &str,
+                       link: &str,
+                       content: &str,
+                       headings: &[Heading]) {
+        let mut heading = None;
+        let mut body = String::new();
+        let mut in_header = false;
+        let mut next_heading = headings.iter();
+
+        let mut opts = Options::empty();
+        opts.insert(OPTION_ENABLE_TABLES);
+        opts.insert(OPTION_ENABLE_FOOTNOTES);
+
+        for event in Parser::new_ext(content, opts) {
+            match event {
+                Event::Start(Tag::Header(_)) => {
+                    // A new heading starts a new document
+                    self.add_document(section, name, link, heading, &body);
+                    heading = next_heading.next();
+                    body.clear();
+                    in_header = true;
+                },
+                Event::End(Tag::Header(_)) => in_header = false,
+                Event::Text(ref text) if !in_header => {
+                    body.push_str(text);
+                    body.push(' ');
+                },
+                Event::SoftBreak | Event::HardBreak => body.push(' '),
+                _ => {},
+            }
+        }
+
+        self.add_document(section, name, link, heading, &body);
+    }
+
+    fn add_document(&mut self, section: Option<&str>, name: &str, link: &str, heading: Option<&Heading>, body: &str) {
+        if heading.is_none() && body.trim().is_empty() {
+            return;
+        }
+
+        let mut document = BTreeMap::new();
+        document.insert("title".to_owned(), name.to_json());
+        if let Some(section) = section {
+            document.insert("section".to_owned(), section.to_json());
+        }
+        match heading {
+            Some(heading) => {
+                document.insert("heading".to_owned(), heading.text.to_json());
+                document.insert("url".to_owned(), format!("{}#{}", link, heading.id).to_json());
+            },
+            None => {
+                document.insert("heading".to_owned(), "".to_json());
+                document.insert("url".to_owned(), link.to_json());
+            },
+        }
+
+        document.insert("body".to_owned(), tokenize(body).join(" ").to_json());
+
+        self.documents.push(document.to_json());
+    }
+}
+
+impl ToJson for SearchIndex {
+    fn to_json(&self) -> Json {
+        let mut index = BTreeMap::new();
+        index.insert("documents".to_owned(), self.documents.to_json());
+        index.to_json()
+    }
+}
+
+/// Splits a text in lowercase words, the punctuation is removed
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rustc_serialize::json::ToJson;
+    use markdown;
+
+    #[test]
+    fn tokenize_text() {
+        assert_eq!(tokenize("Hello, World! It's mdBook."),
+                   vec!["hello", "world", "it", "s", "mdbook"]);
+    }
+
+    #[test]
+    fn chapter_is_split_at_headings() {
+        let content = "Some text\n\n## First\n\nMore text";
+        let (_, headings) = markdown::render_with_headings(content, markdown::CodeOptions::default());
+        let mut index = SearchIndex::new();
+        index.add_chapter(Some("1."), "Intro", "intro.html", content, &headings);
+
+        let json = index.to_json();
+        let documents = json.find("documents").unwrap().as_array().unwrap();
+
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].find("url").unwrap().as_string(), Some("intro.html"));
+        assert_eq!(documents[0].find("body").unwrap().as_string(), Some("some text"));
+        assert_eq!(documents[1].find("url").unwrap().as_string(), Some("intro.html#first"));
+        assert_eq!(documents[1].find("heading").unwrap().as_string(), Some("First"));
+        assert_eq!(documents[1].find("section").unwrap().as_string(), Some("1."));
+    }
+
+    #[test]
+    fn urls_point_to_the_ids_of_the_page() {
+        let content = "## :rocket: Launch\n\nGo";
+        let options = markdown::CodeOptions { emoji: true, ..markdown::CodeOptions::default() };
+        let (html, headings) = markdown::render_with_headings(content, options);
+        let mut index = SearchIndex::new();
+        index.add_chapter(None, "Intro", "intro.html", content, &headings);
+
+        let json = index.to_json();
+        let documents = json.find("documents").unwrap().as_array().unwrap();
+        let url = documents[0].find("url").unwrap().as_string().unwrap();
+        assert_eq!(url, format!("intro.html#{}", headings[0].id));
+        assert_ne!(url, "intro.html#rocket-launch");
+        assert!(html.contains(&format!("id=\"{}\"", headings[0].id)), "{}", html);
+    }
+}
@@ -0,0 +1,61 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use utils;
+use utils::html::escape_html;
+
+/// Sitemap of the book, written to `sitemap.xml` by the html renderer when the book has an absolute
+/// `site_url`, see [sitemaps.org](https://www.sitemaps.org/protocol.html).
+pub struct Sitemap {
+    urls: Vec<(String, Option<String>)>,
+}
+
+impl Sitemap {
+    pub fn new() -> Self {
+        Sitemap { urls: vec![] }
+    }
+
+    /// Adds the page of a chapter. `url` is its absolute url and `modified` the last modification
+    /// time of its source file, when it is known.
+    pub fn add_page(&mut self, url: &str, modified: Option<SystemTime>) {
+        let lastmod = modified.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                              .map(|duration| utils::format_timestamp(duration.as_secs()));
+        self.urls.push((url.to_owned(), lastmod));
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                                    <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+        for &(ref url, ref lastmod) in &self.urls {
+            xml.push_str(&format!("  <url>\n    <loc>{}</loc>\n", escape_html(url)));
+            if let Some(ref lastmod) = *lastmod {
+                xml.push_str(&format!("    <lastmod>{}</lastmod>\n", lastmod));
+            }
+            xml.push_str("  </url>\n");
+        }
+        xml.push_str("</urlset>\n");
+        xml
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn sitemap_lists_pages() {
+        let mut sitemap = Sitemap::new();
+        sitemap.add_page("https://example.com/intro.html", Some(UNIX_EPOCH + Duration::from_secs(86400)));
+        sitemap.add_page("https://example.com/a&b.html", None);
+
+        assert_eq!(sitemap.to_xml(),
+                   "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                    <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n  \
+                    <url>\n    <loc>https://example.com/intro.html</loc>\n    \
+                    <lastmod>1970-01-02T00:00:00Z</lastmod>\n  </url>\n  \
+                    <url>\n    <loc>https://example.com/a&amp;b.html</loc>\n  </url>\n\
+                    </urlset>\n");
+    }
+}
@@ -0,0 +1,223 @@
+//! `standalone.html`, the whole book in a single file
+//!
+//! The print page already has every chapter in the same document. For the standalone version its
+//! stylesheets and scripts are copied into the page, the images, the icon and the fonts of the
+//! stylesheets become `data:` urls and the links of the sidebar to the pages of the chapters point
+//! to their part of the page. The `<base>` is removed, the page is at the root of the book. Files
+//! larger than the limit are not embedded, they are linked from the root of the book instead.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use rustc_serialize::base64::{ToBase64, STANDARD};
+
+use renderer::html_handlebars::links::{rewrite_attributes, is_relative, resolve, to_url};
+
+
+/// Size in bytes above which a file is not embedded in `standalone.html`
+pub const INLINE_MAX_SIZE: usize = 512 * 1024;
+
+
+/// Returns the self-contained version of `html`, a page at the root of the book, and the files that
+/// were too large to be embedded. `pages` maps the links to the pages of the chapters to the anchor
+/// that replaces them. `read` returns the content of a file of the output, by its path relative to
+/// the root, or `None` when there is no such file.
+
+pub fn inline_assets<F>(html: &str,
+                        pages: &HashMap<String, String>,
+                        max_size: usize,
+                        read: F)
+                        -> (String, Vec<PathBuf>)
+    where F: Fn(&Path) -> Option<Vec<u8>>
+{
+    let mut external = vec![];
+    let mut page = String::with_capacity(html.len());
+
+    for line in html.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("<base href=") {
+            continue;
+        }
+
+        if let Some(href) = between(trimmed, "<link rel=\"stylesheet\" href=\"", "\">") {
+            if let Some(css) = local_file(href, &read) {
+                let directory = Path::new(href).parent().unwrap_or(Path::new(""));
+                let css = inline_urls(&String::from_utf8_lossy(&css), directory, max_size, &read, &mut external);
+                page.push_str(&format!("<style>\n{}\n</style>\n", css));
+                continue;
+            }
+        }
+
+        if let Some(src) = between(trimmed, "<script src=\"", "\"></script>") {
+            if let Some(js) = local_file(src, &read) {
+                // The script must not end the element it is copied into
+                let js = String::from_utf8_lossy(&js).replace("</script", "<\\/script");
+                page.push_str(&format!("<script>\n{}\n</script>\n", js));
+                continue;
+            }
+        }
+
+        page.push_str(line);
+        page.push('\n');
+    }
+
+    let page = rewrite_attributes(&page, |name, value| {
+        if name == "href" {
+            if let Some(anchor) = pages.get(value) {
+                return anchor.clone();
+            }
+            if mime_type(value).starts_with("image/") {
+                return embed(value, Path::new(""), max_size, &read, &mut external);
+            }
+        }
+        if name == "src" {
+            return embed(value, Path::new(""), max_size, &read, &mut external);
+        }
+        value.to_owned()
+    });
+
+    (page, external)
+}
+
+
+// The text of `line` between `start` and `end`, when it is all of the line
+fn between<'a>(line: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    if line.len() >= start.len() + end.len() && line.starts_with(start) && line.ends_with(end) {
+        Some(&line[start.len()..line.len() - end.len()])
+    } else {
+        None
+    }
+}
+
+fn local_file<F>(link: &str, read: &F) -> Option<Vec<u8>>
+    where F: Fn(&Path) -> Option<Vec<u8>>
+{
+    if !is_relative(link) {
+        return None;
+    }
+    resolve(Path::new(""), link).and_then(|path| read(&path))
+}
+
+// Embeds the files of the `url(...)` of a stylesheet, relative to its `directory`
+fn inline_urls<F>(css: &str, directory: &Path, max_size: usize, read: &F, external: &mut Vec<PathBuf>) -> String
+    where F: Fn(&Path) -> Option<Vec<u8>>
+{
+    let mut inlined = String::with_capacity(css.len());
+
+    let mut rest = css;
+    while let Some(start) = rest.find("url(") {
+        let value_start = start + 4;
+        let value_end = match rest[value_start..].find(')') {
+            Some(end) => value_start + end,
+            None => break,
+        };
+
+        let value = rest[value_start..value_end].trim().trim_matches(|c| c == '\'' || c == '"');
+        inlined.push_str(&rest[..value_start]);
+        inlined.push_str(&embed(value, directory, max_size, read, external));
+        rest = &rest[value_end..];
+    }
+    inlined.push_str(rest);
+
+    inlined
+}
+
+// The `data:` url of the file at `link`, relative to `directory`. A file that is too large is
+// linked from the root of the book instead and added to `external`, the other links are kept.
+fn embed<F>(link: &str, directory: &Path, max_size: usize, read: &F, external: &mut Vec<PathBuf>) -> String
+    where F: Fn(&Path) -> Option<Vec<u8>>
+{
+    if !is_relative(link) {
+        return link.to_owned();
+    }
+
+    // The query of the fonts is only there to bust the caches, the fragment is kept for svg fonts
+    let (target, fragment) = match link.find('#') {
+        Some(i) => (&link[..i], &link[i..]),
+        None => (link, ""),
+    };
+    let target = target.split('?').next().unwrap_or("");
+
+    let path = match resolve(directory, target) {
+        Some(path) => path,
+        None => return link.to_owned(),
+    };
+    match read(&path) {
+        Some(ref content) if content.len() <= max_size => {
+            format!("data:{};base64,{}{}", mime_type(target), content.to_base64(STANDARD), fragment)
+        },
+        Some(_) => {
+            external.push(path.clone());
+            format!("{}{}", to_url(&path), fragment)
+        },
+        None => link.to_owned(),
+    }
+}
+
+fn mime_type(link: &str) -> &'static str {
+    let extension = Path::new(link.split(|c| c == '?' || c == '#').next().unwrap_or(""))
+                        .extension()
+                        .map(|ext| ext.to_string_lossy().to_lowercase());
+    match extension.as_ref().map(|ext| ext.as_str()) {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("eot") => "application/vnd.ms-fontobject",
+        _ => "application/octet-stream",
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    fn read(path: &Path) -> Option<Vec<u8>> {
+        match path.to_str() {
+            Some("book.css") => Some(b"body { background: url('img/bg.png?v=1'); }".to_vec()),
+            Some("book.js") => Some(b"var end = '</script>';".to_vec()),
+            Some("img/bg.png") | Some("logo.png") => Some(b"png".to_vec()),
+            Some("large.png") => Some(vec![0; 16]),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn assets_are_embedded() {
+        let html = "<head>\n    <base href=\"\">\n    <link rel=\"stylesheet\" href=\"book.css\">\n    \
+                    <link rel=\"stylesheet\" href=\"https://example.com/remote.css\">\n</head>\n<body>\n\
+                    <img src=\"logo.png\"><img src=\"large.png\"><img src=\"missing.png\">\n\
+                    <script src=\"book.js\"></script>\n</body>\n";
+        let (page, external) = inline_assets(html, &HashMap::new(), 8, read);
+
+        assert_eq!(page,
+                   "<head>\n<style>\nbody { background: url(data:image/png;base64,cG5n); }\n</style>\n    \
+                    <link rel=\"stylesheet\" href=\"https://example.com/remote.css\">\n</head>\n<body>\n\
+                    <img src=\"data:image/png;base64,cG5n\"><img src=\"large.png\"><img src=\"missing.png\">\n\
+                    <script>\nvar end = '<\\/script>';\n</script>\n</body>\n");
+        assert_eq!(external, vec![PathBuf::from("large.png")]);
+    }
+
+    #[test]
+    fn links_to_pages_point_inside_of_the_page() {
+        let mut pages = HashMap::new();
+        pages.insert("intro.html".to_owned(), "#intro".to_owned());
+        pages.insert("guide/setup/".to_owned(), "#guide-setup".to_owned());
+        let html = "<a href=\"intro.html\">a</a><a href=\"guide/setup/\">b</a><a href=\"#x\">c</a>\
+                    <link rel=\"shortcut icon\" href=\"logo.png\">\n";
+
+        assert_eq!(inline_assets(html, &pages, INLINE_MAX_SIZE, read).0,
+                   "<a href=\"#intro\">a</a><a href=\"#guide-setup\">b</a><a href=\"#x\">c</a>\
+                    <link rel=\"shortcut icon\" href=\"data:image/png;base64,cG5n\">\n");
+    }
+}
@@ -0,0 +1,78 @@
+use renderer::Renderer;
+use book::MDBook;
+use book::bookitem::items_to_json;
+use utils;
+
+use std::fs;
+use std::path::PathBuf;
+use std::error::Error;
+use std::io::{self, Write};
+use std::collections::BTreeMap;
+
+use rustc_serialize::json::ToJson;
+
+
+/// Dumps the parsed book to a `book.json` file in the destination directory.
+///
+/// The file contains the metadata of the book and the tree of book items in the order of
+/// `SUMMARY.md`. Every item has a `type` (`chapter`, `affix`, `spacer` or `part`), parts only have
+/// a `name`, chapters and affixes
+/// also have a `name`, a `path`, the raw markdown as `content` and their `sub_items`. Chapters
+/// additionally have their `section` number, and the chapters with a front matter its keys as
+/// `metadata`.
+///
+/// ```json
+/// {
+///     "title": "My book",
+///     "author": "",
+///     "description": "",
+///     "language": "en",
+///     "items": [
+///         {"type": "chapter", "section": "1.", "name": "Intro", "path": "intro.md",
+///          "content": "# Intro", "sub_items": []},
+///         {"type": "spacer"}
+///     ]
+/// }
+/// ```
+pub struct JsonRenderer;
+
+impl JsonRenderer {
+    pub fn new() -> Self {
+        JsonRenderer
+    }
+}
+
+impl Renderer for JsonRenderer {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn files_to_write(&self, _book: &MDBook) -> Result<Option<Vec<PathBuf>>, Box<Error>> {
+        Ok(Some(vec![PathBuf::from("book.json")]))
+    }
+
+    fn render(&self, book: &MDBook) -> Result<(), Box<Error>> {
+        debug!("[fn]: render (json)");
+
+        let mut data = BTreeMap::new();
+        data.insert("title".to_owned(), book.get_title().to_json());
+        data.insert("author".to_owned(), book.get_author().to_json());
+        data.insert("description".to_owned(), book.get_description().to_json());
+        data.insert("language".to_owned(), book.get_language().to_json());
+        data.insert("items".to_owned(), items_to_json(&book.content));
+
+        // Check if dest directory exists
+        debug!("[*]: Check if destination directory exists");
+        if let Err(_) = fs::create_dir_all(book.get_dest()) {
+            return Err(Box::new(io::Error::new(io::ErrorKind::Other,
+                                               "Unexpected error when constructing destination path")));
+        }
+
+        let path = book.get_dest().join("book.json");
+        let mut file = try!(utils::fs::create_file(&path));
+        try!(file.write_all(data.to_json().pretty().to_string().as_bytes()));
+        debug!("Creating {:?} ✓", path);
+
+        Ok(())
+    }
+}
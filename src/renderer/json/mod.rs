@@ -0,0 +1,3 @@
+pub use self::json_renderer::JsonRenderer;
+
+mod json_renderer;
@@ -1,9 +1,32 @@
-pub use self::html_handlebars::HtmlHandlebars;
+pub use self::html_handlebars::{HtmlHandlebars, CACHE_FILE, changed_files};
+pub use self::epub::EpubRenderer;
+pub use self::json::JsonRenderer;
+pub use self::external::ExternalRenderer;
 
 mod html_handlebars;
+mod epub;
+mod json;
+mod external;
 
 use std::error::Error;
+use std::path::PathBuf;
 
 pub trait Renderer {
+    /// Short name of the renderer, like `html`, used in the messages of the build
+    fn name(&self) -> &str;
+
     fn render(&self, book: &::book::MDBook) -> Result<(), Box<Error>>;
+
+    /// Renders the book like `render()` and reports every rendered chapter to `progress`. The
+    /// renderers that don't implement it only render the book.
+    fn render_with_progress(&self, book: &::book::MDBook, _progress: &::book::Progress) -> Result<(), Box<Error>> {
+        self.render(book)
+    }
+
+    /// Paths, relative to the output directory, of the files that `render()` would write, without
+    /// writing anything. `None` when the renderer can not tell them without rendering the book, the
+    /// default.
+    fn files_to_write(&self, _book: &::book::MDBook) -> Result<Option<Vec<PathBuf>>, Box<Error>> {
+        Ok(None)
+    }
 }
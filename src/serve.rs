@@ -0,0 +1,126 @@
+//! Serve a book over http and reload the pages when it is rebuilt
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::thread;
+
+use iron;
+use staticfile;
+use ws;
+
+use rustc_serialize::json::ToJson;
+
+use book::MDBook;
+use utils;
+use watch;
+
+const RELOAD_COMMAND: &'static str = "reload";
+// Followed by the JSON list of the output files changed by the rebuild
+const CHANGED_COMMAND: &'static str = "changed ";
+
+/// Builds the book and serves the output directory at `localhost:port`.
+///
+/// A livereload script is injected into every page, it listens on a websocket at `localhost:ws_port`.
+/// Every time a source file changes the book is rebuilt and the pages are told which output files
+/// changed: a page that did not change stays as it is, a page whose content changed swaps it in
+/// place and keeps its scroll position, the other changes reload the page. When `open` is true the
+/// served book is opened in the default browser once it is built. This is blocking!
+
+pub fn serve(book: &mut MDBook, port: &str, ws_port: &str, open: bool) -> Result<(), Box<Error>> {
+    debug!("[fn]: serve");
+
+    let address = format!("localhost:{}", port);
+    let ws_address = format!("localhost:{}", ws_port);
+
+    book.set_livereload(format!(r#"
+        <script type="text/javascript">
+            var socket = new WebSocket("ws://localhost:{}");
+            socket.onmessage = function (event) {{
+                if (event.data.indexOf("{changed}") === 0 && typeof livereload_changed === "function") {{
+                    livereload_changed(JSON.parse(event.data.slice({changed_len})));
+                }} else if (event.data.indexOf("{changed}") === 0 || event.data === "{reload}") {{
+                    socket.close();
+                    location.reload(true); // force reload from server (not from cache)
+                }}
+            }};
+
+            window.onbeforeunload = function() {{
+                socket.close();
+            }}
+        </script>
+    "#,
+                                ws_port,
+                                changed = CHANGED_COMMAND,
+                                changed_len = CHANGED_COMMAND.len(),
+                                reload = RELOAD_COMMAND));
+
+    try!(book.build());
+
+    let staticfile = staticfile::Static::new(book.get_dest());
+    let iron = iron::Iron::new(staticfile);
+    let _iron = try!(iron.http(&*address));
+
+    let ws_server = try!(ws::WebSocket::new(|_| {
+        |_| {
+            Ok(())
+        }
+    }));
+
+    let broadcaster = ws_server.broadcaster();
+
+    thread::spawn(move || {
+        if let Err(e) = ws_server.listen(&*ws_address) {
+            warn!("Livereload server stopped: {}", e);
+        }
+    });
+
+    info!("Serving on {}", address);
+
+    if open {
+        utils::open(format!("http://{}", address));
+    }
+
+    watch::trigger_on_change(book, move |path, book| {
+        info!("File changed: {:?}, building the book", path);
+        match book.build() {
+            Err(e) => error!("Error while building: {}", e),
+            _ => {
+                if let Some(message) = reload_message(book.changed_files()) {
+                    if let Err(e) = broadcaster.send(message) {
+                        warn!("Could not send the reload signal: {}", e);
+                    }
+                }
+            },
+        }
+    })
+}
+
+
+// The message sent to the pages after a rebuild, `None` when no output file changed. The pages are
+// all reloaded when the changed files are not known.
+fn reload_message(changed: Option<Vec<PathBuf>>) -> Option<String> {
+    match changed {
+        Some(ref changed) if changed.is_empty() => None,
+        Some(changed) => {
+            let urls = changed.iter().map(|path| path.to_string_lossy().replace("\\", "/")).collect::<Vec<_>>();
+            Some(format!("{}{}", CHANGED_COMMAND, urls.to_json()))
+        },
+        None => Some(RELOAD_COMMAND.to_owned()),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    #[test]
+    fn reload_messages() {
+        assert_eq!(reload_message(None), Some("reload".to_owned()));
+        assert_eq!(reload_message(Some(vec![])), None);
+        assert_eq!(reload_message(Some(vec![PathBuf::from("guide/intro.html"), PathBuf::from("print.html")])),
+                   Some("changed [\"guide/intro.html\",\"print.html\"]".to_owned()));
+    }
+}
@@ -7,10 +7,13 @@ $( document ).ready(function() {
     window.onunload = function(){};
 
     // Set theme
+    // The default theme of the book was resolved in the page, before the first paint
+    if (typeof default_theme === 'undefined') { default_theme = 'light'; }
+
     var theme = localStorage.getItem('theme');
-    if (theme === null) { theme = 'light'; }
+    if (theme === null) { theme = default_theme; }
 
-    set_theme(theme);
+    set_theme(theme, false);
 
 
     // Syntax highlighting Configuration
@@ -19,9 +22,7 @@ $( document ).ready(function() {
         languages: [],      // Languages used for auto-detection
     });
 
-    $('code').each(function(i, block) {
-        hljs.highlightBlock(block);
-    });
+    setup_code_blocks($(document));
 
     var KEY_CODES = {
         PREVIOUS_KEY: 37,
@@ -52,16 +53,6 @@ $( document ).ready(function() {
     var content = $("#content");
 
 
-    // Add anchors for all content headers
-    content.find("h1, h2, h3, h4, h5").wrap(function(){
-        var wrapper = $("<a class=\"header\">");
-        wrapper.attr("name", $(this).text());
-        // Add so that when you click the link actually shows up in the url bar...
-        wrapper.attr("href", $(location).attr('href') + "#" + $(this).text());
-        return wrapper;
-    });
-
-
     // Toggle sidebar
     $("#sidebar-toggle").click(function(event){
         if ( html.hasClass("sidebar-hidden") ) {
@@ -82,6 +73,47 @@ $( document ).ready(function() {
     });
 
 
+    // Collapsible sections of the sidebar, identified by the number of their parent chapter. Their
+    // state is kept across the pages, the sections containing the current chapter are always open.
+    // The sections the reader never toggled are folded when they are deeper than the fold level of
+    // the book, the renderer marks them as collapsed.
+    var sections_state = JSON.parse(localStorage.getItem('sidebar-sections') || '{}');
+
+    sidebar.find("ul.section").each(function(){
+        var section = $(this);
+        var key = section.attr("data-section");
+        var toggle = $("<i class=\"fa section-toggle\" tabindex=\"0\" role=\"button\"></i>");
+        section.parent().prev("li").prepend(toggle);
+
+        function set_expanded(expanded) {
+            section.toggleClass("collapsed", !expanded);
+            toggle.toggleClass("fa-angle-down", expanded).toggleClass("fa-angle-right", !expanded);
+            toggle.attr("aria-expanded", expanded ? "true" : "false");
+        }
+
+        var expanded = key in sections_state ? sections_state[key] : !section.hasClass("collapsed");
+        expanded = expanded || section.find(".active").length > 0;
+        set_expanded(expanded);
+
+        function switch_state() {
+            expanded = !expanded;
+            set_expanded(expanded);
+
+            sections_state[key] = expanded;
+            localStorage.setItem('sidebar-sections', JSON.stringify(sections_state));
+        }
+
+        toggle.click(switch_state);
+        toggle.on('keydown', function(e){
+            // Enter and space
+            if (e.keyCode === 13 || e.keyCode === 32) {
+                e.preventDefault();
+                switch_state();
+            }
+        });
+    });
+
+
     // Scroll sidebar to current active section
     var activeSection = sidebar.find(".active");
     if(activeSection.length) {
@@ -89,6 +121,34 @@ $( document ).ready(function() {
     }
 
 
+    // Search
+    var searchbar = $("#searchbar");
+    var search_index = null;
+
+    $("#search-toggle").click(function(){
+        var wrapper = $("#search-wrapper");
+        wrapper.toggleClass("hidden");
+
+        if(!wrapper.hasClass("hidden")) {
+            searchbar.focus();
+
+            // The index is only fetched when the search is used for the first time
+            if(search_index === null) {
+                $.getJSON("searchindex.json", function(index){
+                    search_index = index;
+                    show_search_results(search_index, searchbar.val());
+                });
+            }
+        }
+    });
+
+    searchbar.on("input", function(){
+        if(search_index !== null) {
+            show_search_results(search_index, searchbar.val());
+        }
+    });
+
+
     // Print button
     $("#print-button").click(function(){
         var printWindow = window.open("print.html");
@@ -105,24 +165,27 @@ $( document ).ready(function() {
             $('.theme-popup').remove();
         } else {
             var popup = $('<div class="theme-popup"></div>')
-                .append($('<div class="theme" id="light">Light <span class="default">(default)</span><div>'))
+                .append($('<div class="theme" id="light">Light</div>'))
                 .append($('<div class="theme" id="rust">Rust</div>'))
                 .append($('<div class="theme" id="coal">Coal</div>'))
                 .append($('<div class="theme" id="navy">Navy</div>'));
 
+            popup.find('#' + default_theme).append(' <span class="default">(default)</span>');
 
             popup.insertAfter(this);
 
             $('.theme').click(function(){
                 var theme = $(this).attr('id');
 
-                set_theme(theme);
+                set_theme(theme, true);
             });
         }
 
     });
 
-    function set_theme(theme) {
+    // The theme is only remembered when the reader picked it, otherwise a change of the default
+    // theme of the book or of the color scheme of the system is followed
+    function set_theme(theme, store) {
         if (theme == 'coal' || theme == 'navy') {
             $("[href='tomorrow-night.css']").prop('disabled', false);
             $("[href='highlight.css']").prop('disabled', true);
@@ -131,45 +194,30 @@ $( document ).ready(function() {
             $("[href='highlight.css']").prop('disabled', false);
         }
 
-        localStorage.setItem('theme', theme);
+        if (store) {
+            localStorage.setItem('theme', theme);
+        }
 
         $('body').removeClass().addClass(theme);
     }
 
+});
 
-    // Hide Rust code lines prepended with a specific character
-    var hiding_character = "#";
 
-    $("code.language-rust").each(function(i, block){
+// Highlighting and buttons of the code blocks found in `container`, the whole page once it is loaded or
+// the content swapped by the livereload
+function setup_code_blocks(container) {
+    // Code blocks highlighted at build time are left alone
+    container.find('code').not('.syn-code').each(function(i, block) {
+        highlight_block(block);
+    });
 
-        var code_block = $(this);
+
+    // The lines of the Rust code blocks hidden at build time can be revealed
+    container.find("code.language-rust").each(function(i, block){
         var pre_block = $(this).parent();
-        // hide lines
-        var lines = code_block.html().split("\n");
-        var first_non_hidden_line = false;
-        var lines_hidden = false;
-
-        for(var n = 0; n < lines.length; n++){
-            if($.trim(lines[n])[0] == hiding_character){
-                if(first_non_hidden_line){
-                    lines[n] = "<span class=\"hidden\">" + "\n" + lines[n].replace(/(\s*)#/, "$1") + "</span>";
-                }
-                else {
-                    lines[n] = "<span class=\"hidden\">" + lines[n].replace(/(\s*)#/, "$1") + "\n"  +  "</span>";
-                }
-                lines_hidden = true;
-            }
-            else if(first_non_hidden_line) {
-                lines[n] = "\n" + lines[n];
-            }
-            else {
-                first_non_hidden_line = true;
-            }
-        }
-        code_block.html(lines.join(""));
 
-        // If no lines were hidden, return
-        if(!lines_hidden) { return; }
+        if($(this).find("span.hidden").length === 0) { return; }
 
         // add expand button
         pre_block.prepend("<div class=\"buttons\"><i class=\"fa fa-expand\"></i></div>");
@@ -188,8 +236,16 @@ $( document ).ready(function() {
 
 
     // Process playpen code blocks
-    $(".playpen").each(function(block){
+    container.find(".playpen").each(function(block){
         var pre_block = $(this);
+
+        if(pre_block.data("editable")) {
+            pre_block.find("code").attr("contenteditable", "true").attr("spellcheck", "false");
+        }
+
+        // Code that should not be run does not get a play button
+        if(pre_block.data("no-run")) { return; }
+
         // Add play button
         var buttons = pre_block.find(".buttons");
         if( buttons.length === 0 ) {
@@ -204,8 +260,170 @@ $( document ).ready(function() {
     });
 
 
-});
+    // Add a copy button to the code blocks, the hidden lines and the line numbers are not copied
+    container.find("pre > code").each(function(i, block){
+        var code_block = $(this);
+        var pre_block = code_block.parent();
 
+        var buttons = pre_block.find(".buttons");
+        if( buttons.length === 0 ) {
+            pre_block.prepend("<div class=\"buttons\"></div>");
+            buttons = pre_block.find(".buttons");
+        }
+
+        var copy_button = $("<i class=\"fa fa-copy clip-button\" title=\"Copy to clipboard\"></i>");
+        buttons.append(copy_button);
+
+        copy_button.click(function(e){
+            var code = code_block.clone();
+            code.find(".hidden").remove();
+
+            if(copy_to_clipboard(code.text())) {
+                copy_button.removeClass("fa-copy").addClass("fa-check");
+                setTimeout(function(){
+                    copy_button.removeClass("fa-check").addClass("fa-copy");
+                }, 1500);
+            }
+        });
+    });
+}
+
+// Called by the livereload script of `mdbook serve` with the output files changed by a rebuild,
+// relative to the served directory. Nothing happens when neither the page nor the files it loads
+// changed. When only the content of the page changed it is swapped in place, which keeps the scroll
+// position, anything else reloads the page.
+function livereload_changed(changed) {
+    var page = decodeURI(window.location.pathname).replace(/^\//, "");
+    if (page === "" || /\/$/.test(page)) { page += "index.html"; }
+
+    // The assets of the page are in its output root, the directory of its language (`fr/`) in a
+    // translated book
+    var root = new URL(".", document.baseURI);
+    var prefix = root.origin === window.location.origin ? decodeURI(root.pathname).replace(/^\//, "") : "";
+
+    // The other pages and the files the page does not load don't matter
+    var assets = changed.filter(function(file){
+        if (file.indexOf(prefix) !== 0) { return false; }
+        file = file.slice(prefix.length);
+        return !/\.(html|gz)$/.test(file) && LIVERELOAD_IGNORED.indexOf(file) === -1;
+    });
+    if (assets.length === 0 && changed.indexOf(page) === -1) { return; }
+
+    // Math and diagrams are only rendered when the page loads
+    if (assets.length > 0 || typeof MathJax !== "undefined" || typeof mermaid !== "undefined") {
+        window.location.reload(true);
+        return;
+    }
+
+    $.ajax({ url: window.location.pathname, dataType: "text", cache: false })
+        .done(function(html){
+            var fresh = $(new DOMParser().parseFromString(html, "text/html"));
+            if (layout(fresh) !== layout($(document))) {
+                window.location.reload(true);
+                return;
+            }
+
+            document.title = fresh.find("title").text();
+            var content = $("#content");
+            content.html(fresh.find("#content").html());
+            setup_code_blocks(content);
+        })
+        .fail(function(){ window.location.reload(true); });
+
+    // The sidebar and the links to the previous and next chapters
+    function layout(page) {
+        var links = page.find(".nav-chapters").map(function(){ return $(this).attr("href"); }).get();
+        return page.find("#sidebar").text() + links.join(" ");
+    }
+}
+
+// Output files that are not loaded by the pages
+var LIVERELOAD_IGNORED = ["searchindex.json", "sitemap.xml", ".mdbook-cache.json"];
+
+
+function show_search_results(index, query) {
+    var results = $("#searchresults");
+    results.empty();
+
+    var terms = query.toLowerCase().split(/[^a-z0-9\u00C0-\uFFFF]+/).filter(function(term){
+        return term.length > 0;
+    });
+    if(terms.length === 0) { return; }
+
+    var matches = [];
+    $.each(index.documents, function(i, doc){
+        var words = (doc.title + " " + doc.heading + " " + doc.body).toLowerCase().split(" ");
+        var score = 0;
+
+        // Every term has to be the beginning of a word of the document
+        for(var t = 0; t < terms.length; t++) {
+            var count = words.filter(function(word){ return word.indexOf(terms[t]) === 0; }).length;
+            if(count === 0) { return; }
+            score += count;
+        }
+
+        matches.push({ doc: doc, score: score });
+    });
+
+    matches.sort(function(a, b){ return b.score - a.score; });
+
+    $.each(matches.slice(0, 30), function(i, match){
+        var doc = match.doc;
+        var title = (doc.section ? doc.section + " " : "") + doc.title;
+        if(doc.heading && doc.heading !== doc.title) { title += " » " + doc.heading; }
+
+        var link = $("<a>").attr("href", doc.url).text(title);
+        results.append($("<li>").append(link));
+    });
+
+    if(matches.length === 0) {
+        results.append($("<li class=\"no-results\">").text("No results"));
+    }
+}
+
+
+// Copies the text through a temporary text area, returns true if it worked
+function copy_to_clipboard(text) {
+    var textarea = $("<textarea>").val(text).css({ position: "fixed", top: 0, left: 0, opacity: 0 });
+    $("body").append(textarea);
+    textarea.select();
+
+    var copied = false;
+    try {
+        copied = document.execCommand("copy");
+    } catch(e) {}
+
+    textarea.remove();
+    return copied;
+}
+
+
+// Highlights a code block with highlight.js, which replaces its content. The lines that were hidden at
+// build time are hidden again afterwards.
+function highlight_block(block) {
+    var hidden = [];
+    var line = 0;
+    $(block).contents().each(function(){
+        if($(this).is("span.hidden")) { hidden.push(line); }
+        line += $(this).text().split("\n").length - 1;
+    });
+
+    hljs.highlightBlock(block);
+
+    if(hidden.length === 0) { return; }
+
+    var lines = $(block).html().split("\n");
+    var html = "";
+    for(var n = 0; n < lines.length; n++){
+        var end = n < lines.length - 1 ? "\n" : "";
+        if(hidden.indexOf(n) >= 0) {
+            html += "<span class=\"hidden\">" + lines[n] + end + "</span>";
+        } else {
+            html += lines[n] + end;
+        }
+    }
+    $(block).html(html);
+}
 
 function run_rust_code(code_block) {
     var result_block = code_block.find(".result");
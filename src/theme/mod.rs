@@ -1,5 +1,6 @@
+use std::collections::BTreeMap;
 use std::path::Path;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Read;
 
 
@@ -20,8 +21,8 @@ pub static FONT_AWESOME_WOFF2: &'static [u8] = include_bytes!("_FontAwesome/font
 pub static FONT_AWESOME_OTF: &'static [u8] = include_bytes!("_FontAwesome/fonts/FontAwesome.otf");
 
 /// The `Theme` struct should be used instead of the static variables because the `new()` method
-/// will look if the user has a theme directory in his source folder and use the users theme instead
-/// of the default.
+/// will look for the files of the user in the given theme directory and use them instead of the
+/// default ones.
 ///
 /// You should exceptionnaly use the static variables only if you need the default theme even if the
 /// user has specified another theme.
@@ -34,10 +35,12 @@ pub struct Theme {
     pub tomorrow_night_css: Vec<u8>,
     pub highlight_js: Vec<u8>,
     pub jquery: Vec<u8>,
+    /// The other `*.hbs` files of the theme directory, by name without the extension
+    pub partials: BTreeMap<String, Vec<u8>>,
 }
 
 impl Theme {
-    pub fn new(src: &Path) -> Self {
+    pub fn new(theme_dir: &Path) -> Self {
 
         // Default theme
         let mut theme = Theme {
@@ -49,63 +52,78 @@ impl Theme {
             tomorrow_night_css: TOMORROW_NIGHT_CSS.to_owned(),
             highlight_js: HIGHLIGHT_JS.to_owned(),
             jquery: JQUERY.to_owned(),
+            partials: BTreeMap::new(),
         };
 
         // Check if the given path exists
-        if !src.exists() || !src.is_dir() {
-            return theme;
-        }
-
-        let src = src.join("theme");
-        // If src does exist, check if there is a theme directory in it
-        if !src.exists() || !src.is_dir() {
+        if !theme_dir.exists() || !theme_dir.is_dir() {
             return theme;
         }
 
         // Check for individual files if they exist
 
         // index.hbs
-        if let Ok(mut f) = File::open(&src.join("index.hbs")) {
+        if let Ok(mut f) = File::open(&theme_dir.join("index.hbs")) {
             theme.index.clear(); // Reset the value, because read_to_string appends...
             let _ = f.read_to_end(&mut theme.index);
         }
 
         // book.js
-        if let Ok(mut f) = File::open(&src.join("book.js")) {
+        if let Ok(mut f) = File::open(&theme_dir.join("book.js")) {
             theme.js.clear();
             let _ = f.read_to_end(&mut theme.js);
         }
 
         // book.css
-        if let Ok(mut f) = File::open(&src.join("book.css")) {
+        if let Ok(mut f) = File::open(&theme_dir.join("book.css")) {
             theme.css.clear();
             let _ = f.read_to_end(&mut theme.css);
         }
 
         // favicon.png
-        if let Ok(mut f) = File::open(&src.join("favicon.png")) {
+        if let Ok(mut f) = File::open(&theme_dir.join("favicon.png")) {
             theme.favicon.clear();
             let _ = f.read_to_end(&mut theme.favicon);
         }
 
         // highlight.js
-        if let Ok(mut f) = File::open(&src.join("highlight.js")) {
+        if let Ok(mut f) = File::open(&theme_dir.join("highlight.js")) {
             theme.highlight_js.clear();
             let _ = f.read_to_end(&mut theme.highlight_js);
         }
 
         // highlight.css
-        if let Ok(mut f) = File::open(&src.join("highlight.css")) {
+        if let Ok(mut f) = File::open(&theme_dir.join("highlight.css")) {
             theme.highlight_css.clear();
             let _ = f.read_to_end(&mut theme.highlight_css);
         }
 
         // tomorrow-night.css
-        if let Ok(mut f) = File::open(&src.join("tomorrow-night.css")) {
+        if let Ok(mut f) = File::open(&theme_dir.join("tomorrow-night.css")) {
             theme.tomorrow_night_css.clear();
             let _ = f.read_to_end(&mut theme.tomorrow_night_css);
         }
 
+        // Handlebars partials, `header.hbs` is used with `{{> header}}`
+        if let Ok(entries) = fs::read_dir(theme_dir) {
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                if path.extension().map_or(true, |ext| ext != "hbs") || path.file_name() == Some("index.hbs".as_ref()) {
+                    continue;
+                }
+                let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                    Some(name) => name.to_owned(),
+                    None => continue,
+                };
+                if let Ok(mut f) = File::open(&path) {
+                    let mut partial = vec![];
+                    if f.read_to_end(&mut partial).is_ok() {
+                        theme.partials.insert(name, partial);
+                    }
+                }
+            }
+        }
+
         theme
     }
 }
@@ -1,11 +1,24 @@
-use std::path::{Path, Component};
+use std::path::{Path, PathBuf, Component};
+use std::env;
 use std::error::Error;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 use std::fs::{self, metadata, File};
 
-/// Takes a path to a file and try to read the file into a String
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+/// Takes a path to a file and try to read the file into a String. A leading byte order mark is
+/// removed, a file that is not valid UTF-8 is an error that names the file and the position of the
+/// first invalid byte.
 
 pub fn file_to_string(path: &Path) -> Result<String, Box<Error>> {
+    read_text(path, false)
+}
+
+/// Like [`file_to_string()`](fn.file_to_string.html), but with `latin1_fallback` a file that is
+/// not valid UTF-8 is decoded as latin-1, with a warning, instead of being an error.
+
+pub fn read_text(path: &Path, latin1_fallback: bool) -> Result<String, Box<Error>> {
     let mut file = match File::open(path) {
         Ok(f) => f,
         Err(e) => {
@@ -14,14 +27,39 @@ pub fn file_to_string(path: &Path) -> Result<String, Box<Error>> {
         },
     };
 
-    let mut content = String::new();
+    let mut bytes = vec![];
 
-    if let Err(e) = file.read_to_string(&mut content) {
+    if let Err(e) = file.read_to_end(&mut bytes) {
         debug!("[*]: Failed to read {:?}", path);
         return Err(Box::new(e));
     }
 
-    Ok(content)
+    let bytes = if bytes.starts_with(b"\xEF\xBB\xBF") { &bytes[3..] } else { &bytes[..] };
+
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(content) => Ok(content),
+        Err(ref e) if latin1_fallback => {
+            let (line, column) = text_position(bytes, e.utf8_error().valid_up_to());
+            warn!("{}: invalid UTF-8 at line {}, column {}, read as latin-1", path.display(), line, column);
+            Ok(bytes.iter().map(|&b| b as char).collect())
+        },
+        Err(e) => {
+            let (line, column) = text_position(bytes, e.utf8_error().valid_up_to());
+            Err(Box::new(io::Error::new(io::ErrorKind::InvalidData,
+                                        format!("{}: invalid UTF-8 at line {}, column {}",
+                                                path.display(),
+                                                line,
+                                                column))))
+        },
+    }
+}
+
+// Line and column, from 1, of the byte at `offset` of the valid UTF-8 text before it
+fn text_position(bytes: &[u8], offset: usize) -> (usize, usize) {
+    let before = String::from_utf8_lossy(&bytes[..offset]);
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |last| last.chars().count()) + 1;
+    (line, column)
 }
 
 /// Takes a path and returns a path containing just enough `../` to point to the root of the given path.
@@ -91,20 +129,154 @@ pub fn create_file(path: &Path) -> Result<File, Box<Error>> {
     Ok(f)
 }
 
-/// Removes all the content of a directory but not the directory itself
+/// Removes all the content of a directory but not the directory itself and returns the paths that
+/// were removed.
+///
+/// The `.git` entry of `dir` is always kept, so that the output can live in a `gh-pages` worktree.
+/// It is a directory in a clone and a file pointing to the repository in a worktree, both are kept.
+/// To avoid wiping a book by accident, it refuses to empty the current working directory, one of
+/// the `protected` directories (the root and the source directory of the book) or a directory
+/// containing one of them.
+
+pub fn remove_dir_content(dir: &Path, protected: &[&Path]) -> Result<Vec<PathBuf>, Box<Error>> {
+    remove_dir_content_except(dir, protected, &[])
+}
+
+/// Same as [`remove_dir_content()`](fn.remove_dir_content.html) but also keeps the paths matching
+/// one of the `preserve` patterns, see [`glob_match()`](fn.glob_match.html). They are matched
+/// against the path relative to `dir`, the directories containing a preserved path are emptied
+/// instead of removed.
+///
+/// An entry that can not be removed, for example because of its permissions, does not stop the
+/// removal of the others. The error lists all the entries that could not be removed.
+
+pub fn remove_dir_content_except(dir: &Path,
+                                 protected: &[&Path],
+                                 preserve: &[String])
+                                 -> Result<Vec<PathBuf>, Box<Error>> {
+    let content = try!(removable_dir_content_except(dir, protected, preserve));
+
+    let mut failed = vec![];
+    for item in &content {
+        let removed = if item.is_dir() {
+            fs::remove_dir_all(item)
+        } else {
+            fs::remove_file(item)
+        };
+        if let Err(e) = removed {
+            failed.push(format!("{:?}: {}", item, e));
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(Box::new(io::Error::new(io::ErrorKind::Other,
+                                           format!("Could not remove the content of {:?}:\n    {}",
+                                                   dir,
+                                                   failed.join("\n    ")))));
+    }
+    Ok(content)
+}
+
+/// Returns the paths that [`remove_dir_content()`](fn.remove_dir_content.html) would remove, without
+/// removing anything. It fails in the same cases.
+
+pub fn removable_dir_content(dir: &Path, protected: &[&Path]) -> Result<Vec<PathBuf>, Box<Error>> {
+    removable_dir_content_except(dir, protected, &[])
+}
+
+/// Returns the paths that [`remove_dir_content_except()`](fn.remove_dir_content_except.html) would
+/// remove, without removing anything.
+
+pub fn removable_dir_content_except(dir: &Path,
+                                    protected: &[&Path],
+                                    preserve: &[String])
+                                    -> Result<Vec<PathBuf>, Box<Error>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let canonical = try!(dir.canonicalize());
+    let contains_protected = protected.iter().any(|path| {
+        path.canonicalize().map(|path| path.starts_with(&canonical)).unwrap_or(false)
+    });
+    let is_cwd = env::current_dir().and_then(|cwd| cwd.canonicalize()).map(|cwd| cwd == canonical).unwrap_or(false);
+
+    if contains_protected || is_cwd {
+        return Err(Box::new(io::Error::new(io::ErrorKind::Other,
+                                           format!("Refusing to remove the content of {:?}, it contains the book or \
+                                                    its sources, or it is the current directory",
+                                                   dir))));
+    }
+
+    let (content, _) = try!(removable_paths(dir, "", preserve));
+    Ok(content)
+}
+
+// Entry of the emptied directory that is never removed
+const GIT_DIR: &'static str = ".git";
 
-pub fn remove_dir_content(dir: &Path) -> Result<(), Box<Error>> {
+// The paths to remove to empty `dir` except the preserved paths, and whether it is all of its
+// entries. `relative` is the path of `dir` in the directory being emptied, with `/` separators.
+fn removable_paths(dir: &Path, relative: &str, preserve: &[String]) -> io::Result<(Vec<PathBuf>, bool)> {
+    let mut entries = vec![];
     for item in try!(fs::read_dir(dir)) {
         if let Ok(item) = item {
-            let item = item.path();
-            if item.is_dir() {
-                try!(fs::remove_dir_all(item));
-            } else {
-                try!(fs::remove_file(item));
+            entries.push(item.path());
+        }
+    }
+    entries.sort();
+
+    let mut content = vec![];
+    let mut all = true;
+    for item in entries {
+        let name = item.file_name().map_or(String::new(), |name| name.to_string_lossy().into_owned());
+        let path = format!("{}{}", relative, name);
+
+        if (relative.is_empty() && name == GIT_DIR) || preserve.iter().any(|pattern| glob_match(pattern, &path)) {
+            all = false;
+            continue;
+        }
+
+        // Only look inside the directories when something in them could be preserved
+        if !preserve.is_empty() && item.is_dir() && !item.symlink_metadata().map(|m| m.file_type().is_symlink()).unwrap_or(false) {
+            let (inner, inner_all) = try!(removable_paths(&item, &format!("{}/", path), preserve));
+            if !inner_all {
+                all = false;
+                content.extend(inner);
+                continue;
             }
         }
+        content.push(item);
+    }
+    Ok((content, all))
+}
+
+/// Matches `path` against a glob `pattern`. `?` matches one character and `*` any number of
+/// characters except `/`, `**` matches across directories. For example `*.pdf` matches `book.pdf`
+/// but not `downloads/book.pdf`, `downloads/**` and `**/*.pdf` match both.
+
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    glob_match_chars(&pattern, &path)
+}
+
+fn glob_match_chars(pattern: &[char], path: &[char]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(&'*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            // `**/` also matches no directory at all
+            (rest.first() == Some(&'/') && glob_match_chars(&rest[1..], path)) ||
+            (0..path.len() + 1).any(|i| glob_match_chars(rest, &path[i..]))
+        },
+        Some(&'*') => {
+            (0..path.len() + 1).take_while(|&i| i == 0 || path[i - 1] != '/')
+                               .any(|i| glob_match_chars(&pattern[1..], &path[i..]))
+        },
+        Some(&'?') => !path.is_empty() && path[0] != '/' && glob_match_chars(&pattern[1..], &path[1..]),
+        Some(&c) => path.first() == Some(&c) && glob_match_chars(&pattern[1..], &path[1..]),
     }
-    Ok(())
 }
 
 ///
@@ -150,7 +322,7 @@ pub fn copy_files_except_ext(from: &Path, to: &Path, recursive: bool, ext_blackl
                 debug!("[*] creating path for file: {:?}",
                        &to.join(entry.path().file_name().expect("a file should have a file name...")));
 
-                output!("[*] Copying file: {:?}\n    to {:?}",
+                info!("Copying file: {:?}\n    to {:?}",
                         entry.path(),
                         &to.join(entry.path().file_name().expect("a file should have a file name...")));
                 try!(fs::copy(entry.path(),
@@ -161,6 +333,75 @@ pub fn copy_files_except_ext(from: &Path, to: &Path, recursive: bool, ext_blackl
     Ok(())
 }
 
+/// Returns the paths, relative to `from`, of the files in `from` and its sub directories, except
+/// the files with the extensions given in `ext_blacklist` and the content of the `skipped`
+/// directories
+
+pub fn list_files_except_ext(from: &Path, skipped: &[&Path], ext_blacklist: &[&str]) -> Result<Vec<PathBuf>, Box<Error>> {
+    let mut files = vec![];
+    if skipped.contains(&from) {
+        return Ok(files);
+    }
+
+    for entry in try!(fs::read_dir(from)) {
+        let entry = try!(entry);
+        let metadata = try!(entry.metadata());
+
+        if metadata.is_dir() {
+            for path in try!(list_files_except_ext(&entry.path(), skipped, ext_blacklist)) {
+                files.push(Path::new(&entry.file_name()).join(path));
+            }
+        } else if metadata.is_file() {
+            let blacklisted = entry.path()
+                                   .extension()
+                                   .map_or(false, |ext| ext_blacklist.contains(&&*ext.to_string_lossy()));
+            if !blacklisted {
+                files.push(PathBuf::from(entry.file_name()));
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Writes a gzip copy, `file.ext.gz`, next to the files of `dir` and its sub directories whose
+/// extension is one of `extensions` and whose size is at least `min_size` bytes. The existing
+/// copies of the smaller files are removed, and the `.git` directory is left out. Returns the paths
+/// of the written copies.
+
+pub fn gzip_files(dir: &Path, extensions: &[&str], min_size: u64) -> Result<Vec<PathBuf>, Box<Error>> {
+    let mut compressed = vec![];
+
+    for path in try!(list_files_except_ext(dir, &[&dir.join(GIT_DIR)], &[])) {
+        let path = dir.join(path);
+        let listed = path.extension().map_or(false, |ext| extensions.contains(&&*ext.to_string_lossy()));
+        if !listed {
+            continue;
+        }
+
+        let mut gz = path.clone().into_os_string();
+        gz.push(".gz");
+        let gz = PathBuf::from(gz);
+
+        if try!(fs::metadata(&path)).len() < min_size {
+            if gz.exists() {
+                try!(fs::remove_file(&gz));
+            }
+            continue;
+        }
+
+        let mut content = vec![];
+        try!(try!(File::open(&path)).read_to_end(&mut content));
+        let mut encoder = GzEncoder::new(try!(File::create(&gz)), Compression::best());
+        try!(encoder.write_all(&content));
+        try!(encoder.finish());
+        compressed.push(gz);
+    }
+
+    Ok(compressed)
+}
+
 
 // ------------------------------------------------------------------------------------------------
 // ------------------------------------------------------------------------------------------------
@@ -171,8 +412,13 @@ pub fn copy_files_except_ext(from: &Path, to: &Path, recursive: bool, ext_blackl
 mod tests {
     extern crate tempdir;
 
-    use super::copy_files_except_ext;
+    use super::{copy_files_except_ext, list_files_except_ext, remove_dir_content, remove_dir_content_except,
+                glob_match, gzip_files, file_to_string, read_text};
+    use std::path::{Path, PathBuf};
     use std::fs;
+    use std::io::{Read, Write};
+
+    use flate2::read::GzDecoder;
 
     #[test]
     fn copy_files_except_ext_test() {
@@ -235,4 +481,131 @@ mod tests {
         }
 
     }
+
+    #[test]
+    fn list_files_except_ext_skips_directories() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        for dir in &["images", "theme", "book"] {
+            fs::create_dir(tmp.path().join(dir)).unwrap();
+        }
+        for file in &["intro.md", "LICENSE", "images/logo.png", "theme/book.css", "book/index.html"] {
+            fs::File::create(tmp.path().join(file)).unwrap();
+        }
+
+        let skipped = [tmp.path().join("theme"), tmp.path().join("book")];
+        let skipped = skipped.iter().map(|p| p.as_path()).collect::<Vec<&Path>>();
+        assert_eq!(list_files_except_ext(tmp.path(), &skipped, &["md"]).unwrap(),
+                   vec![PathBuf::from("LICENSE"), PathBuf::from("images/logo.png")]);
+    }
+
+    #[test]
+    fn remove_dir_content_keeps_git_and_root() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let dest = tmp.path().join("book");
+        fs::create_dir_all(dest.join(".git")).expect("Could not create book/.git");
+        fs::create_dir_all(dest.join("sub")).expect("Could not create book/sub");
+        fs::File::create(dest.join("index.html")).expect("Could not create book/index.html");
+
+        let mut removed = remove_dir_content(&dest, &[tmp.path()]).unwrap();
+        removed.sort();
+
+        assert_eq!(removed, vec![dest.join("index.html"), dest.join("sub")]);
+        assert!(dest.join(".git").exists());
+        assert!(remove_dir_content(tmp.path(), &[tmp.path()]).is_err());
+
+        // Nor a directory containing a protected one
+        let src = tmp.path().join("src");
+        fs::create_dir_all(&src).expect("Could not create src");
+        assert!(remove_dir_content(&src, &[tmp.path(), &src]).is_err());
+        assert!(remove_dir_content(tmp.path().parent().unwrap(), &[tmp.path(), &src]).is_err());
+    }
+
+    #[test]
+    fn remove_dir_content_keeps_preserved_paths() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let dest = tmp.path().join("book");
+        fs::create_dir_all(dest.join("downloads/old")).expect("Could not create book/downloads/old");
+        fs::create_dir_all(dest.join("sub")).expect("Could not create book/sub");
+        for file in &["CNAME", "index.html", "downloads/book.pdf", "downloads/notes.txt", "sub/other.pdf"] {
+            fs::File::create(dest.join(file)).expect("Could not create a file");
+        }
+
+        let preserve = vec!["CNAME".to_owned(), "downloads/*.pdf".to_owned()];
+        let removed = remove_dir_content_except(&dest, &[tmp.path()], &preserve).unwrap();
+
+        assert_eq!(removed,
+                   vec![dest.join("downloads/notes.txt"), dest.join("downloads/old"), dest.join("index.html"),
+                        dest.join("sub")]);
+        assert!(dest.join("CNAME").exists());
+        assert!(dest.join("downloads/book.pdf").exists());
+    }
+
+    #[test]
+    fn remove_dir_content_keeps_git_worktree_file() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let dest = tmp.path().join("book");
+        fs::create_dir_all(&dest).expect("Could not create book");
+        fs::File::create(dest.join(".git")).expect("Could not create book/.git");
+        fs::File::create(dest.join("index.html")).expect("Could not create book/index.html");
+
+        let removed = remove_dir_content(&dest, &[tmp.path()]).unwrap();
+        assert_eq!(removed, vec![dest.join("index.html")]);
+        assert!(dest.join(".git").is_file());
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("CNAME", "CNAME"));
+        assert!(!glob_match("CNAME", "sub/CNAME"));
+        assert!(glob_match("*.pdf", "book.pdf"));
+        assert!(!glob_match("*.pdf", "downloads/book.pdf"));
+        assert!(glob_match("**/*.pdf", "book.pdf"));
+        assert!(glob_match("**/*.pdf", "downloads/2017/book.pdf"));
+        assert!(glob_match("downloads/**", "downloads/2017/book.pdf"));
+        assert!(glob_match("?.txt", "a.txt"));
+        assert!(!glob_match("?.txt", "ab.txt"));
+    }
+
+    #[test]
+    fn gzip_copies_of_large_text_files() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let dest = tmp.path();
+        fs::create_dir_all(dest.join("css")).unwrap();
+        let large = "<p>Some text</p>\n".repeat(100);
+        for file in &["index.html", "css/book.css", "image.png"] {
+            fs::File::create(dest.join(file)).unwrap().write_all(large.as_bytes()).unwrap();
+        }
+        fs::File::create(dest.join("small.js")).unwrap().write_all(b"var a;").unwrap();
+        fs::File::create(dest.join("small.js.gz")).unwrap();
+
+        let compressed = gzip_files(dest, &["html", "css", "js"], 1024).unwrap();
+        assert_eq!(compressed, vec![dest.join("css/book.css.gz"), dest.join("index.html.gz")]);
+        assert!(dest.join("index.html").exists());
+        assert!(!dest.join("image.png.gz").exists());
+        assert!(!dest.join("small.js.gz").exists());
+
+        let mut content = String::new();
+        GzDecoder::new(fs::File::open(dest.join("index.html.gz")).unwrap()).read_to_string(&mut content).unwrap();
+        assert_eq!(content, large);
+    }
+
+    #[test]
+    fn byte_order_mark_is_removed() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let path = tmp.path().join("bom.md");
+        fs::File::create(&path).unwrap().write_all(b"\xEF\xBB\xBF# Chapter\n").unwrap();
+
+        assert_eq!(file_to_string(&path).unwrap(), "# Chapter\n");
+    }
+
+    #[test]
+    fn invalid_utf8_names_the_file_and_the_position() {
+        let tmp = tempdir::TempDir::new("mdbook").expect("Could not create a temp dir");
+        let path = tmp.path().join("latin1.md");
+        fs::File::create(&path).unwrap().write_all(b"# Chapter\n\nCaf\xE9 cr\xE8me\n").unwrap();
+
+        let error = file_to_string(&path).unwrap_err().to_string();
+        assert_eq!(error, format!("{}: invalid UTF-8 at line 3, column 4", path.display()));
+        assert_eq!(read_text(&path, true).unwrap(), "# Chapter\n\nCaf\u{e9} cr\u{e8}me\n");
+    }
 }
@@ -0,0 +1,63 @@
+use std::collections::HashSet;
+
+/// Escapes the characters that have a special meaning in html: `&`, `<`, `>`, `"` and `'`
+
+pub fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Turns the text of a heading into an identifier that can be used in urls: the text is lowercased,
+/// whitespace is replaced by hyphens and the punctuation is removed.
+///
+/// ```
+/// # use mdbook::utils::html::slugify;
+/// assert_eq!(slugify("Hello, World!"), "hello-world");
+/// assert_eq!(slugify("Use `MDBook::new()`"), "use-mdbooknew");
+/// ```
+
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            slug.extend(c.to_lowercase());
+        } else if c.is_whitespace() {
+            slug.push('-');
+        }
+    }
+
+    if slug.is_empty() {
+        slug.push_str("section");
+    }
+
+    slug
+}
+
+/// Returns `id`, or `id-1`, `id-2`, ... if it is already in `used`, and adds the result to `used`.
+/// This is used to give distinct identifiers to the headings of a page that have the same text.
+
+pub fn unique_id(id: &str, used: &mut HashSet<String>) -> String {
+    let mut unique = id.to_owned();
+    let mut i = 1;
+
+    while used.contains(&unique) {
+        unique = format!("{}-{}", id, i);
+        i += 1;
+    }
+
+    used.insert(unique.clone());
+    unique
+}
@@ -1 +1,263 @@
 pub mod fs;
+pub mod html;
+
+use std::cmp;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Command;
+
+use pulldown_cmark::{Parser, Event, Tag};
+
+/// Formats a number of seconds since the Unix epoch as a UTC date and time in the
+/// `CCYY-MM-DDThh:mm:ssZ` form, the one of `dcterms:modified` and of the dates of sitemaps
+
+pub fn format_timestamp(seconds: u64) -> String {
+    let days = (seconds / 86400) as i64;
+    let time = seconds % 86400;
+
+    // Converts days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            time / 3600,
+            time % 3600 / 60,
+            time % 60)
+}
+
+/// Counts the words of a markdown text. The code blocks and the html are not counted, the inline
+/// code is.
+
+pub fn word_count(markdown: &str) -> usize {
+    let mut text = String::new();
+    let mut in_code_block = false;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            Event::Text(ref t) if !in_code_block => text.push_str(t),
+            // The inline tags do not separate words, `*un*done` is one word
+            Event::Start(Tag::Emphasis) | Event::End(Tag::Emphasis) | Event::Start(Tag::Strong) |
+            Event::End(Tag::Strong) | Event::Start(Tag::Code) | Event::End(Tag::Code) |
+            Event::Start(Tag::Link(..)) | Event::End(Tag::Link(..)) => {},
+            Event::Start(_) | Event::End(_) | Event::SoftBreak | Event::HardBreak => text.push(' '),
+            _ => {},
+        }
+    }
+
+    text.split_whitespace().count()
+}
+
+/// Text of the first paragraph of a markdown text, without its formatting, cut after `max_chars`
+/// characters at the end of a word and followed by `…` when it is longer. `None` when there is no
+/// paragraph with text.
+
+pub fn first_paragraph(markdown: &str, max_chars: usize) -> Option<String> {
+    let mut text = String::new();
+    let mut in_paragraph = false;
+    // The alternative text of the images is not part of the text
+    let mut in_image = false;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::Paragraph) => in_paragraph = true,
+            Event::End(Tag::Paragraph) if !text.trim().is_empty() => break,
+            Event::End(Tag::Paragraph) => in_paragraph = false,
+            Event::Start(Tag::Image(..)) => in_image = true,
+            Event::End(Tag::Image(..)) => in_image = false,
+            Event::Text(ref t) if in_paragraph && !in_image => text.push_str(t),
+            Event::SoftBreak | Event::HardBreak if in_paragraph => text.push(' '),
+            _ => {},
+        }
+    }
+
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut paragraph = String::new();
+    for word in words {
+        let length = paragraph.chars().count();
+        if length > 0 && length + 1 + word.chars().count() > max_chars {
+            paragraph.push('…');
+            break;
+        }
+        if length > 0 {
+            paragraph.push(' ');
+        }
+        paragraph.push_str(word);
+    }
+    Some(paragraph)
+}
+
+/// Estimated reading time in minutes of `words` words at `words_per_minute`, rounded up and at
+/// least one minute
+
+pub fn reading_time(words: usize, words_per_minute: usize) -> usize {
+    let words_per_minute = cmp::max(words_per_minute, 1);
+    cmp::max((words + words_per_minute - 1) / words_per_minute, 1)
+}
+
+/// Replaces every line ending of `text`, `\r\n`, `\n` or a lone `\r`, with `line_ending`
+
+pub fn normalize_line_endings(text: &str, line_ending: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    let mut lines = text.split('\n').peekable();
+
+    while let Some(line) = lines.next() {
+        let line = if line.ends_with('\r') { &line[..line.len() - 1] } else { line };
+        normalized.push_str(&line.split('\r').collect::<Vec<_>>().join(line_ending));
+        if lines.peek().is_some() {
+            normalized.push_str(line_ending);
+        }
+    }
+
+    normalized
+}
+
+/// Opens `target`, a file or an url, in the default browser with the opener of the platform:
+/// `xdg-open` on Linux, `open` on macOS and `start` on Windows. Failing to open it is not an error,
+/// when there is no opener the target is printed instead.
+
+pub fn open<P: AsRef<OsStr>>(target: P) {
+    let target = target.as_ref();
+    debug!("[fn]: open {:?}", target);
+
+    match opener(target).status() {
+        Ok(ref status) if status.success() => {},
+        _ => warn!("Could not open a browser, the book is at {}", target.to_string_lossy()),
+    }
+}
+
+/// Returns the date of the last commit of the file at `path` in the strict ISO 8601 form, like
+/// `2017-05-03T10:20:30+02:00`, or `None` when git is not installed, when the file is not in a git
+/// repository or when it was never committed
+
+pub fn git_commit_date(path: &Path) -> Option<String> {
+    let name = match path.file_name() {
+        Some(name) => name,
+        None => return None,
+    };
+    let dir = match path.parent() {
+        Some(dir) if dir != Path::new("") => dir,
+        _ => Path::new("."),
+    };
+
+    let output = match Command::new("git")
+                           .arg("log")
+                           .arg("-1")
+                           .arg("--format=%cI")
+                           .arg("--")
+                           .arg(name)
+                           .current_dir(dir)
+                           .output() {
+        Ok(output) => output,
+        Err(e) => {
+            debug!("[*]: Could not run git for {:?}: {}", path, e);
+            return None;
+        },
+    };
+
+    let date = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    if output.status.success() && !date.is_empty() {
+        Some(date)
+    } else {
+        None
+    }
+}
+
+/// Returns a command that runs `command` with the shell of the platform, `sh` or `cmd` on Windows
+
+#[cfg(target_os = "windows")]
+pub fn shell<S: AsRef<OsStr>>(command: S) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// Returns a command that runs `command` with the shell of the platform, `sh` or `cmd` on Windows
+
+#[cfg(not(target_os = "windows"))]
+pub fn shell<S: AsRef<OsStr>>(command: S) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(target_os = "windows")]
+fn opener(target: &OsStr) -> Command {
+    // `start` is built into the shell, its first quoted argument is the title of the window
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg("start").arg("").arg(target);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn opener(target: &OsStr) -> Command {
+    let mut command = Command::new("open");
+    command.arg(target);
+    command
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn opener(target: &OsStr) -> Command {
+    let mut command = Command::new("xdg-open");
+    command.arg(target);
+    command
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_timestamp_as_utc_date() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951782400 + 3661), "2000-02-29T01:01:01Z");
+    }
+
+    #[test]
+    fn count_words_without_code_blocks() {
+        let markdown = "# Getting started\n\nInstall *mdBook* with `cargo install`:\n\n\
+                        ```bash\ncargo install mdbook --vers 0.0.21\n```\n\n\
+                        - one\n- two\n\n<div class=\"note\">html</div>\n";
+        assert_eq!(word_count(markdown), 9);
+        assert_eq!(word_count(""), 0);
+    }
+
+    #[test]
+    fn first_paragraph_as_text() {
+        let markdown = "# Getting started\n\n![logo](logo.png)\n\nInstall *mdBook* with\n`cargo install`.\n\nThen...";
+        assert_eq!(first_paragraph(markdown, 100), Some("Install mdBook with cargo install.".to_owned()));
+        assert_eq!(first_paragraph(markdown, 20), Some("Install mdBook with…".to_owned()));
+        assert_eq!(first_paragraph("# Title\n\n```\ncode\n```\n", 100), None);
+    }
+
+    #[test]
+    fn reading_time_in_minutes() {
+        assert_eq!(reading_time(0, 200), 1);
+        assert_eq!(reading_time(200, 200), 1);
+        assert_eq!(reading_time(1401, 200), 8);
+        assert_eq!(reading_time(10, 0), 10);
+    }
+
+    #[test]
+    fn line_endings_are_normalized() {
+        assert_eq!(normalize_line_endings("a\r\nb\nc\rd\r\n", "\n"), "a\nb\nc\nd\n");
+        assert_eq!(normalize_line_endings("a\r\nb\nc", "\r\n"), "a\r\nb\r\nc");
+        assert_eq!(normalize_line_endings("", "\n"), "");
+    }
+}
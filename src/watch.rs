@@ -0,0 +1,131 @@
+//! Watch the source files of a book and react to changes
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use notify::{self, Watcher};
+
+use book::MDBook;
+
+/// Calls the closure when a source file of the book is changed. This is blocking!
+///
+/// Editors often write a file multiple times when saving it, all the events emitted within the
+/// debounce interval of the book (`watch_debounce_ms`) that follow a change are therefore coalesced
+/// into a single call. Changes inside the output directory and the ones matching the ignore list of
+/// the book are ignored, otherwise every build would trigger a new one.
+
+pub fn trigger_on_change<F>(book: &mut MDBook, closure: F) -> Result<(), Box<Error>>
+    where F: Fn(&Path, &mut MDBook) -> ()
+{
+    debug!("[fn]: trigger_on_change");
+
+    // Create a channel to receive the events.
+    let (tx, rx) = channel();
+
+    let mut watcher: notify::RecommendedWatcher = try!(notify::Watcher::new(tx));
+
+    // Add the source directory to the watcher
+    try!(watcher.watch(book.get_src()));
+    watch_other_paths(&mut watcher, book);
+
+    info!("Listening for changes...");
+
+    loop {
+        let event = try!(rx.recv());
+
+        let path = match changed_path(event, book) {
+            Some(p) => p,
+            None => continue,
+        };
+
+        try!(debounce(&rx, Duration::from_millis(book.get_watch_debounce_ms())));
+
+        closure(&path, book);
+
+        // Editors that save a file by renaming a new one over it remove the watch of the file, and
+        // the directories that did not exist yet could have been created since
+        watch_other_paths(&mut watcher, book);
+    }
+}
+
+// Watches the paths outside of the source directory, the ones that do not exist are skipped
+fn watch_other_paths(watcher: &mut notify::RecommendedWatcher, book: &MDBook) {
+    // The theme directory
+    let theme_path = book.get_theme_path();
+    if !theme_path.starts_with(book.get_src()) {
+        let _ = watcher.watch(&theme_path);
+    }
+
+    // The config file, it's not located in the source directory
+    match book.get_config_path() {
+        Some(config_path) => {
+            let _ = watcher.watch(config_path);
+        },
+        None => {
+            let _ = watcher.watch(book.get_root().join("book.json"));
+            let _ = watcher.watch(book.get_root().join("book.toml"));
+        },
+    }
+
+    for path in book.get_watch_paths() {
+        let _ = watcher.watch(path);
+    }
+}
+
+// Returns the path of the event, unless it lies in the output directory or is ignored. Errors of
+// the watcher, for example about a file that was removed, are only reported.
+fn changed_path(event: notify::Event, book: &MDBook) -> Option<PathBuf> {
+    if let Err(e) = event.op {
+        warn!("Error while watching {:?}: {:?}", event.path, e);
+        return None;
+    }
+
+    match event.path {
+        Some(ref path) if path.starts_with(book.get_dest()) => None,
+        Some(ref path) if is_ignored(path, book.get_root(), book.get_watch_ignore()) => None,
+        path => path,
+    }
+}
+
+// A pattern is either a path relative to the root of the book or `*.ext`
+fn is_ignored(path: &Path, root: &Path, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| {
+        if pattern.starts_with("*.") {
+            path.extension().map_or(false, |ext| ext.to_string_lossy() == &pattern[2..])
+        } else {
+            path.starts_with(root.join(pattern))
+        }
+    })
+}
+
+// Drops the events received until the channel stays quiet for `interval`
+fn debounce(rx: &Receiver<notify::Event>, interval: Duration) -> Result<(), Box<Error>> {
+    loop {
+        match rx.recv_timeout(interval) {
+            Ok(_) => continue,
+            Err(RecvTimeoutError::Timeout) => return Ok(()),
+            Err(e) => return Err(Box::new(e)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::is_ignored;
+
+    use std::path::Path;
+
+    #[test]
+    fn ignore_patterns() {
+        let patterns = vec!["*.swp".to_owned(), "src/drafts".to_owned()];
+        let root = Path::new("book");
+
+        assert!(is_ignored(Path::new("book/src/.chapter.md.swp"), root, &patterns));
+        assert!(is_ignored(Path::new("book/src/drafts/a.md"), root, &patterns));
+        assert!(!is_ignored(Path::new("book/src/drafts.md"), root, &patterns));
+        assert!(!is_ignored(Path::new("book/src/chapter.md"), root, &patterns));
+    }
+}